@@ -1,163 +1,392 @@
-use std::{fs::File, io::Write, path::Path};
+use std::{env, fs::File, io::Write, path::Path};
 
 use common::ux::*;
+use serde::Deserialize;
 
 mod build_utils;
 
 use build_utils::{gen_u8_slice, make_page_maker};
 
-const PAGE_MAKERS: &[(&'static str, WrappedPage)] = &[
-    (
-        "spinner",
-        WrappedPage::Spinner {
-            text: rt_str("text", "&str"),
-        },
-    ),
-    (
-        "info",
-        WrappedPage::Info {
-            icon: rt("icon", "Icon"),
-            text: rt_str("text", "&str"),
-        },
-    ),
-    (
-        "confirm_reject",
-        WrappedPage::ConfirmReject {
-            title: rt_str("title", "&str"),
-            text: rt_str("text", "&str"),
-            confirm: rt_str("confirm", "&str"),
-            reject: rt_str("reject", "&str"),
-        },
-    ),
-    (
-        "review_pairs_intro",
-        WrappedPage::GenericPage {
-            navigation_info: Some(WrappedNavigationInfo {
-                active_page: rt("active_page", "u32"),
-                n_pages: rt("n_pages", "u32"),
-                skip_text: None,
-                nav_info: WrappedNavInfo::NavWithButtons {
-                    has_back_button: ct(true),
-                    has_page_indicator: ct(true),
-                    quit_text: Some(ct_str("Reject")),
-                },
-            }),
-            page_content_info: WrappedPageContentInfo {
-                title: None,
-                top_right_icon: ct(Icon::None), // TODO: support icons
-                page_content: WrappedPageContent::TextSubtext {
-                    text: rt_str("intro_text", "&str"),
-                    subtext: rt_str("intro_subtext", "&str"),
-                },
-            },
-        },
-    ),
-    (
-        "review_pairs_content",
-        WrappedPage::GenericPage {
-            navigation_info: Some(WrappedNavigationInfo {
-                active_page: rt("active_page", "u32"),
-                n_pages: rt("n_pages", "u32"),
-                skip_text: None,
-                nav_info: WrappedNavInfo::NavWithButtons {
-                    has_back_button: ct(true),
-                    has_page_indicator: ct(true),
-                    quit_text: Some(ct_str("Reject")),
-                },
-            }),
-            page_content_info: WrappedPageContentInfo {
-                title: None,
-                top_right_icon: ct(Icon::None), // TODO: support icons
-                page_content: WrappedPageContent::TagValueList {
-                    list: rt("pairs", "&[TagValue]"),
-                },
-            },
-        },
-    ),
-    (
-        "review_pairs_final_longpress",
-        WrappedPage::GenericPage {
-            navigation_info: Some(WrappedNavigationInfo {
-                active_page: rt("active_page", "u32"),
-                n_pages: rt("n_pages", "u32"),
-                skip_text: None,
-                nav_info: WrappedNavInfo::NavWithButtons {
-                    has_back_button: ct(true),
-                    has_page_indicator: ct(true),
-                    quit_text: Some(ct_str("Reject")),
-                },
-            }),
-            page_content_info: WrappedPageContentInfo {
-                title: None,
-                top_right_icon: ct(Icon::None), // TODO: support icons
-                page_content: WrappedPageContent::ConfirmationLongPress {
-                    text: rt_str("final_text", "&str"),
-                    long_press_text: rt_str("final_button_text", "&str"),
-                },
-            },
-        },
-    ),
-    (
-        "review_pairs_final_confirmationbutton",
-        WrappedPage::GenericPage {
-            navigation_info: Some(WrappedNavigationInfo {
-                active_page: rt("active_page", "u32"),
-                n_pages: rt("n_pages", "u32"),
-                skip_text: None,
-                nav_info: WrappedNavInfo::NavWithButtons {
-                    has_back_button: ct(true),
-                    has_page_indicator: ct(true),
-                    quit_text: Some(ct_str("Reject")),
-                },
-            }),
-            page_content_info: WrappedPageContentInfo {
-                title: None,
-                top_right_icon: ct(Icon::None), // TODO: support icons
-                page_content: WrappedPageContent::ConfirmationButton {
-                    text: rt_str("final_text", "&str"),
-                    button_text: rt_str("final_button_text", "&str"),
-                },
-            },
-        },
-    ),
+/// One entry of `ux_pages.ron` (or an app's override file): the name of the generated page-maker
+/// function, and the `WrappedPage` it wraps. See `ux_pages.ron` for the manifest format.
+#[derive(Deserialize)]
+struct PageManifestEntry {
+    name: String,
+    page: WrappedPage,
+}
+
+/// Default manifest shipped with `app-sdk`, declaring every built-in page maker.
+const DEFAULT_UX_PAGES_RON: &str = include_str!("ux_pages.ron");
+
+/// Loads the page manifest: the built-in `ux_pages.ron`, with any entry overridden or extended by
+/// the file named in the `VANADIUM_APP_UX_PAGES` environment variable, if set. An override entry
+/// is matched to a default entry by `name`; a name with no default match is simply appended, so
+/// an app can both customize a built-in page and add entirely new ones.
+fn load_page_manifest() -> Vec<PageManifestEntry> {
+    let mut entries: Vec<PageManifestEntry> =
+        ron::de::from_str(DEFAULT_UX_PAGES_RON).expect("Could not parse ux_pages.ron");
+
+    if let Ok(override_path) = env::var("VANADIUM_APP_UX_PAGES") {
+        println!("cargo:rerun-if-env-changed=VANADIUM_APP_UX_PAGES");
+        println!("cargo:rerun-if-changed={}", override_path);
+
+        let override_ron = std::fs::read_to_string(&override_path)
+            .unwrap_or_else(|e| panic!("Could not read {}: {}", override_path, e));
+        let overrides: Vec<PageManifestEntry> =
+            ron::de::from_str(&override_ron).expect("Could not parse app UX page overrides");
+
+        for override_entry in overrides {
+            if let Some(existing) = entries.iter_mut().find(|e| e.name == override_entry.name) {
+                *existing = override_entry;
+            } else {
+                entries.push(override_entry);
+            }
+        }
+    }
+
+    entries
+}
+
+/// A symbolic icon, as declared in `icon_theme.ron`: either an alias for a built-in [`Icon`]
+/// variant, or a custom glyph loaded from a bitmap file.
+#[derive(Deserialize)]
+enum IconSpec {
+    Builtin(String),
+    Custom {
+        width: u16,
+        height: u16,
+        bpp: String,
+        bitmap_file: String,
+    },
+}
+
+/// One entry of `icon_theme.ron` (or an app's override file): a symbolic name and the icon it
+/// resolves to. See `icon_theme.ron` for the manifest format.
+#[derive(Deserialize)]
+struct IconThemeEntry {
+    name: String,
+    icon: IconSpec,
+}
+
+/// Default icon theme shipped with `app-sdk`.
+const DEFAULT_ICON_THEME_RON: &str = include_str!("icon_theme.ron");
+
+/// Loads the icon theme the same way [`load_page_manifest`] loads the page manifest: the
+/// built-in `icon_theme.ron`, overridden/extended by `VANADIUM_APP_ICON_THEME` if set.
+fn load_icon_theme() -> Vec<IconThemeEntry> {
+    let mut entries: Vec<IconThemeEntry> =
+        ron::de::from_str(DEFAULT_ICON_THEME_RON).expect("Could not parse icon_theme.ron");
+
+    if let Ok(override_path) = env::var("VANADIUM_APP_ICON_THEME") {
+        println!("cargo:rerun-if-env-changed=VANADIUM_APP_ICON_THEME");
+        println!("cargo:rerun-if-changed={}", override_path);
+
+        let override_ron = std::fs::read_to_string(&override_path)
+            .unwrap_or_else(|e| panic!("Could not read {}: {}", override_path, e));
+        let overrides: Vec<IconThemeEntry> =
+            ron::de::from_str(&override_ron).expect("Could not parse app icon theme overrides");
+
+        for override_entry in overrides {
+            if let Some(existing) = entries.iter_mut().find(|e| e.name == override_entry.name) {
+                *existing = override_entry;
+            } else {
+                entries.push(override_entry);
+            }
+        }
+    }
+
+    entries
+}
+
+/// Generates one named accessor per `icon_theme.ron` entry: `pub const ICON_<NAME>: Icon = ..;`
+/// for a [`IconSpec::Builtin`] alias, or `pub fn icon_<name>() -> Icon { .. }` for a
+/// [`IconSpec::Custom`] glyph (a `const` can't hold the `Vec<u8>` bitmap payload).
+fn make_icon_theme(file: &mut File) {
+    for entry in load_icon_theme() {
+        match entry.icon {
+            IconSpec::Builtin(variant) => {
+                writeln!(
+                    file,
+                    "pub const ICON_{}: Icon = Icon::{};",
+                    entry.name.to_uppercase(),
+                    variant
+                )
+                .expect("Could not write");
+            }
+            IconSpec::Custom {
+                width,
+                height,
+                bpp,
+                bitmap_file,
+            } => {
+                let bitmap_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(&bitmap_file);
+                let bitmap = std::fs::read(&bitmap_path).unwrap_or_else(|e| {
+                    panic!("Could not read icon bitmap {}: {}", bitmap_file, e)
+                });
+                println!("cargo:rerun-if-changed={}", bitmap_path.display());
+
+                writeln!(file, "pub fn icon_{}() -> Icon {{", entry.name).expect("Could not write");
+                writeln!(
+                    file,
+                    "    Icon::Custom {{ width: {}, height: {}, bpp: IconBpp::{}, bitmap: alloc::vec!{} }}",
+                    width,
+                    height,
+                    bpp,
+                    gen_u8_slice(&bitmap)
+                )
+                .expect("Could not write");
+                writeln!(file, "}}").expect("Could not write");
+            }
+        }
+    }
+    writeln!(file).expect("Could not write");
+}
+
+/// One entry of an `i18n/*.ron` file: a stable key and its text in that file's language.
+#[derive(Deserialize)]
+struct I18nEntry {
+    key: String,
+    text: String,
+}
+
+/// The i18n manifest: `(language name, file contents)`, base language first. The base language
+/// (`en`) fixes the key order that [`StringId`] assignment follows; every other language is
+/// matched against it by key, falling back to the base text for any key it doesn't translate.
+const I18N_LANGUAGES: &[(&str, &str)] = &[
+    ("en", include_str!("i18n/en.ron")),
+    ("fr", include_str!("i18n/fr.ron")),
 ];
 
-// Precomputed pages with no variable part, so they can be directly
-// embedded in the binary as constants.
-fn make_const_pages(file: &mut File) {
-    let default_pages: &[(&'static str, Page)] = &[(
-        // "Application is ready"
-        "APP_DASHBOARD",
-        Page::GenericPage {
-            navigation_info: None,
-            page_content_info: PageContentInfo {
-                title: None,
-                top_right_icon: Icon::None,
-                page_content: PageContent::TextSubtext {
-                    text: "Application".into(),
-                    subtext: "is ready".into(),
-                },
-            },
-        },
-    )];
-
-    for (page_name, page) in default_pages {
-        let serialized = page.serialized();
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Generates the localization tables: a [`StringId`] const per key (named after it, in the base
+/// language's key order), a `Language` enum, one string table per language, and the
+/// `set_language`/`t` functions used to resolve a [`StringId`] against the active language.
+fn make_i18n_tables(file: &mut File) {
+    let base: Vec<I18nEntry> =
+        ron::de::from_str(I18N_LANGUAGES[0].1).expect("Could not parse i18n/en.ron");
 
+    writeln!(
+        file,
+        "/// A stable, build-time-assigned id for a localized UX string. See [`t`]."
+    )
+    .expect("Could not write");
+    writeln!(
+        file,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\npub struct StringId(pub u16);\n"
+    )
+    .expect("Could not write");
+
+    for (id, entry) in base.iter().enumerate() {
         writeln!(
             file,
-            "pub const RAW_PAGE_{}: [u8; {}] = {};",
-            page_name,
-            serialized.len(),
-            gen_u8_slice(&serialized)
+            "pub const STR_{}: StringId = StringId({});",
+            entry.key.to_uppercase(),
+            id
         )
         .expect("Could not write");
     }
-
     writeln!(file).expect("Could not write");
+
+    writeln!(file, "/// A UX language selectable via [`set_language`].").expect("Could not write");
+    writeln!(file, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\npub enum Language {{").expect("Could not write");
+    for (lang, _) in I18N_LANGUAGES {
+        writeln!(file, "    {},", capitalize(lang)).expect("Could not write");
+    }
+    writeln!(file, "}}\n").expect("Could not write");
+
+    for (lang, contents) in I18N_LANGUAGES {
+        let entries: Vec<I18nEntry> = ron::de::from_str(contents)
+            .unwrap_or_else(|e| panic!("Could not parse i18n/{}.ron: {}", lang, e));
+        let by_key: std::collections::HashMap<&str, &str> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.text.as_str()))
+            .collect();
+
+        writeln!(
+            file,
+            "const STRINGS_{}: &[&str] = &[",
+            lang.to_uppercase()
+        )
+        .expect("Could not write");
+        for entry in &base {
+            let text = by_key.get(entry.key.as_str()).copied().unwrap_or(&entry.text);
+            writeln!(file, "    {:?},", text).expect("Could not write");
+        }
+        writeln!(file, "];\n").expect("Could not write");
+    }
+
+    writeln!(
+        file,
+        "static mut CURRENT_LANGUAGE: Language = Language::{};\n",
+        capitalize(I18N_LANGUAGES[0].0)
+    )
+    .expect("Could not write");
+
+    writeln!(
+        file,
+        "/// Sets the active language for [`t`], and informs the device so any page built after\n\
+         /// this call renders in the chosen locale.\n\
+         pub fn set_language(lang: Language) {{\n    \
+             unsafe {{ CURRENT_LANGUAGE = lang; }}\n    \
+             ecalls::set_language(lang as u32);\n\
+         }}\n"
+    )
+    .expect("Could not write");
+
+    writeln!(
+        file,
+        "/// Resolves a [`StringId`] to its text in the active language (see [`set_language`])."
+    )
+    .expect("Could not write");
+    writeln!(file, "pub fn t(id: StringId) -> &'static str {{").expect("Could not write");
+    writeln!(file, "    let table: &[&str] = match unsafe {{ CURRENT_LANGUAGE }} {{").expect("Could not write");
+    for (lang, _) in I18N_LANGUAGES {
+        writeln!(
+            file,
+            "        Language::{} => STRINGS_{},",
+            capitalize(lang),
+            lang.to_uppercase()
+        )
+        .expect("Could not write");
+    }
+    writeln!(file, "    }};").expect("Could not write");
+    writeln!(file, "    table[id.0 as usize]\n}}\n").expect("Could not write");
+}
+
+/// Pages with no runtime-variable part other than the active language, built on demand (rather
+/// than serialized once at build time) so they always reflect the current [`set_language`] call.
+fn make_const_pages(file: &mut File) {
+    writeln!(
+        file,
+        "/// Builds the \"V-App dashboard\" screen shown while no V-App is running, in the active\n\
+         /// language (see [`set_language`])."
+    )
+    .expect("Could not write");
+    writeln!(file, "pub fn app_dashboard_page() -> Vec<u8> {{").expect("Could not write");
+    writeln!(file, "    Page::GenericPage {{").expect("Could not write");
+    writeln!(file, "        navigation_info: None,").expect("Could not write");
+    writeln!(file, "        page_content_info: PageContentInfo {{").expect("Could not write");
+    writeln!(file, "            title: None,").expect("Could not write");
+    writeln!(file, "            top_right_icon: Icon::None,").expect("Could not write");
+    writeln!(file, "            page_content: PageContent::TextSubtext {{").expect("Could not write");
+    writeln!(file, "                text: t(STR_APP_DASHBOARD_TITLE).into(),").expect("Could not write");
+    writeln!(file, "                subtext: t(STR_APP_DASHBOARD_SUBTITLE).into(),").expect("Could not write");
+    writeln!(file, "            }},").expect("Could not write");
+    writeln!(file, "        }},").expect("Could not write");
+    writeln!(file, "    }}.serialized()").expect("Could not write");
+    writeln!(file, "}}\n").expect("Could not write");
+}
+
+/// How many [`TagValue`] entries [`make_review_pairs_content`]-style pages hold per screen. Kept
+/// as a single named constant here (rather than scattered magic numbers) so [`make_show_review`]
+/// and the content page maker agree on how a pair list is sliced into pages.
+const PAIRS_PER_PAGE: usize = 2;
+
+/// Generates `ReviewFinalKind` and the `show_review` driver: given the intro text, the full list
+/// of [`TagValue`] pairs, and which final-screen variant to show, it precomputes `n_pages`
+/// (intro + pair pages + final confirmation), streams each `review_pairs_*` page in as it's
+/// shown, and translates back/next/reject/confirm button events into page transitions, returning
+/// the final confirm/reject result. This replaces hand-rolling that loop once per caller.
+fn make_show_review(file: &mut File) {
+    writeln!(
+        file,
+        "/// Which final-screen variant [`show_review`] should show after the reviewed pairs."
+    )
+    .expect("Could not write");
+    writeln!(
+        file,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\npub enum ReviewFinalKind {{\n    LongPress,\n    ConfirmationButton,\n}}\n"
+    )
+    .expect("Could not write");
+
+    writeln!(
+        file,
+        "/// Shows a full paginated review flow: an intro screen, one screen per {} [`TagValue`]\n\
+         /// pairs, and a final confirm/reject screen (see [`ReviewFinalKind`]). Returns `true` if the\n\
+         /// user confirmed, `false` if they rejected.\n\
+         pub fn show_review(\n    \
+             intro_text: &str,\n    \
+             intro_subtext: &str,\n    \
+             pairs: &[TagValue],\n    \
+             final_text: &str,\n    \
+             final_button_text: &str,\n    \
+             final_kind: ReviewFinalKind,\n    \
+             top_right_icon: Icon,\n\
+         ) -> bool {{",
+        PAIRS_PER_PAGE
+    )
+    .expect("Could not write");
+
+    writeln!(
+        file,
+        "    let n_pair_pages = (pairs.len() + {per_page} - 1) / {per_page};\n    \
+             let n_pages = (2 + n_pair_pages) as u32;\n\n    \
+             let mut serialized_pages = Vec::with_capacity(n_pages as usize);\n    \
+             serialized_pages.push(make_review_pairs_intro(0, n_pages, top_right_icon.clone(), intro_text, intro_subtext));\n\n    \
+             let mut active_page = 0usize;\n\n    \
+             loop {{\n        \
+                 show_page_raw(&serialized_pages[active_page]);\n\n        \
+                 if active_page + 1 < n_pages as usize && serialized_pages.len() == active_page + 1 {{\n            \
+                     let next_page_index = active_page + 1;\n            \
+                     let next_page = if next_page_index == (n_pages - 1) as usize {{\n                \
+                         match final_kind {{\n                    \
+                             ReviewFinalKind::LongPress => make_review_pairs_final_longpress(\n                        \
+                                 next_page_index as u32,\n                        \
+                                 n_pages,\n                        \
+                                 top_right_icon.clone(),\n                        \
+                                 final_text,\n                        \
+                                 final_button_text,\n                    \
+                             ),\n                    \
+                             ReviewFinalKind::ConfirmationButton => make_review_pairs_final_confirmationbutton(\n                        \
+                                 next_page_index as u32,\n                        \
+                                 n_pages,\n                        \
+                                 top_right_icon.clone(),\n                        \
+                                 final_text,\n                        \
+                                 final_button_text,\n                    \
+                             ),\n                \
+                         }}\n            \
+                     }} else {{\n                \
+                         let chunk_index = next_page_index - 1;\n                \
+                         let pair_chunk = pairs.chunks({per_page}).nth(chunk_index).unwrap();\n                \
+                         make_review_pairs_content(next_page_index as u32, n_pages, top_right_icon.clone(), pair_chunk)\n            \
+                     }};\n            \
+                     serialized_pages.push(next_page);\n        \
+                 }}\n\n        \
+                 loop {{\n            \
+                     match crate::ux::get_event() {{\n                \
+                         Event::Action(Action::PreviousPage) if active_page > 0 => {{\n                    \
+                             active_page -= 1;\n                    \
+                             break;\n                \
+                         }}\n                \
+                         Event::Action(Action::NextPage) if active_page + 1 < n_pages as usize => {{\n                    \
+                             active_page += 1;\n                    \
+                             break;\n                \
+                         }}\n                \
+                         Event::Action(Action::Quit) => return false,\n                \
+                         Event::Action(Action::Confirm) => return true,\n                \
+                         _ => {{}}\n            \
+                     }}\n        \
+                 }}\n    \
+             }}\n\
+         }}\n",
+        per_page = PAIRS_PER_PAGE
+    )
+    .expect("Could not write");
 }
 
 fn main() {
+    println!("cargo:rerun-if-changed=ux_pages.ron");
+    println!("cargo:rerun-if-changed=i18n/en.ron");
+    println!("cargo:rerun-if-changed=i18n/fr.ron");
+    println!("cargo:rerun-if-changed=icon_theme.ron");
+
     let dest_path = Path::new("src/ux_generated.rs");
     let mut file = File::create(&dest_path).expect("Could not create file");
 
@@ -178,9 +407,13 @@ fn show_page_raw(page: &[u8]) {{
     )
     .expect("Could not write");
 
+    make_i18n_tables(&mut file);
+    make_icon_theme(&mut file);
     make_const_pages(&mut file);
 
-    for (fn_name, wrapped_page) in PAGE_MAKERS.iter() {
-        make_page_maker(&mut file, &wrapped_page.serialize_wrapped(), fn_name);
+    for entry in load_page_manifest() {
+        make_page_maker(&mut file, &entry.page.serialize_wrapped(), &entry.name);
     }
+
+    make_show_review(&mut file);
 }
@@ -0,0 +1,230 @@
+//! Minimal DER/ASN.1 TLV encoding for ECDSA signatures, so apps built on this SDK don't each have
+//! to hand-roll the `SEQUENCE { INTEGER r, INTEGER s }` format. `no_std`/`alloc`-only, with no
+//! curve- or ecall-specific knowledge: callers supply the raw scalars (and, for low-S
+//! normalization, the curve order) themselves.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Errors returned while parsing a DER structure produced by this module's encoders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asn1Error {
+    /// The tag byte wasn't the one expected at this position.
+    UnexpectedTag { expected: u8, got: u8 },
+    /// A length field was malformed or declared more bytes than remain.
+    InvalidLength,
+    /// The buffer ended before a declared field could be read in full.
+    UnexpectedEnd,
+    /// Extra bytes followed the structure that was parsed.
+    TrailingBytes,
+    /// An `INTEGER` had a leading `0x00` pad byte it didn't need (i.e. not the unique DER
+    /// encoding of its value).
+    NonCanonicalInteger,
+    /// An `INTEGER`'s value didn't fit in the caller's fixed-width output.
+    IntegerTooLarge,
+    /// An `INTEGER` encoded a negative value, which r/s scalars never are.
+    NegativeInteger,
+}
+
+impl fmt::Display for Asn1Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Asn1Error::UnexpectedTag { expected, got } => {
+                write!(f, "expected DER tag 0x{expected:02x}, got 0x{got:02x}")
+            }
+            Asn1Error::InvalidLength => write!(f, "invalid or truncated DER length"),
+            Asn1Error::UnexpectedEnd => write!(f, "DER structure ended early"),
+            Asn1Error::TrailingBytes => write!(f, "trailing bytes after DER structure"),
+            Asn1Error::NonCanonicalInteger => write!(f, "non-canonical DER INTEGER encoding"),
+            Asn1Error::IntegerTooLarge => write!(f, "DER INTEGER value too large"),
+            Asn1Error::NegativeInteger => write!(f, "DER INTEGER value is negative"),
+        }
+    }
+}
+
+impl core::error::Error for Asn1Error {}
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// Appends a DER length field for a value of `len` bytes (short form below 128, long form
+/// otherwise; every signature this module builds is well under 128 bytes, but this stays correct
+/// for larger structures too).
+fn write_length(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let len_bytes = (len as u64).to_be_bytes();
+    let first_nonzero = len_bytes.iter().position(|&b| b != 0).unwrap_or(len_bytes.len() - 1);
+    let significant = &len_bytes[first_nonzero..];
+    out.push(0x80 | significant.len() as u8);
+    out.extend_from_slice(significant);
+}
+
+/// Reads a DER length field, returning `(value, rest)`.
+fn read_length(der: &[u8]) -> Result<(usize, &[u8]), Asn1Error> {
+    let (&first, rest) = der.split_first().ok_or(Asn1Error::UnexpectedEnd)?;
+    if first & 0x80 == 0 {
+        return Ok((first as usize, rest));
+    }
+    let n_bytes = (first & 0x7f) as usize;
+    if n_bytes == 0 || n_bytes > 8 || rest.len() < n_bytes {
+        return Err(Asn1Error::InvalidLength);
+    }
+    let (len_bytes, rest) = rest.split_at(n_bytes);
+    let mut len: usize = 0;
+    for &b in len_bytes {
+        len = len.checked_shl(8).ok_or(Asn1Error::InvalidLength)? | b as usize;
+    }
+    Ok((len, rest))
+}
+
+/// Appends one DER `INTEGER` encoding `scalar`'s big-endian value, stripping leading zero bytes
+/// and re-inserting exactly one if the remaining high bit is set (DER integers are always
+/// signed, so an unpadded high bit would otherwise read back as negative).
+fn write_integer(scalar: &[u8], out: &mut Vec<u8>) {
+    let mut bytes = scalar;
+    while bytes.len() > 1 && bytes[0] == 0 {
+        bytes = &bytes[1..];
+    }
+    let needs_pad = bytes.first().is_some_and(|&b| b & 0x80 != 0);
+
+    out.push(TAG_INTEGER);
+    write_length(bytes.len() + needs_pad as usize, out);
+    if needs_pad {
+        out.push(0x00);
+    }
+    out.extend_from_slice(bytes);
+}
+
+/// Reads one DER `INTEGER` TLV from the front of `der`, validating that it's the unique
+/// canonical encoding of a non-negative integer and that its value fits in `N` bytes, returning
+/// it left-padded with zeros together with the rest of the buffer.
+fn read_integer<const N: usize>(der: &[u8]) -> Result<([u8; N], &[u8]), Asn1Error> {
+    let (&tag, rest) = der.split_first().ok_or(Asn1Error::UnexpectedEnd)?;
+    if tag != TAG_INTEGER {
+        return Err(Asn1Error::UnexpectedTag { expected: TAG_INTEGER, got: tag });
+    }
+    let (len, rest) = read_length(rest)?;
+    if rest.len() < len {
+        return Err(Asn1Error::UnexpectedEnd);
+    }
+    let (value, rest) = rest.split_at(len);
+
+    if value.is_empty() {
+        return Err(Asn1Error::NonCanonicalInteger);
+    }
+    if value[0] & 0x80 != 0 {
+        return Err(Asn1Error::NegativeInteger);
+    }
+    if value.len() > 1 && value[0] == 0 && value[1] & 0x80 == 0 {
+        // A leading zero that isn't there to keep the next byte's high bit from looking like a
+        // sign bit: there are two ways to encode this value, so it isn't canonical DER.
+        return Err(Asn1Error::NonCanonicalInteger);
+    }
+    // A remaining leading zero is only the sign pad, not part of the value.
+    let value = if value.len() > 1 && value[0] == 0 { &value[1..] } else { value };
+    if value.len() > N {
+        return Err(Asn1Error::IntegerTooLarge);
+    }
+
+    let mut out = [0u8; N];
+    out[N - value.len()..].copy_from_slice(value);
+    Ok((out, rest))
+}
+
+/// Returns `true` if `a > b`, comparing as big-endian unsigned integers of equal width.
+fn is_greater(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b.iter()).find(|(x, y)| x != y).is_some_and(|(x, y)| x > y)
+}
+
+/// Right-shifts a 256-bit big-endian value by one bit (i.e. divides by two, rounding down).
+fn shr1(a: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut carry = 0u8;
+    for i in 0..32 {
+        out[i] = (a[i] >> 1) | (carry << 7);
+        carry = a[i] & 1;
+    }
+    out
+}
+
+/// Subtracts `b` from `a`, both 256-bit big-endian values with `a >= b`.
+fn sub(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let diff = a[i] as i16 - b[i] as i16 - borrow;
+        if diff < 0 {
+            out[i] = (diff + 256) as u8;
+            borrow = 1;
+        } else {
+            out[i] = diff as u8;
+            borrow = 0;
+        }
+    }
+    out
+}
+
+/// BIP-62 "low-S" normalization: returns `s` unchanged if `s <= curve_order / 2`, otherwise
+/// returns `curve_order - s`. Pure big-endian arithmetic; doesn't touch any ecall, so it works
+/// the same for every curve this SDK supports.
+pub fn normalize_low_s(s: &[u8; 32], curve_order: &[u8; 32]) -> [u8; 32] {
+    let half_order = shr1(curve_order);
+    if is_greater(s, &half_order) {
+        sub(curve_order, s)
+    } else {
+        *s
+    }
+}
+
+/// Encodes a compact ECDSA `(r, s)` pair as a DER `SEQUENCE { INTEGER r, INTEGER s }`.
+///
+/// Pass `curve_order` to normalize `s` to the curve's lower half first ([`normalize_low_s`],
+/// BIP-62's "low-S" rule); pass `None` to encode `s` exactly as given.
+pub fn encode_ecdsa_signature(r: &[u8; 32], s: &[u8; 32], curve_order: Option<&[u8; 32]>) -> Vec<u8> {
+    let normalized_s;
+    let s = match curve_order {
+        Some(order) => {
+            normalized_s = normalize_low_s(s, order);
+            &normalized_s
+        }
+        None => s,
+    };
+
+    let mut body = Vec::new();
+    write_integer(r, &mut body);
+    write_integer(s, &mut body);
+
+    let mut out = Vec::with_capacity(2 + body.len());
+    out.push(TAG_SEQUENCE);
+    write_length(body.len(), &mut out);
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Parses a DER `SEQUENCE { INTEGER r, INTEGER s }` ECDSA signature, rejecting anything that
+/// isn't the unique canonical encoding of exactly that: trailing bytes, non-minimal integer
+/// padding, a negative integer, or an `r`/`s` wider than 32 bytes are all errors.
+pub fn decode_ecdsa_signature(der: &[u8]) -> Result<([u8; 32], [u8; 32]), Asn1Error> {
+    let (&tag, rest) = der.split_first().ok_or(Asn1Error::UnexpectedEnd)?;
+    if tag != TAG_SEQUENCE {
+        return Err(Asn1Error::UnexpectedTag { expected: TAG_SEQUENCE, got: tag });
+    }
+    let (len, rest) = read_length(rest)?;
+    if rest.len() < len {
+        return Err(Asn1Error::UnexpectedEnd);
+    }
+    let (body, trailing) = rest.split_at(len);
+    if !trailing.is_empty() {
+        return Err(Asn1Error::TrailingBytes);
+    }
+
+    let (r, body) = read_integer::<32>(body)?;
+    let (s, body) = read_integer::<32>(body)?;
+    if !body.is_empty() {
+        return Err(Asn1Error::TrailingBytes);
+    }
+    Ok((r, s))
+}
@@ -13,17 +13,38 @@
 //!   transmission and reception without requiring large buffers.
 //! - **Length-Prefixing**: Messages are prefixed with their length, enabling dynamic buffer allocation
 //!   only when necessary.
+//! - **Optional authenticated-encrypted session**: once a [`Session`] has been derived from a shared
+//!   secret (e.g. the ephemeral key established by the preload handshake), every chunk of a message
+//!   can be sent as ChaCha20-Poly1305 ciphertext instead of cleartext; see [`Session`] below.
+//! - **Pluggable framing**: the [`Decoder`]/[`Encoder`] traits separate "how a message is framed into
+//!   chunks" from "how chunks are moved over the wire", with [`LengthDelimitedCodec`] as the built-in
+//!   implementation backing [`receive_message`]/[`send_message`]. [`MessageStream`] uses the same
+//!   framing without ever buffering a whole message, for handlers that can consume it incrementally.
 //!
 //! Note: This module is not thread-safe. It is designed for single-threaded execution due to the use of
 //! a static mutable buffer for chunk reuse.
 
-use crate::{xrecv, xsend};
+use crate::{xrecv, xrecv_reuse, xsend, ChunkBuffer};
 use alloc::vec::Vec;
 use core::cmp::min;
-use core::convert::TryInto;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
 
 use common::comm::{ACK, CHUNK_LENGTH};
 
+/// Size in bytes of a [`Session`]'s derived keys.
+const SESSION_KEY_LENGTH: usize = 32;
+
+/// Size in bytes of the Poly1305 tag appended to every encrypted chunk.
+const TAG_LENGTH: usize = 16;
+
+/// Mode flag carried in the first byte of a message's first chunk, so the receiver knows whether
+/// to authenticate-and-decrypt the chunks that follow or to read them as cleartext.
+const MODE_CLEARTEXT: u8 = 0x00;
+const MODE_ENCRYPTED: u8 = 0x01;
+
 /// Error types that can occur during message transmission.
 #[derive(Debug)]
 pub enum MessageError {
@@ -35,6 +56,9 @@ pub enum MessageError {
     FailedToReadMessage,
     /// Error when the message length cannot be determined due to insufficient bytes.
     FailedToReadLength,
+    /// Error when an encrypted chunk fails to authenticate, or an encrypted message arrives with
+    /// no [`Session`] in scope to decrypt it.
+    AuthenticationFailed,
 }
 
 impl core::fmt::Display for MessageError {
@@ -44,108 +68,565 @@ impl core::fmt::Display for MessageError {
             MessageError::TooManyBytesReceived => write!(f, "Too many bytes received"),
             MessageError::FailedToReadMessage => write!(f, "Failed to read message"),
             MessageError::FailedToReadLength => write!(f, "Failed to read message length"),
+            MessageError::AuthenticationFailed => write!(f, "Chunk failed authentication"),
         }
     }
 }
 
 impl core::error::Error for MessageError {}
 
-/// Receives a message, handling chunked data reception and error management.
-///
-/// The function starts by attempting to read a fixed-size chunk to extract the message length.
-/// It then continues reading in chunks until the entire message is received, sending an
-/// acknowledgment (`ACK`) byte for each chunk received. Errors occur if any unexpected
-/// conditions are encountered, such as insufficient bytes or extra bytes in a chunk.
-///
-/// # Errors
-///
-/// - Returns `MessageError::FailedToReadLength` if the initial chunk is too small to contain the
-///   message length.
-/// - Returns `MessageError::TooManyBytesReceived` if unexpected extra bytes are received.
-/// - Returns `MessageError::FailedToReadMessage` if a chunk is empty or fails to be read.
-///
-/// # Returns
+/// One direction of the session transport: encrypts or decrypts chunks under a fixed key with a
+/// strictly monotonic 64-bit nonce counter, so the same key is never used with the same nonce
+/// twice even across many messages in the same direction.
+struct DirectionCipher {
+    key: [u8; SESSION_KEY_LENGTH],
+    nonce_counter: u64,
+}
+
+impl DirectionCipher {
+    fn new(key: [u8; SESSION_KEY_LENGTH]) -> Self {
+        DirectionCipher { key, nonce_counter: 0 }
+    }
+
+    fn next_nonce(&mut self) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&self.nonce_counter.to_be_bytes());
+        self.nonce_counter = self
+            .nonce_counter
+            .checked_add(1)
+            .expect("session nonce counter exhausted");
+        nonce
+    }
+
+    fn encrypt(&mut self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.key));
+        let nonce = self.next_nonce();
+        cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: ad })
+            .expect("encryption with a fresh nonce cannot fail")
+    }
+
+    fn decrypt(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, MessageError> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.key));
+        let nonce = self.next_nonce();
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: ciphertext, aad: ad })
+            .map_err(|_| MessageError::AuthenticationFailed)
+    }
+}
+
+/// An authenticated-encrypted session layered on top of the chunked protocol below. Derived once
+/// a shared secret is available (e.g. the `ephemeral_sk` that `handler_preload_vapp` returns after
+/// the preload handshake), and then passed to [`receive_message`]/[`send_message`] (or a
+/// [`MessageStream`]) for as long as the two endpoints want their traffic encrypted; cleartext
+/// exchanges (such as registering a V-App, which happens before any shared secret exists) simply
+/// pass `None` instead.
 ///
-/// - On success, returns `Ok(Vec<u8>)` with the received message data.
-pub fn receive_message() -> Result<Vec<u8>, MessageError> {
-    let first_chunk = xrecv(256);
+/// A session keeps one [`DirectionCipher`] per direction, so the initiator's send key is the
+/// responder's receive key and vice versa: neither direction's nonce counter is ever shared with
+/// the other, even though both derive from the same secret.
+pub struct Session {
+    send: DirectionCipher,
+    receive: DirectionCipher,
+}
 
-    // Ensure we have at least 4 bytes for the length.
-    if first_chunk.len() < 4 {
-        return Err(MessageError::FailedToReadLength);
+impl Session {
+    /// Derives a `Session` from a 32-byte secret shared by both endpoints. `initiator` must be
+    /// `true` on exactly one side of the session (e.g. the client) and `false` on the other (the
+    /// V-App), so the two ends agree on which derived key is used to send and which to receive.
+    pub fn derive(shared_secret: &[u8; 32], initiator: bool) -> Self {
+        let key_a = Self::derive_key(shared_secret, 1);
+        let key_b = Self::derive_key(shared_secret, 2);
+        let (send_key, receive_key) = if initiator { (key_a, key_b) } else { (key_b, key_a) };
+        Session {
+            send: DirectionCipher::new(send_key),
+            receive: DirectionCipher::new(receive_key),
+        }
     }
 
-    // Extract the message length.
-    let length = u32::from_be_bytes(first_chunk[0..4].try_into().unwrap()) as usize;
+    fn derive_key(shared_secret: &[u8; 32], label: u8) -> [u8; SESSION_KEY_LENGTH] {
+        let mut hasher = Sha256::new();
+        hasher.update(shared_secret);
+        hasher.update([label]);
+        hasher.finalize().into()
+    }
+}
 
-    // Check for unexpected extra bytes.
-    if first_chunk.len() > 4 + length {
-        return Err(MessageError::TooManyBytesReceived);
+/// Decrypts (if `encrypted`) and appends one chunk's body to `result`, returning how many
+/// plaintext bytes were delivered. Shared by [`LengthDelimitedCodec::decode`] and
+/// [`MessageStream::receive`], which differ only in what they do with the decoded bytes.
+fn append_body(
+    session: &mut Option<&mut Session>,
+    encrypted: bool,
+    length_prefix: &[u8; 4],
+    body: &[u8],
+    result: &mut Vec<u8>,
+) -> Result<usize, MessageError> {
+    if encrypted {
+        let plaintext = session
+            .as_mut()
+            .expect("checked for Some when the mode flag was read")
+            .receive
+            .decrypt(length_prefix, body)?;
+        result.extend_from_slice(&plaintext);
+        Ok(plaintext.len())
+    } else {
+        result.extend_from_slice(body);
+        Ok(body.len())
     }
+}
 
-    // Initialize the result with the data from the first chunk.
-    let mut result = Vec::with_capacity(length);
-    result.extend_from_slice(&first_chunk[4..]);
+/// A value decoded incrementally from a stream of raw chunks, one [`Decoder::decode`] call per
+/// chunk. Returns `Ok(None)` while more chunks are needed, `Ok(Some(item))` once `item` is
+/// complete.
+pub trait Decoder {
+    type Item;
 
-    // Calculate the remaining bytes to read.
-    let mut remaining_bytes = length - result.len();
+    fn decode(&mut self, chunk: &[u8]) -> Result<Option<Self::Item>, MessageError>;
+}
 
-    while remaining_bytes > 0 {
-        // Send ACK to maintain the alternating protocol.
-        xsend(&ACK);
+/// The destination an [`Encoder`] pushes wire chunks into. `send_chunk` owns both sending the
+/// chunk and waiting for the peer to be ready for the next one, so an `Encoder` only has to decide
+/// how to split its item into chunks.
+pub trait ChunkSink {
+    fn send_chunk(&mut self, chunk: &[u8]);
+}
+
+/// Encodes a complete `Item` into one or more wire chunks, pushed through a [`ChunkSink`].
+pub trait Encoder<Item: ?Sized> {
+    fn encode(&mut self, item: &Item, out: &mut impl ChunkSink);
+}
+
+/// What [`LengthDelimitedCodec::decode`] is waiting for next.
+enum DecodeState {
+    /// No header parsed yet; the next chunk must start with the mode flag and 4-byte length.
+    AwaitingHeader,
+    /// A header has been parsed; `remaining` plaintext bytes are still to be appended to `result`.
+    Reading {
+        encrypted: bool,
+        length_prefix: [u8; 4],
+        remaining: usize,
+        result: Vec<u8>,
+    },
+}
+
+/// The built-in [`Decoder`]/[`Encoder`] implementing this module's length-prefixed, optionally
+/// encrypted framing: a mode flag and big-endian length in the first chunk, followed by as many
+/// chunks as needed to carry the (optionally encrypted) message body.
+pub struct LengthDelimitedCodec<'s> {
+    session: Option<&'s mut Session>,
+    state: DecodeState,
+}
+
+impl<'s> LengthDelimitedCodec<'s> {
+    pub fn new(session: Option<&'s mut Session>) -> Self {
+        LengthDelimitedCodec { session, state: DecodeState::AwaitingHeader }
+    }
+}
+
+impl<'s> Decoder for LengthDelimitedCodec<'s> {
+    type Item = Vec<u8>;
+
+    fn decode(&mut self, chunk: &[u8]) -> Result<Option<Vec<u8>>, MessageError> {
+        match &self.state {
+            DecodeState::AwaitingHeader => {
+                if chunk.len() < 5 {
+                    return Err(MessageError::FailedToReadLength);
+                }
+
+                let mode = chunk[0];
+                let mut length_prefix = [0u8; 4];
+                length_prefix.copy_from_slice(&chunk[1..5]);
+                let length = u32::from_be_bytes(length_prefix) as usize;
+
+                let encrypted = match mode {
+                    MODE_CLEARTEXT => false,
+                    MODE_ENCRYPTED => true,
+                    _ => return Err(MessageError::InvalidLength),
+                };
+                // Reject both directions of mismatch: an encrypted message with no session to
+                // decrypt it with, and (just as important) a cleartext message when a session
+                // *is* in scope -- otherwise a malicious transport could flip the mode flag to
+                // downgrade an authenticated message to unauthenticated cleartext.
+                if encrypted != self.session.is_some() {
+                    return Err(MessageError::AuthenticationFailed);
+                }
+
+                let body = &chunk[5..];
+                if body.len() > (if encrypted { TAG_LENGTH } else { 0 }) + length {
+                    return Err(MessageError::TooManyBytesReceived);
+                }
+
+                let mut result = Vec::with_capacity(length);
+                let delivered =
+                    append_body(&mut self.session, encrypted, &length_prefix, body, &mut result)?;
 
-        let chunk = xrecv(CHUNK_LENGTH);
+                if delivered == length {
+                    return Ok(Some(result));
+                }
+                self.state = DecodeState::Reading {
+                    encrypted,
+                    length_prefix,
+                    remaining: length - delivered,
+                    result,
+                };
+                Ok(None)
+            }
+            DecodeState::Reading { .. } => {
+                if chunk.is_empty() {
+                    return Err(MessageError::FailedToReadMessage);
+                }
 
-        if chunk.is_empty() {
-            return Err(MessageError::FailedToReadMessage);
+                let DecodeState::Reading { encrypted, length_prefix, remaining, mut result } =
+                    core::mem::replace(&mut self.state, DecodeState::AwaitingHeader)
+                else {
+                    unreachable!()
+                };
+
+                let max_chunk_bytes = remaining + if encrypted { TAG_LENGTH } else { 0 };
+                if chunk.len() > max_chunk_bytes {
+                    return Err(MessageError::TooManyBytesReceived);
+                }
+
+                let delivered =
+                    append_body(&mut self.session, encrypted, &length_prefix, chunk, &mut result)?;
+                let remaining = remaining - delivered;
+
+                if remaining == 0 {
+                    Ok(Some(result))
+                } else {
+                    self.state = DecodeState::Reading { encrypted, length_prefix, remaining, result };
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+impl<'s> Encoder<[u8]> for LengthDelimitedCodec<'s> {
+    fn encode(&mut self, item: &[u8], out: &mut impl ChunkSink) {
+        let encrypted = self.session.is_some();
+        let mode = if encrypted { MODE_ENCRYPTED } else { MODE_CLEARTEXT };
+        let length_be = (item.len() as u32).to_be_bytes();
+
+        let overhead = 5 + if encrypted { TAG_LENGTH } else { 0 };
+        let first_chunk_msg_bytes = min(CHUNK_LENGTH.saturating_sub(overhead), item.len());
+        let first_body = &item[..first_chunk_msg_bytes];
+        let first_wire_body = if encrypted {
+            self.session.as_mut().unwrap().send.encrypt(&length_be, first_body)
+        } else {
+            first_body.to_vec()
+        };
+        out.send_chunk(&[&[mode], &length_be[..], &first_wire_body].concat());
+
+        let max_chunk_msg_bytes = CHUNK_LENGTH.saturating_sub(if encrypted { TAG_LENGTH } else { 0 });
+        let mut total_bytes_sent = first_chunk_msg_bytes;
+        while total_bytes_sent < item.len() {
+            let end_idx = min(total_bytes_sent + max_chunk_msg_bytes, item.len());
+            let chunk = &item[total_bytes_sent..end_idx];
+            let wire_chunk = if encrypted {
+                self.session.as_mut().unwrap().send.encrypt(&length_be, chunk)
+            } else {
+                chunk.to_vec()
+            };
+            out.send_chunk(&wire_chunk);
+            total_bytes_sent = end_idx;
         }
+    }
+}
+
+/// The [`ChunkSink`] backing [`send_message`]: sends a chunk immediately, but waits for an `ACK`
+/// before every chunk after the first, mirroring this module's alternating protocol.
+struct CommChunkSink {
+    started: bool,
+}
 
-        if chunk.len() > remaining_bytes {
+impl ChunkSink for CommChunkSink {
+    fn send_chunk(&mut self, chunk: &[u8]) {
+        if self.started {
+            let _ = xrecv(CHUNK_LENGTH);
+        }
+        xsend(chunk);
+        self.started = true;
+    }
+}
+
+/// Receives a message's bytes one chunk at a time, without ever materializing the whole message
+/// in memory, for handlers that can consume it incrementally (e.g. feeding each chunk straight
+/// into a running hash instead of hashing a fully-buffered `Vec<u8>` afterwards). Uses the same
+/// framing as [`LengthDelimitedCodec`]; prefer [`receive_message`] when the full message is needed
+/// in memory anyway.
+pub struct MessageStream<'s> {
+    session: Option<&'s mut Session>,
+    buf: ChunkBuffer,
+}
+
+impl<'s> MessageStream<'s> {
+    pub fn new(session: Option<&'s mut Session>) -> Self {
+        MessageStream { session, buf: ChunkBuffer::new() }
+    }
+
+    /// Reads a full message, calling `on_chunk` with each chunk of plaintext as it is decoded.
+    /// Returns the total message length once every chunk has been delivered. Chunks are read with
+    /// [`xrecv_reuse`] into this stream's own [`ChunkBuffer`], so only each chunk's decrypted
+    /// plaintext is allocated, never the whole message.
+    pub fn receive(&mut self, mut on_chunk: impl FnMut(&[u8])) -> Result<usize, MessageError> {
+        let first_chunk = xrecv_reuse(&mut self.buf, 256);
+        if first_chunk.len() < 5 {
+            return Err(MessageError::FailedToReadLength);
+        }
+
+        let mode = first_chunk[0];
+        let mut length_prefix = [0u8; 4];
+        length_prefix.copy_from_slice(&first_chunk[1..5]);
+        let length = u32::from_be_bytes(length_prefix) as usize;
+
+        let encrypted = match mode {
+            MODE_CLEARTEXT => false,
+            MODE_ENCRYPTED => true,
+            _ => return Err(MessageError::InvalidLength),
+        };
+        // See the identical check in `LengthDelimitedCodec::decode`: a session in scope must
+        // reject a downgrade to `MODE_CLEARTEXT`, not just the reverse.
+        if encrypted != self.session.is_some() {
+            return Err(MessageError::AuthenticationFailed);
+        }
+
+        let body = &first_chunk[5..];
+        if body.len() > (if encrypted { TAG_LENGTH } else { 0 }) + length {
             return Err(MessageError::TooManyBytesReceived);
         }
+        let mut delivered = deliver_chunk(
+            &mut self.session,
+            encrypted,
+            &length_prefix,
+            body,
+            &mut on_chunk,
+        )?;
+
+        while delivered < length {
+            xsend(&ACK);
+            let chunk = xrecv_reuse(&mut self.buf, CHUNK_LENGTH);
+            if chunk.is_empty() {
+                return Err(MessageError::FailedToReadMessage);
+            }
+
+            let max_chunk_bytes = (length - delivered) + if encrypted { TAG_LENGTH } else { 0 };
+            if chunk.len() > max_chunk_bytes {
+                return Err(MessageError::TooManyBytesReceived);
+            }
+
+            delivered += deliver_chunk(
+                &mut self.session,
+                encrypted,
+                &length_prefix,
+                chunk,
+                &mut on_chunk,
+            )?;
+        }
 
-        result.extend_from_slice(&chunk);
-        remaining_bytes -= chunk.len();
+        Ok(length)
     }
+}
 
-    Ok(result)
+/// Decrypts (if `encrypted`) one chunk's body and hands the plaintext to `on_chunk`, returning how
+/// many plaintext bytes were delivered. The [`MessageStream`] counterpart to [`append_body`],
+/// which instead accumulates into a `Vec`.
+fn deliver_chunk(
+    session: &mut Option<&mut Session>,
+    encrypted: bool,
+    length_prefix: &[u8; 4],
+    body: &[u8],
+    on_chunk: &mut impl FnMut(&[u8]),
+) -> Result<usize, MessageError> {
+    if encrypted {
+        let plaintext = session
+            .as_mut()
+            .expect("checked for Some when the mode flag was read")
+            .receive
+            .decrypt(length_prefix, body)?;
+        on_chunk(&plaintext);
+        Ok(plaintext.len())
+    } else {
+        on_chunk(body);
+        Ok(body.len())
+    }
+}
+
+/// Receives a message, handling chunked data reception and error management.
+///
+/// Thin wrapper around [`LengthDelimitedCodec`]: each chunk is fed to [`Decoder::decode`], sending
+/// an `ACK` and reading another chunk whenever it asks for more. Chunks are read with
+/// [`xrecv_reuse`] into a single [`ChunkBuffer`], so the steady-state loop allocates nothing per
+/// chunk; only the final, fully assembled message is allocated, once, by [`LengthDelimitedCodec`].
+///
+/// # Errors
+///
+/// - Returns `MessageError::FailedToReadLength` if the initial chunk is too small to contain the
+///   mode flag and message length.
+/// - Returns `MessageError::TooManyBytesReceived` if unexpected extra bytes are received.
+/// - Returns `MessageError::FailedToReadMessage` if a chunk is empty or fails to be read.
+/// - Returns `MessageError::AuthenticationFailed` if an encrypted chunk fails to authenticate, or
+///   if the message claims to be encrypted but no `session` was provided.
+///
+/// # Returns
+///
+/// - On success, returns `Ok(Vec<u8>)` with the received (and, if applicable, decrypted) message
+///   data.
+pub fn receive_message(session: Option<&mut Session>) -> Result<Vec<u8>, MessageError> {
+    let mut codec = LengthDelimitedCodec::new(session);
+    let mut buf = ChunkBuffer::new();
+    let mut chunk = xrecv_reuse(&mut buf, 256);
+    loop {
+        if let Some(item) = codec.decode(chunk)? {
+            return Ok(item);
+        }
+        xsend(&ACK);
+        chunk = xrecv_reuse(&mut buf, CHUNK_LENGTH);
+    }
 }
 
 /// Sends a message, managing chunking and acknowledgment control for transmission.
 ///
-/// The function begins by encoding the message length in big-endian format and sending an initial
-/// chunk containing this length along with part of the message (if any). It then continues sending
-/// chunks, waiting for an acknowledgment (`ACK`) byte from the receiver before each chunk is sent.
-/// The process ensures that messages are transmitted sequentially and fully.
+/// Thin wrapper around [`LengthDelimitedCodec`]: the whole message is handed to
+/// [`Encoder::encode`], which pushes each wire chunk through a [`CommChunkSink`].
 ///
 /// # Parameters
 ///
 /// - `msg`: A reference to the message bytes (`&[u8]`) that should be sent.
+/// - `session`: `Some` to send this message under an authenticated-encrypted session, `None` to
+///   send it as cleartext (e.g. for the register/get-info commands, which run before any session
+///   exists).
 ///
 /// The function does not return a value, nor any error.
 /// On native execution, the function will panic if the underlying calls to `xsend` or `xrecv` panic.
 /// On Risc-V targets, communication failure causes the ECALL to fail, which will arrest the execution of the VM.
-pub fn send_message(msg: &[u8]) {
-    // Encode the message length in big-endian format.
-    let length_be = (msg.len() as u32).to_be_bytes();
+pub fn send_message(msg: &[u8], session: Option<&mut Session>) {
+    let mut codec = LengthDelimitedCodec::new(session);
+    let mut sink = CommChunkSink { started: false };
+    codec.encode(msg, &mut sink);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct VecSink {
+        chunks: Vec<Vec<u8>>,
+    }
+
+    impl ChunkSink for VecSink {
+        fn send_chunk(&mut self, chunk: &[u8]) {
+            self.chunks.push(chunk.to_vec());
+        }
+    }
 
-    // Calculate how much of the message fits in the first chunk.
-    let first_chunk_msg_bytes = min(CHUNK_LENGTH - 4, msg.len());
+    fn encode(msg: &[u8], session: Option<&mut Session>) -> Vec<Vec<u8>> {
+        let mut codec = LengthDelimitedCodec::new(session);
+        let mut sink = VecSink { chunks: Vec::new() };
+        codec.encode(msg, &mut sink);
+        sink.chunks
+    }
+
+    fn decode(chunks: &[Vec<u8>], session: Option<&mut Session>) -> Result<Vec<u8>, MessageError> {
+        let mut codec = LengthDelimitedCodec::new(session);
+        for chunk in chunks {
+            if let Some(item) = codec.decode(chunk)? {
+                return Ok(item);
+            }
+        }
+        Err(MessageError::FailedToReadMessage)
+    }
 
-    // Send the initial chunk containing the length and part of the message.
-    xsend(&[&length_be, &msg[..first_chunk_msg_bytes]].concat());
+    #[test]
+    fn cleartext_round_trip_without_a_session() {
+        let msg = b"hello cleartext".to_vec();
+        let chunks = encode(&msg, None);
+        assert_eq!(decode(&chunks, None).unwrap(), msg);
+    }
 
-    let mut total_bytes_sent = first_chunk_msg_bytes;
+    #[test]
+    fn encrypted_round_trip_with_matching_sessions() {
+        let secret = [7u8; 32];
+        let mut sender = Session::derive(&secret, true);
+        let mut receiver = Session::derive(&secret, false);
 
-    // Send the remaining chunks.
-    while total_bytes_sent < msg.len() {
-        // Wait for ACK to maintain the alternating protocol.
-        let _ = xrecv(CHUNK_LENGTH);
+        let msg = b"hello encrypted".to_vec();
+        let chunks = encode(&msg, Some(&mut sender));
+        assert_eq!(decode(&chunks, Some(&mut receiver)).unwrap(), msg);
+    }
 
-        let end_idx = min(total_bytes_sent + CHUNK_LENGTH, msg.len());
-        let chunk = &msg[total_bytes_sent..end_idx];
+    #[test]
+    fn encrypted_round_trip_across_multiple_chunks() {
+        let secret = [9u8; 32];
+        let mut sender = Session::derive(&secret, true);
+        let mut receiver = Session::derive(&secret, false);
 
-        xsend(chunk);
-        total_bytes_sent = end_idx;
+        let msg = alloc::vec![0xABu8; CHUNK_LENGTH * 3 + 17];
+        let chunks = encode(&msg, Some(&mut sender));
+        assert!(chunks.len() > 1);
+        assert_eq!(decode(&chunks, Some(&mut receiver)).unwrap(), msg);
+    }
+
+    #[test]
+    fn rejects_encrypted_message_with_no_session() {
+        let secret = [1u8; 32];
+        let mut sender = Session::derive(&secret, true);
+
+        let msg = b"no one to decrypt me".to_vec();
+        let chunks = encode(&msg, Some(&mut sender));
+
+        assert!(matches!(decode(&chunks, None), Err(MessageError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn rejects_cleartext_downgrade_when_a_session_is_in_scope() {
+        let secret = [2u8; 32];
+        let mut receiver = Session::derive(&secret, false);
+
+        // A cleartext message, even though the receiver has a session in scope: a malicious
+        // transport flipping the mode-flag byte must not be able to downgrade it.
+        let msg = b"forged cleartext".to_vec();
+        let chunks = encode(&msg, None);
+
+        assert!(matches!(
+            decode(&chunks, Some(&mut receiver)),
+            Err(MessageError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn rejects_tampered_ciphertext() {
+        let secret = [3u8; 32];
+        let mut sender = Session::derive(&secret, true);
+        let mut receiver = Session::derive(&secret, false);
+
+        let msg = b"hello encrypted".to_vec();
+        let mut chunks = encode(&msg, Some(&mut sender));
+        let last = chunks.last_mut().unwrap();
+        let tamper_idx = last.len() - 1;
+        last[tamper_idx] ^= 0xff;
+
+        assert!(matches!(
+            decode(&chunks, Some(&mut receiver)),
+            Err(MessageError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn rejects_sessions_derived_with_the_same_initiator_flag() {
+        // Both ends deriving with the same `initiator` value means their send/receive keys don't
+        // line up, so this must fail the same way a session with the wrong secret would.
+        let secret = [4u8; 32];
+        let mut sender = Session::derive(&secret, true);
+        let mut receiver = Session::derive(&secret, true);
+
+        let msg = b"hello".to_vec();
+        let chunks = encode(&msg, Some(&mut sender));
+
+        assert!(matches!(
+            decode(&chunks, Some(&mut receiver)),
+            Err(MessageError::AuthenticationFailed)
+        ));
     }
 }
@@ -0,0 +1,678 @@
+//! Elliptic-curve primitives exposed to V-Apps: master-fingerprint computation,
+//! BIP-32/SLIP-0010 hierarchical-deterministic key derivation, and secp256k1 public-key recovery
+//! and BIP-340 Schnorr signing/verification.
+//!
+//! The BIP-39 seed never leaves the host, so every curve here is ultimately backed by the
+//! `get_master_fingerprint` and `derive_hd_node` ecalls; this module only gives each supported
+//! curve its own zero-sized marker type implementing [`Curve`], so that callers can select a
+//! curve at the type level instead of threading a runtime curve id around.
+//!
+//! [`Secp256k1::recover`] and the BIP-340 Schnorr methods are the exception: recovery only
+//! touches public data (it is given the message hash and signature, not a seed-derived key), and
+//! verification doesn't touch a seed-derived key either, so rather than a single-purpose ecall
+//! they're built out of the generic modular-arithmetic and curve-point ecalls (`bn_*`/`ecfp_*`).
+
+use crate::ecalls;
+use zeroize::Zeroizing;
+
+/// Numeric curve identifiers understood by the curve-related `ecalls`.
+#[repr(u32)]
+enum CurveId {
+    Secp256k1 = 0,
+    Ed25519 = 1,
+    Secp256r1 = 2,
+}
+
+/// A BIP-32/SLIP-0010 hierarchical-deterministic node: a private key together with its chain
+/// code.
+pub struct HdNode {
+    pub chaincode: [u8; 32],
+    pub privkey: Zeroizing<[u8; 32]>,
+}
+
+/// An elliptic curve usable for HD key derivation and master-fingerprint computation.
+pub trait Curve {
+    /// Returns the fingerprint of the master public key for this curve, computed as the first
+    /// 32 bits of `ripemd160(sha256(pk))`, where `pk` is the compressed public key.
+    fn get_master_fingerprint() -> u32;
+
+    /// Derives the HD node at `path` (a sequence of BIP-32 indexes, hardened ones having the
+    /// top bit set) from the seed held by the host.
+    fn derive_hd_node(path: &[u32]) -> Result<HdNode, &'static str>;
+}
+
+/// Implements [`Curve`] for a zero-sized marker type by forwarding to the `ecalls` with the
+/// given curve id.
+macro_rules! impl_curve {
+    ($name:ident, $id:expr, $doc:expr) => {
+        #[doc = $doc]
+        pub struct $name;
+
+        impl Curve for $name {
+            fn get_master_fingerprint() -> u32 {
+                ecalls::get_master_fingerprint($id as u32)
+            }
+
+            fn derive_hd_node(path: &[u32]) -> Result<HdNode, &'static str> {
+                let mut privkey = [0u8; 32];
+                let mut chaincode = [0u8; 32];
+
+                // SAFETY: `privkey` and `chaincode` are 32-byte stack buffers, matching what
+                // `derive_hd_node` requires; `path` is a valid slice owned by the caller.
+                let ok = unsafe {
+                    ecalls::derive_hd_node(
+                        $id as u32,
+                        path.as_ptr(),
+                        path.len(),
+                        privkey.as_mut_ptr(),
+                        chaincode.as_mut_ptr(),
+                    )
+                };
+
+                if ok == 0 {
+                    return Err("Failed to derive HD node");
+                }
+
+                Ok(HdNode {
+                    chaincode,
+                    privkey: Zeroizing::new(privkey),
+                })
+            }
+        }
+    };
+}
+
+impl_curve!(
+    Secp256k1,
+    CurveId::Secp256k1,
+    "The secp256k1 curve used by Bitcoin, Ethereum, and most other chains."
+);
+
+/// The secp256k1 field prime `p`.
+#[rustfmt::skip]
+const FIELD_SIZE: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xfc, 0x2f,
+];
+
+/// The order `n` of the secp256k1 curve.
+#[rustfmt::skip]
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b,
+    0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// `(p + 1) / 4`: since `p ≡ 3 (mod 4)`, a field element's square root (when it exists) is
+/// `element^SQR_EXPONENT mod p`.
+#[rustfmt::skip]
+const SQR_EXPONENT: [u8; 32] = [
+    0x3f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xbf, 0xff, 0xff, 0x0c,
+];
+
+/// The generator `G`, SEC1 uncompressed (`0x04 || X || Y`).
+#[rustfmt::skip]
+const GENERATOR: [u8; 65] = {
+    let mut g = [0u8; 65];
+    g[0] = 0x04;
+    let x: [u8; 32] = [
+        0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac,
+        0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b, 0x07,
+        0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9,
+        0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+    ];
+    let y: [u8; 32] = [
+        0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65,
+        0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08, 0xa8,
+        0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19,
+        0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10, 0xd4, 0xb8,
+    ];
+    let mut i = 0;
+    while i < 32 {
+        g[1 + i] = x[i];
+        g[33 + i] = y[i];
+        i += 1;
+    }
+    g
+};
+
+/// Adds two 256-bit big-endian values with no modular reduction, returning the sum truncated to
+/// 32 bytes together with whether it overflowed. Used only to shift `r` by the curve order when
+/// recovering from a high recovery id; every other field/scalar operation below goes through the
+/// `bn_*` ecalls instead.
+fn add_no_mod(a: &[u8; 32], b: &[u8; 32]) -> ([u8; 32], bool) {
+    let mut out = [0u8; 32];
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        let sum = a[i] as u16 + b[i] as u16 + carry;
+        out[i] = sum as u8;
+        carry = sum >> 8;
+    }
+    (out, carry != 0)
+}
+
+fn is_less_than(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b.iter()).find(|(x, y)| x != y).is_some_and(|(x, y)| x < y)
+}
+
+/// Solves `y² = x³ + 7 (mod p)` for `y`, returning an error if `x` is not the X coordinate of a
+/// curve point. Used both to lift a compact ECDSA signature's `r` to the nonce point in
+/// [`Secp256k1::recover`], and to lift a BIP-340 x-only public key to a full point.
+fn curve_y_from_x(x: &[u8; 32]) -> Result<[u8; 32], &'static str> {
+    const SEVEN: [u8; 32] = {
+        let mut b = [0u8; 32];
+        b[31] = 7;
+        b
+    };
+
+    let mut x2 = [0u8; 32];
+    let mut x3 = [0u8; 32];
+    let mut y_squared = [0u8; 32];
+    unsafe {
+        if ecalls::bn_multm(x2.as_mut_ptr(), x.as_ptr(), x.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+            return Err("Field multiplication failed");
+        }
+        if ecalls::bn_multm(x3.as_mut_ptr(), x2.as_ptr(), x.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+            return Err("Field multiplication failed");
+        }
+        if ecalls::bn_addm(y_squared.as_mut_ptr(), x3.as_ptr(), SEVEN.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+            return Err("Field addition failed");
+        }
+    }
+
+    // p ≡ 3 (mod 4), so the square root (if it exists) is y_squared^SQR_EXPONENT mod p.
+    let mut y = [0u8; 32];
+    let mut y_check = [0u8; 32];
+    unsafe {
+        if ecalls::bn_powm(y.as_mut_ptr(), y_squared.as_ptr(), SQR_EXPONENT.as_ptr(), 32, FIELD_SIZE.as_ptr(), 32) == 0 {
+            return Err("Field exponentiation failed");
+        }
+        if ecalls::bn_multm(y_check.as_mut_ptr(), y.as_ptr(), y.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+            return Err("Field multiplication failed");
+        }
+    }
+    if y_check != y_squared {
+        return Err("x is not the X coordinate of a curve point");
+    }
+
+    Ok(y)
+}
+
+/// Negates `scalar` modulo the curve order, i.e. returns `n - scalar`. `scalar` must already be
+/// reduced mod `n`.
+fn negate_scalar(scalar: &[u8; 32]) -> Result<[u8; 32], &'static str> {
+    let mut out = [0u8; 32];
+    unsafe {
+        if ecalls::bn_subm(out.as_mut_ptr(), [0u8; 32].as_ptr(), scalar.as_ptr(), CURVE_ORDER.as_ptr(), 32) == 0 {
+            return Err("Scalar negation failed");
+        }
+    }
+    Ok(out)
+}
+
+/// `SHA256(SHA256(tag) || SHA256(tag) || msgs...)`, the tagged-hash construction BIP-340 uses to
+/// domain-separate its auxiliary, nonce and challenge hashes from each other and from unrelated
+/// uses of SHA-256.
+fn tagged_hash(tag: &[u8], msgs: &[&[u8]]) -> [u8; 32] {
+    use crate::hash::Sha256;
+    use common::accumulator::Hasher;
+
+    let mut tag_hash = [0u8; 32];
+    let mut tag_hasher = Sha256::new();
+    tag_hasher.update(tag);
+    tag_hasher.digest(&mut tag_hash);
+
+    let mut hasher = Sha256::new();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    for msg in msgs {
+        hasher.update(msg);
+    }
+    let mut out = [0u8; 32];
+    hasher.digest(&mut out);
+    out
+}
+
+impl Secp256k1 {
+    /// Derives the public key for the key at `path`, SEC1 uncompressed (`0x04 || X || Y`).
+    pub fn derive_pubkey(path: &[u32]) -> Result<[u8; 65], &'static str> {
+        let hd_node = Self::derive_hd_node(path)?;
+
+        let mut pubkey = [0u8; 65];
+        pubkey[0] = 0x04;
+        unsafe {
+            if ecalls::ecfp_scalar_mult(
+                CurveId::Secp256k1 as u32,
+                pubkey.as_mut_ptr(),
+                GENERATOR.as_ptr(),
+                hd_node.privkey.as_ptr(),
+                32,
+            ) == 0
+            {
+                return Err("Scalar multiplication failed");
+            }
+        }
+        Ok(pubkey)
+    }
+
+    /// Recovers the 64-byte uncompressed public key (`X || Y`) that produced a compact ECDSA
+    /// signature `(r, s)` over `msg_hash`, given the 2-bit recovery id `v` (`0..=3`).
+    ///
+    /// The low bit of `v` is the Y-parity of the signature's nonce point `R`; the high bit says
+    /// whether `r` needs the curve order added back in before it can be lifted to `R` (the rare
+    /// case where, during signing, `R`'s X coordinate reduced mod `n` wrapped past the curve
+    /// order). Implements the standard ECDSA public-key-recovery algorithm:
+    /// `Q = r⁻¹ · (s·R − z·G)`, with `z` the message hash reduced mod the curve order.
+    pub fn recover(
+        msg_hash: &[u8; 32],
+        v: u8,
+        r: &[u8; 32],
+        s: &[u8; 32],
+    ) -> Result<[u8; 64], &'static str> {
+        if *r == [0u8; 32] || *s == [0u8; 32] {
+            return Err("Invalid signature: r or s is zero");
+        }
+        if v > 3 {
+            return Err("Invalid recovery id");
+        }
+
+        // Lift `r` to the X coordinate of `R`, adding the curve order back in if the high bit of
+        // the recovery id says it wrapped.
+        let x_bytes = if v & 2 != 0 {
+            let (sum, overflow) = add_no_mod(r, &CURVE_ORDER);
+            if overflow || !is_less_than(&sum, &FIELD_SIZE) {
+                return Err("Invalid recovery id");
+            }
+            sum
+        } else {
+            *r
+        };
+
+        let mut y = curve_y_from_x(&x_bytes).map_err(|_| "r is not the X coordinate of a curve point")?;
+
+        // Flip the parity of `y` to match the low bit of the recovery id, if needed.
+        let y_is_odd = y[31] & 1 == 1;
+        if y_is_odd != (v & 1 != 0) {
+            let mut y_neg = [0u8; 32];
+            unsafe {
+                if ecalls::bn_subm(y_neg.as_mut_ptr(), [0u8; 32].as_ptr(), y.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+                    return Err("Field negation failed");
+                }
+            }
+            y = y_neg;
+        }
+
+        let mut r_point = [0u8; 65];
+        r_point[0] = 0x04;
+        r_point[1..33].copy_from_slice(&x_bytes);
+        r_point[33..65].copy_from_slice(&y);
+
+        // z = msg_hash mod n.
+        let mut z = [0u8; 32];
+        // s·R.
+        let mut s_r = [0u8; 65];
+        // z·G.
+        let mut z_g = [0u8; 65];
+        unsafe {
+            if ecalls::bn_modm(z.as_mut_ptr(), msg_hash.as_ptr(), 32, CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar reduction failed");
+            }
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, s_r.as_mut_ptr(), r_point.as_ptr(), s.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, z_g.as_mut_ptr(), GENERATOR.as_ptr(), z.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+        }
+
+        // diff = s·R − z·G, i.e. s·R + (−z·G), and −(x, y) = (x, p − y).
+        let mut z_g_neg = z_g;
+        unsafe {
+            if ecalls::bn_subm(z_g_neg[33..65].as_mut_ptr(), [0u8; 32].as_ptr(), z_g[33..65].as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+                return Err("Field negation failed");
+            }
+        }
+
+        let mut diff = [0u8; 65];
+        let mut r_inv = [0u8; 32];
+        let mut q = [0u8; 65];
+        unsafe {
+            if ecalls::ecfp_add_point(CurveId::Secp256k1 as u32, diff.as_mut_ptr(), s_r.as_ptr(), z_g_neg.as_ptr()) == 0 {
+                return Err("Point addition failed");
+            }
+            // The curve order is prime, so a modular inverse mod n is well-defined here.
+            if ecalls::bn_modinv_prime(r_inv.as_mut_ptr(), r.as_ptr(), CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Modular inversion failed");
+            }
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, q.as_mut_ptr(), diff.as_ptr(), r_inv.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+        }
+
+        let mut pubkey = [0u8; 64];
+        pubkey.copy_from_slice(&q[1..65]);
+        Ok(pubkey)
+    }
+
+    /// Signs `msg_hash` with the key at `path`, returning a compact `(r, s)` signature together
+    /// with the recovery id that [`Secp256k1::recover`] needs to get the signer's public key
+    /// back out of it.
+    ///
+    /// The underlying `ecdsa_sign` ecall doesn't report a recovery id, so this tries all four
+    /// candidates against the signature it produced and returns whichever one recovers back to
+    /// the signing key; exactly one of them always does.
+    pub fn sign_recoverable(
+        path: &[u32],
+        msg_hash: &[u8; 32],
+    ) -> Result<([u8; 32], [u8; 32], u8), &'static str> {
+        let hd_node = Self::derive_hd_node(path)?;
+
+        // Mode and hash-id ecall parameters each currently have only one supported value
+        // (RFC6979 deterministic nonces, SHA-256), per the `ecdsa_sign` ecall's doc comment.
+        const MODE_RFC6979: u32 = 0;
+        const HASH_ID_SHA256: u32 = 0;
+
+        let mut der = [0u8; 72];
+        let der_len = unsafe {
+            ecalls::ecdsa_sign(
+                CurveId::Secp256k1 as u32,
+                MODE_RFC6979,
+                HASH_ID_SHA256,
+                hd_node.privkey.as_ptr(),
+                msg_hash.as_ptr(),
+                der.as_mut_ptr(),
+            )
+        };
+        if der_len == 0 {
+            return Err("ECDSA signing failed");
+        }
+        let (r, s) =
+            crate::asn1::decode_ecdsa_signature(&der[..der_len]).map_err(|_| "Malformed DER signature")?;
+
+        let pubkey = Self::derive_pubkey(path)?;
+
+        for recid in 0u8..=3 {
+            if let Ok(recovered) = Self::recover(msg_hash, recid, &r, &s) {
+                if recovered == pubkey[1..65] {
+                    return Ok((r, s, recid));
+                }
+            }
+        }
+
+        unreachable!("one of the four recovery ids always recovers the signer's public key")
+    }
+
+    /// Creates a BIP-340 Schnorr signature over `msg` with the key at `path`, returning the
+    /// 64-byte `R.x || s` encoding.
+    ///
+    /// Follows BIP-340: the secret key is negated if its public key has an odd Y (a BIP-340
+    /// public key is represented by its X coordinate alone, always paired with the even-Y
+    /// point), the nonce is derived from a tagged hash of the (randomness-masked) secret key,
+    /// the signer's X-only pubkey and the message, and the challenge from a tagged hash of the
+    /// nonce point's X coordinate, the pubkey and the message.
+    pub fn sign_schnorr_bip340(path: &[u32], msg: &[u8]) -> Result<[u8; 64], &'static str> {
+        let hd_node = Self::derive_hd_node(path)?;
+        let pubkey = Self::derive_pubkey(path)?;
+
+        let mut px = [0u8; 32];
+        px.copy_from_slice(&pubkey[1..33]);
+        let p_is_odd = pubkey[64] & 1 == 1;
+
+        let d = if p_is_odd {
+            negate_scalar(&hd_node.privkey)?
+        } else {
+            *hd_node.privkey
+        };
+
+        let mut aux_rand = [0u8; 32];
+        unsafe {
+            if ecalls::get_random_bytes(aux_rand.as_mut_ptr(), 32) == 0 {
+                return Err("Failed to generate randomness");
+            }
+        }
+        let aux_hash = tagged_hash(b"BIP0340/aux", &[&aux_rand]);
+        let mut t = [0u8; 32];
+        for i in 0..32 {
+            t[i] = d[i] ^ aux_hash[i];
+        }
+
+        let rand = tagged_hash(b"BIP0340/nonce", &[&t, &px, msg]);
+        let mut k = [0u8; 32];
+        unsafe {
+            if ecalls::bn_modm(k.as_mut_ptr(), rand.as_ptr(), 32, CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar reduction failed");
+            }
+        }
+
+        let mut r_point = [0u8; 65];
+        unsafe {
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, r_point.as_mut_ptr(), GENERATOR.as_ptr(), k.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+        }
+        let r_is_odd = r_point[64] & 1 == 1;
+        let k = if r_is_odd { negate_scalar(&k)? } else { k };
+
+        let mut rx = [0u8; 32];
+        rx.copy_from_slice(&r_point[1..33]);
+
+        let e = tagged_hash(b"BIP0340/challenge", &[&rx, &px, msg]);
+        let mut e_mod = [0u8; 32];
+        let mut e_d = [0u8; 32];
+        let mut s = [0u8; 32];
+        unsafe {
+            if ecalls::bn_modm(e_mod.as_mut_ptr(), e.as_ptr(), 32, CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar reduction failed");
+            }
+            if ecalls::bn_multm(e_d.as_mut_ptr(), e_mod.as_ptr(), d.as_ptr(), CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+            if ecalls::bn_addm(s.as_mut_ptr(), k.as_ptr(), e_d.as_ptr(), CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar addition failed");
+            }
+        }
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&rx);
+        signature[32..].copy_from_slice(&s);
+        Ok(signature)
+    }
+
+    /// Verifies a BIP-340 Schnorr `signature` (`R.x || s`) over `msg` against the X-only public
+    /// key `pubkey`, recomputing the challenge `e` and checking `s·G == R + e·P`.
+    pub fn verify_schnorr_bip340(pubkey: &[u8; 32], msg: &[u8], signature: &[u8; 64]) -> Result<bool, &'static str> {
+        let mut rx = [0u8; 32];
+        rx.copy_from_slice(&signature[..32]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&signature[32..]);
+
+        if !is_less_than(&rx, &FIELD_SIZE) || !is_less_than(&s, &CURVE_ORDER) {
+            return Err("Invalid signature encoding");
+        }
+
+        // Lift the X-only pubkey to the curve point with even Y, per BIP-340.
+        let mut p_y = match curve_y_from_x(pubkey) {
+            Ok(y) => y,
+            Err(_) => return Ok(false),
+        };
+        if p_y[31] & 1 == 1 {
+            let mut p_y_neg = [0u8; 32];
+            unsafe {
+                if ecalls::bn_subm(p_y_neg.as_mut_ptr(), [0u8; 32].as_ptr(), p_y.as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+                    return Err("Field negation failed");
+                }
+            }
+            p_y = p_y_neg;
+        }
+        let mut p_point = [0u8; 65];
+        p_point[0] = 0x04;
+        p_point[1..33].copy_from_slice(pubkey);
+        p_point[33..65].copy_from_slice(&p_y);
+
+        let e = tagged_hash(b"BIP0340/challenge", &[&rx, pubkey, msg]);
+        let mut e_mod = [0u8; 32];
+        unsafe {
+            if ecalls::bn_modm(e_mod.as_mut_ptr(), e.as_ptr(), 32, CURVE_ORDER.as_ptr(), 32) == 0 {
+                return Err("Scalar reduction failed");
+            }
+        }
+
+        let mut s_g = [0u8; 65];
+        let mut e_p = [0u8; 65];
+        unsafe {
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, s_g.as_mut_ptr(), GENERATOR.as_ptr(), s.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+            if ecalls::ecfp_scalar_mult(CurveId::Secp256k1 as u32, e_p.as_mut_ptr(), p_point.as_ptr(), e_mod.as_ptr(), 32) == 0 {
+                return Err("Scalar multiplication failed");
+            }
+        }
+
+        let mut e_p_neg = e_p;
+        unsafe {
+            if ecalls::bn_subm(e_p_neg[33..65].as_mut_ptr(), [0u8; 32].as_ptr(), e_p[33..65].as_ptr(), FIELD_SIZE.as_ptr(), 32) == 0 {
+                return Err("Field negation failed");
+            }
+        }
+
+        let mut r_check = [0u8; 65];
+        unsafe {
+            if ecalls::ecfp_add_point(CurveId::Secp256k1 as u32, r_check.as_mut_ptr(), s_g.as_ptr(), e_p_neg.as_ptr()) == 0 {
+                return Err("Point addition failed");
+            }
+        }
+
+        if r_check == [0u8; 65] {
+            return Ok(false); // R is the point at infinity.
+        }
+        if r_check[64] & 1 == 1 {
+            return Ok(false); // R must have even Y.
+        }
+        Ok(r_check[1..33] == rx)
+    }
+}
+
+impl_curve!(
+    Ed25519,
+    CurveId::Ed25519,
+    "The Ed25519 curve (e.g. Stellar, Solana). SLIP-0010 only defines hardened derivation for \
+     this curve; the host rejects paths containing a non-hardened index."
+);
+
+impl_curve!(
+    Secp256r1,
+    CurveId::Secp256r1,
+    "The secp256r1 (NIST P-256) curve, e.g. as used by some Tezos accounts and for FIDO/WebAuthn \
+     attestation."
+);
+
+/// The order `n` of the secp256r1 curve.
+#[rustfmt::skip]
+const P256_CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84,
+    0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+/// The generator `G` of the secp256r1 curve, SEC1 uncompressed (`0x04 || X || Y`).
+#[rustfmt::skip]
+const P256_GENERATOR: [u8; 65] = {
+    let mut g = [0u8; 65];
+    g[0] = 0x04;
+    let x: [u8; 32] = [
+        0x6b, 0x17, 0xd1, 0xf2, 0xe1, 0x2c, 0x42, 0x47,
+        0xf8, 0xbc, 0xe6, 0xe5, 0x63, 0xa4, 0x40, 0xf2,
+        0x77, 0x03, 0x7d, 0x81, 0x2d, 0xeb, 0x33, 0xa0,
+        0xf4, 0xa1, 0x39, 0x45, 0xd8, 0x98, 0xc2, 0x96,
+    ];
+    let y: [u8; 32] = [
+        0x4f, 0xe3, 0x42, 0xe2, 0xfe, 0x1a, 0x7f, 0x9b,
+        0x8e, 0xe7, 0xeb, 0x4a, 0x7c, 0x0f, 0x9e, 0x16,
+        0x2b, 0xce, 0x33, 0x57, 0x6b, 0x31, 0x5e, 0xce,
+        0xcb, 0xb6, 0x40, 0x68, 0x37, 0xbf, 0x51, 0xf5,
+    ];
+    let mut i = 0;
+    while i < 32 {
+        g[1 + i] = x[i];
+        g[33 + i] = y[i];
+        i += 1;
+    }
+    g
+};
+
+impl Secp256r1 {
+    /// Derives the public key for the key at `path`, SEC1 uncompressed (`0x04 || X || Y`).
+    pub fn derive_pubkey(path: &[u32]) -> Result<[u8; 65], &'static str> {
+        let hd_node = Self::derive_hd_node(path)?;
+
+        let mut pubkey = [0u8; 65];
+        pubkey[0] = 0x04;
+        unsafe {
+            if ecalls::ecfp_scalar_mult(
+                CurveId::Secp256r1 as u32,
+                pubkey.as_mut_ptr(),
+                P256_GENERATOR.as_ptr(),
+                hd_node.privkey.as_ptr(),
+                32,
+            ) == 0
+            {
+                return Err("Scalar multiplication failed");
+            }
+        }
+        Ok(pubkey)
+    }
+
+    /// Signs `msg_hash` with the key at `path`, returning a compact `(r, s)` ECDSA signature.
+    ///
+    /// Unlike [`Secp256k1::sign_recoverable`], this doesn't also return a recovery id: P-256
+    /// signatures are verified against a known public key (e.g. during WebAuthn assertion), not
+    /// recovered from, so there's no need to reconstruct `Q` from `(r, s)` alone.
+    pub fn sign(path: &[u32], msg_hash: &[u8; 32]) -> Result<([u8; 32], [u8; 32]), &'static str> {
+        let hd_node = Self::derive_hd_node(path)?;
+
+        // Mode and hash-id ecall parameters each currently have only one supported value
+        // (RFC6979 deterministic nonces, SHA-256), per the `ecdsa_sign` ecall's doc comment.
+        const MODE_RFC6979: u32 = 0;
+        const HASH_ID_SHA256: u32 = 0;
+
+        let mut der = [0u8; 72];
+        let der_len = unsafe {
+            ecalls::ecdsa_sign(
+                CurveId::Secp256r1 as u32,
+                MODE_RFC6979,
+                HASH_ID_SHA256,
+                hd_node.privkey.as_ptr(),
+                msg_hash.as_ptr(),
+                der.as_mut_ptr(),
+            )
+        };
+        if der_len == 0 {
+            return Err("ECDSA signing failed");
+        }
+
+        crate::asn1::decode_ecdsa_signature(&der[..der_len]).map_err(|_| "Malformed DER signature")
+    }
+
+    /// Verifies a compact `(r, s)` ECDSA signature over `msg_hash` against the 65-byte
+    /// uncompressed public key `pubkey`.
+    pub fn verify(pubkey: &[u8; 65], msg_hash: &[u8; 32], r: &[u8; 32], s: &[u8; 32]) -> Result<bool, &'static str> {
+        let der = crate::asn1::encode_ecdsa_signature(r, s, Some(&P256_CURVE_ORDER));
+        let ok = unsafe {
+            ecalls::ecdsa_verify(
+                CurveId::Secp256r1 as u32,
+                pubkey.as_ptr(),
+                msg_hash.as_ptr(),
+                der.as_ptr(),
+                der.len(),
+            )
+        };
+        Ok(ok != 0)
+    }
+}
@@ -0,0 +1,273 @@
+//! Elliptic-curve point arithmetic over [`ModulusProvider`]-based modular big numbers (see
+//! [`crate::bignum`]), generic over a curve parameter set ([`CurveParams`]) so secp256k1 (see
+//! [`Secp256k1Params`]) is one instantiation among others.
+//!
+//! Points are kept in Jacobian coordinates `(X, Y, Z)`, representing the affine point
+//! `(X/Z², Y/Z³)`, so [`PointJacobian::add`] and [`PointJacobian::double`] never need a modular
+//! inversion; [`PointJacobian::to_affine`] performs the single inversion needed to read out
+//! `(x, y)`, via Fermat's little theorem (`z^(p-2) mod p`).
+
+use core::marker::PhantomData;
+
+use crate::bignum::{BigNum, BigNumMod, ModulusProvider};
+
+/// Parameters of a short Weierstrass curve `y² = x³ + a·x + b` over `F_p`, with a generator of
+/// prime order [`CurveParams::ORDER`]. `N` is the byte length of field and scalar elements (32
+/// for secp256k1).
+pub trait CurveParams<const N: usize> {
+    /// Provider for the field modulus `p` that point coordinates live in.
+    type Field: ModulusProvider<N> + Default;
+
+    /// Curve coefficient `a`. [`PointJacobian::double`] uses the doubling formula for `a == 0`,
+    /// which covers secp256k1 but not a curve with a nonzero `a`.
+    const A: [u8; N];
+    /// Curve coefficient `b`.
+    const B: [u8; N];
+    /// Generator's affine X coordinate.
+    const GX: [u8; N];
+    /// Generator's affine Y coordinate.
+    const GY: [u8; N];
+    /// Order of the generator's subgroup, i.e. the scalar modulus.
+    const ORDER: [u8; N];
+}
+
+/// secp256k1's field modulus, `2²⁵⁶ − 2³² − 977`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Secp256k1Field;
+
+impl ModulusProvider<32> for Secp256k1Field {
+    const M: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff,
+        0xfc, 0x2f,
+    ];
+}
+
+/// secp256k1's curve parameters: `y² = x³ + 7`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Secp256k1Params;
+
+impl CurveParams<32> for Secp256k1Params {
+    type Field = Secp256k1Field;
+
+    const A: [u8; 32] = [0u8; 32];
+    const B: [u8; 32] = {
+        let mut b = [0u8; 32];
+        b[31] = 7;
+        b
+    };
+    const GX: [u8; 32] = [
+        0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+        0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8,
+        0x17, 0x98,
+    ];
+    const GY: [u8; 32] = [
+        0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65, 0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08,
+        0xa8, 0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19, 0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10,
+        0xd4, 0xb8,
+    ];
+    const ORDER: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36,
+        0x41, 0x41,
+    ];
+}
+
+/// A point on curve `C` in Jacobian coordinates `(X, Y, Z)`; `Z == 0` is the point at infinity.
+#[derive(Clone)]
+pub struct PointJacobian<const N: usize, C: CurveParams<N>> {
+    x: BigNumMod<N, C::Field>,
+    y: BigNumMod<N, C::Field>,
+    z: BigNumMod<N, C::Field>,
+    _curve: PhantomData<C>,
+}
+
+/// Whether `v` is the field's zero element.
+fn is_zero<const N: usize, M: ModulusProvider<N> + Default>(v: &BigNumMod<N, M>) -> bool {
+    *v == M::default().new_big_num_mod([0u8; N])
+}
+
+/// Constant-operation-count select between `a` and `b`, blending via field arithmetic
+/// (`b + bit*(a - b)`) instead of branching on the secret `bit`.
+fn blend<const N: usize, C: CurveParams<N>>(
+    bit: u8,
+    a: &BigNumMod<N, C::Field>,
+    b: &BigNumMod<N, C::Field>,
+) -> BigNumMod<N, C::Field> {
+    let mut bit_bytes = [0u8; N];
+    bit_bytes[N - 1] = bit;
+    let bit_elem = C::Field::default().new_big_num_mod(bit_bytes);
+    let diff = a - b;
+    b + &(&bit_elem * &diff)
+}
+
+/// `bytes - 2`, as a big-endian byte array, used to build the Fermat-inversion exponent `p - 2`.
+fn minus_two<const N: usize>(bytes: [u8; N]) -> [u8; N] {
+    let mut out = bytes;
+    let mut borrow: i16 = 2;
+    for byte in out.iter_mut().rev() {
+        let mut value = *byte as i16 - borrow;
+        borrow = 0;
+        if value < 0 {
+            value += 256;
+            borrow = 1;
+        }
+        *byte = value as u8;
+        if borrow == 0 {
+            break;
+        }
+    }
+    out
+}
+
+impl<const N: usize, C: CurveParams<N>> PointJacobian<N, C> {
+    /// The point at infinity, the Jacobian-coordinates identity (`Z == 0`).
+    pub fn identity() -> Self {
+        let field = C::Field::default();
+        PointJacobian {
+            x: field.new_big_num_mod([0u8; N]),
+            y: field.new_big_num_mod([0u8; N]),
+            z: field.new_big_num_mod([0u8; N]),
+            _curve: PhantomData,
+        }
+    }
+
+    /// The curve's generator point.
+    pub fn generator() -> Self {
+        Self::from_affine(C::GX, C::GY)
+    }
+
+    /// Builds a Jacobian point from affine coordinates (`Z = 1`).
+    pub fn from_affine(x: [u8; N], y: [u8; N]) -> Self {
+        let field = C::Field::default();
+        let mut one = [0u8; N];
+        one[N - 1] = 1;
+        PointJacobian {
+            x: field.new_big_num_mod(x),
+            y: field.new_big_num_mod(y),
+            z: field.new_big_num_mod(one),
+            _curve: PhantomData,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        is_zero(&self.z)
+    }
+
+    /// Doubles this point. Uses the `a == 0` doubling formula (see [`CurveParams::A`]):
+    /// `A=X1², B=Y1², C=B², D=2((X1+B)²−A−C), E=3A, X3=E²−2D, Y3=E(D−X3)−8C, Z3=2Y1Z1`.
+    pub fn double(&self) -> Self {
+        if self.is_identity() {
+            return self.clone();
+        }
+
+        let field_a = &self.x * &self.x;
+        let field_b = &self.y * &self.y;
+        let field_c = &field_b * &field_b;
+
+        let x1_plus_b = &self.x + &field_b;
+        let x1_plus_b_sq = &x1_plus_b * &x1_plus_b;
+        let d = &(&x1_plus_b_sq - &field_a) - &field_c;
+        let d = &d + &d;
+
+        let e = &(&field_a + &field_a) + &field_a;
+
+        let x3 = &(&e * &e) - &(&d + &d);
+
+        let four_c = &(&field_c + &field_c) + &(&field_c + &field_c);
+        let eight_c = &four_c + &four_c;
+        let y3 = &(&e * &(&d - &x3)) - &eight_c;
+
+        let z3 = &(&self.y * &self.z) + &(&self.y * &self.z);
+
+        PointJacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+            _curve: PhantomData,
+        }
+    }
+
+    /// Adds `other` to this point. Uses the general addition formula:
+    /// `U1=X1Z2², U2=X2Z1², S1=Y1Z2³, S2=Y2Z1³, H=U2−U1, R=S2−S1`, delegating to [`Self::double`]
+    /// when `H == R == 0` and returning [`Self::identity`] when `H == 0` but `R != 0` (the points
+    /// are inverses of each other); otherwise `X3=R²−H³−2U1H², Y3=R(U1H²−X3)−S1H³, Z3=HZ1Z2`.
+    pub fn add(&self, other: &Self) -> Self {
+        if self.is_identity() {
+            return other.clone();
+        }
+        if other.is_identity() {
+            return self.clone();
+        }
+
+        let z1z1 = &self.z * &self.z;
+        let z2z2 = &other.z * &other.z;
+        let u1 = &self.x * &z2z2;
+        let u2 = &other.x * &z1z1;
+        let s1 = &(&self.y * &other.z) * &z2z2;
+        let s2 = &(&other.y * &self.z) * &z1z1;
+
+        let h = &u2 - &u1;
+        let r = &s2 - &s1;
+
+        if is_zero(&h) {
+            return if is_zero(&r) {
+                self.double()
+            } else {
+                Self::identity()
+            };
+        }
+
+        let h2 = &h * &h;
+        let h3 = &h2 * &h;
+        let u1_h2 = &u1 * &h2;
+
+        let x3 = &(&r * &r) - &(&(&h3 + &u1_h2) + &u1_h2);
+        let y3 = &(&r * &(&u1_h2 - &x3)) - &(&s1 * &h3);
+        let z3 = &(&h * &self.z) * &other.z;
+
+        PointJacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+            _curve: PhantomData,
+        }
+    }
+
+    /// Scalar multiplication: a double-and-add over `scalar`'s big-endian bits that always
+    /// computes both the doubled and the doubled-plus-added point and blends between them
+    /// ([`blend`]), so no branch is taken on a bit's value. It is not fully constant-time:
+    /// [`Self::double`] and [`Self::add`] return early when an operand is the identity, so the
+    /// number of field operations depends on the scalar's leading zero bits.
+    pub fn scalar_mul(&self, scalar: &[u8; N]) -> Self {
+        let mut acc = Self::identity();
+        for &byte in scalar.iter() {
+            for bit_index in (0..8).rev() {
+                let bit = (byte >> bit_index) & 1;
+                let doubled = acc.double();
+                let added = doubled.add(self);
+                acc = PointJacobian {
+                    x: blend::<N, C>(bit, &added.x, &doubled.x),
+                    y: blend::<N, C>(bit, &added.y, &doubled.y),
+                    z: blend::<N, C>(bit, &added.z, &doubled.z),
+                    _curve: PhantomData,
+                };
+            }
+        }
+        acc
+    }
+
+    /// Converts to affine coordinates `(x, y) = (X/Z², Y/Z³)`, via one modular inversion of `Z`
+    /// computed with Fermat's little theorem (`Z^(p-2) mod p`).
+    pub fn to_affine(&self) -> ([u8; N], [u8; N]) {
+        let p_minus_2 = BigNum::from_be_bytes(minus_two(C::Field::M));
+        let z_inv = self.z.pow(&p_minus_2);
+        let z_inv2 = &z_inv * &z_inv;
+        let z_inv3 = &z_inv2 * &z_inv;
+
+        let x = &self.x * &z_inv2;
+        let y = &self.y * &z_inv3;
+
+        (x.to_be_bytes(), y.to_be_bytes())
+    }
+}
@@ -56,7 +56,11 @@ pub fn get_device_property(property_id: u32) -> u32 {
 /// Retrieves the fingerprint for the master public key for the specified curve.
 ///
 /// # Parameters
-/// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+/// - `curve`: The elliptic curve identifier (see
+///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+///   32-byte compressed points and scalars for Ed25519).
 ///
 /// # Returns
 /// The master fingerprint as a 32-bit unsigned integer, computed as the first 32 bits of
@@ -69,6 +73,25 @@ pub fn get_master_fingerprint(curve: u32) -> u32 {
     ecalls_module::get_master_fingerprint(curve)
 }
 
+/// A single scatter/gather segment, as used by [`xsendv`]/[`xrecvv`]: a base pointer and a
+/// length, mirroring the classic POSIX `readv`/`writev` `iovec`. The same type is used for both
+/// directions, since only whether the host reads or writes through `base` differs.
+#[repr(C)]
+pub struct IoVec {
+    pub base: *mut u8,
+    pub len: usize,
+}
+
+/// The trap frame written by the host when delivering a fault to a registered trap handler (see
+/// [`set_trap_handler`]): which kind of fault occurred, the faulting address (if applicable,
+/// `0` otherwise), and the faulting program counter.
+#[repr(C)]
+pub struct TrapFrame {
+    pub trap_cause: u32,
+    pub faulting_addr: u32,
+    pub faulting_pc: u32,
+}
+
 forward_to_ecall! {
     /// Prints a fatal error message and exits the V-App.
     ///
@@ -107,6 +130,36 @@ forward_to_ecall! {
     /// - `buffer` must be a valid pointer to at least `max_size` bytes of writable memory.
     pub unsafe fn xrecv(buffer: *mut u8, max_size: usize) -> usize;
 
+    /// Sends the concatenation of `count` scatter/gather segments to the host as a single
+    /// logical buffer, without first copying them into one contiguous buffer.
+    ///
+    /// # Parameters
+    /// - `iovec`: Pointer to an array of `count` [`IoVec`] descriptors, read in order.
+    /// - `count`: Number of descriptors in the array.
+    ///
+    /// # Safety
+    /// - `iovec` must be a valid pointer to `count` readable [`IoVec`] descriptors.
+    /// - Each descriptor's `base` must be a valid pointer to at least `len` bytes of readable
+    ///   memory.
+    pub unsafe fn xsendv(iovec: *const IoVec, count: usize);
+
+    /// Receives a buffer from the host, scattering it across `count` segments in order: each
+    /// segment is filled before moving on to the next, without requiring a single contiguous
+    /// guest buffer large enough for the whole transfer.
+    ///
+    /// # Parameters
+    /// - `iovec`: Pointer to an array of `count` [`IoVec`] descriptors, filled in order.
+    /// - `count`: Number of descriptors in the array.
+    ///
+    /// # Returns
+    /// The total number of bytes received, across all segments.
+    ///
+    /// # Safety
+    /// - `iovec` must be a valid pointer to `count` readable [`IoVec`] descriptors.
+    /// - Each descriptor's `base` must be a valid pointer to at least `len` bytes of writable
+    ///   memory.
+    pub unsafe fn xrecvv(iovec: *const IoVec, count: usize) -> usize;
+
     /// Sends a buffer to print to the host.
     ///
     /// # Parameters
@@ -130,6 +183,22 @@ forward_to_ecall! {
     ///   `size_of::<EventData>()` (16) bytes.
     pub unsafe fn get_event(data: *mut EventData) -> u32;
 
+    /// Copies the out-of-line payload referenced by an [`EventCode::Indirect`] event into `out`.
+    ///
+    /// # Parameters
+    /// - `offset`: The `offset` field of the [`common::ux::IndirectPayload`] descriptor returned
+    ///   alongside the event.
+    /// - `len`: The `len` field of the same descriptor.
+    /// - `out`: Pointer to a buffer of at least `len` bytes to receive the payload.
+    ///
+    /// # Returns
+    /// The number of bytes copied, which is `len` on success. Returns 0 if the descriptor is no
+    /// longer valid, e.g. because the host already recycled the arena slot for a later event.
+    ///
+    /// # Safety
+    /// - `out` must be a valid pointer to at least `len` bytes of writable memory.
+    pub unsafe fn read_event_payload(offset: u32, len: u32, out: *mut u8) -> u32;
+
     /// Reads a 32-byte value from the specified storage slot.
     ///
     /// # Parameters
@@ -184,6 +253,16 @@ forward_to_ecall! {
     /// - `step_desc` must be a valid pointer to at least `step_desc_len` bytes of readable memory.
     pub unsafe fn show_step(step_desc: *const u8, step_desc_len: usize) -> u32;
 
+    /// Sets the active UX language, used to resolve the localized strings baked into
+    /// `ux_generated.rs` (see `crate::ux_generated::{set_language, t}`).
+    ///
+    /// # Parameters
+    /// - `lang_id`: the language's index in the build-time i18n manifest (see `build.rs`).
+    ///
+    /// # Returns
+    /// 1 on success, 0 if `lang_id` is not a known language.
+    pub unsafe fn set_language(lang_id: u32) -> u32;
+
     /// Computes the remainder of dividing `n` by `m`, storing the result in `r`.
     ///
     /// # Parameters
@@ -300,7 +379,11 @@ forward_to_ecall! {
     /// Derives a hierarchical deterministic (HD) node, made of the private key and the corresponding chain code.
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
     /// - `path`: Pointer to the derivation path array.
     /// - `path_len`: Length of the derivation path array.
     /// - `privkey`: Pointer to the buffer to store the derived private key.
@@ -350,7 +433,11 @@ forward_to_ecall! {
     /// Adds two elliptic curve points `p` and `q`, storing the result in `r`.
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
     /// - `r`: Pointer to the result buffer.
     /// - `p`: Pointer to the first point buffer.
     /// - `q`: Pointer to the second point buffer.
@@ -359,14 +446,19 @@ forward_to_ecall! {
     /// 1 on success, 0 on error.
     ///
     /// # Safety
-    /// - `r` must be a valid pointer to at least 65 bytes of writable memory.
-    /// - `p` and `q` must each be a valid pointer to at least 65 bytes of readable memory.
+    /// - `r`, `p` and `q` must each be a valid pointer to at least as many bytes as the given
+    ///   `curve`'s native point size: 65 (uncompressed SEC1) for `Secp256k1`/`Secp256r1`, 32
+    ///   (compressed) for `Ed25519`.
     pub unsafe fn ecfp_add_point(curve: u32, r: *mut u8, p: *const u8, q: *const u8) -> u32;
 
     /// Multiplies an elliptic curve point `p` by a scalar `k`, storing the result in `r`.
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
     /// - `r`: Pointer to the result buffer.
     /// - `p`: Pointer to the point buffer.
     /// - `k`: Pointer to the scalar buffer.
@@ -376,11 +468,50 @@ forward_to_ecall! {
     /// 1 on success, 0 on error.
     ///
     /// # Safety
-    /// - `r` must be a valid pointer to at least 65 bytes of writable memory.
-    /// - `p` must be a valid pointer to at least 65 bytes of readable memory.
+    /// - `r` and `p` must each be a valid pointer to at least as many bytes as the given
+    ///   `curve`'s native point size: 65 (uncompressed SEC1) for `Secp256k1`/`Secp256r1`, 32
+    ///   (compressed) for `Ed25519`.
     /// - `k` must be a valid pointer to at least `k_len` bytes of readable memory.
     pub unsafe fn ecfp_scalar_mult(curve: u32, r: *mut u8, p: *const u8, k: *const u8, k_len: usize) -> u32;
 
+    /// Computes an elliptic-curve Diffie-Hellman shared secret `k · P`, where `k` is our private
+    /// key and `P` is the peer's public key, the canonical companion to [`ecfp_scalar_mult`] for
+    /// key agreement: the host additionally validates that `P` is on the curve and isn't the
+    /// point at infinity before multiplying, and can derive the secret in the form most callers
+    /// actually want instead of a raw point.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve identifier. `Secp256k1` and `Secp256r1` are supported;
+    ///   `Ed25519` is not, since it isn't a Diffie-Hellman-friendly curve (use X25519 off-device
+    ///   if Ed25519-based key agreement is needed).
+    /// - `privkey`: Pointer to our 32-byte private key.
+    /// - `their_pubkey`: Pointer to the peer's 65-byte uncompressed public key (`0x04 || X || Y`).
+    /// - `mode`: Selects the output encoding:
+    ///   - `RAW_X` (0): the 32-byte X coordinate of `k · P`, unhashed.
+    ///   - `FULL_POINT` (1): the full 65-byte uncompressed point `k · P`.
+    ///   - `SHA256` (2): `SHA256(compressed(k · P))`, 33-byte SEC1 compressed point, 32-byte
+    ///     output; the canonical form for deriving a symmetric key, since it's fixed-size
+    ///     regardless of curve and doesn't leak the Y parity as a separate bit.
+    /// - `secret`: Pointer to the output buffer. Must be large enough for the selected `mode`
+    ///   (32 bytes for `RAW_X`/`SHA256`, 65 bytes for `FULL_POINT`).
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error (including an invalid `their_pubkey`: not on the curve, or the
+    /// point at infinity).
+    ///
+    /// # Safety
+    /// - `privkey` must be a valid pointer to at least 32 bytes of readable memory.
+    /// - `their_pubkey` must be a valid pointer to at least 65 bytes of readable memory.
+    /// - `secret` must be a valid pointer to a writable buffer large enough for the selected
+    ///   `mode` (see above).
+    pub unsafe fn ecdh(
+        curve: u32,
+        privkey: *const u8,
+        their_pubkey: *const u8,
+        mode: u32,
+        secret: *mut u8,
+    ) -> u32;
+
     /// Generates `size` random bytes using a cryptographically secure random number generator,
     /// and writes them to the provided buffer.
     ///
@@ -395,13 +526,71 @@ forward_to_ecall! {
     /// - `buffer` must be a valid pointer to at least `size` bytes of writable memory.
     pub unsafe fn get_random_bytes(buffer: *mut u8, size: usize) -> u32;
 
+    /// Seeds a deterministic HMAC-DRBG (NIST SP 800-90A, over SHA-256) with `seed`, replacing
+    /// any state left by a previous call. Unlike [`get_random_bytes`] (backed by the device's
+    /// hardware TRNG on a real target), the DRBG's output depends only on the seed and the
+    /// sequence of [`drbg_generate`]/[`drbg_reseed`] calls, which makes it the tool to reach for
+    /// when a signing-flow unit test needs the exact same "random" nonces and blinding factors on
+    /// every run.
+    ///
+    /// Sets the DRBG state `(K, V)` to `K = 0x00…00`, `V = 0x01…01` (32 bytes each), then runs
+    /// the HMAC-DRBG Update step with `seed` as the only additional input, i.e.:
+    /// `K = HMAC(K, V ‖ 0x00 ‖ seed)`, `V = HMAC(K, V)`, `K = HMAC(K, V ‖ 0x01 ‖ seed)`,
+    /// `V = HMAC(K, V)`.
+    ///
+    /// # Parameters
+    /// - `seed`: Pointer to the seed material.
+    /// - `seed_len`: Length of the seed material.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `seed` must be a valid pointer to at least `seed_len` bytes of readable memory.
+    pub unsafe fn drbg_instantiate(seed: *const u8, seed_len: usize) -> u32;
+
+    /// Mixes fresh entropy into an already-[`drbg_instantiate`]d HMAC-DRBG's state, running the
+    /// same Update step as instantiation but starting from the current `(K, V)` instead of the
+    /// all-zero/all-one initial state.
+    ///
+    /// # Parameters
+    /// - `entropy`: Pointer to the additional entropy.
+    /// - `entropy_len`: Length of the additional entropy.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error (including if the DRBG hasn't been instantiated yet).
+    ///
+    /// # Safety
+    /// - `entropy` must be a valid pointer to at least `entropy_len` bytes of readable memory.
+    pub unsafe fn drbg_reseed(entropy: *const u8, entropy_len: usize) -> u32;
+
+    /// Fills `buffer` with `size` bytes from the HMAC-DRBG instantiated by [`drbg_instantiate`],
+    /// by repeatedly setting `V = HMAC(K, V)` and concatenating `V` blocks until `size` bytes
+    /// have been produced (truncating the last block as needed), then running the Update step
+    /// with no additional input (`K = HMAC(K, V ‖ 0x00)`, `V = HMAC(K, V)`), per SP 800-90A.
+    ///
+    /// # Parameters
+    /// - `buffer`: Pointer to the buffer where the generated bytes will be written.
+    /// - `size`: The number of bytes to generate.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error (including if the DRBG hasn't been instantiated yet).
+    ///
+    /// # Safety
+    /// - `buffer` must be a valid pointer to at least `size` bytes of writable memory.
+    pub unsafe fn drbg_generate(buffer: *mut u8, size: usize) -> u32;
+
     /// Signs a message hash using ECDSA.
     ///
     /// # Warning
     /// **This ecall is unstable and subject to change in future versions.**
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
     /// - `mode`: The signing mode. Only `RFC6979` is supported.
     /// - `hash_id`: The hash identifier. Only `Sha256` is supported.
     /// - `privkey`: Pointer to the private key buffer.
@@ -431,7 +620,11 @@ forward_to_ecall! {
     /// **This ecall is unstable and subject to change in future versions.**
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
     /// - `pubkey`: Pointer to the public key buffer.
     /// - `msg_hash`: Pointer to the message hash buffer.
     /// - `signature`: Pointer to the signature buffer.
@@ -452,15 +645,97 @@ forward_to_ecall! {
         signature_len: usize,
     ) -> u32;
 
+    /// Signs a message hash using ECDSA, also returning the recovery id needed to reconstruct
+    /// the public key from the signature alone (see [`ecdsa_recover`]).
+    ///
+    /// # Warning
+    /// **This ecall is unstable and subject to change in future versions.**
+    ///
+    /// The recovery id is derived from the nonce point `R`: bit 0 is the parity of `R.y`, bit 1
+    /// is set when `R.x >= n` (the curve order), which can happen with negligible but nonzero
+    /// probability.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
+    /// - `mode`: The signing mode. Only `RFC6979` is supported.
+    /// - `hash_id`: The hash identifier. Only `Sha256` is supported.
+    /// - `privkey`: Pointer to the private key buffer.
+    /// - `msg_hash`: Pointer to the message hash buffer.
+    /// - `signature`: Pointer to the buffer to store the 64-byte compact (r || s) signature.
+    /// - `recovery_id`: Pointer to the byte to store the recovery id (0-3) in.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `privkey` must be a valid pointer to at least 32 bytes of readable memory.
+    /// - `msg_hash` must be a valid pointer to at least 32 bytes of readable memory.
+    /// - `signature` must be a valid pointer to at least 64 bytes of writable memory.
+    /// - `recovery_id` must be a valid pointer to 1 byte of writable memory.
+    pub unsafe fn ecdsa_sign_recoverable(
+        curve: u32,
+        mode: u32,
+        hash_id: u32,
+        privkey: *const u8,
+        msg_hash: *const u8,
+        signature: *mut u8,
+        recovery_id: *mut u8,
+    ) -> u32;
+
+    /// Recovers the public key from a compact ECDSA signature, its recovery id, and the signed
+    /// message hash (the inverse of [`ecdsa_sign_recoverable`]).
+    ///
+    /// # Warning
+    /// **This ecall is unstable and subject to change in future versions.**
+    ///
+    /// `R` is reconstructed from `x = r` (or `r + n` if bit 1 of `recovery_id` is set) and the
+    /// y-parity given by bit 0 of `recovery_id`; the call fails if `x >= p` or the resulting point
+    /// isn't on the curve. The public key is then `Q = r⁻¹ · (s·R − e·G) mod n`, where `e` is the
+    /// truncated message hash; the call fails if `Q` is the point at infinity.
+    ///
+    /// # Parameters
+    /// - `curve`: The elliptic curve identifier (see
+    ///   [`common::ecall_constants::CurveId`]): `Secp256k1`, `Secp256r1` (NIST P-256) and
+    ///   `Ed25519` are all supported, each with its own native point/scalar size (65-byte
+    ///   uncompressed SEC1 points and 32-byte scalars for the two Weierstrass curves,
+    ///   32-byte compressed points and scalars for Ed25519).
+    /// - `msg_hash`: Pointer to the message hash buffer.
+    /// - `signature`: Pointer to the 64-byte compact (r || s) signature.
+    /// - `recovery_id`: The recovery id (0-3) returned by [`ecdsa_sign_recoverable`].
+    /// - `pubkey`: Pointer to the buffer to store the recovered 65-byte uncompressed public key.
+    ///
+    /// # Returns
+    /// 1 on success, 0 if the signature/recovery id don't correspond to a valid point.
+    ///
+    /// # Safety
+    /// - `msg_hash` must be a valid pointer to at least 32 bytes of readable memory.
+    /// - `signature` must be a valid pointer to at least 64 bytes of readable memory.
+    /// - `pubkey` must be a valid pointer to at least 65 bytes of writable memory.
+    pub unsafe fn ecdsa_recover(
+        curve: u32,
+        msg_hash: *const u8,
+        signature: *const u8,
+        recovery_id: u32,
+        pubkey: *mut u8,
+    ) -> u32;
+
     /// Signs a message using Schnorr signature.
     ///
     /// # Warning
     /// **This ecall is unstable and subject to change in future versions.**
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
-    /// - `mode`: The signing mode. Only `BIP340` is supported.
-    /// - `hash_id`: The hash identifier.
+    /// - `curve`: The elliptic curve identifier. `Secp256k1` and `Ed25519` are supported;
+    ///   `Secp256r1` is not, since P-256 has no standard Schnorr/EdDSA scheme.
+    /// - `mode`: The signing mode: `BIP340` for `Secp256k1` (the 32-byte x-only public key is
+    ///   the even-Y lift of `privkey`'s point), or `Ed25519` for pure EdDSA (the 32-byte
+    ///   compressed public key is derived from `privkey` per RFC 8032; `entropy` and `hash_id`
+    ///   are ignored, since EdDSA's nonce is deterministic from the private key and message).
+    /// - `hash_id`: The hash identifier. Ignored for `Ed25519`.
     /// - `privkey`: Pointer to the private key buffer.
     /// - `msg`: Pointer to the message buffer.
     /// - `msg_len`: Length of the message buffer.
@@ -492,9 +767,11 @@ forward_to_ecall! {
     /// **This ecall is unstable and subject to change in future versions.**
     ///
     /// # Parameters
-    /// - `curve`: The elliptic curve identifier. Currently only `Secp256k1` is supported.
+    /// - `curve`: The elliptic curve identifier. `Secp256k1` and `Ed25519` are supported (see
+    ///   [`schnorr_sign`]); `Secp256r1` is not.
     /// - `mode`: The verification mode. It must match the mode used for signing.
-    /// - `hash_id`: The hash identifier. Only `Sha256` is supported.
+    /// - `hash_id`: The hash identifier. Only `Sha256` is supported for `BIP340`; ignored for
+    ///   `Ed25519`.
     /// - `pubkey`: Pointer to the public key buffer.
     /// - `msg`: Pointer to the message buffer.
     /// - `msg_len`: Length of the message buffer.
@@ -505,8 +782,8 @@ forward_to_ecall! {
     /// 1 on success, 0 on error.
     ///
     /// # Safety
-    /// - `pubkey` must be a valid pointer to at least 32 bytes of readable memory
-    ///   (x-only BIP-340 public key).
+    /// - `pubkey` must be a valid pointer to at least 32 bytes of readable memory (an x-only
+    ///   BIP-340 public key for `Secp256k1`, or a compressed Ed25519 public key for `Ed25519`).
     /// - `msg` must be a valid pointer to at least `msg_len` bytes of readable memory.
     /// - `signature` must be a valid pointer to at least `signature_len` bytes of readable memory.
     pub unsafe fn schnorr_verify(
@@ -579,6 +856,220 @@ forward_to_ecall! {
     /// - `digest` must be a valid pointer to a writable buffer large enough to hold the digest
     ///   for the given `hash_id`.
     pub unsafe fn hash_final(hash_id: u32, ctx: *mut u8, digest: *mut u8) -> u32;
+
+    /// Computes a one-shot HMAC, i.e. `H((K ⊕ opad) ‖ H((K ⊕ ipad) ‖ m))`, using the block size
+    /// of the given hash algorithm (64 bytes for SHA-256 and RIPEMD-160, 128 bytes for SHA-512).
+    /// A `key` longer than the block size is first replaced by `H(key)`, as specified by the
+    /// HMAC construction.
+    ///
+    /// # Parameters
+    /// - `hash_id`: The hash algorithm identifier (see [`common::ecall_constants::HashId`]).
+    /// - `key`: Pointer to the key buffer.
+    /// - `key_len`: Length of the key.
+    /// - `msg`: Pointer to the message buffer.
+    /// - `msg_len`: Length of the message.
+    /// - `mac`: Pointer to the output buffer where the MAC is written. Must be large enough to
+    ///   hold the digest for the given `hash_id` (e.g. 32 bytes for SHA-256).
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `key` must be a valid pointer to at least `key_len` bytes of readable memory.
+    /// - `msg` must be a valid pointer to at least `msg_len` bytes of readable memory.
+    /// - `mac` must be a valid pointer to a writable buffer large enough to hold the digest for
+    ///   the given `hash_id`.
+    pub unsafe fn hmac(
+        hash_id: u32,
+        key: *const u8,
+        key_len: usize,
+        msg: *const u8,
+        msg_len: usize,
+        mac: *mut u8,
+    ) -> u32;
+
+    /// Computes the HKDF-Extract step (RFC 5869): `PRK = HMAC(salt, ikm)`, using the given hash
+    /// algorithm. `PRK` has the same length as the hash's digest.
+    ///
+    /// # Parameters
+    /// - `hash_id`: The hash algorithm identifier (see [`common::ecall_constants::HashId`]).
+    /// - `salt`: Pointer to the salt buffer. May be a dangling pointer if `salt_len` is 0, in
+    ///   which case a string of zeros of the hash's digest length is used as the salt, as
+    ///   specified by RFC 5869.
+    /// - `salt_len`: Length of the salt.
+    /// - `ikm`: Pointer to the input keying material buffer.
+    /// - `ikm_len`: Length of the input keying material.
+    /// - `prk`: Pointer to the output buffer where `PRK` is written. Must be large enough to
+    ///   hold the digest for the given `hash_id`.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `salt` must be a valid pointer to at least `salt_len` bytes of readable memory, unless
+    ///   `salt_len` is 0.
+    /// - `ikm` must be a valid pointer to at least `ikm_len` bytes of readable memory.
+    /// - `prk` must be a valid pointer to a writable buffer large enough to hold the digest for
+    ///   the given `hash_id`.
+    pub unsafe fn hkdf_extract(
+        hash_id: u32,
+        salt: *const u8,
+        salt_len: usize,
+        ikm: *const u8,
+        ikm_len: usize,
+        prk: *mut u8,
+    ) -> u32;
+
+    /// Computes the HKDF-Expand step (RFC 5869): `okm = T(1) ‖ T(2) ‖ ... ‖ T(n)`, truncated to
+    /// `okm_len` bytes, where `T(0)` is empty and `T(i) = HMAC(prk, T(i - 1) ‖ info ‖ i)` for
+    /// `i` in `1..=n`, `n = ceil(okm_len / hashlen)`.
+    ///
+    /// # Parameters
+    /// - `hash_id`: The hash algorithm identifier (see [`common::ecall_constants::HashId`]).
+    /// - `prk`: Pointer to the pseudorandom key buffer, normally the output of [`hkdf_extract`].
+    ///   Must be at least as long as the hash's digest.
+    /// - `prk_len`: Length of `prk`.
+    /// - `info`: Pointer to the context/application-specific info buffer. May be a dangling
+    ///   pointer if `info_len` is 0.
+    /// - `info_len`: Length of `info`.
+    /// - `okm`: Pointer to the output buffer where the output keying material is written.
+    /// - `okm_len`: Requested length of the output keying material, in bytes. Must not exceed
+    ///   255 times the hash's digest length, per RFC 5869; larger values are rejected.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error (including an `okm_len` that is too large).
+    ///
+    /// # Safety
+    /// - `prk` must be a valid pointer to at least `prk_len` bytes of readable memory.
+    /// - `info` must be a valid pointer to at least `info_len` bytes of readable memory, unless
+    ///   `info_len` is 0.
+    /// - `okm` must be a valid pointer to a writable buffer of at least `okm_len` bytes.
+    pub unsafe fn hkdf_expand(
+        hash_id: u32,
+        prk: *const u8,
+        prk_len: usize,
+        info: *const u8,
+        info_len: usize,
+        okm: *mut u8,
+        okm_len: usize,
+    ) -> u32;
+
+    /// Compares two buffers of equal length in a way that is branch- and data-independent in
+    /// time: the whole buffer is scanned regardless of where (or whether) a difference is found,
+    /// so the call takes the same time for "equal", "differ in the first byte" and "differ in the
+    /// last byte". Intended for comparing MACs, PINs, and signature tags, where an early-exit
+    /// comparison leaks how many leading bytes of a secret the caller's guess got right.
+    ///
+    /// # Parameters
+    /// - `a`: Pointer to the first buffer.
+    /// - `b`: Pointer to the second buffer.
+    /// - `len`: Length of both buffers.
+    ///
+    /// # Returns
+    /// 1 if the buffers are equal, 0 otherwise.
+    ///
+    /// # Safety
+    /// - `a` and `b` must each be a valid pointer to at least `len` bytes of readable memory.
+    pub unsafe fn ct_memcmp(a: *const u8, b: *const u8, len: usize) -> u32;
+
+    /// Hex-encodes `data` into `out` without data-dependent branches or table lookups: each
+    /// nibble is mapped to its ASCII digit by arithmetic (`n + b'0'`, plus `+ (b'a' - b'0' - 10)`
+    /// when `n >= 10`, computed unconditionally via a mask rather than a branch), so the time and
+    /// memory-access pattern don't depend on the encoded byte values. Intended for encoding secret
+    /// material (e.g. a key or MAC) for display or transport.
+    ///
+    /// # Parameters
+    /// - `data`: Pointer to the input buffer.
+    /// - `data_len`: Length of the input buffer.
+    /// - `out`: Pointer to the output buffer, written as lowercase hex.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `data` must be a valid pointer to at least `data_len` bytes of readable memory.
+    /// - `out` must be a valid pointer to at least `2 * data_len` bytes of writable memory.
+    pub unsafe fn ct_hex_encode(data: *const u8, data_len: usize, out: *mut u8) -> u32;
+
+    /// Decodes a hex string `data` into `out`, the inverse of [`ct_hex_encode`], with the same
+    /// constant-time arithmetic-only digit mapping (no table lookups or data-dependent branches).
+    /// Accepts both uppercase and lowercase hex digits.
+    ///
+    /// # Parameters
+    /// - `data`: Pointer to the input hex string.
+    /// - `data_len`: Length of the input, which must be even.
+    /// - `out`: Pointer to the output buffer.
+    ///
+    /// # Returns
+    /// 1 on success, 0 on error (including an odd `data_len`, or a byte that isn't a valid hex
+    /// digit — note that rejection on invalid input is itself not constant-time, since the
+    /// caller's `data` is assumed to already be a plain hex string, not secret).
+    ///
+    /// # Safety
+    /// - `data` must be a valid pointer to at least `data_len` bytes of readable memory.
+    /// - `out` must be a valid pointer to at least `data_len / 2` bytes of writable memory.
+    pub unsafe fn ct_hex_decode(data: *const u8, data_len: usize, out: *mut u8) -> u32;
+
+    /// Base64-encodes `data` into `out` using arithmetic character selection instead of a lookup
+    /// table, so that the memory-access pattern doesn't depend on the encoded byte values.
+    /// Intended for encoding secret material (e.g. a key or MAC) for display or transport.
+    ///
+    /// # Parameters
+    /// - `data`: Pointer to the input buffer.
+    /// - `data_len`: Length of the input buffer.
+    /// - `out`: Pointer to the output buffer.
+    /// - `mode`: Selects the alphabet and padding:
+    ///   - `STANDARD` (0): the standard `+`/`/` alphabet, `=`-padded.
+    ///   - `STANDARD_NO_PAD` (1): the standard alphabet, unpadded.
+    ///   - `URL_SAFE` (2): the URL-safe `-`/`_` alphabet, `=`-padded.
+    ///   - `URL_SAFE_NO_PAD` (3): the URL-safe alphabet, unpadded.
+    ///
+    /// # Returns
+    /// The length of the encoded output on success, 0 on error.
+    ///
+    /// # Safety
+    /// - `data` must be a valid pointer to at least `data_len` bytes of readable memory.
+    /// - `out` must be a valid pointer to at least `4 * data_len.div_ceil(3)` bytes of writable
+    ///   memory (the padded size; always sufficient for the unpadded modes too).
+    pub unsafe fn ct_base64_encode(data: *const u8, data_len: usize, out: *mut u8, mode: u32) -> usize;
+
+    /// Decodes a base64 string `data` into `out`, the inverse of [`ct_base64_encode`], with the
+    /// same constant-time arithmetic character mapping (no table lookups or data-dependent
+    /// branches).
+    ///
+    /// # Parameters
+    /// - `data`: Pointer to the input base64 string.
+    /// - `data_len`: Length of the input.
+    /// - `out`: Pointer to the output buffer.
+    /// - `mode`: The alphabet/padding the input was encoded with (see [`ct_base64_encode`]); the
+    ///   `*_NO_PAD` variants also accept a correctly-padded input, but not the reverse.
+    ///
+    /// # Returns
+    /// The length of the decoded output on success, 0 on error (including a malformed input for
+    /// the given `mode` — as with [`ct_hex_decode`], rejection on invalid input is itself not
+    /// constant-time, since `data` is assumed to be a plain base64 string, not secret).
+    ///
+    /// # Safety
+    /// - `data` must be a valid pointer to at least `data_len` bytes of readable memory.
+    /// - `out` must be a valid pointer to at least `3 * data_len.div_ceil(4)` bytes of writable
+    ///   memory.
+    pub unsafe fn ct_base64_decode(data: *const u8, data_len: usize, out: *mut u8, mode: u32) -> usize;
+
+    /// Registers the V-App's trap handler. A recoverable host- or guest-level fault (a bad
+    /// ecall argument, an out-of-bounds memory access, ...) now writes a [`TrapFrame`] to
+    /// `frame`, pushes the faulting `pc` onto the stack as a return address, and resumes
+    /// execution at `handler`, instead of tearing down the V-App.
+    ///
+    /// # Parameters
+    /// - `handler`: Guest address to resume execution at when a fault is delivered.
+    /// - `frame`: Pointer to a [`TrapFrame`]-sized buffer the host fills in on each delivery.
+    ///
+    /// # Safety
+    /// - `handler` must be the address of valid, executable V-App code.
+    /// - `frame` must be a valid pointer to a writable buffer of at least
+    ///   `size_of::<TrapFrame>()` (12) bytes, for as long as the trap handler remains
+    ///   registered.
+    pub unsafe fn set_trap_handler(handler: u32, frame: *mut TrapFrame);
 }
 
 #[cfg(test)]
@@ -67,3 +67,38 @@ mod hashers {
 }
 
 pub use hashers::{Ripemd160, Sha256, Sha512};
+
+/// Keccak-256 and SHA3-256, which the secure element has no native support for. These two
+/// digests only differ in their domain-separation padding byte (`0x01` vs `0x06`), so they're
+/// implemented in software via the `sha3` crate rather than through an ecall.
+mod sha3_hashers {
+    use super::*;
+    use sha3::{Digest, Keccak256 as Keccak256Impl, Sha3_256 as Sha3_256Impl};
+
+    macro_rules! impl_sha3_hash {
+        ($name:ident, $inner:ty) => {
+            #[derive(Clone)]
+            pub struct $name($inner);
+
+            impl Hasher<32> for $name {
+                fn new() -> Self {
+                    $name(<$inner>::new())
+                }
+
+                fn update(&mut self, data: &[u8]) -> &mut Self {
+                    self.0.update(data);
+                    self
+                }
+
+                fn digest(self, digest: &mut [u8; 32]) {
+                    digest.copy_from_slice(&self.0.finalize());
+                }
+            }
+        };
+    }
+
+    impl_sha3_hash!(Keccak256, Keccak256Impl);
+    impl_sha3_hash!(Sha3_256, Sha3_256Impl);
+}
+
+pub use sha3_hashers::{Keccak256, Sha3_256};
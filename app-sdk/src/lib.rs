@@ -8,9 +8,11 @@ extern crate lazy_static;
 use alloc::vec::Vec;
 
 pub mod app;
+pub mod asn1;
 pub mod bignum;
 pub mod comm;
 pub mod curve;
+pub mod ec;
 pub mod hash;
 pub mod ux;
 
@@ -91,17 +93,39 @@ fn my_panic(info: &core::panic::PanicInfo) -> ! {
     fatal(&message); // does not return
 }
 
-pub fn xrecv(size: usize) -> Vec<u8> {
-    // We allocate a buffer with the requested size, but we don't initialize its content.
-    // xrecv guarantees that recv_size have been overwritten with the received data, and we
-    // do not access any further data.
-    let mut buffer = Vec::with_capacity(size);
+/// A reusable receive buffer for [`xrecv_reuse`]. Keeping one of these alive across a receive
+/// loop (e.g. `comm::receive_message`'s steady state) lets every chunk after the first reuse the
+/// same allocation instead of paying for a fresh `Vec` on every chunk.
+#[derive(Default)]
+pub struct ChunkBuffer {
+    buffer: Vec<u8>,
+}
+
+impl ChunkBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Receives up to `size` bytes into `buf`'s existing allocation, only growing it if its capacity
+/// doesn't already cover `size`, and returns a borrowed slice of exactly the bytes received.
+///
+/// We extend `buf`'s length to `size` without initializing the new bytes: `Ecall::xrecv`
+/// guarantees that the first `recv_size` bytes are overwritten with the received data, and the
+/// returned slice never exposes anything past that.
+pub fn xrecv_reuse(buf: &mut ChunkBuffer, size: usize) -> &[u8] {
+    buf.buffer.reserve(size.saturating_sub(buf.buffer.len()));
     unsafe {
-        buffer.set_len(size);
+        buf.buffer.set_len(size);
     }
 
-    let recv_size = Ecall::xrecv(buffer.as_mut_ptr(), buffer.len());
-    buffer[0..recv_size].to_vec()
+    let recv_size = Ecall::xrecv(buf.buffer.as_mut_ptr(), buf.buffer.len());
+    &buf.buffer[0..recv_size]
+}
+
+pub fn xrecv(size: usize) -> Vec<u8> {
+    let mut buf = ChunkBuffer::new();
+    xrecv_reuse(&mut buf, size).to_vec()
 }
 
 pub fn xrecv_to(buf: &mut [u8]) -> usize {
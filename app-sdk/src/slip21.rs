@@ -1,9 +1,14 @@
 use crate::ecalls;
 use alloc::vec::Vec;
+use core::fmt;
 use core::ops::Deref;
+use common::ecall_constants::HashId;
 use subtle::ConstantTimeEq;
 use zeroize::Zeroizing;
 
+/// Domain-separation tag for [`Slip21Key`]'s `Debug` fingerprint (see the `impl Debug` below).
+const DEBUG_TAG: &[u8] = b"VND_SLIP21_DEBUG";
+
 /// An opaque type representing a SLIP-21 derived key.
 ///
 /// This type prevents direct access to the key material to mitigate side-channel attacks.
@@ -12,7 +17,8 @@ use zeroize::Zeroizing;
 /// # Security
 ///
 /// - Implements constant-time equality comparison to prevent timing attacks.
-/// - Does not implement `Debug` to prevent accidental logging of key material.
+/// - `Debug` prints a non-invertible fingerprint rather than the key itself; see
+///   [`Slip21Key::display_secret`] for the explicit, auditable way to dump the raw bytes.
 /// - Does not implement `Clone` to limit the number of copies in memory.
 /// - Automatically zeros memory on drop using `Zeroizing`.
 pub struct Slip21Key {
@@ -58,6 +64,55 @@ impl Slip21Key {
     pub fn dangerous_as_raw_bytes(&self) -> &[u8; 32] {
         self.key.deref()
     }
+
+    /// Returns a [`DisplaySecret`] wrapper that, when formatted, prints the raw key bytes.
+    ///
+    /// This is the intentional, auditable escape hatch for cases that genuinely need to dump
+    /// the key (e.g. test assertions): a reader scanning for accidental key exposure can grep
+    /// for this one name, rather than every ad-hoc `dangerous_as_raw_bytes()` call site needing
+    /// individual scrutiny.
+    pub fn display_secret(&self) -> DisplaySecret {
+        DisplaySecret {
+            secret: *self.key.deref(),
+        }
+    }
+
+    /// Derives a related subkey as `self.key XOR tweak`, in constant time over the key bytes.
+    ///
+    /// Lets a V-App derive many purpose-specific keys from one SLIP-21 leaf (e.g. one tweak per
+    /// purpose) without another costly, seed-exposing `derive_slip21_node` ecall round-trip.
+    pub fn tweak_xor(&self, tweak: &[u8; 32]) -> Slip21Key {
+        let key = self.key.deref();
+        let mut tweaked = [0u8; 32];
+        for i in 0..32 {
+            tweaked[i] = key[i] ^ tweak[i];
+        }
+        Slip21Key::from_bytes(tweaked)
+    }
+
+    /// Derives a related subkey as `SHA256(SHA256(context) || SHA256(context) || self.key)`, the
+    /// same BIP-340-style tagged-hash construction used for domain separation elsewhere in this
+    /// SDK (e.g. the BIP-340 tagged hashes in [`crate::curve`]). Unlike
+    /// [`Slip21Key::tweak_xor`], the output is not invertible from `context` alone, so distinct
+    /// contexts yield subkeys with no algebraic relationship a caller could exploit.
+    pub fn tweak_hashed(&self, context: &[u8]) -> Slip21Key {
+        use crate::hash::Sha256;
+        use common::accumulator::Hasher;
+
+        let mut context_hash = [0u8; 32];
+        let mut context_hasher = Sha256::new();
+        context_hasher.update(context);
+        context_hasher.digest(&mut context_hash);
+
+        let mut hasher = Sha256::new();
+        hasher.update(&context_hash);
+        hasher.update(&context_hash);
+        hasher.update(self.key.deref());
+        let mut tweaked = [0u8; 32];
+        hasher.digest(&mut tweaked);
+
+        Slip21Key::from_bytes(tweaked)
+    }
 }
 
 impl PartialEq for Slip21Key {
@@ -69,27 +124,70 @@ impl PartialEq for Slip21Key {
 
 impl Eq for Slip21Key {}
 
-/// Derives a SLIP-21 key node, based on the BIP39 seed.
-/// The key corresponds to the last 32-bytes of the corresponding SLIP-21 node.
-/// The initial 32 bytes (only used for further derivations) are not returned.
-///
-/// # Returns
-/// A `Slip21Key` opaque type representing the derived SLIP-21 key.
+/// Prints a domain-separated, non-invertible fingerprint of the key rather than the key
+/// itself: `H = SHA256(SHA256(tag) || SHA256(tag) || key)`, showing only the first 8 bytes of
+/// `H`. This lets two keys be visually distinguished and compared in logs without ever
+/// revealing key material, mirroring the tagged-double-hash `Debug` pattern the vendored
+/// `secp256k1` crate uses for `SecretKey`/`Keypair` (see `libs/secp256k1/src/secret.rs`).
+impl fmt::Debug for Slip21Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use crate::hash::Sha256;
+        use common::accumulator::Hasher;
+
+        let mut tag_hash = [0u8; 32];
+        let mut tag_hasher = Sha256::new();
+        tag_hasher.update(DEBUG_TAG);
+        tag_hasher.digest(&mut tag_hash);
+
+        let mut hasher = Sha256::new();
+        hasher.update(&tag_hash);
+        hasher.update(&tag_hash);
+        hasher.update(self.key.deref());
+        let mut fingerprint = [0u8; 32];
+        hasher.digest(&mut fingerprint);
+
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&fingerprint[..8]);
+
+        f.debug_tuple("Slip21Key")
+            .field(&format_args!("#{:016x}", u64::from_be_bytes(prefix)))
+            .finish()
+    }
+}
+
+/// Helper for explicitly printing a [`Slip21Key`]'s raw bytes (see [`Slip21Key::display_secret`]).
+/// Formats as lowercase hex via [`fmt::Display`]; unlike [`Slip21Key`] itself, this type's
+/// `Debug`/`Display` impls intentionally reveal the secret, since constructing one already
+/// required calling the explicitly-named `display_secret()`.
+pub struct DisplaySecret {
+    secret: [u8; 32],
+}
+
+impl fmt::Debug for DisplaySecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DisplaySecret")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+impl fmt::Display for DisplaySecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.secret {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes `labels` the way the `derive_slip21_node` ecall expects: each label prefixed by its
+/// own length byte, concatenated in order.
 ///
 /// # Panics
 /// This function will panic if either:
 /// - The total length of the encoded labels exceeds 256 bytes.
 /// - Any individual label exceeds 252 bytes.
-/// - (Ledger-specific) `labels` has length 0 (no master key derivation)
-/// - (Ledger-specific) Any label contains a '/' character.
-///
-/// # Security
-///
-/// The returned key is wrapped in an opaque type that:
-/// - Prevents direct access to raw bytes (unless explicitly using `dangerous_as_raw_bytes()`)
-/// - Implements constant-time equality comparison
-/// - Automatically zeros memory on drop
-pub fn derive_slip21_key(labels: &[&[u8]]) -> Slip21Key {
+fn encode_labels(labels: &[&[u8]]) -> Vec<u8> {
     // compute the total length of the encoded labels as the sum of their lengths,
     // each increased by 1 because of the length prefix.
     let encoded_length = labels.iter().map(|label| label.len() + 1).sum::<usize>();
@@ -107,17 +205,124 @@ pub fn derive_slip21_key(labels: &[&[u8]]) -> Slip21Key {
         encoded_labels.extend_from_slice(label);
     }
 
-    let mut node = [0u8; 64];
-    if ecalls::derive_slip21_node(
-        encoded_labels.as_ptr(),
-        encoded_labels.len(),
-        node.as_mut_ptr(),
-    ) == 0
-    {
-        panic!("Failed to derive SLIP-21 node");
+    encoded_labels
+}
+
+/// Computes a one-shot HMAC-SHA256 via the secure element's `hmac` ecall.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = [0u8; 32];
+    if 0 == unsafe {
+        ecalls::hmac(
+            HashId::Sha256 as u32,
+            key.as_ptr(),
+            key.len(),
+            data.as_ptr(),
+            data.len(),
+            mac.as_mut_ptr(),
+        )
+    } {
+        panic!("Failed to compute HMAC-SHA256");
     }
-    // only return the last 32 bytes, which are the SLIP-21 key
-    let mut key = [0u8; 32];
-    key.copy_from_slice(&node[32..64]);
-    Slip21Key::from_bytes(key)
+    mac
+}
+
+/// A full 64-byte SLIP-21 node: a 32-byte chain value (used only for further derivations) and a
+/// 32-byte key (extracted via [`Slip21Node::key`]).
+///
+/// Unlike [`Slip21Key`] (and [`derive_slip21_key`], which discards the chain value), this lets a
+/// V-App derive a node once via the `derive_slip21_node` ecall and then branch cheaply to many
+/// sub-keys with [`Slip21Node::derive_child_hmac_sha256`], entirely in-VM.
+///
+/// # Security
+///
+/// Same envelope as [`Slip21Key`]: the full node is wrapped in `Zeroizing`, this type has no
+/// `Debug`/`Clone`, and it is zeroed on drop.
+pub struct Slip21Node {
+    node: Zeroizing<[u8; 64]>,
+}
+
+impl Slip21Node {
+    /// Derives the SLIP-21 root node for `labels` via the one-time ecall, retaining the chain
+    /// value so children can be derived locally afterwards (see
+    /// [`Slip21Node::derive_child_hmac_sha256`]).
+    ///
+    /// Subject to the same panics as [`derive_slip21_key`].
+    pub fn derive(labels: &[&[u8]]) -> Self {
+        let encoded_labels = encode_labels(labels);
+
+        let mut node = [0u8; 64];
+        if ecalls::derive_slip21_node(
+            encoded_labels.as_ptr(),
+            encoded_labels.len(),
+            node.as_mut_ptr(),
+        ) == 0
+        {
+            panic!("Failed to derive SLIP-21 node");
+        }
+
+        Slip21Node {
+            node: Zeroizing::new(node),
+        }
+    }
+
+    /// Derives a child node locally, without another ecall round-trip.
+    ///
+    /// # Not SLIP-21
+    ///
+    /// This is *not* the SLIP-21 child derivation, despite living on `Slip21Node`: real SLIP-21
+    /// computes both halves of a child node from a single `HMAC-SHA512(key = chain, data = 0x00
+    /// || label)` call. This method instead makes two separate `HMAC-SHA256` calls (one with a
+    /// `0x00` data prefix for the chain value, one with `0x01` for the key), which is a different
+    /// construction that happens to also split one parent secret into two children's worth of
+    /// output. It will not agree with any other wallet or library deriving the same path under
+    /// SLIP-21, so don't use it where SLIP-21-compatible subkeys are required - only where an
+    /// in-VM-only, vanadium-specific derivation is acceptable. Named `_hmac_sha256` rather than
+    /// `derive_child` so that isn't implied by the `Slip21Node` type name.
+    pub fn derive_child_hmac_sha256(&self, label: &[u8]) -> Slip21Node {
+        let chain = &self.node[0..32];
+
+        let mut data = Vec::with_capacity(1 + label.len());
+        data.push(0x00);
+        data.extend_from_slice(label);
+
+        let mut child = [0u8; 64];
+        child[0..32].copy_from_slice(&hmac_sha256(chain, &data));
+        data[0] = 0x01;
+        child[32..64].copy_from_slice(&hmac_sha256(chain, &data));
+
+        Slip21Node {
+            node: Zeroizing::new(child),
+        }
+    }
+
+    /// Extracts this node's key material (its last 32 bytes) as an opaque [`Slip21Key`].
+    pub fn key(&self) -> Slip21Key {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&self.node[32..64]);
+        Slip21Key::from_bytes(key)
+    }
+}
+
+/// Derives a SLIP-21 key node, based on the BIP39 seed.
+/// The key corresponds to the last 32-bytes of the corresponding SLIP-21 node.
+/// The initial 32 bytes (only used for further derivations) are not returned.
+///
+/// # Returns
+/// A `Slip21Key` opaque type representing the derived SLIP-21 key.
+///
+/// # Panics
+/// This function will panic if either:
+/// - The total length of the encoded labels exceeds 256 bytes.
+/// - Any individual label exceeds 252 bytes.
+/// - (Ledger-specific) `labels` has length 0 (no master key derivation)
+/// - (Ledger-specific) Any label contains a '/' character.
+///
+/// # Security
+///
+/// The returned key is wrapped in an opaque type that:
+/// - Prevents direct access to raw bytes (unless explicitly using `dangerous_as_raw_bytes()`)
+/// - Implements constant-time equality comparison
+/// - Automatically zeros memory on drop
+pub fn derive_slip21_key(labels: &[&[u8]]) -> Slip21Key {
+    Slip21Node::derive(labels).key()
 }
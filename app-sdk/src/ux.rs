@@ -11,11 +11,12 @@ use alloc::vec::Vec;
 
 use common::ecall_constants::DEVICE_PROPERTY_ID;
 pub use common::ux::{
-    Action, Deserializable, Event, EventCode, EventData, Icon, NavInfo, NavigationInfo, Page,
-    PageContent, PageContentInfo, TagValue,
+    Action, Deserializable, Event, EventCode, EventData, EventWire, Gesture, Icon, IndirectPayload,
+    NavInfo, NavigationInfo, Page, PageContent, PageContentInfo, TagValue, TouchEvent,
 };
 
 use crate::ux_generated;
+pub use crate::ux_generated::{set_language, Language, StringId};
 
 // Returns true if the device supports the page UX model, false if it supports the step UX model.
 // It panics for unsupported devices
@@ -54,6 +55,14 @@ pub fn get_event() -> Event {
                 // TODO: sanitize?
                 return Event::Action(action);
             }
+            EventCode::Touch => {
+                let touch = unsafe { event_data.touch };
+                return Event::Touch(touch);
+            }
+            EventCode::Indirect => {
+                let indirect = unsafe { event_data.indirect };
+                return Event::Indirect(indirect);
+            }
             EventCode::Unknown => {
                 let data = unsafe { event_data.raw };
                 return Event::Unknown(data);
@@ -89,7 +98,9 @@ pub fn get_action() -> Action {
     }
 }
 
-// Temporary function; similar to nbgl_useCaseReview
+/// Similar to `nbgl_useCaseReview`. Drives the auto-paginating flow generated by `build.rs` (see
+/// [`ux_generated::show_review`]/`ReviewFinalKind`); kept as a thin wrapper so existing callers
+/// don't need to spell out `ReviewFinalKind` themselves.
 pub fn review_pairs(
     intro_text: &str,
     intro_subtext: &str,
@@ -98,83 +109,43 @@ pub fn review_pairs(
     final_button_text: &str,
     long_press: bool,
 ) -> bool {
-    // As this is still too slow to compute everything at once, we use a 'streaming' approach where we compute
-    // the next page only after showing the current one.
-    // While we're computing the page, we're not able to listen to touch events, so it will currently miss
-    // user touches something before the precomputation of the next page is completed.
-    // TODO: improve this
-
-    // Calculate total number of pages
-    let n_pair_pages = ((pairs.len() + 1) / 2) as u32;
-    let n_pages = 2 + n_pair_pages; // intro + pair pages + final
-
-    // Initialize with capacity, but start empty
-    let mut serialized_pages = Vec::with_capacity(n_pages as usize);
-
-    // Compute and add the first page (intro)
-    serialized_pages.push(make_review_pairs_intro(
-        0,
-        n_pages,
+    review_pairs_with_icon(
         intro_text,
         intro_subtext,
-    ));
-
-    let mut active_page = 0;
-
-    loop {
-        // Show the current page
-        show_page_raw(&serialized_pages[active_page]);
-
-        // Compute the next page if it exists and hasn't been computed
-        if active_page + 1 < n_pages as usize && serialized_pages.len() == active_page + 1 {
-            let next_page_index = active_page + 1;
-            let next_page = if next_page_index == (n_pages - 1) as usize {
-                // Final page
-                if long_press {
-                    make_review_pairs_final_longpress(
-                        next_page_index as u32,
-                        n_pages,
-                        final_text,
-                        final_button_text,
-                    )
-                } else {
-                    make_review_pairs_final_confirmationbutton(
-                        next_page_index as u32,
-                        n_pages,
-                        final_text,
-                        final_button_text,
-                    )
-                }
-            } else {
-                // Pair page (indices 1 to n_pair_pages)
-                let chunk_index = next_page_index - 1;
-                let pair_chunk = pairs.chunks(2).nth(chunk_index as usize).unwrap();
-                make_review_pairs_content(next_page_index as u32, n_pages, pair_chunk)
-            };
-            serialized_pages.push(next_page);
-        }
+        pairs,
+        final_text,
+        final_button_text,
+        long_press,
+        Icon::None,
+    )
+}
 
-        // Process events
-        loop {
-            match get_event() {
-                Event::Action(Action::PreviousPage) if active_page > 0 => {
-                    active_page -= 1;
-                    break;
-                }
-                Event::Action(Action::NextPage) if active_page + 1 < n_pages as usize => {
-                    active_page += 1;
-                    break;
-                }
-                Event::Action(Action::Quit) => {
-                    return false;
-                }
-                Event::Action(Action::Confirm) => {
-                    return true;
-                }
-                _ => {} // Ignore other events
-            }
-        }
-    }
+/// Like [`review_pairs`], but lets the caller set the icon shown in the top-right corner of every
+/// screen (see [`ux_generated::show_review`] and the named icons in `icon_theme.ron`, e.g.
+/// `ux_generated::ICON_READY`).
+pub fn review_pairs_with_icon(
+    intro_text: &str,
+    intro_subtext: &str,
+    pairs: &[TagValue],
+    final_text: &str,
+    final_button_text: &str,
+    long_press: bool,
+    top_right_icon: Icon,
+) -> bool {
+    let final_kind = if long_press {
+        ux_generated::ReviewFinalKind::LongPress
+    } else {
+        ux_generated::ReviewFinalKind::ConfirmationButton
+    };
+    ux_generated::show_review(
+        intro_text,
+        intro_subtext,
+        pairs,
+        final_text,
+        final_button_text,
+        final_kind,
+        top_right_icon,
+    )
 }
 
 pub fn show_spinner(text: &str) {
@@ -251,8 +222,24 @@ pub fn show_confirm_reject(title: &str, text: &str, confirm: &str, reject: &str)
 #[inline(always)]
 pub fn ux_idle() {
     if has_page_api() {
-        show_page_raw(&ux_generated::RAW_PAGE_APP_DASHBOARD);
+        show_page_raw(&ux_generated::app_dashboard_page());
     } else {
         show_step_raw(&ux_generated::RAW_STEP_APP_DASHBOARD);
     }
 }
+
+/// Copies the out-of-line payload described by an [`Event::Indirect`] into `buf`.
+///
+/// `payload.len` must not exceed `buf.len()`, and the descriptor must still be valid, i.e. this
+/// must be called before the next [`get_event`]/[`wait`]/[`get_action`] call, since the host is
+/// free to recycle the arena slot once the next event is delivered.
+pub fn read_event_payload(payload: IndirectPayload, buf: &mut [u8]) -> Result<(), &'static str> {
+    if (payload.len as usize) > buf.len() {
+        return Err("buffer too small for indirect event payload");
+    }
+    let copied = ecalls::read_event_payload(payload.offset, payload.len, buf.as_mut_ptr());
+    if copied != payload.len {
+        return Err("indirect event payload descriptor is no longer valid");
+    }
+    Ok(())
+}
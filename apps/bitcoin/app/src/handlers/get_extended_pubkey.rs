@@ -1,12 +1,47 @@
 use alloc::{borrow::Cow, vec::Vec};
 
-use common::message::{RequestGetExtendedPubkey, ResponseGetExtendedPubkey};
+use common::bip32::DerivationPath;
+use common::message::{ExtendedPubkeyScriptType, Network, RequestGetExtendedPubkey, ResponseGetExtendedPubkey};
 use sdk::{
     curve::{Curve, EcfpPrivateKey, EcfpPublicKey, Secp256k1, ToPublicKey},
     hash::{Hasher, Ripemd160, Sha256},
 };
 
-const BIP32_TESTNET_PUBKEY_VERSION: u32 = 0x043587CFu32;
+/// BIP-32/SLIP-132 version words, keyed by (network, script type). The depth/parent
+/// fingerprint/child number/chaincode/compressed-key layout that follows is identical for all
+/// of them; only this 4-byte prefix differs.
+fn xpub_version(network: Network, script_type: ExtendedPubkeyScriptType) -> u32 {
+    match (network, script_type) {
+        (Network::MAINNET, ExtendedPubkeyScriptType::LEGACY) => 0x0488B21E,
+        (Network::MAINNET, ExtendedPubkeyScriptType::P2SH_P2WPKH) => 0x049D7CB2,
+        (Network::MAINNET, ExtendedPubkeyScriptType::P2WPKH) => 0x04B24746,
+        (Network::TESTNET, ExtendedPubkeyScriptType::LEGACY) => 0x043587CF,
+        (Network::TESTNET, ExtendedPubkeyScriptType::P2SH_P2WPKH) => 0x044A5262,
+        (Network::TESTNET, ExtendedPubkeyScriptType::P2WPKH) => 0x045F1CF6,
+    }
+}
+
+/// Rejects obviously-wrong (script_type, path) pairs: a `ypub`/`upub` or `zpub`/`vpub` prefix
+/// should only ever be handed out for a path under the BIP-49/BIP-84 purpose it corresponds to,
+/// since presenting e.g. a `44'` (legacy) key under a `zpub` prefix would mislead a wallet into
+/// treating a P2PKH key as if it controlled a P2WPKH output. `LEGACY` has no such check, since
+/// it is also the fallback prefix for custom, non-BIP-44 paths.
+fn validate_path_for_script_type(
+    bip32_path: &[u32],
+    script_type: ExtendedPubkeyScriptType,
+) -> Result<(), &'static str> {
+    use common::bip32::HARDENED;
+
+    let expected_purpose = match script_type {
+        ExtendedPubkeyScriptType::LEGACY => return Ok(()),
+        ExtendedPubkeyScriptType::P2SH_P2WPKH => 49,
+        ExtendedPubkeyScriptType::P2WPKH => 84,
+    };
+    match bip32_path.first() {
+        Some(&purpose) if purpose == HARDENED | expected_purpose => Ok(()),
+        _ => Err("Derivation path is not a plausible path for the requested script type"),
+    }
+}
 
 fn get_pubkey_fingerprint(pubkey: &EcfpPublicKey<Secp256k1, 32>) -> u32 {
     let pk_bytes = pubkey.as_ref().to_bytes();
@@ -24,39 +59,34 @@ fn get_pubkey_fingerprint(pubkey: &EcfpPublicKey<Secp256k1, 32>) -> u32 {
 pub fn handle_get_extended_pubkey<'a, 'b>(
     req: &'a RequestGetExtendedPubkey,
 ) -> Result<ResponseGetExtendedPubkey<'b>, &'static str> {
-    if req.bip32_path.len() > 256 {
-        return Err("Derivation path is too long");
-    }
+    let path = DerivationPath::new(req.bip32_path.clone()).map_err(|_| "Derivation path is too long")?;
 
     if req.display {
         todo!("Display is not yet implemented")
     }
 
-    let hd_node = sdk::curve::Secp256k1::derive_hd_node(&req.bip32_path)?;
+    validate_path_for_script_type(&path, req.script_type)?;
+
+    let hd_node = sdk::curve::Secp256k1::derive_hd_node(&path)?;
     let privkey: EcfpPrivateKey<Secp256k1, 32> = EcfpPrivateKey::new(*hd_node.privkey);
     let pubkey = privkey.to_public_key();
     let pubkey_bytes = pubkey.as_ref().to_bytes();
 
-    let depth = req.bip32_path.len() as u8;
+    let depth = path.len() as u8;
 
-    let parent_fpr: u32 = if req.bip32_path.is_empty() {
+    let parent_fpr: u32 = if path.is_empty() {
         0
     } else {
-        let hd_node =
-            sdk::curve::Secp256k1::derive_hd_node(&req.bip32_path[..req.bip32_path.len() - 1])?;
+        let hd_node = sdk::curve::Secp256k1::derive_hd_node(&path.parent())?;
         let parent_privkey: EcfpPrivateKey<Secp256k1, 32> = EcfpPrivateKey::new(*hd_node.privkey);
         let parent_pubkey = parent_privkey.to_public_key();
         get_pubkey_fingerprint(&parent_pubkey)
     };
 
-    let child_number: u32 = if req.bip32_path.is_empty() {
-        0
-    } else {
-        req.bip32_path[req.bip32_path.len() - 1]
-    };
+    let child_number: u32 = path.child_number();
 
     let mut xpub = Vec::with_capacity(78);
-    xpub.extend_from_slice(&BIP32_TESTNET_PUBKEY_VERSION.to_be_bytes());
+    xpub.extend_from_slice(&xpub_version(req.network, req.script_type).to_be_bytes());
     xpub.push(depth);
     xpub.extend_from_slice(&parent_fpr.to_be_bytes());
     xpub.extend_from_slice(&child_number.to_be_bytes());
@@ -73,54 +103,7 @@ pub fn handle_get_extended_pubkey<'a, 'b>(
 mod tests {
     use super::*;
     use bs58;
-
-    use std::num::ParseIntError;
-
-    // TODO: this should be implemented and tested elsewhere
-    /// Parse a Bitcoin-style derivation path (e.g., "m/48'/1'/4'/1'/0/7") into a list of
-    /// child indices as `u32`. Hardened indices are marked by an apostrophe (`'`).
-    pub fn parse_derivation_path(path: &str) -> Result<Vec<u32>, String> {
-        // Split by '/' to get each component. e.g. "m/48'/1'/4'/1'/0/7" -> ["m", "48'", "1'", "4'", "1'", "0", "7"]
-        let mut components = path.split('/').collect::<Vec<&str>>();
-
-        // The first component should be "m". Remove it if present.
-        if let Some(first) = components.first() {
-            if *first == "m" {
-                components.remove(0);
-            }
-        }
-
-        let mut indices = Vec::new();
-        for comp in components {
-            // Check if this component is hardened
-            let hardened = comp.ends_with('\'');
-
-            // Remove the apostrophe if hardened
-            let raw_index = if hardened {
-                &comp[..comp.len() - 1]
-            } else {
-                comp
-            };
-
-            // Parse the numeric portion
-            let index: u32 = raw_index.parse::<u32>().map_err(|e: ParseIntError| {
-                format!("Invalid derivation index '{}': {}", comp, e)
-            })?;
-
-            // If hardened, add the 0x80000000 mask
-            let child_number = if hardened {
-                0x80000000_u32
-                    .checked_add(index)
-                    .ok_or_else(|| format!("Invalid hardened index '{}': overflowed", comp))?
-            } else {
-                index
-            };
-
-            indices.push(child_number);
-        }
-
-        Ok(indices)
-    }
+    use common::bip32::parse_path;
 
     #[test]
     fn test_handle_get_extended_pubkey() {
@@ -138,8 +121,9 @@ mod tests {
             // decode the derivation path into a Vec<u32>
 
             let req = RequestGetExtendedPubkey {
-                bip32_path: parse_derivation_path(path).unwrap(),
+                bip32_path: parse_path(path).unwrap().into_vec(),
                 display: false,
+                ..Default::default()
             };
 
             let response = handle_get_extended_pubkey(&req).unwrap();
@@ -153,4 +137,32 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_handle_get_extended_pubkey_mainnet_zpub() {
+        let req = RequestGetExtendedPubkey {
+            bip32_path: parse_path("m/84'/0'/0'").unwrap().into_vec(),
+            display: false,
+            network: Network::MAINNET,
+            script_type: ExtendedPubkeyScriptType::P2WPKH,
+        };
+
+        let response = handle_get_extended_pubkey(&req).unwrap();
+
+        // A zpub always starts with the SLIP-132 "zpub" version word.
+        assert_eq!(&response.pubkey[..4], &0x04B24746u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_handle_get_extended_pubkey_rejects_implausible_path() {
+        let req = RequestGetExtendedPubkey {
+            // A legacy (44') path requested under the native-segwit (zpub) prefix.
+            bip32_path: parse_path("m/44'/0'/0'").unwrap().into_vec(),
+            display: false,
+            network: Network::MAINNET,
+            script_type: ExtendedPubkeyScriptType::P2WPKH,
+        };
+
+        assert!(handle_get_extended_pubkey(&req).is_err());
+    }
 }
\ No newline at end of file
@@ -1,7 +1,11 @@
 mod get_address;
 mod get_extended_pubkey;
 mod get_master_fingerprint;
+mod sign_message;
+mod verify_message;
 
 pub use get_address::handle_get_address;
 pub use get_extended_pubkey::handle_get_extended_pubkey;
 pub use get_master_fingerprint::handle_get_master_fingerprint;
+pub use sign_message::handle_sign_message;
+pub use verify_message::handle_verify_message;
@@ -0,0 +1,39 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use common::message::{domain_separated_message, RequestSignMessage, ResponseSignMessage, SignatureScheme};
+use sdk::{
+    curve::Secp256k1,
+    hash::{Hasher, Sha256},
+};
+
+pub fn handle_sign_message<'a, 'b>(
+    req: &'a RequestSignMessage,
+) -> Result<ResponseSignMessage<'b>, &'static str> {
+    if req.derivation_path.len() > 256 {
+        return Err("Derivation path is too long");
+    }
+
+    let prefixed = domain_separated_message(&req.message);
+
+    let signature: Vec<u8> = match req.scheme {
+        SignatureScheme::ECDSA => {
+            let mut msg_hash = [0u8; 32];
+            let mut hasher = Sha256::new();
+            hasher.update(&prefixed);
+            hasher.digest(&mut msg_hash);
+
+            let (r, s, _recid) = Secp256k1::sign_recoverable(&req.derivation_path, &msg_hash)?;
+            let mut sig = Vec::with_capacity(64);
+            sig.extend_from_slice(&r);
+            sig.extend_from_slice(&s);
+            sig
+        }
+        SignatureScheme::SCHNORR => {
+            Secp256k1::sign_schnorr_bip340(&req.derivation_path, &prefixed)?.to_vec()
+        }
+    };
+
+    Ok(ResponseSignMessage {
+        signature: Cow::Owned(signature),
+    })
+}
@@ -0,0 +1,52 @@
+use common::message::{domain_separated_message, RequestVerifyMessage, ResponseVerifyMessage, SignatureScheme};
+use sdk::{
+    curve::Secp256k1,
+    hash::{Hasher, Sha256},
+};
+
+pub fn handle_verify_message(req: &RequestVerifyMessage) -> Result<ResponseVerifyMessage, &'static str> {
+    if req.derivation_path.len() > 256 {
+        return Err("Derivation path is too long");
+    }
+
+    let prefixed = domain_separated_message(&req.message);
+
+    let valid = match req.scheme {
+        SignatureScheme::ECDSA => {
+            if req.signature.len() != 64 {
+                return Err("Invalid signature length");
+            }
+            let mut r = [0u8; 32];
+            let mut s = [0u8; 32];
+            r.copy_from_slice(&req.signature[..32]);
+            s.copy_from_slice(&req.signature[32..]);
+
+            let mut msg_hash = [0u8; 32];
+            let mut hasher = Sha256::new();
+            hasher.update(&prefixed);
+            hasher.digest(&mut msg_hash);
+
+            let pubkey = Secp256k1::derive_pubkey(&req.derivation_path)?;
+            (0u8..=3).any(|recid| {
+                Secp256k1::recover(&msg_hash, recid, &r, &s)
+                    .map(|recovered| recovered == pubkey[1..65])
+                    .unwrap_or(false)
+            })
+        }
+        SignatureScheme::SCHNORR => {
+            if req.signature.len() != 64 {
+                return Err("Invalid signature length");
+            }
+            let mut signature = [0u8; 64];
+            signature.copy_from_slice(&req.signature);
+
+            let pubkey = Secp256k1::derive_pubkey(&req.derivation_path)?;
+            let mut pubkey_x = [0u8; 32];
+            pubkey_x.copy_from_slice(&pubkey[1..33]);
+
+            Secp256k1::verify_schnorr_bip340(&pubkey_x, &prefixed, &signature)?
+        }
+    };
+
+    Ok(ResponseVerifyMessage { valid })
+}
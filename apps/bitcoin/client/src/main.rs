@@ -15,6 +15,17 @@ mod client;
 use sdk::vanadium_client::client_utils::{create_default_client, ClientType};
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use bitcoin::bip32::{ChildNumber, DerivationPath as BtcDerivationPath, Fingerprint};
+use bitcoin::hex::FromHex;
+use bitcoin::psbt::Psbt;
+use bitcoin::taproot::{self, TapLeafHash};
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Address, Amount, OutPoint, PublicKey, ScriptBuf,
+    Sequence, Transaction, TxIn, TxOut, Txid, Witness, XOnlyPublicKey,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "vnd-bitcoin-cli")]
@@ -59,6 +70,39 @@ enum CliCommand {
         #[clap(long)]
         psbt: String,
     },
+    CreatePsbt {
+        /// Comma-separated list of `txid:vout:amount_sats` inputs to spend.
+        #[clap(long)]
+        inputs: String,
+        /// Comma-separated list of `address:amount_sats` outputs to pay.
+        #[clap(long)]
+        outputs: String,
+        /// Wallet policy descriptor template used to derive the change output, if any.
+        #[clap(long)]
+        change_descriptor_template: Option<String>,
+        /// Keys info for the change descriptor, in the same format as `--keys-info`.
+        #[clap(long)]
+        change_keys_info: Option<String>,
+        /// Amount (in sats) sent back to the wallet as change.
+        #[clap(long)]
+        change_amount: Option<u64>,
+    },
+    UpdatePsbt {
+        #[clap(long)]
+        psbt: String,
+        #[clap(long)]
+        keys_info: String,
+        /// Comma-separated list of `txid:vout:amount_sats:script_pubkey_hex` witness UTXOs.
+        #[clap(long, default_value = "")]
+        witness_utxos: String,
+        /// Comma-separated list of `txid:raw_tx_hex` previous transactions, for non-segwit inputs.
+        #[clap(long, default_value = "")]
+        non_witness_utxos: String,
+    },
+    FinalizePsbt {
+        #[clap(long)]
+        psbt: String,
+    },
     Exit,
 }
 
@@ -166,6 +210,28 @@ impl Hinter for CommandCompleter {
 
 impl Helper for CommandCompleter {}
 
+/// The Bitcoin network this CLI talks to, selecting which `bitcoin::Network` every address and
+/// PSBT it parses or prints is validated against (see [`Args::network`]).
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+#[clap(rename_all = "snake_case")]
+enum NetworkArg {
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl From<NetworkArg> for bitcoin::Network {
+    fn from(network: NetworkArg) -> Self {
+        match network {
+            NetworkArg::Mainnet => bitcoin::Network::Bitcoin,
+            NetworkArg::Testnet => bitcoin::Network::Testnet,
+            NetworkArg::Signet => bitcoin::Network::Signet,
+            NetworkArg::Regtest => bitcoin::Network::Regtest,
+        }
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "Vanadium", about = "Run a V-App on Vanadium")]
 struct Args {
@@ -179,6 +245,10 @@ struct Args {
     /// Use the native interface
     #[arg(long, group = "interface")]
     native: bool,
+
+    /// Bitcoin network to validate every parsed or printed address and PSBT against.
+    #[arg(long, value_enum, default_value = "mainnet")]
+    network: NetworkArg,
 }
 
 // a bit of a hack: we convert the prompt in a format that clap can parse
@@ -210,9 +280,322 @@ fn parse_keys_info(keys_info: &str) -> Result<Vec<common::bip388::KeyInformation
     Ok(keys_info)
 }
 
+// parse a single "txid:vout:amount_sats" input descriptor, as accepted by `create_psbt`
+fn parse_outpoint_amount(s: &str) -> Result<(OutPoint, Amount), &'static str> {
+    let mut parts = s.splitn(3, ':');
+    let txid = parts.next().ok_or("missing txid")?;
+    let vout = parts.next().ok_or("missing vout")?;
+    let amount = parts.next().ok_or("missing amount")?;
+
+    let txid: Txid = txid.parse().map_err(|_| "invalid txid")?;
+    let vout: u32 = vout.parse().map_err(|_| "invalid vout")?;
+    let amount: u64 = amount.parse().map_err(|_| "invalid amount")?;
+
+    Ok((OutPoint::new(txid, vout), Amount::from_sat(amount)))
+}
+
+// parse a single "address:amount_sats" output descriptor, as accepted by `create_psbt`
+fn parse_address_amount(
+    s: &str,
+    network: bitcoin::Network,
+) -> Result<(Address, Amount), &'static str> {
+    let mut parts = s.splitn(2, ':');
+    let address = parts.next().ok_or("missing address")?;
+    let amount = parts.next().ok_or("missing amount")?;
+
+    let address = Address::from_str(address)
+        .map_err(|_| "invalid address")?
+        .require_network(network)
+        .map_err(|_| "address is for the wrong network")?;
+    let amount: u64 = amount.parse().map_err(|_| "invalid amount")?;
+
+    Ok((address, amount.into()))
+}
+
+// parse a single "txid:vout:amount_sats:script_pubkey_hex" witness UTXO descriptor, as accepted
+// by `update_psbt`
+fn parse_witness_utxo(s: &str) -> Result<(OutPoint, TxOut), &'static str> {
+    let mut parts = s.splitn(4, ':');
+    let txid = parts.next().ok_or("missing txid")?;
+    let vout = parts.next().ok_or("missing vout")?;
+    let amount = parts.next().ok_or("missing amount")?;
+    let script_pubkey = parts.next().ok_or("missing script_pubkey")?;
+
+    let txid: Txid = txid.parse().map_err(|_| "invalid txid")?;
+    let vout: u32 = vout.parse().map_err(|_| "invalid vout")?;
+    let amount: u64 = amount.parse().map_err(|_| "invalid amount")?;
+    let script_pubkey =
+        ScriptBuf::from_hex(script_pubkey).map_err(|_| "invalid script_pubkey hex")?;
+
+    Ok((
+        OutPoint::new(txid, vout),
+        TxOut {
+            value: Amount::from_sat(amount),
+            script_pubkey,
+        },
+    ))
+}
+
+// parse a single "txid:raw_tx_hex" previous-transaction descriptor, as accepted by `update_psbt`
+fn parse_non_witness_utxo(s: &str) -> Result<(Txid, Transaction), &'static str> {
+    let mut parts = s.splitn(2, ':');
+    let txid = parts.next().ok_or("missing txid")?;
+    let raw_tx = parts.next().ok_or("missing raw tx hex")?;
+
+    let txid: Txid = txid.parse().map_err(|_| "invalid txid")?;
+    let raw_tx = Vec::from_hex(raw_tx).map_err(|_| "invalid raw tx hex")?;
+    let tx: Transaction =
+        bitcoin::consensus::deserialize(&raw_tx).map_err(|_| "invalid raw tx")?;
+
+    Ok((txid, tx))
+}
+
+// Creator role (BIP174): builds an unsigned PSBT spending `inputs` to `outputs`, optionally
+// appending a change output derived from the device via a wallet policy (`change_descriptor_template`
+// / `change_keys_info`), since computing the change amount (fee estimation) isn't something this
+// CLI has the chain context to do on its own.
+async fn create_psbt(
+    bitcoin_client: &mut BitcoinClient,
+    inputs: &str,
+    outputs: &str,
+    change_descriptor_template: Option<&str>,
+    change_keys_info: Option<&str>,
+    change_amount: Option<u64>,
+) -> Result<Psbt, Box<dyn std::error::Error>> {
+    let network = bitcoin_client.network();
+    let inputs = inputs
+        .split(',')
+        .map(|s| parse_outpoint_amount(s.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut outputs = outputs
+        .split(',')
+        .map(|s| parse_address_amount(s.trim(), network))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let (Some(descriptor_template), Some(keys_info), Some(change_amount)) =
+        (change_descriptor_template, change_keys_info, change_amount)
+    {
+        let keys_info = parse_keys_info(keys_info)?;
+        let wallet_policy_coords = common::message::WalletPolicyCoordinates {
+            is_change: true,
+            address_index: 0,
+        };
+        let wallet_policy_msg = common::message::WalletPolicy {
+            template: descriptor_template.to_string(),
+            keys_info: keys_info
+                .iter()
+                .map(|ki| common::message::PubkeyInfo {
+                    pubkey: ki.pubkey.encode().to_vec(),
+                    origin: ki.origin_info.as_ref().map(|origin_info| {
+                        common::message::KeyOrigin {
+                            fingerprint: origin_info.fingerprint,
+                            path: common::message::Bip32Path(
+                                origin_info
+                                    .derivation_path
+                                    .iter()
+                                    .map(|step| u32::from(*step))
+                                    .collect(),
+                            ),
+                        }
+                    }),
+                })
+                .collect(),
+        };
+
+        let change_address = bitcoin_client
+            .get_address(
+                &common::message::Account::WalletPolicy(wallet_policy_msg),
+                "",
+                &common::message::AccountCoordinates::WalletPolicy(wallet_policy_coords),
+                &[42u8; 32], // placeholder HMAC; the change policy is not looked up among registered accounts
+                false,
+            )
+            .await?;
+        let change_address = Address::from_str(&change_address)
+            .map_err(|_| "invalid change address")?
+            .require_network(network)
+            .map_err(|_| "device returned a change address for the wrong network")?;
+        outputs.push((change_address, Amount::from_sat(change_amount)));
+    }
+
+    let unsigned_tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs
+            .iter()
+            .map(|(outpoint, _)| TxIn {
+                previous_output: *outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: outputs
+            .iter()
+            .map(|(address, amount)| TxOut {
+                value: *amount,
+                script_pubkey: address.script_pubkey(),
+            })
+            .collect(),
+    };
+
+    Ok(Psbt::from_unsigned_tx(unsigned_tx)?)
+}
+
+// Updater role (BIP174): attaches witness/non-witness UTXOs and the wallet policy's key origins
+// (as BIP32 derivation paths) to every input of an existing PSBT.
+fn update_psbt(
+    psbt: &str,
+    keys_info: &str,
+    witness_utxos: &str,
+    non_witness_utxos: &str,
+) -> Result<Psbt, Box<dyn std::error::Error>> {
+    let psbt_bytes = base64::engine::general_purpose::STANDARD
+        .decode(psbt)
+        .map_err(|_| "Failed to decode PSBT")?;
+    let mut psbt = Psbt::deserialize(&psbt_bytes).map_err(|_| "Failed to parse PSBT")?;
+
+    for utxo in witness_utxos.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let (outpoint, txout) = parse_witness_utxo(utxo)?;
+        let index = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .position(|txin| txin.previous_output == outpoint)
+            .ok_or("PSBT has no input for the given witness UTXO's outpoint")?;
+        psbt.inputs[index].witness_utxo = Some(txout);
+    }
+
+    for utxo in non_witness_utxos
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        let (txid, tx) = parse_non_witness_utxo(utxo)?;
+        for (index, txin) in psbt.unsigned_tx.input.iter().enumerate() {
+            if txin.previous_output.txid == txid {
+                psbt.inputs[index].non_witness_utxo = Some(tx.clone());
+            }
+        }
+    }
+
+    let keys_info = parse_keys_info(keys_info)?;
+    for input in psbt.inputs.iter_mut() {
+        for ki in &keys_info {
+            let (Some(origin_info), Ok(pubkey)) =
+                (&ki.origin_info, PublicKey::from_slice(&ki.pubkey.encode()))
+            else {
+                continue;
+            };
+            let fingerprint = Fingerprint::from(origin_info.fingerprint.to_be_bytes());
+            let derivation_path: BtcDerivationPath = origin_info
+                .derivation_path
+                .iter()
+                .map(|step| ChildNumber::from(*step))
+                .collect();
+            input
+                .bip32_derivation
+                .insert(pubkey.inner, (fingerprint, derivation_path));
+        }
+    }
+
+    Ok(psbt)
+}
+
+// Finalizer + Extractor roles (BIP174): turns each input's partial signature(s) into a final
+// `script_witness`, for P2WPKH inputs (from a partial ECDSA signature) and Taproot key-path
+// inputs (from `tap_key_sig`); any other input (e.g. P2WSH or Taproot script-path) is left
+// unfinalized. Once every input is finalized, extracts and returns the network-serialized raw
+// transaction instead of a PSBT.
+fn finalize_psbt(psbt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let psbt_bytes = base64::engine::general_purpose::STANDARD
+        .decode(psbt)
+        .map_err(|_| "Failed to decode PSBT")?;
+    let mut psbt = Psbt::deserialize(&psbt_bytes).map_err(|_| "Failed to parse PSBT")?;
+
+    let mut all_finalized = true;
+    for index in 0..psbt.inputs.len() {
+        let is_p2wpkh = psbt.inputs[index]
+            .witness_utxo
+            .as_ref()
+            .map(|utxo| utxo.script_pubkey.is_p2wpkh())
+            .unwrap_or(false);
+
+        if is_p2wpkh {
+            let Some((pubkey, sig)) = psbt.inputs[index].partial_sigs.iter().next() else {
+                all_finalized = false;
+                continue;
+            };
+            let mut witness = Witness::new();
+            witness.push(sig.to_vec());
+            witness.push(pubkey.to_bytes());
+            psbt.inputs[index].final_script_witness = Some(witness);
+            psbt.inputs[index].partial_sigs.clear();
+            psbt.inputs[index].bip32_derivation.clear();
+        } else if let Some(sig) = psbt.inputs[index].tap_key_sig {
+            let mut witness = Witness::new();
+            witness.push(sig.to_vec());
+            psbt.inputs[index].final_script_witness = Some(witness);
+            psbt.inputs[index].tap_key_sig = None;
+            psbt.inputs[index].tap_script_sigs.clear();
+            psbt.inputs[index].bip32_derivation.clear();
+            psbt.inputs[index].tap_key_origins.clear();
+        } else {
+            all_finalized = false;
+        }
+    }
+
+    if all_finalized {
+        let tx = psbt.extract_tx().map_err(|e| e.to_string())?;
+        Ok(bitcoin::consensus::encode::serialize_hex(&tx))
+    } else {
+        println!("Not every input could be finalized; returning the partially-finalized PSBT");
+        Ok(base64::engine::general_purpose::STANDARD.encode(psbt.serialize()))
+    }
+}
+
+// embeds a single partial signature returned by `sign_psbt` into the standard BIP174 field(s)
+// `finalize_psbt` later reads back out, branching on whether it's an ECDSA (P2WPKH) or Schnorr
+// (Taproot key- or script-path) signature per `PartialSignature`'s `pubkey`/`leaf_hash` shape.
+fn apply_partial_signature(
+    psbt: &mut Psbt,
+    part_sig: &client::PartialSignature,
+) -> Result<(), &'static str> {
+    let input = psbt
+        .inputs
+        .get_mut(part_sig.input_index as usize)
+        .ok_or("Signature for out-of-range input index")?;
+
+    match (&part_sig.leaf_hash, part_sig.pubkey.len()) {
+        (None, 33) => {
+            let pubkey = PublicKey::from_slice(&part_sig.pubkey).map_err(|_| "Invalid public key")?;
+            let sig = bitcoin::ecdsa::Signature::from_slice(&part_sig.signature)
+                .map_err(|_| "Invalid ECDSA signature")?;
+            input.partial_sigs.insert(pubkey, sig);
+        }
+        (None, _) => {
+            let sig = taproot::Signature::from_slice(&part_sig.signature)
+                .map_err(|_| "Invalid Schnorr signature")?;
+            input.tap_key_sig = Some(sig);
+        }
+        (Some(leaf_hash), _) => {
+            let xonly = XOnlyPublicKey::from_slice(&part_sig.pubkey)
+                .map_err(|_| "Invalid x-only public key")?;
+            let leaf_hash =
+                TapLeafHash::from_slice(leaf_hash).map_err(|_| "Invalid leaf hash")?;
+            let sig = taproot::Signature::from_slice(&part_sig.signature)
+                .map_err(|_| "Invalid Schnorr signature")?;
+            input.tap_script_sigs.insert((xonly, leaf_hash), sig);
+        }
+    }
+
+    Ok(())
+}
+
 async fn handle_cli_command(
     bitcoin_client: &mut BitcoinClient,
     cli: &Cli,
+    registered_wallets: &mut HashMap<String, (Vec<u8>, Vec<u8>)>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match &cli.command {
         CliCommand::GetFingerprint => {
@@ -232,11 +615,45 @@ async fn handle_cli_command(
             descriptor_template,
             keys_info,
         } => {
-            println!(
-                "Executing register_account for {:?} account: {:?} {:?}",
-                name, descriptor_template, keys_info
-            );
-            println!("(Not implemented)");
+            let descriptor_template = descriptor_template
+                .as_deref()
+                .ok_or("--descriptor-template is required")?;
+            let keys_info_arg = keys_info.as_deref().ok_or("--keys-info is required")?;
+
+            let keys_info = parse_keys_info(keys_info_arg)?;
+            let wallet_policy_msg = common::message::WalletPolicy {
+                template: descriptor_template.to_string(),
+                keys_info: keys_info
+                    .iter()
+                    .map(|ki| common::message::PubkeyInfo {
+                        pubkey: ki.pubkey.encode().to_vec(),
+                        origin: ki.origin_info.as_ref().map(|origin_info| {
+                            common::message::KeyOrigin {
+                                fingerprint: origin_info.fingerprint,
+                                path: common::message::Bip32Path(
+                                    origin_info
+                                        .derivation_path
+                                        .iter()
+                                        .map(|step| u32::from(*step))
+                                        .collect(),
+                                ),
+                            }
+                        }),
+                    })
+                    .collect(),
+            };
+
+            let account_name = name.clone().unwrap_or_default();
+            let (wallet_id, wallet_hmac) = bitcoin_client
+                .register_account(
+                    &common::message::Account::WalletPolicy(wallet_policy_msg),
+                    &account_name,
+                )
+                .await?;
+
+            println!("Wallet ID:   {}", hex::encode(&wallet_id));
+            println!("Wallet HMAC: {}", hex::encode(&wallet_hmac));
+            registered_wallets.insert(account_name, (wallet_id, wallet_hmac));
         }
         CliCommand::GetAddress {
             display,
@@ -277,24 +694,39 @@ async fn handle_cli_command(
                     .collect(),
             };
 
+            let account_name = name.as_deref().unwrap_or("");
+            let (_, wallet_hmac) = registered_wallets
+                .get(account_name)
+                .ok_or("Account not registered; run register_account first")?;
+            let wallet_hmac: &[u8; 32] = wallet_hmac
+                .as_slice()
+                .try_into()
+                .map_err(|_| "Stored wallet HMAC has an unexpected length")?;
+
             let addr = bitcoin_client
                 .get_address(
                     &common::message::Account::WalletPolicy(wallet_policy_msg),
-                    name.as_deref().unwrap_or(""),
+                    account_name,
                     &common::message::AccountCoordinates::WalletPolicy(wallet_policy_coords),
-                    &[42u8; 32], // TODO: placeholder
+                    wallet_hmac,
                     *display,
                 )
                 .await?;
+            let addr = Address::from_str(&addr)
+                .map_err(|_| "Device returned an invalid address")?
+                .require_network(bitcoin_client.network())
+                .map_err(|_| "Device returned an address for the wrong network")?;
             println!("{}", addr);
         }
         CliCommand::SignPsbt { psbt } => {
-            let psbt = base64::engine::general_purpose::STANDARD
+            let psbt_bytes = base64::engine::general_purpose::STANDARD
                 .decode(&psbt)
                 .map_err(|_| "Failed to decode PSBT")?;
-            let partial_sigs = bitcoin_client.sign_psbt(&psbt).await?;
+            let partial_sigs = bitcoin_client.sign_psbt(&psbt_bytes).await?;
 
             println!("{} signatures returned", partial_sigs.len());
+            let mut signed_psbt =
+                Psbt::deserialize(&psbt_bytes).map_err(|_| "Failed to parse PSBT")?;
             for part_sig in &partial_sigs {
                 println!("Input index: {}", part_sig.input_index);
                 println!("Public key: {}", hex::encode(&part_sig.pubkey));
@@ -302,7 +734,48 @@ async fn handle_cli_command(
                 if let Some(leaf_hash) = &part_sig.leaf_hash {
                     println!("Leaf hash: {}", hex::encode(leaf_hash));
                 }
+                apply_partial_signature(&mut signed_psbt, part_sig)?;
             }
+            println!(
+                "Updated PSBT (ready for finalize_psbt): {}",
+                base64::engine::general_purpose::STANDARD.encode(signed_psbt.serialize())
+            );
+        }
+        CliCommand::CreatePsbt {
+            inputs,
+            outputs,
+            change_descriptor_template,
+            change_keys_info,
+            change_amount,
+        } => {
+            let psbt = create_psbt(
+                bitcoin_client,
+                inputs,
+                outputs,
+                change_descriptor_template.as_deref(),
+                change_keys_info.as_deref(),
+                *change_amount,
+            )
+            .await?;
+            println!(
+                "{}",
+                base64::engine::general_purpose::STANDARD.encode(psbt.serialize())
+            );
+        }
+        CliCommand::UpdatePsbt {
+            psbt,
+            keys_info,
+            witness_utxos,
+            non_witness_utxos,
+        } => {
+            let psbt = update_psbt(psbt, keys_info, witness_utxos, non_witness_utxos)?;
+            println!(
+                "{}",
+                base64::engine::general_purpose::STANDARD.encode(psbt.serialize())
+            );
+        }
+        CliCommand::FinalizePsbt { psbt } => {
+            println!("{}", finalize_psbt(psbt)?);
         }
         CliCommand::Exit => {
             return Err("Exiting".into());
@@ -327,8 +800,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         ClientType::Tcp
     };
-    let mut bitcoin_client =
-        BitcoinClient::new(create_default_client("vnd-bitcoin", client_type).await?);
+    let network: bitcoin::Network = args.network.into();
+    let mut bitcoin_client = BitcoinClient::new(
+        create_default_client("vnd-bitcoin", client_type).await?,
+        network,
+    );
+
+    let mut registered_wallets: HashMap<String, (Vec<u8>, Vec<u8>)> = HashMap::new();
 
     let mut rl = Editor::<CommandCompleter, rustyline::history::DefaultHistory>::new()?;
     rl.set_helper(Some(CommandCompleter));
@@ -353,7 +831,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 match Cli::try_parse_from(clap_args) {
                     Ok(cli) => {
-                        if let Err(e) = handle_cli_command(&mut bitcoin_client, &cli).await {
+                        if let Err(e) =
+                            handle_cli_command(&mut bitcoin_client, &cli, &mut registered_wallets)
+                                .await
+                        {
                             println!("Error: {}", e);
                         }
                     }
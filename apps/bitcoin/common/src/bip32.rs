@@ -0,0 +1,202 @@
+//! Parsing and validation for human-readable BIP-32 derivation paths (e.g. `m/48'/1'/4'/1'/0/7`).
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Paths longer than this are rejected, matching the cap each handler already enforces on the
+/// raw `Vec<u32>` it receives over the wire.
+pub const MAX_PATH_LEN: usize = 256;
+
+/// The hardened-derivation bit, set on a child index to mark it as a hardened child.
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// Errors produced while parsing or validating a derivation path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bip32Error {
+    /// A path component was empty (e.g. a stray `//` or a trailing `/`).
+    EmptyComponent,
+    /// A component wasn't a valid unsigned integer, with or without a trailing hardened marker.
+    InvalidIndex,
+    /// An index was larger than `0x7FFFFFFF`, hardened or not (the top bit is reserved for the
+    /// hardened marker).
+    IndexOutOfRange,
+    /// The path has more than [`MAX_PATH_LEN`] elements.
+    TooLong,
+}
+
+impl fmt::Display for Bip32Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bip32Error::EmptyComponent => "derivation path contains an empty component",
+            Bip32Error::InvalidIndex => "derivation path contains a non-numeric component",
+            Bip32Error::IndexOutOfRange => "derivation index is out of range",
+            Bip32Error::TooLong => "derivation path is too long",
+        })
+    }
+}
+
+/// A validated BIP-32 derivation path: at most [`MAX_PATH_LEN`] child indices, each already
+/// encoded with the hardened bit ([`HARDENED`]) where applicable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Wraps a raw list of child indices, rejecting anything longer than [`MAX_PATH_LEN`].
+    pub fn new(indices: Vec<u32>) -> Result<Self, Bip32Error> {
+        if indices.len() > MAX_PATH_LEN {
+            return Err(Bip32Error::TooLong);
+        }
+        Ok(DerivationPath(indices))
+    }
+
+    /// Parses a string such as `m/48'/1'/4'/1'/0/7`. Both `'` and `h`/`H` are accepted as the
+    /// hardened-derivation marker, and a leading `m` or `m/` is optional.
+    pub fn parse(path: &str) -> Result<Self, Bip32Error> {
+        let path = path.strip_prefix("m/").or_else(|| path.strip_prefix('m')).unwrap_or(path);
+        if path.is_empty() {
+            return Ok(DerivationPath(Vec::new()));
+        }
+
+        let mut indices = Vec::new();
+        for component in path.split('/') {
+            if component.is_empty() {
+                return Err(Bip32Error::EmptyComponent);
+            }
+
+            let (digits, hardened) = match component.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (component, false),
+            };
+
+            let index: u32 = digits.parse().map_err(|_| Bip32Error::InvalidIndex)?;
+            if index > 0x7FFF_FFFF {
+                return Err(Bip32Error::IndexOutOfRange);
+            }
+
+            indices.push(if hardened { HARDENED | index } else { index });
+        }
+
+        Self::new(indices)
+    }
+
+    /// The underlying child indices, each already carrying the hardened bit where applicable.
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// This path's parent, i.e. all but the last index. Empty if this path is already the root.
+    pub fn parent(&self) -> DerivationPath {
+        match self.0.len() {
+            0 => DerivationPath(Vec::new()),
+            n => DerivationPath(self.0[..n - 1].to_vec()),
+        }
+    }
+
+    /// The last child index in the path, or `0` for the root.
+    pub fn child_number(&self) -> u32 {
+        self.0.last().copied().unwrap_or(0)
+    }
+
+    pub fn into_vec(self) -> Vec<u32> {
+        self.0
+    }
+}
+
+impl core::ops::Deref for DerivationPath {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl TryFrom<&[u32]> for DerivationPath {
+    type Error = Bip32Error;
+
+    fn try_from(indices: &[u32]) -> Result<Self, Bip32Error> {
+        DerivationPath::new(indices.to_vec())
+    }
+}
+
+impl core::str::FromStr for DerivationPath {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        DerivationPath::parse(s)
+    }
+}
+
+/// Parses a human-readable derivation path such as `m/48'/1'/4'/1'/0/7`. See
+/// [`DerivationPath::parse`] for the accepted syntax.
+pub fn parse_path(path: &str) -> Result<DerivationPath, Bip32Error> {
+    DerivationPath::parse(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hardened_and_unhardened_components() {
+        let path = parse_path("m/48'/1'/4'/1'/0/7").unwrap();
+        assert_eq!(
+            path.as_slice(),
+            &[
+                HARDENED | 48,
+                HARDENED | 1,
+                HARDENED | 4,
+                HARDENED | 1,
+                0,
+                7,
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_h_marker_and_missing_m_prefix() {
+        assert_eq!(parse_path("48h/1H").unwrap(), parse_path("48'/1'").unwrap());
+    }
+
+    #[test]
+    fn root_path_is_empty() {
+        assert!(parse_path("m").unwrap().is_empty());
+        assert!(parse_path("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_empty_component() {
+        assert_eq!(parse_path("44'//0").unwrap_err(), Bip32Error::EmptyComponent);
+    }
+
+    #[test]
+    fn rejects_non_numeric_component() {
+        assert_eq!(parse_path("m/foo'").unwrap_err(), Bip32Error::InvalidIndex);
+    }
+
+    #[test]
+    fn rejects_out_of_range_index() {
+        assert_eq!(parse_path("2147483648").unwrap_err(), Bip32Error::IndexOutOfRange);
+    }
+
+    #[test]
+    fn rejects_too_long_path() {
+        let path = core::iter::repeat("0").take(MAX_PATH_LEN + 1).collect::<Vec<_>>().join("/");
+        assert_eq!(parse_path(&path).unwrap_err(), Bip32Error::TooLong);
+    }
+
+    #[test]
+    fn parent_and_child_number() {
+        let path = parse_path("m/44'/1'/0'").unwrap();
+        assert_eq!(path.child_number(), HARDENED | 0);
+        assert_eq!(path.parent().as_slice(), &[HARDENED | 44, HARDENED | 1]);
+        assert!(path.parent().parent().parent().is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+#![no_std]
+
+extern crate alloc;
+
+pub mod bip32;
+pub mod message;
@@ -17,21 +17,48 @@ use super::*;
 
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct RequestGetVersion { }
+pub struct RequestGetVersion<'a> {
+    /// Feature strings (e.g. `"taproot"`, `"musig2"`, `"wallet_policy_v2"`) the client knows how
+    /// to speak, so the app can gate newer behavior on the handshake instead of a hard version bump.
+    pub supported_features: Vec<Cow<'a, str>>,
+}
 
-impl<'a> MessageRead<'a> for RequestGetVersion {
-    fn from_reader(r: &mut BytesReader, _: &[u8]) -> Result<Self> {
-        r.read_to_end();
-        Ok(Self::default())
+impl<'a> MessageRead<'a> for RequestGetVersion<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.supported_features.push(r.read_string(bytes).map(Cow::Borrowed)?),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
     }
 }
 
-impl MessageWrite for RequestGetVersion { }
+impl<'a> MessageWrite for RequestGetVersion<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + self.supported_features.iter().map(|s| 1 + sizeof_len((s).len())).sum::<usize>()
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        for s in &self.supported_features { w.write_with_tag(10, |w| w.write_string(&**s))?; }
+        Ok(())
+    }
+}
 
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct ResponseGetVersion<'a> {
     pub version: Cow<'a, str>,
+    /// Features the client must support for the session to proceed; if the client's
+    /// `supported_features` is missing any of these, the session should abort cleanly rather
+    /// than fail mid-`sign_psbt`.
+    pub required_features: Vec<Cow<'a, str>>,
+    /// Features the app supports but does not require the client to understand.
+    pub optional_features: Vec<Cow<'a, str>>,
 }
 
 impl<'a> MessageRead<'a> for ResponseGetVersion<'a> {
@@ -40,6 +67,8 @@ impl<'a> MessageRead<'a> for ResponseGetVersion<'a> {
         while !r.is_eof() {
             match r.next_tag(bytes) {
                 Ok(10) => msg.version = r.read_string(bytes).map(Cow::Borrowed)?,
+                Ok(18) => msg.required_features.push(r.read_string(bytes).map(Cow::Borrowed)?),
+                Ok(26) => msg.optional_features.push(r.read_string(bytes).map(Cow::Borrowed)?),
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -52,14 +81,32 @@ impl<'a> MessageWrite for ResponseGetVersion<'a> {
     fn get_size(&self) -> usize {
         0
         + if self.version == "" { 0 } else { 1 + sizeof_len((&self.version).len()) }
+        + self.required_features.iter().map(|s| 1 + sizeof_len((s).len())).sum::<usize>()
+        + self.optional_features.iter().map(|s| 1 + sizeof_len((s).len())).sum::<usize>()
     }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
         if self.version != "" { w.write_with_tag(10, |w| w.write_string(&**&self.version))?; }
+        for s in &self.required_features { w.write_with_tag(18, |w| w.write_string(&**s))?; }
+        for s in &self.optional_features { w.write_with_tag(26, |w| w.write_string(&**s))?; }
         Ok(())
     }
 }
 
+/// Checks a client's `supported_features` against the app's `required_features`, per the version
+/// handshake. Returns the first missing required feature, if any; the session must abort cleanly
+/// rather than proceed (e.g. into `sign_psbt`) if this returns `Some`.
+pub fn missing_required_feature<'a>(
+    request: &RequestGetVersion,
+    response: &'a ResponseGetVersion,
+) -> Option<&'a str> {
+    response
+        .required_features
+        .iter()
+        .find(|required| !request.supported_features.iter().any(|supported| supported == *required))
+        .map(|f| f.as_ref())
+}
+
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct RequestExit { }
@@ -118,11 +165,85 @@ impl MessageWrite for ResponseGetMasterFingerprint {
     }
 }
 
+/// Which chain the requested extended public key is for, selecting the version word's
+/// mainnet/testnet half (see [`ExtendedPubkeyScriptType`] for the other half).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Network {
+    MAINNET = 0,
+    TESTNET = 1,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network::TESTNET
+    }
+}
+
+impl From<i32> for Network {
+    fn from(i: i32) -> Self {
+        match i {
+            0 => Network::MAINNET,
+            1 => Network::TESTNET,
+            _ => Self::default(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Network {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "MAINNET" => Network::MAINNET,
+            "TESTNET" => Network::TESTNET,
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Which output-script convention the requested extended public key is for, per SLIP-132:
+/// `xpub`/`tpub` for legacy P2PKH/P2SH, `ypub`/`upub` for wrapped segwit (P2SH-P2WPKH),
+/// `zpub`/`vpub` for native segwit (P2WPKH).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExtendedPubkeyScriptType {
+    LEGACY = 0,
+    P2SH_P2WPKH = 1,
+    P2WPKH = 2,
+}
+
+impl Default for ExtendedPubkeyScriptType {
+    fn default() -> Self {
+        ExtendedPubkeyScriptType::LEGACY
+    }
+}
+
+impl From<i32> for ExtendedPubkeyScriptType {
+    fn from(i: i32) -> Self {
+        match i {
+            0 => ExtendedPubkeyScriptType::LEGACY,
+            1 => ExtendedPubkeyScriptType::P2SH_P2WPKH,
+            2 => ExtendedPubkeyScriptType::P2WPKH,
+            _ => Self::default(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for ExtendedPubkeyScriptType {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "LEGACY" => ExtendedPubkeyScriptType::LEGACY,
+            "P2SH_P2WPKH" => ExtendedPubkeyScriptType::P2SH_P2WPKH,
+            "P2WPKH" => ExtendedPubkeyScriptType::P2WPKH,
+            _ => Self::default(),
+        }
+    }
+}
+
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct RequestGetExtendedPubkey {
     pub display: bool,
     pub bip32_path: Vec<u32>,
+    pub network: Network,
+    pub script_type: ExtendedPubkeyScriptType,
 }
 
 impl<'a> MessageRead<'a> for RequestGetExtendedPubkey {
@@ -132,6 +253,8 @@ impl<'a> MessageRead<'a> for RequestGetExtendedPubkey {
             match r.next_tag(bytes) {
                 Ok(8) => msg.display = r.read_bool(bytes)?,
                 Ok(18) => msg.bip32_path = r.read_packed(bytes, |r, bytes| Ok(r.read_uint32(bytes)?))?,
+                Ok(24) => msg.network = r.read_enum(bytes)?,
+                Ok(32) => msg.script_type = r.read_enum(bytes)?,
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -145,11 +268,15 @@ impl MessageWrite for RequestGetExtendedPubkey {
         0
         + if self.display == false { 0 } else { 1 + sizeof_varint(*(&self.display) as u64) }
         + if self.bip32_path.is_empty() { 0 } else { 1 + sizeof_len(self.bip32_path.iter().map(|s| sizeof_varint(*(s) as u64)).sum::<usize>()) }
+        + if self.network == Network::TESTNET { 0 } else { 1 + sizeof_varint(*(&self.network) as u64) }
+        + if self.script_type == ExtendedPubkeyScriptType::LEGACY { 0 } else { 1 + sizeof_varint(*(&self.script_type) as u64) }
     }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
         if self.display != false { w.write_with_tag(8, |w| w.write_bool(*&self.display))?; }
         w.write_packed_with_tag(18, &self.bip32_path, |w, m| w.write_uint32(*m), &|m| sizeof_varint(*(m) as u64))?;
+        if self.network != Network::TESTNET { w.write_with_tag(24, |w| w.write_enum(*&self.network as i32))?; }
+        if self.script_type != ExtendedPubkeyScriptType::LEGACY { w.write_with_tag(32, |w| w.write_enum(*&self.script_type as i32))?; }
         Ok(())
     }
 }
@@ -578,10 +705,62 @@ impl<'a> MessageWrite for ResponseSignPsbt<'a> {
     }
 }
 
+/// Machine-readable counterpart to `ResponseError::error_msg`, following the code+message
+/// pairing record-oriented protocols like USP use for their error records: `error_msg` stays
+/// free-form for logs/UI, while `error_code` lets a client branch (retry vs. surface to the user)
+/// without parsing English text.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorCode {
+    UNSPECIFIED = 0,
+    USER_REJECTED = 1,
+    INVALID_PSBT = 2,
+    UNSUPPORTED_POLICY = 3,
+    DERIVATION_ERROR = 4,
+    NOT_REGISTERED = 5,
+    INTERNAL = 6,
+}
+
+impl Default for ErrorCode {
+    fn default() -> Self {
+        ErrorCode::UNSPECIFIED
+    }
+}
+
+impl From<i32> for ErrorCode {
+    fn from(i: i32) -> Self {
+        match i {
+            0 => ErrorCode::UNSPECIFIED,
+            1 => ErrorCode::USER_REJECTED,
+            2 => ErrorCode::INVALID_PSBT,
+            3 => ErrorCode::UNSUPPORTED_POLICY,
+            4 => ErrorCode::DERIVATION_ERROR,
+            5 => ErrorCode::NOT_REGISTERED,
+            6 => ErrorCode::INTERNAL,
+            _ => Self::default(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for ErrorCode {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "UNSPECIFIED" => ErrorCode::UNSPECIFIED,
+            "USER_REJECTED" => ErrorCode::USER_REJECTED,
+            "INVALID_PSBT" => ErrorCode::INVALID_PSBT,
+            "UNSUPPORTED_POLICY" => ErrorCode::UNSUPPORTED_POLICY,
+            "DERIVATION_ERROR" => ErrorCode::DERIVATION_ERROR,
+            "NOT_REGISTERED" => ErrorCode::NOT_REGISTERED,
+            "INTERNAL" => ErrorCode::INTERNAL,
+            _ => Self::default(),
+        }
+    }
+}
+
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct ResponseError<'a> {
     pub error_msg: Cow<'a, str>,
+    pub error_code: ErrorCode,
 }
 
 impl<'a> MessageRead<'a> for ResponseError<'a> {
@@ -590,6 +769,7 @@ impl<'a> MessageRead<'a> for ResponseError<'a> {
         while !r.is_eof() {
             match r.next_tag(bytes) {
                 Ok(10) => msg.error_msg = r.read_string(bytes).map(Cow::Borrowed)?,
+                Ok(16) => msg.error_code = r.read_enum(bytes)?,
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -602,32 +782,60 @@ impl<'a> MessageWrite for ResponseError<'a> {
     fn get_size(&self) -> usize {
         0
         + if self.error_msg == "" { 0 } else { 1 + sizeof_len((&self.error_msg).len()) }
+        + if self.error_code == ErrorCode::UNSPECIFIED { 0 } else { 1 + sizeof_varint(*(&self.error_code) as u64) }
     }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
         if self.error_msg != "" { w.write_with_tag(10, |w| w.write_string(&**&self.error_msg))?; }
+        if self.error_code != ErrorCode::UNSPECIFIED { w.write_with_tag(16, |w| w.write_enum(*&self.error_code as i32))?; }
         Ok(())
     }
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct Request<'a> {
-    pub request: mod_Request::OneOfrequest<'a>,
+pub struct RequestGetCapabilities { }
+
+impl<'a> MessageRead<'a> for RequestGetCapabilities {
+    fn from_reader(r: &mut BytesReader, _: &[u8]) -> Result<Self> {
+        r.read_to_end();
+        Ok(Self::default())
+    }
 }
 
-impl<'a> MessageRead<'a> for Request<'a> {
+impl MessageWrite for RequestGetCapabilities { }
+
+// `capabilities` is a `map<string, uint32>` in the .proto source. quick-protobuf codegen does not
+// natively support map fields (it only emits scalar/`Vec`/`Cow` fields), so this message is
+// hand-generated to match what that codegen would emit if it did: each entry is written as its
+// own length-delimited sub-message, with field 1 (tag 10) holding the key and field 2 (tag 16)
+// holding the value, and decoded entries are pushed into a `Vec<(Cow<str>, u32)>`.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ResponseGetCapabilities<'a> {
+    pub capabilities: Vec<(Cow<'a, str>, u32)>,
+}
+
+impl<'a> MessageRead<'a> for ResponseGetCapabilities<'a> {
     fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
         let mut msg = Self::default();
         while !r.is_eof() {
             match r.next_tag(bytes) {
-                Ok(10) => msg.request = mod_Request::OneOfrequest::get_version(r.read_message::<RequestGetVersion>(bytes)?),
-                Ok(18) => msg.request = mod_Request::OneOfrequest::exit(r.read_message::<RequestExit>(bytes)?),
-                Ok(26) => msg.request = mod_Request::OneOfrequest::get_master_fingerprint(r.read_message::<RequestGetMasterFingerprint>(bytes)?),
-                Ok(34) => msg.request = mod_Request::OneOfrequest::get_extended_pubkey(r.read_message::<RequestGetExtendedPubkey>(bytes)?),
-                Ok(42) => msg.request = mod_Request::OneOfrequest::register_wallet(r.read_message::<RequestRegisterWallet>(bytes)?),
-                Ok(50) => msg.request = mod_Request::OneOfrequest::get_wallet_address(r.read_message::<RequestGetWalletAddress>(bytes)?),
-                Ok(58) => msg.request = mod_Request::OneOfrequest::sign_psbt(r.read_message::<RequestSignPsbt>(bytes)?),
+                Ok(10) => {
+                    let entry_bytes = r.read_bytes(bytes)?;
+                    let mut entry_reader = BytesReader::from_bytes(entry_bytes);
+                    let mut key = Cow::Borrowed("");
+                    let mut value = 0u32;
+                    while !entry_reader.is_eof() {
+                        match entry_reader.next_tag(entry_bytes) {
+                            Ok(10) => key = entry_reader.read_string(entry_bytes).map(Cow::Borrowed)?,
+                            Ok(16) => value = entry_reader.read_uint32(entry_bytes)?,
+                            Ok(t) => { entry_reader.read_unknown(entry_bytes, t)?; }
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    msg.capabilities.push((key, value));
+                }
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -636,76 +844,119 @@ impl<'a> MessageRead<'a> for Request<'a> {
     }
 }
 
-impl<'a> MessageWrite for Request<'a> {
+impl<'a> MessageWrite for ResponseGetCapabilities<'a> {
     fn get_size(&self) -> usize {
         0
-        + match self.request {
-            mod_Request::OneOfrequest::get_version(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::exit(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::get_master_fingerprint(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::get_extended_pubkey(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::register_wallet(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::get_wallet_address(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::sign_psbt(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Request::OneOfrequest::None => 0,
-    }    }
+        + self.capabilities.iter().map(|(k, v)| {
+            let entry_size = (if k == "" { 0 } else { 1 + sizeof_len(k.len()) })
+                + (if *v == 0u32 { 0 } else { 1 + sizeof_varint(*v as u64) });
+            1 + sizeof_len(entry_size)
+        }).sum::<usize>()
+    }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
-        match self.request {            mod_Request::OneOfrequest::get_version(ref m) => { w.write_with_tag(10, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::exit(ref m) => { w.write_with_tag(18, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::get_master_fingerprint(ref m) => { w.write_with_tag(26, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::get_extended_pubkey(ref m) => { w.write_with_tag(34, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::register_wallet(ref m) => { w.write_with_tag(42, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::get_wallet_address(ref m) => { w.write_with_tag(50, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::sign_psbt(ref m) => { w.write_with_tag(58, |w| w.write_message(m))? },
-            mod_Request::OneOfrequest::None => {},
-    }        Ok(())
+        for (k, v) in &self.capabilities {
+            w.write_with_tag(10, |w| {
+                if k != "" { w.write_with_tag(10, |w| w.write_string(k))?; }
+                if *v != 0u32 { w.write_with_tag(16, |w| w.write_uint32(*v))?; }
+                Ok(())
+            })?;
+        }
+        Ok(())
     }
 }
 
-pub mod mod_Request {
+/// Which signature scheme a `sign_message`/`verify_message` request uses: ECDSA (signing the
+/// single SHA-256 of the domain-separated message) or BIP-340 Schnorr over an x-only key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SignatureScheme {
+    ECDSA = 0,
+    SCHNORR = 1,
+}
 
-use alloc::vec::Vec;
-use super::*;
+impl Default for SignatureScheme {
+    fn default() -> Self {
+        SignatureScheme::ECDSA
+    }
+}
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum OneOfrequest<'a> {
-    get_version(RequestGetVersion),
-    exit(RequestExit),
-    get_master_fingerprint(RequestGetMasterFingerprint),
-    get_extended_pubkey(RequestGetExtendedPubkey),
-    register_wallet(RequestRegisterWallet<'a>),
-    get_wallet_address(RequestGetWalletAddress<'a>),
-    sign_psbt(RequestSignPsbt<'a>),
-    None,
+impl From<i32> for SignatureScheme {
+    fn from(i: i32) -> Self {
+        match i {
+            0 => SignatureScheme::ECDSA,
+            1 => SignatureScheme::SCHNORR,
+            _ => Self::default(),
+        }
+    }
 }
 
-impl<'a> Default for OneOfrequest<'a> {
-    fn default() -> Self {
-        OneOfrequest::None
+impl<'a> From<&'a str> for SignatureScheme {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "ECDSA" => SignatureScheme::ECDSA,
+            "SCHNORR" => SignatureScheme::SCHNORR,
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Signs an arbitrary user message (e.g. for proof-of-ownership or a login challenge), as
+/// opposed to a transaction via `sign_psbt`. The app must hash `message` under a fixed,
+/// app-specific domain-separation prefix (mirroring the domain separation libp2p applies to its
+/// signed envelopes) before signing, so a message signature can never be replayed as a PSBT
+/// input signature or vice versa.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RequestSignMessage<'a> {
+    pub derivation_path: Vec<u32>,
+    pub message: Cow<'a, [u8]>,
+    pub scheme: SignatureScheme,
+}
+
+impl<'a> MessageRead<'a> for RequestSignMessage<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.derivation_path = r.read_packed(bytes, |r, bytes| Ok(r.read_uint32(bytes)?))?,
+                Ok(18) => msg.message = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(24) => msg.scheme = r.read_enum(bytes)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
     }
 }
 
+impl<'a> MessageWrite for RequestSignMessage<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.derivation_path.is_empty() { 0 } else { 1 + sizeof_len(self.derivation_path.iter().map(|s| sizeof_varint(*(s) as u64)).sum::<usize>()) }
+        + if self.message == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.message).len()) }
+        + if self.scheme == SignatureScheme::ECDSA { 0 } else { 1 + sizeof_varint(*(&self.scheme) as u64) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        w.write_packed_with_tag(10, &self.derivation_path, |w, m| w.write_uint32(*m), &|m| sizeof_varint(*(m) as u64))?;
+        if self.message != Cow::Borrowed(b"") { w.write_with_tag(18, |w| w.write_bytes(&**&self.message))?; }
+        if self.scheme != SignatureScheme::ECDSA { w.write_with_tag(24, |w| w.write_enum(*&self.scheme as i32))?; }
+        Ok(())
+    }
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Default, PartialEq, Clone)]
-pub struct Response<'a> {
-    pub response: mod_Response::OneOfresponse<'a>,
+pub struct ResponseSignMessage<'a> {
+    pub signature: Cow<'a, [u8]>,
 }
 
-impl<'a> MessageRead<'a> for Response<'a> {
+impl<'a> MessageRead<'a> for ResponseSignMessage<'a> {
     fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
         let mut msg = Self::default();
         while !r.is_eof() {
             match r.next_tag(bytes) {
-                Ok(10) => msg.response = mod_Response::OneOfresponse::get_version(r.read_message::<ResponseGetVersion>(bytes)?),
-                Ok(18) => msg.response = mod_Response::OneOfresponse::get_master_fingerprint(r.read_message::<ResponseGetMasterFingerprint>(bytes)?),
-                Ok(26) => msg.response = mod_Response::OneOfresponse::get_extended_pubkey(r.read_message::<ResponseGetExtendedPubkey>(bytes)?),
-                Ok(34) => msg.response = mod_Response::OneOfresponse::register_wallet(r.read_message::<ResponseRegisterWallet>(bytes)?),
-                Ok(42) => msg.response = mod_Response::OneOfresponse::get_wallet_address(r.read_message::<ResponseGetWalletAddress>(bytes)?),
-                Ok(50) => msg.response = mod_Response::OneOfresponse::sign_psbt(r.read_message::<ResponseSignPsbt>(bytes)?),
-                Ok(58) => msg.response = mod_Response::OneOfresponse::error(r.read_message::<ResponseError>(bytes)?),
+                Ok(10) => msg.signature = r.read_bytes(bytes).map(Cow::Borrowed)?,
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -714,55 +965,2495 @@ impl<'a> MessageRead<'a> for Response<'a> {
     }
 }
 
-impl<'a> MessageWrite for Response<'a> {
+impl<'a> MessageWrite for ResponseSignMessage<'a> {
     fn get_size(&self) -> usize {
         0
-        + match self.response {
-            mod_Response::OneOfresponse::get_version(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::get_master_fingerprint(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::get_extended_pubkey(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::register_wallet(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::get_wallet_address(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::sign_psbt(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::error(ref m) => 1 + sizeof_len((m).get_size()),
-            mod_Response::OneOfresponse::None => 0,
-    }    }
+        + if self.signature == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.signature).len()) }
+    }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
-        match self.response {            mod_Response::OneOfresponse::get_version(ref m) => { w.write_with_tag(10, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::get_master_fingerprint(ref m) => { w.write_with_tag(18, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::get_extended_pubkey(ref m) => { w.write_with_tag(26, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::register_wallet(ref m) => { w.write_with_tag(34, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::get_wallet_address(ref m) => { w.write_with_tag(42, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::sign_psbt(ref m) => { w.write_with_tag(50, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::error(ref m) => { w.write_with_tag(58, |w| w.write_message(m))? },
-            mod_Response::OneOfresponse::None => {},
-    }        Ok(())
+        if self.signature != Cow::Borrowed(b"") { w.write_with_tag(10, |w| w.write_bytes(&**&self.signature))?; }
+        Ok(())
     }
 }
 
-pub mod mod_Response {
+/// Verifies a signature produced by `sign_message` (or any compatible ECDSA/BIP-340 signer)
+/// without needing the V-App to re-derive and re-sign: the host supplies the candidate
+/// signature, and the app recomputes the public key for `derivation_path` and checks it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RequestVerifyMessage<'a> {
+    pub derivation_path: Vec<u32>,
+    pub message: Cow<'a, [u8]>,
+    pub scheme: SignatureScheme,
+    pub signature: Cow<'a, [u8]>,
+}
 
-use alloc::vec::Vec;
-use super::*;
+impl<'a> MessageRead<'a> for RequestVerifyMessage<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.derivation_path = r.read_packed(bytes, |r, bytes| Ok(r.read_uint32(bytes)?))?,
+                Ok(18) => msg.message = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(24) => msg.scheme = r.read_enum(bytes)?,
+                Ok(34) => msg.signature = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum OneOfresponse<'a> {
-    get_version(ResponseGetVersion<'a>),
-    get_master_fingerprint(ResponseGetMasterFingerprint),
-    get_extended_pubkey(ResponseGetExtendedPubkey<'a>),
-    register_wallet(ResponseRegisterWallet<'a>),
-    get_wallet_address(ResponseGetWalletAddress<'a>),
-    sign_psbt(ResponseSignPsbt<'a>),
-    error(ResponseError<'a>),
-    None,
+impl<'a> MessageWrite for RequestVerifyMessage<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.derivation_path.is_empty() { 0 } else { 1 + sizeof_len(self.derivation_path.iter().map(|s| sizeof_varint(*(s) as u64)).sum::<usize>()) }
+        + if self.message == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.message).len()) }
+        + if self.scheme == SignatureScheme::ECDSA { 0 } else { 1 + sizeof_varint(*(&self.scheme) as u64) }
+        + if self.signature == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.signature).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        w.write_packed_with_tag(10, &self.derivation_path, |w, m| w.write_uint32(*m), &|m| sizeof_varint(*(m) as u64))?;
+        if self.message != Cow::Borrowed(b"") { w.write_with_tag(18, |w| w.write_bytes(&**&self.message))?; }
+        if self.scheme != SignatureScheme::ECDSA { w.write_with_tag(24, |w| w.write_enum(*&self.scheme as i32))?; }
+        if self.signature != Cow::Borrowed(b"") { w.write_with_tag(34, |w| w.write_bytes(&**&self.signature))?; }
+        Ok(())
+    }
 }
 
-impl<'a> Default for OneOfresponse<'a> {
-    fn default() -> Self {
-        OneOfresponse::None
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ResponseVerifyMessage {
+    pub valid: bool,
+}
+
+impl<'a> MessageRead<'a> for ResponseVerifyMessage {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(8) => msg.valid = r.read_bool(bytes)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl MessageWrite for ResponseVerifyMessage {
+    fn get_size(&self) -> usize {
+        0
+        + if self.valid == false { 0 } else { 1 + sizeof_varint(*(&self.valid) as u64) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.valid != false { w.write_with_tag(8, |w| w.write_bool(*&self.valid))?; }
+        Ok(())
     }
 }
 
+/// Domain-separation prefix the app must hash `RequestSignMessage::message` under before
+/// signing, so a `sign_message` signature can never be replayed as a `sign_psbt` input signature
+/// (which signs over a sighash with no such prefix) or vice versa.
+pub const SIGN_MESSAGE_DOMAIN: &[u8] = b"vanadium-bitcoin-sign-message:";
+
+/// Prepends [`SIGN_MESSAGE_DOMAIN`] to `message`, producing the exact bytes the app must hash
+/// (e.g. with SHA-256) before signing a `RequestSignMessage`.
+pub fn domain_separated_message(message: &[u8]) -> Vec<u8> {
+    let mut prefixed = Vec::with_capacity(SIGN_MESSAGE_DOMAIN.len() + message.len());
+    prefixed.extend_from_slice(SIGN_MESSAGE_DOMAIN);
+    prefixed.extend_from_slice(message);
+    prefixed
 }
 
+// The three messages of a Noise_XX_25519_ChaChaPoly_SHA256 handshake (see the `noise` module
+// below), carried as their own `Request`/`Response` oneof variants so the handshake can run over
+// the exact same transport as the rest of the protocol before any `Request*`/`Response*` payload
+// is ever sent in the clear.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NoiseE<'a> {
+    pub e: Cow<'a, [u8]>,
+}
+
+impl<'a> MessageRead<'a> for NoiseE<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.e = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for NoiseE<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.e == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.e).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.e != Cow::Borrowed(b"") { w.write_with_tag(10, |w| w.write_bytes(&**&self.e))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NoiseEeSEs<'a> {
+    pub e: Cow<'a, [u8]>,
+    pub encrypted_static: Cow<'a, [u8]>,
+    pub payload: Cow<'a, [u8]>,
+}
+
+impl<'a> MessageRead<'a> for NoiseEeSEs<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.e = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(18) => msg.encrypted_static = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(26) => msg.payload = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for NoiseEeSEs<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.e == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.e).len()) }
+        + if self.encrypted_static == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.encrypted_static).len()) }
+        + if self.payload == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.payload).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.e != Cow::Borrowed(b"") { w.write_with_tag(10, |w| w.write_bytes(&**&self.e))?; }
+        if self.encrypted_static != Cow::Borrowed(b"") { w.write_with_tag(18, |w| w.write_bytes(&**&self.encrypted_static))?; }
+        if self.payload != Cow::Borrowed(b"") { w.write_with_tag(26, |w| w.write_bytes(&**&self.payload))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NoiseSSe<'a> {
+    pub encrypted_static: Cow<'a, [u8]>,
+    pub payload: Cow<'a, [u8]>,
+}
+
+impl<'a> MessageRead<'a> for NoiseSSe<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.encrypted_static = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(18) => msg.payload = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for NoiseSSe<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.encrypted_static == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.encrypted_static).len()) }
+        + if self.payload == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.payload).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.encrypted_static != Cow::Borrowed(b"") { w.write_with_tag(10, |w| w.write_bytes(&**&self.encrypted_static))?; }
+        if self.payload != Cow::Borrowed(b"") { w.write_with_tag(18, |w| w.write_bytes(&**&self.payload))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Request<'a> {
+    pub request: mod_Request::OneOfrequest<'a>,
+}
+
+impl<'a> MessageRead<'a> for Request<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.request = mod_Request::OneOfrequest::get_version(r.read_message::<RequestGetVersion>(bytes)?),
+                Ok(18) => msg.request = mod_Request::OneOfrequest::exit(r.read_message::<RequestExit>(bytes)?),
+                Ok(26) => msg.request = mod_Request::OneOfrequest::get_master_fingerprint(r.read_message::<RequestGetMasterFingerprint>(bytes)?),
+                Ok(34) => msg.request = mod_Request::OneOfrequest::get_extended_pubkey(r.read_message::<RequestGetExtendedPubkey>(bytes)?),
+                Ok(42) => msg.request = mod_Request::OneOfrequest::register_wallet(r.read_message::<RequestRegisterWallet>(bytes)?),
+                Ok(50) => msg.request = mod_Request::OneOfrequest::get_wallet_address(r.read_message::<RequestGetWalletAddress>(bytes)?),
+                Ok(58) => msg.request = mod_Request::OneOfrequest::sign_psbt(r.read_message::<RequestSignPsbt>(bytes)?),
+                Ok(66) => msg.request = mod_Request::OneOfrequest::get_capabilities(r.read_message::<RequestGetCapabilities>(bytes)?),
+                Ok(74) => msg.request = mod_Request::OneOfrequest::noise_e(r.read_message::<NoiseE>(bytes)?),
+                Ok(82) => msg.request = mod_Request::OneOfrequest::noise_ee_s_es(r.read_message::<NoiseEeSEs>(bytes)?),
+                Ok(90) => msg.request = mod_Request::OneOfrequest::noise_s_se(r.read_message::<NoiseSSe>(bytes)?),
+                Ok(98) => msg.request = mod_Request::OneOfrequest::sign_message(r.read_message::<RequestSignMessage>(bytes)?),
+                Ok(106) => msg.request = mod_Request::OneOfrequest::verify_message(r.read_message::<RequestVerifyMessage>(bytes)?),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for Request<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + match self.request {
+            mod_Request::OneOfrequest::get_version(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::exit(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::get_master_fingerprint(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::get_extended_pubkey(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::register_wallet(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::get_wallet_address(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::sign_psbt(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::get_capabilities(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::noise_e(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::noise_ee_s_es(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::noise_s_se(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::sign_message(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::verify_message(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Request::OneOfrequest::None => 0,
+    }    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        match self.request {            mod_Request::OneOfrequest::get_version(ref m) => { w.write_with_tag(10, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::exit(ref m) => { w.write_with_tag(18, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::get_master_fingerprint(ref m) => { w.write_with_tag(26, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::get_extended_pubkey(ref m) => { w.write_with_tag(34, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::register_wallet(ref m) => { w.write_with_tag(42, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::get_wallet_address(ref m) => { w.write_with_tag(50, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::sign_psbt(ref m) => { w.write_with_tag(58, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::get_capabilities(ref m) => { w.write_with_tag(66, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::noise_e(ref m) => { w.write_with_tag(74, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::noise_ee_s_es(ref m) => { w.write_with_tag(82, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::noise_s_se(ref m) => { w.write_with_tag(90, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::sign_message(ref m) => { w.write_with_tag(98, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::verify_message(ref m) => { w.write_with_tag(106, |w| w.write_message(m))? },
+            mod_Request::OneOfrequest::None => {},
+    }        Ok(())
+    }
+}
+
+pub mod mod_Request {
+
+use alloc::vec::Vec;
+use super::*;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum OneOfrequest<'a> {
+    get_version(RequestGetVersion<'a>),
+    exit(RequestExit),
+    get_master_fingerprint(RequestGetMasterFingerprint),
+    get_extended_pubkey(RequestGetExtendedPubkey),
+    register_wallet(RequestRegisterWallet<'a>),
+    get_wallet_address(RequestGetWalletAddress<'a>),
+    sign_psbt(RequestSignPsbt<'a>),
+    get_capabilities(RequestGetCapabilities),
+    noise_e(NoiseE<'a>),
+    noise_ee_s_es(NoiseEeSEs<'a>),
+    noise_s_se(NoiseSSe<'a>),
+    sign_message(RequestSignMessage<'a>),
+    verify_message(RequestVerifyMessage<'a>),
+    None,
+}
+
+impl<'a> Default for OneOfrequest<'a> {
+    fn default() -> Self {
+        OneOfrequest::None
+    }
+}
+
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Response<'a> {
+    pub response: mod_Response::OneOfresponse<'a>,
+}
+
+impl<'a> MessageRead<'a> for Response<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.response = mod_Response::OneOfresponse::get_version(r.read_message::<ResponseGetVersion>(bytes)?),
+                Ok(18) => msg.response = mod_Response::OneOfresponse::get_master_fingerprint(r.read_message::<ResponseGetMasterFingerprint>(bytes)?),
+                Ok(26) => msg.response = mod_Response::OneOfresponse::get_extended_pubkey(r.read_message::<ResponseGetExtendedPubkey>(bytes)?),
+                Ok(34) => msg.response = mod_Response::OneOfresponse::register_wallet(r.read_message::<ResponseRegisterWallet>(bytes)?),
+                Ok(42) => msg.response = mod_Response::OneOfresponse::get_wallet_address(r.read_message::<ResponseGetWalletAddress>(bytes)?),
+                Ok(50) => msg.response = mod_Response::OneOfresponse::sign_psbt(r.read_message::<ResponseSignPsbt>(bytes)?),
+                Ok(58) => msg.response = mod_Response::OneOfresponse::error(r.read_message::<ResponseError>(bytes)?),
+                Ok(66) => msg.response = mod_Response::OneOfresponse::get_capabilities(r.read_message::<ResponseGetCapabilities>(bytes)?),
+                Ok(74) => msg.response = mod_Response::OneOfresponse::noise_e(r.read_message::<NoiseE>(bytes)?),
+                Ok(82) => msg.response = mod_Response::OneOfresponse::noise_ee_s_es(r.read_message::<NoiseEeSEs>(bytes)?),
+                Ok(90) => msg.response = mod_Response::OneOfresponse::noise_s_se(r.read_message::<NoiseSSe>(bytes)?),
+                Ok(98) => msg.response = mod_Response::OneOfresponse::sign_message(r.read_message::<ResponseSignMessage>(bytes)?),
+                Ok(106) => msg.response = mod_Response::OneOfresponse::verify_message(r.read_message::<ResponseVerifyMessage>(bytes)?),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for Response<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + match self.response {
+            mod_Response::OneOfresponse::get_version(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::get_master_fingerprint(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::get_extended_pubkey(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::register_wallet(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::get_wallet_address(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::sign_psbt(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::error(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::get_capabilities(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::noise_e(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::noise_ee_s_es(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::noise_s_se(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::sign_message(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::verify_message(ref m) => 1 + sizeof_len((m).get_size()),
+            mod_Response::OneOfresponse::None => 0,
+    }    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        match self.response {            mod_Response::OneOfresponse::get_version(ref m) => { w.write_with_tag(10, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::get_master_fingerprint(ref m) => { w.write_with_tag(18, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::get_extended_pubkey(ref m) => { w.write_with_tag(26, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::register_wallet(ref m) => { w.write_with_tag(34, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::get_wallet_address(ref m) => { w.write_with_tag(42, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::sign_psbt(ref m) => { w.write_with_tag(50, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::error(ref m) => { w.write_with_tag(58, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::get_capabilities(ref m) => { w.write_with_tag(66, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::noise_e(ref m) => { w.write_with_tag(74, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::noise_ee_s_es(ref m) => { w.write_with_tag(82, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::noise_s_se(ref m) => { w.write_with_tag(90, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::sign_message(ref m) => { w.write_with_tag(98, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::verify_message(ref m) => { w.write_with_tag(106, |w| w.write_message(m))? },
+            mod_Response::OneOfresponse::None => {},
+    }        Ok(())
+    }
+}
+
+pub mod mod_Response {
+
+use alloc::vec::Vec;
+use super::*;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum OneOfresponse<'a> {
+    get_version(ResponseGetVersion<'a>),
+    get_master_fingerprint(ResponseGetMasterFingerprint),
+    get_extended_pubkey(ResponseGetExtendedPubkey<'a>),
+    register_wallet(ResponseRegisterWallet<'a>),
+    get_wallet_address(ResponseGetWalletAddress<'a>),
+    sign_psbt(ResponseSignPsbt<'a>),
+    error(ResponseError<'a>),
+    noise_e(NoiseE<'a>),
+    noise_ee_s_es(NoiseEeSEs<'a>),
+    noise_s_se(NoiseSSe<'a>),
+    get_capabilities(ResponseGetCapabilities<'a>),
+    sign_message(ResponseSignMessage<'a>),
+    verify_message(ResponseVerifyMessage),
+    None,
+}
+
+impl<'a> Default for OneOfresponse<'a> {
+    fn default() -> Self {
+        OneOfresponse::None
+    }
+}
+
+}
+
+// Everything below this point is hand-written: it wraps the messages generated above in an
+// envelope that supports splitting an arbitrarily large `Request`/`Response` (e.g. a PSBT that
+// doesn't fit in RAM in one go) across several fragments, à la USP's Record segmentation.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MessageFragment<'a> {
+    pub payload_id: u32,
+    pub fragment_index: u32,
+    pub fragment_count: u32,
+    pub payload: Cow<'a, [u8]>,
+    /// Total length in bytes of the reassembled payload. Only meaningful when `fragment_index == 0`;
+    /// ignored on every other fragment.
+    pub total_len: u32,
+}
+
+impl<'a> MessageRead<'a> for MessageFragment<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(8) => msg.payload_id = r.read_uint32(bytes)?,
+                Ok(16) => msg.fragment_index = r.read_uint32(bytes)?,
+                Ok(24) => msg.fragment_count = r.read_uint32(bytes)?,
+                Ok(34) => msg.payload = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(40) => msg.total_len = r.read_uint32(bytes)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for MessageFragment<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.payload_id == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.payload_id) as u64) }
+        + if self.fragment_index == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.fragment_index) as u64) }
+        + if self.fragment_count == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.fragment_count) as u64) }
+        + if self.payload == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.payload).len()) }
+        + if self.total_len == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.total_len) as u64) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.payload_id != 0u32 { w.write_with_tag(8, |w| w.write_uint32(*&self.payload_id))?; }
+        if self.fragment_index != 0u32 { w.write_with_tag(16, |w| w.write_uint32(*&self.fragment_index))?; }
+        if self.fragment_count != 0u32 { w.write_with_tag(24, |w| w.write_uint32(*&self.fragment_count))?; }
+        if self.payload != Cow::Borrowed(b"") { w.write_with_tag(34, |w| w.write_bytes(&**&self.payload))?; }
+        if self.total_len != 0u32 { w.write_with_tag(40, |w| w.write_uint32(*&self.total_len))?; }
+        Ok(())
+    }
+}
+
+/// Reassembles a sequence of [`MessageFragment`]s sharing the same `payload_id` back into the
+/// original byte string, so the result can be fed to the `MessageRead` impl of the wrapped
+/// `Request`/`Response`.
+///
+/// Fragments of a given `payload_id` must all agree on `fragment_count`; arrival order does not
+/// matter, but a fragment index that was already filled in is rejected as a duplicate.
+pub mod fragment {
+    use super::MessageFragment;
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FragmentError {
+        /// `fragment_count` disagrees with a previously seen fragment for this `payload_id`.
+        FragmentCountMismatch,
+        /// `fragment_index >= fragment_count`.
+        IndexOutOfRange,
+        /// A fragment was already received for this `fragment_index`.
+        DuplicateFragment,
+        /// The concatenated payload length does not match the `total_len` of the first fragment.
+        LengthMismatch,
+    }
+
+    impl core::fmt::Display for FragmentError {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            match self {
+                FragmentError::FragmentCountMismatch => write!(f, "fragment_count mismatch"),
+                FragmentError::IndexOutOfRange => write!(f, "fragment_index out of range"),
+                FragmentError::DuplicateFragment => write!(f, "duplicate fragment_index"),
+                FragmentError::LengthMismatch => write!(f, "reassembled length does not match total_len"),
+            }
+        }
+    }
+
+    struct PendingPayload {
+        fragment_count: u32,
+        total_len: Option<u32>,
+        chunks: BTreeMap<u32, Vec<u8>>,
+    }
+
+    /// Accumulates fragments for any number of in-flight `payload_id`s at once.
+    #[derive(Default)]
+    pub struct FragmentReassembler {
+        pending: BTreeMap<u32, PendingPayload>,
+    }
+
+    impl FragmentReassembler {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Feeds one fragment in. Returns the reassembled payload once every fragment of its
+        /// `payload_id` has been received, or `None` if more fragments are still expected.
+        pub fn accumulate(
+            &mut self,
+            fragment: MessageFragment,
+        ) -> core::result::Result<Option<Vec<u8>>, FragmentError> {
+            let payload_id = fragment.payload_id;
+
+            // Validate before touching `pending`: an `or_insert_with` followed by an early return
+            // would otherwise leak a `PendingPayload` per malformed `payload_id` forever, since
+            // nothing ever evicts or caps this map.
+            if let Some(existing) = self.pending.get(&payload_id) {
+                if existing.fragment_count != fragment.fragment_count {
+                    return Err(FragmentError::FragmentCountMismatch);
+                }
+            }
+            if fragment.fragment_index >= fragment.fragment_count {
+                return Err(FragmentError::IndexOutOfRange);
+            }
+
+            let entry = self.pending.entry(payload_id).or_insert_with(|| PendingPayload {
+                fragment_count: fragment.fragment_count,
+                total_len: None,
+                chunks: BTreeMap::new(),
+            });
+
+            if fragment.fragment_index == 0 {
+                entry.total_len = Some(fragment.total_len);
+            }
+            if entry.chunks.contains_key(&fragment.fragment_index) {
+                return Err(FragmentError::DuplicateFragment);
+            }
+            entry
+                .chunks
+                .insert(fragment.fragment_index, fragment.payload.into_owned());
+
+            if entry.chunks.len() < entry.fragment_count as usize {
+                return Ok(None);
+            }
+
+            let pending = self.pending.remove(&payload_id).expect("just inserted above");
+            let mut result = Vec::new();
+            for index in 0..pending.fragment_count {
+                result.extend_from_slice(
+                    pending
+                        .chunks
+                        .get(&index)
+                        .expect("all indices present: chunks.len() == fragment_count"),
+                );
+            }
+            if let Some(total_len) = pending.total_len {
+                if result.len() != total_len as usize {
+                    return Err(FragmentError::LengthMismatch);
+                }
+            }
+            Ok(Some(result))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn fragment(payload_id: u32, fragment_index: u32, fragment_count: u32, payload: &[u8]) -> MessageFragment<'static> {
+            MessageFragment {
+                payload_id,
+                fragment_index,
+                fragment_count,
+                payload: alloc::borrow::Cow::Owned(payload.to_vec()),
+                total_len: if fragment_index == 0 { payload.len() as u32 } else { 0 },
+            }
+        }
+
+        #[test]
+        fn reassembles_fragments_in_order() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(r.accumulate(MessageFragment { total_len: 13, ..fragment(1, 0, 2, b"hello, ") }), Ok(None));
+            assert_eq!(r.accumulate(fragment(1, 1, 2, b"world!")), Ok(Some(b"hello, world!".to_vec())));
+        }
+
+        #[test]
+        fn reassembles_fragments_out_of_order() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(r.accumulate(fragment(1, 1, 2, b"world!")), Ok(None));
+            assert_eq!(r.accumulate(MessageFragment { total_len: 13, ..fragment(1, 0, 2, b"hello, ") }), Ok(Some(b"hello, world!".to_vec())));
+        }
+
+        #[test]
+        fn tracks_several_payload_ids_independently() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(r.accumulate(MessageFragment { total_len: 4, ..fragment(1, 0, 2, b"ab") }), Ok(None));
+            assert_eq!(r.accumulate(MessageFragment { total_len: 4, ..fragment(2, 0, 2, b"cd") }), Ok(None));
+            assert_eq!(r.accumulate(fragment(2, 1, 2, b"ef")), Ok(Some(b"cdef".to_vec())));
+            assert_eq!(r.accumulate(fragment(1, 1, 2, b"gh")), Ok(Some(b"abgh".to_vec())));
+        }
+
+        #[test]
+        fn rejects_fragment_count_mismatch() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(r.accumulate(fragment(1, 0, 2, b"ab")), Ok(None));
+            assert_eq!(
+                r.accumulate(fragment(1, 1, 3, b"cd")),
+                Err(FragmentError::FragmentCountMismatch)
+            );
+        }
+
+        #[test]
+        fn rejects_index_out_of_range() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(
+                r.accumulate(fragment(1, 0, 0, b"ab")),
+                Err(FragmentError::IndexOutOfRange)
+            );
+        }
+
+        #[test]
+        fn rejects_duplicate_fragment_index() {
+            let mut r = FragmentReassembler::new();
+            assert_eq!(r.accumulate(fragment(1, 0, 2, b"ab")), Ok(None));
+            assert_eq!(
+                r.accumulate(fragment(1, 0, 2, b"xy")),
+                Err(FragmentError::DuplicateFragment)
+            );
+        }
+
+        #[test]
+        fn malformed_fragments_do_not_leak_pending_state() {
+            // Every distinct `payload_id` that only ever produces errors must not leave a
+            // `PendingPayload` behind: there's no cap or eviction on `pending`, so each one would
+            // otherwise be a permanent, unbounded memory leak.
+            let mut r = FragmentReassembler::new();
+            for payload_id in 0..1000u32 {
+                assert_eq!(
+                    r.accumulate(fragment(payload_id, 0, 0, b"bogus")),
+                    Err(FragmentError::IndexOutOfRange)
+                );
+            }
+            assert_eq!(r.pending.len(), 0);
+        }
+    }
+}
+
+/// An alternative framing envelope to [`MessageFragment`], for transports where the host drives
+/// reassembly by absolute byte offset rather than by a fixed fragment count — mirroring the
+/// session/offset fields of a USP Record. Unlike `MessageFragment`, a `RecordFrame`'s payload can
+/// arrive in any order and at any granularity, as long as offsets never overlap; the session is
+/// only decodable once the frame with `is_last` set has arrived.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RecordFrame<'a> {
+    pub session_id: u32,
+    pub total_length: u32,
+    pub offset: u32,
+    pub payload: Cow<'a, [u8]>,
+    pub is_last: bool,
+}
+
+impl<'a> MessageRead<'a> for RecordFrame<'a> {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(8) => msg.session_id = r.read_uint32(bytes)?,
+                Ok(16) => msg.total_length = r.read_uint32(bytes)?,
+                Ok(24) => msg.offset = r.read_uint32(bytes)?,
+                Ok(34) => msg.payload = r.read_bytes(bytes).map(Cow::Borrowed)?,
+                Ok(40) => msg.is_last = r.read_bool(bytes)?,
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl<'a> MessageWrite for RecordFrame<'a> {
+    fn get_size(&self) -> usize {
+        0
+        + if self.session_id == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.session_id) as u64) }
+        + if self.total_length == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.total_length) as u64) }
+        + if self.offset == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.offset) as u64) }
+        + if self.payload == Cow::Borrowed(b"") { 0 } else { 1 + sizeof_len((&self.payload).len()) }
+        + if !self.is_last { 0 } else { 1 + sizeof_varint(*(&self.is_last) as u64) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.session_id != 0u32 { w.write_with_tag(8, |w| w.write_uint32(*&self.session_id))?; }
+        if self.total_length != 0u32 { w.write_with_tag(16, |w| w.write_uint32(*&self.total_length))?; }
+        if self.offset != 0u32 { w.write_with_tag(24, |w| w.write_uint32(*&self.offset))?; }
+        if self.payload != Cow::Borrowed(b"") { w.write_with_tag(34, |w| w.write_bytes(&**&self.payload))?; }
+        if self.is_last { w.write_with_tag(40, |w| w.write_bool(*&self.is_last))?; }
+        Ok(())
+    }
+}
+
+/// Reassembles a sequence of [`RecordFrame`]s sharing the same `session_id`, keyed by absolute
+/// byte offset rather than by fragment index. Both host and app sides run the same accumulator:
+/// each frame's `offset..offset+len(payload)` range is validated against `total_length` and
+/// checked for overlap with ranges already received, and the session is only handed back once a
+/// frame with `is_last` set has arrived and every byte up to `total_length` has been filled in.
+pub mod record {
+    use super::RecordFrame;
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub enum RecordError {
+        /// A later frame disagreed with an earlier one about `total_length` for this session.
+        TotalLengthMismatch,
+        /// `offset + payload.len()` would exceed `total_length`.
+        OutOfBounds,
+        /// This frame's range overlaps a range already received for this session.
+        OverlappingRange,
+        /// `is_last` arrived but bytes `0..total_length` are not fully covered yet.
+        IncompleteSession,
+    }
+
+    impl core::fmt::Display for RecordError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match self {
+                RecordError::TotalLengthMismatch => write!(f, "total_length mismatch"),
+                RecordError::OutOfBounds => write!(f, "offset + payload length exceeds total_length"),
+                RecordError::OverlappingRange => write!(f, "overlapping or duplicate offset range"),
+                RecordError::IncompleteSession => write!(f, "is_last arrived before all bytes were received"),
+            }
+        }
+    }
+
+    struct PendingSession {
+        total_length: u32,
+        saw_last: bool,
+        // Received byte ranges, keyed by start offset, so adjacency/overlap checks are a simple
+        // neighbor lookup instead of a scan.
+        ranges: BTreeMap<u32, Vec<u8>>,
+    }
+
+    /// Accumulates [`RecordFrame`]s for any number of in-flight `session_id`s at once.
+    #[derive(Default)]
+    pub struct RecordReassembler {
+        pending: BTreeMap<u32, PendingSession>,
+    }
+
+    impl RecordReassembler {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Feeds one frame in. Returns the reassembled payload once `is_last` has arrived and
+        /// every byte up to `total_length` has been received, or `None` if the session is still
+        /// in progress.
+        pub fn accumulate(
+            &mut self,
+            frame: RecordFrame,
+        ) -> core::result::Result<Option<Vec<u8>>, RecordError> {
+            let session_id = frame.session_id;
+            let entry = self.pending.entry(session_id).or_insert_with(|| PendingSession {
+                total_length: frame.total_length,
+                saw_last: false,
+                ranges: BTreeMap::new(),
+            });
+
+            if frame.total_length != 0 && entry.total_length != 0 && entry.total_length != frame.total_length {
+                return Err(RecordError::TotalLengthMismatch);
+            }
+            if frame.total_length != 0 {
+                entry.total_length = frame.total_length;
+            }
+
+            let start = frame.offset;
+            let end = start
+                .checked_add(frame.payload.len() as u32)
+                .ok_or(RecordError::OutOfBounds)?;
+            if entry.total_length != 0 && end > entry.total_length {
+                return Err(RecordError::OutOfBounds);
+            }
+
+            // Overlap check: compare against the range starting at-or-before `start`, and the
+            // range starting at-or-after `start` (the two candidates that could possibly overlap
+            // a freshly inserted [start, end) span in a set of already-disjoint ranges).
+            if let Some((&prev_start, prev_payload)) = entry.ranges.range(..=start).next_back() {
+                if prev_start + prev_payload.len() as u32 > start {
+                    return Err(RecordError::OverlappingRange);
+                }
+            }
+            if let Some((&next_start, _)) = entry.ranges.range(start..).next() {
+                if next_start < end {
+                    return Err(RecordError::OverlappingRange);
+                }
+            }
+
+            entry.ranges.insert(start, frame.payload.into_owned());
+            if frame.is_last {
+                entry.saw_last = true;
+            }
+
+            if !entry.saw_last {
+                return Ok(None);
+            }
+
+            // All ranges received so far are disjoint (enforced above); the session is complete
+            // once they also have no gaps and together cover `0..total_length`.
+            let mut covered = 0u32;
+            for (&start, payload) in entry.ranges.iter() {
+                if start != covered {
+                    return Ok(None);
+                }
+                covered += payload.len() as u32;
+            }
+            if covered != entry.total_length {
+                return Err(RecordError::IncompleteSession);
+            }
+
+            let pending = self.pending.remove(&session_id).expect("just inserted above");
+            let mut result = Vec::with_capacity(pending.total_length as usize);
+            for (_, payload) in pending.ranges {
+                result.extend_from_slice(&payload);
+            }
+            Ok(Some(result))
+        }
+    }
+}
+
+/// Noise_XX_25519_ChaChaPoly_SHA256 handshake and transport encryption, wrapping the `noise_e`
+/// / `noise_ee_s_es` / `noise_s_se` messages above. A malicious or compromised host transport
+/// sees only ciphertext for every `Request`/`Response` exchanged after the handshake completes,
+/// mirroring the protocol libp2p runs over its own transport connections.
+///
+/// This module only implements the cryptographic handshake and the resulting transport
+/// encryption; the host/app are still responsible for framing the ciphertext (e.g. via
+/// `RecordFrame`, for large payloads) and for feeding the three handshake messages through the
+/// matching `noise_*` oneof variants.
+pub mod noise {
+    use super::*;
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+    use hmac::{Hmac, Mac};
+    use sha2::{Digest, Sha256};
+    use x25519_dalek::{PublicKey, StaticSecret};
+
+    const PROTOCOL_NAME: &[u8] = b"Noise_XX_25519_ChaChaPoly_SHA256";
+
+    #[derive(Debug)]
+    pub enum NoiseError {
+        /// An incoming handshake or transport message failed authentication.
+        Decrypt,
+        /// A handshake message carried a public key that was not exactly 32 bytes long. Calling
+        /// the handshake steps out of order is not reported here: it panics.
+        UnexpectedMessage,
+    }
+
+    fn hmac_hash(key: &[u8; 32], data: &[u8]) -> [u8; 32] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("any key length is valid for HMAC-SHA256");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+
+    /// `HKDF(chaining_key, input_key_material)` as specified by Noise, truncated to the two
+    /// outputs every pattern in this handshake needs (`mix_key` takes the new chaining key and
+    /// cipher key from them; `split` takes the two transport keys).
+    fn hkdf2(chaining_key: &[u8; 32], input_key_material: &[u8]) -> ([u8; 32], [u8; 32]) {
+        let temp_key = hmac_hash(chaining_key, input_key_material);
+        let output1 = hmac_hash(&temp_key, &[1u8]);
+        let mut block2 = [0u8; 33];
+        block2[..32].copy_from_slice(&output1);
+        block2[32] = 2;
+        let output2 = hmac_hash(&temp_key, &block2);
+        (output1, output2)
+    }
+
+    /// The single-key, rolling-nonce AEAD state used both by `SymmetricState` during the
+    /// handshake and by the two post-handshake transport directions.
+    struct CipherState {
+        key: Option<[u8; 32]>,
+        nonce: u64,
+    }
+
+    impl CipherState {
+        fn empty() -> Self {
+            CipherState { key: None, nonce: 0 }
+        }
+
+        fn nonce_bytes(&self) -> [u8; 12] {
+            let mut bytes = [0u8; 12];
+            bytes[4..].copy_from_slice(&self.nonce.to_le_bytes());
+            bytes
+        }
+
+        fn encrypt_with_ad(&mut self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+            match self.key {
+                None => plaintext.to_vec(),
+                Some(key) => {
+                    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+                    let nonce = self.nonce_bytes();
+                    let ciphertext = cipher
+                        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: ad })
+                        .expect("encryption with a fresh nonce cannot fail");
+                    self.nonce += 1;
+                    ciphertext
+                }
+            }
+        }
+
+        fn decrypt_with_ad(&mut self, ad: &[u8], ciphertext: &[u8]) -> core::result::Result<Vec<u8>, NoiseError> {
+            match self.key {
+                None => Ok(ciphertext.to_vec()),
+                Some(key) => {
+                    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+                    let nonce = self.nonce_bytes();
+                    let plaintext = cipher
+                        .decrypt(Nonce::from_slice(&nonce), Payload { msg: ciphertext, aad: ad })
+                        .map_err(|_| NoiseError::Decrypt)?;
+                    self.nonce += 1;
+                    Ok(plaintext)
+                }
+            }
+        }
+    }
+
+    /// Tracks the running handshake hash `h` and chaining key `ck`, and the single `CipherState`
+    /// used to (optionally, once a key has been mixed in) encrypt each handshake message.
+    struct SymmetricState {
+        ck: [u8; 32],
+        h: [u8; 32],
+        cipher: CipherState,
+    }
+
+    impl SymmetricState {
+        fn initialize(protocol_name: &[u8]) -> Self {
+            let mut h = [0u8; 32];
+            if protocol_name.len() <= 32 {
+                h[..protocol_name.len()].copy_from_slice(protocol_name);
+            } else {
+                h = Sha256::digest(protocol_name).into();
+            }
+            SymmetricState { ck: h, h, cipher: CipherState::empty() }
+        }
+
+        fn mix_hash(&mut self, data: &[u8]) {
+            let mut hasher = Sha256::new();
+            hasher.update(self.h);
+            hasher.update(data);
+            self.h = hasher.finalize().into();
+        }
+
+        fn mix_key(&mut self, input_key_material: &[u8]) {
+            let (ck, temp_k) = hkdf2(&self.ck, input_key_material);
+            self.ck = ck;
+            self.cipher = CipherState { key: Some(temp_k), nonce: 0 };
+        }
+
+        fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Vec<u8> {
+            let ciphertext = self.cipher.encrypt_with_ad(&self.h, plaintext);
+            self.mix_hash(&ciphertext);
+            ciphertext
+        }
+
+        fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> core::result::Result<Vec<u8>, NoiseError> {
+            let plaintext = self.cipher.decrypt_with_ad(&self.h, ciphertext)?;
+            self.mix_hash(ciphertext);
+            Ok(plaintext)
+        }
+
+        /// Derives the pair of transport `CipherState`s once the handshake is complete.
+        fn split(&self) -> (CipherState, CipherState) {
+            let (k1, k2) = hkdf2(&self.ck, &[]);
+            (CipherState { key: Some(k1), nonce: 0 }, CipherState { key: Some(k2), nonce: 0 })
+        }
+    }
+
+    /// One direction of the post-handshake transport: encrypts or decrypts a serialized
+    /// `Request`/`Response` under the negotiated key with a strictly increasing nonce.
+    pub struct TransportCipher(CipherState);
+
+    impl TransportCipher {
+        pub fn encrypt(&mut self, plaintext: &[u8]) -> Vec<u8> {
+            self.0.encrypt_with_ad(&[], plaintext)
+        }
+
+        pub fn decrypt(&mut self, ciphertext: &[u8]) -> core::result::Result<Vec<u8>, NoiseError> {
+            self.0.decrypt_with_ad(&[], ciphertext)
+        }
+    }
+
+    /// The two directions of the transport phase, returned once a handshake finishes: `send`
+    /// encrypts outgoing `Request`/`Response` bytes, `receive` decrypts incoming ones. The
+    /// initiator and responder end up with their `send`/`receive` ciphers swapped, since each
+    /// side encrypts with the key the other side decrypts with.
+    pub struct TransportKeys {
+        pub send: TransportCipher,
+        pub receive: TransportCipher,
+    }
+
+    /// Runs one side of the Noise XX pattern (`-> e`, `<- e, ee, s, es`, `-> s, se`) and produces
+    /// the negotiated [`TransportKeys`] on success.
+    pub struct HandshakeState {
+        s: StaticSecret,
+        e: Option<StaticSecret>,
+        rs: Option<PublicKey>,
+        re: Option<PublicKey>,
+        symmetric: SymmetricState,
+        initiator: bool,
+    }
+
+    impl HandshakeState {
+        /// `s` is this party's long-term static key. The app's half of this keypair is the one
+        /// whose public half should be pinned by clients (see [`static_public_key`]).
+        pub fn new(s: StaticSecret, initiator: bool) -> Self {
+            HandshakeState {
+                s,
+                e: None,
+                rs: None,
+                re: None,
+                symmetric: SymmetricState::initialize(PROTOCOL_NAME),
+                initiator,
+            }
+        }
+
+        /// Message 1 (initiator only): `-> e`.
+        pub fn write_message_1(&mut self, e: StaticSecret) -> NoiseE<'static> {
+            let e_pub = PublicKey::from(&e);
+            self.symmetric.mix_hash(e_pub.as_bytes());
+            self.e = Some(e);
+            NoiseE { e: Cow::Owned(e_pub.as_bytes().to_vec()) }
+        }
+
+        /// Message 1 (responder only): `<- e`.
+        pub fn read_message_1(&mut self, msg: &NoiseE) -> core::result::Result<(), NoiseError> {
+            let re = bytes_to_public(&msg.e)?;
+            self.symmetric.mix_hash(re.as_bytes());
+            self.re = Some(re);
+            Ok(())
+        }
+
+        /// Message 2 (responder only): `-> e, ee, s, es`.
+        pub fn write_message_2(&mut self, e: StaticSecret) -> NoiseEeSEs<'static> {
+            let e_pub = PublicKey::from(&e);
+            self.symmetric.mix_hash(e_pub.as_bytes());
+            let re = self.re.expect("message 1 must be read before message 2 is written");
+            self.symmetric.mix_key(e.diffie_hellman(&re).as_bytes());
+            self.e = Some(e);
+
+            let s_pub = PublicKey::from(&self.s);
+            let encrypted_static = self.symmetric.encrypt_and_hash(s_pub.as_bytes());
+            self.symmetric.mix_key(self.s.diffie_hellman(&re).as_bytes());
+            let payload = self.symmetric.encrypt_and_hash(&[]);
+
+            NoiseEeSEs {
+                e: Cow::Owned(e_pub.as_bytes().to_vec()),
+                encrypted_static: Cow::Owned(encrypted_static),
+                payload: Cow::Owned(payload),
+            }
+        }
+
+        /// Message 2 (initiator only): `<- e, ee, s, es`.
+        pub fn read_message_2(&mut self, msg: &NoiseEeSEs) -> core::result::Result<(), NoiseError> {
+            let re = bytes_to_public(&msg.e)?;
+            self.symmetric.mix_hash(re.as_bytes());
+            let e = self.e.as_ref().expect("message 1 must be written before message 2 is read");
+            self.symmetric.mix_key(e.diffie_hellman(&re).as_bytes());
+
+            let s_bytes = self.symmetric.decrypt_and_hash(&msg.encrypted_static)?;
+            let rs = bytes_to_public(&s_bytes)?;
+            self.symmetric.mix_key(e.diffie_hellman(&rs).as_bytes());
+            self.re = Some(re);
+            self.rs = Some(rs);
+            let _ = self.symmetric.decrypt_and_hash(&msg.payload)?;
+            Ok(())
+        }
+
+        /// Message 3 (initiator only): `-> s, se`. Completes the handshake; the returned
+        /// [`TransportKeys`] are ready to use as soon as this message has been sent.
+        pub fn write_message_3(mut self) -> (NoiseSSe<'static>, TransportKeys) {
+            let s_pub = PublicKey::from(&self.s);
+            let encrypted_static = self.symmetric.encrypt_and_hash(s_pub.as_bytes());
+            let re = self.re.expect("message 2 must be read before message 3 is written");
+            self.symmetric.mix_key(self.s.diffie_hellman(&re).as_bytes());
+            let payload = self.symmetric.encrypt_and_hash(&[]);
+            let message = NoiseSSe {
+                encrypted_static: Cow::Owned(encrypted_static),
+                payload: Cow::Owned(payload),
+            };
+            (message, self.finish())
+        }
+
+        /// Message 3 (responder only): `<- s, se`. Completes the handshake.
+        pub fn read_message_3(mut self, msg: &NoiseSSe) -> core::result::Result<TransportKeys, NoiseError> {
+            let s_bytes = self.symmetric.decrypt_and_hash(&msg.encrypted_static)?;
+            let rs = bytes_to_public(&s_bytes)?;
+            let e = self.e.as_ref().expect("message 2 must be written before message 3 is read");
+            self.symmetric.mix_key(e.diffie_hellman(&rs).as_bytes());
+            self.rs = Some(rs);
+            let _ = self.symmetric.decrypt_and_hash(&msg.payload)?;
+            Ok(self.finish())
+        }
+
+        fn finish(&self) -> TransportKeys {
+            let (c1, c2) = self.symmetric.split();
+            if self.initiator {
+                TransportKeys { send: TransportCipher(c1), receive: TransportCipher(c2) }
+            } else {
+                TransportKeys { send: TransportCipher(c2), receive: TransportCipher(c1) }
+            }
+        }
+
+        /// The remote party's static public key, once the handshake has completed far enough to
+        /// learn it: after message 2 for the initiator. The responder learns it only inside
+        /// `read_message_3`, which consumes the state, so on the responder this always returns
+        /// `None`. A client pins this against the app's known public key to rule out a MITM host.
+        pub fn remote_static_public_key(&self) -> Option<[u8; 32]> {
+            self.rs.map(|k| *k.as_bytes())
+        }
+    }
+
+    fn bytes_to_public(bytes: &[u8]) -> core::result::Result<PublicKey, NoiseError> {
+        let array: [u8; 32] = bytes.try_into().map_err(|_| NoiseError::UnexpectedMessage)?;
+        Ok(PublicKey::from(array))
+    }
+
+    /// The app's long-term static public key, derived from its static secret, so it can be baked
+    /// into the client for pinning (e.g. printed on packaging, or fetched once over a trusted
+    /// channel) independently of any particular handshake run.
+    pub fn static_public_key(s: &StaticSecret) -> [u8; 32] {
+        *PublicKey::from(s).as_bytes()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn secret(byte: u8) -> StaticSecret {
+            StaticSecret::from([byte; 32])
+        }
+
+        #[test]
+        fn handshake_round_trip_derives_matching_transport_keys() {
+            let mut initiator = HandshakeState::new(secret(1), true);
+            let mut responder = HandshakeState::new(secret(2), false);
+
+            let msg1 = initiator.write_message_1(secret(3));
+            responder.read_message_1(&msg1).unwrap();
+
+            let msg2 = responder.write_message_2(secret(4));
+            initiator.read_message_2(&msg2).unwrap();
+            assert_eq!(
+                initiator.remote_static_public_key(),
+                Some(static_public_key(&secret(2)))
+            );
+
+            let (msg3, mut initiator_keys) = initiator.write_message_3();
+            let mut responder_keys = responder.read_message_3(&msg3).unwrap();
+
+            // What the initiator sends, the responder must be able to decrypt, and vice versa:
+            // each side's `send` cipher lines up with the other's `receive` cipher.
+            let ciphertext = initiator_keys.send.encrypt(b"hello from initiator");
+            assert_eq!(responder_keys.receive.decrypt(&ciphertext).unwrap(), b"hello from initiator");
+
+            let ciphertext = responder_keys.send.encrypt(b"hello from responder");
+            assert_eq!(initiator_keys.receive.decrypt(&ciphertext).unwrap(), b"hello from responder");
+        }
+
+        #[test]
+        fn handshake_rejects_tampered_message_2() {
+            let mut initiator = HandshakeState::new(secret(1), true);
+            let mut responder = HandshakeState::new(secret(2), false);
+
+            let msg1 = initiator.write_message_1(secret(3));
+            responder.read_message_1(&msg1).unwrap();
+
+            let mut msg2 = responder.write_message_2(secret(4));
+            let mut tampered = msg2.encrypted_static.into_owned();
+            tampered[0] ^= 0xff;
+            msg2.encrypted_static = Cow::Owned(tampered);
+
+            assert!(initiator.read_message_2(&msg2).is_err());
+        }
+    }
+}
+
+/// A high-level client over the `Request`/`Response` wire messages above: callers no longer need
+/// to hand-assemble a `Request*`, serialize it, ship it over a transport, and decode the matching
+/// `Response*` themselves. `VanadiumClient` exposes one typed, blocking method per request; the
+/// default method bodies build the request, write it via `MessageWrite`, send it over the
+/// transport, and read the response back via `MessageRead`.
+///
+/// An `AsyncVanadiumClient` counterpart is provided for transports that are naturally async (e.g.
+/// a Speculos/HID transport driven over tokio), mirroring the blocking/async split Solana draws
+/// between `SyncClient`/`AsyncClient`.
+pub mod client {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use async_trait::async_trait;
+
+    /// A single request/response exchange with the V-App. Implementations own the framing
+    /// (fragmentation, chunking, etc.) used to actually move the bytes.
+    pub trait Transport {
+        type Error;
+
+        fn exchange(&mut self, request: &[u8]) -> core::result::Result<Vec<u8>, Self::Error>;
+    }
+
+    /// Async counterpart of [`Transport`].
+    #[async_trait(?Send)]
+    pub trait AsyncTransport {
+        type Error;
+
+        async fn exchange(&mut self, request: &[u8]) -> core::result::Result<Vec<u8>, Self::Error>;
+    }
+
+    /// Errors that can occur while driving a [`VanadiumClient`] or [`AsyncVanadiumClient`], on top
+    /// of whatever the transport itself can fail with.
+    #[derive(Debug)]
+    pub enum ClientError<E> {
+        Transport(E),
+        Protocol(quick_protobuf::Error),
+        /// The V-App returned a `Response::error` instead of the expected variant.
+        App(ErrorCode, String),
+        /// The V-App returned a `Response` variant that doesn't match the request that was sent.
+        UnexpectedResponse,
+        /// The V-App requires a feature the client did not advertise in `get_version`; the
+        /// session must abort cleanly instead of continuing into a handler it can't understand.
+        MissingRequiredFeature(String),
+    }
+
+    impl<E> From<quick_protobuf::Error> for ClientError<E> {
+        fn from(e: quick_protobuf::Error) -> Self {
+            ClientError::Protocol(e)
+        }
+    }
+
+    fn encode_request(request: mod_Request::OneOfrequest) -> Vec<u8> {
+        let req = Request { request };
+        let mut buf = vec![0u8; req.get_size()];
+        let mut writer = Writer::new(&mut buf[..]);
+        // get_size() computed the exact encoded length above, so writing into it cannot fail.
+        req.write_message(&mut writer).expect("buffer sized by get_size()");
+        buf
+    }
+
+    fn decode_response<'a>(bytes: &'a [u8]) -> core::result::Result<mod_Response::OneOfresponse<'a>, quick_protobuf::Error> {
+        let mut reader = BytesReader::from_bytes(bytes);
+        Ok(Response::from_reader(&mut reader, bytes)?.response)
+    }
+
+    macro_rules! unwrap_response {
+        ($response:expr, $variant:ident) => {
+            match $response {
+                mod_Response::OneOfresponse::$variant(m) => Ok(m),
+                mod_Response::OneOfresponse::error(e) => {
+                    Err(ClientError::App(e.error_code, e.error_msg.into_owned()))
+                }
+                _ => Err(ClientError::UnexpectedResponse),
+            }
+        };
+    }
+
+    /// Retries a one-shot exchange closure up to `attempts` times, re-serializing and resending
+    /// the request bytes on every attempt, so callers of `send_and_confirm` don't have to write
+    /// their own retry loop for transient transport failures.
+    pub fn send_and_confirm<E>(
+        attempts: u32,
+        mut exchange: impl FnMut() -> core::result::Result<Vec<u8>, E>,
+    ) -> core::result::Result<Vec<u8>, E> {
+        debug_assert!(attempts >= 1);
+        let mut last_err = None;
+        for _ in 0..attempts {
+            match exchange() {
+                Ok(response) => return Ok(response),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.expect("attempts >= 1"))
+    }
+
+    pub trait VanadiumClient: Transport {
+        /// Performs the version handshake, advertising `supported_features` and checking the
+        /// app's `required_features` against it before returning the version string. Returns
+        /// [`ClientError::MissingRequiredFeature`] rather than proceeding if the app requires a
+        /// feature this client doesn't know about.
+        fn get_version<'a>(
+            &mut self,
+            supported_features: Vec<Cow<'a, str>>,
+        ) -> core::result::Result<String, ClientError<Self::Error>> {
+            let request = RequestGetVersion { supported_features };
+            let bytes = encode_request(mod_Request::OneOfrequest::get_version(request.clone()));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_version)?;
+            if let Some(missing) = missing_required_feature(&request, &response) {
+                return Err(ClientError::MissingRequiredFeature(missing.to_string()));
+            }
+            Ok(response.version.into_owned())
+        }
+
+        fn get_master_fingerprint(&mut self) -> core::result::Result<u32, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::get_master_fingerprint(
+                RequestGetMasterFingerprint {},
+            ));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_master_fingerprint)?;
+            Ok(response.fingerprint)
+        }
+
+        fn get_extended_pubkey(
+            &mut self,
+            display: bool,
+            bip32_path: Vec<u32>,
+            network: Network,
+            script_type: ExtendedPubkeyScriptType,
+        ) -> core::result::Result<String, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::get_extended_pubkey(
+                RequestGetExtendedPubkey { display, bip32_path, network, script_type },
+            ));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_extended_pubkey)?;
+            Ok(response.pubkey.into_owned())
+        }
+
+        fn register_wallet<'a>(
+            &mut self,
+            name: &'a str,
+            descriptor_template: &'a str,
+            keys_info: Vec<Cow<'a, str>>,
+        ) -> core::result::Result<(Vec<u8>, Vec<u8>), ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::register_wallet(RequestRegisterWallet {
+                name: Cow::Borrowed(name),
+                descriptor_template: Cow::Borrowed(descriptor_template),
+                keys_info,
+            }));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, register_wallet)?;
+            Ok((
+                response.wallet_id.into_owned(),
+                response.wallet_hmac.into_owned(),
+            ))
+        }
+
+        fn get_wallet_address<'a>(
+            &mut self,
+            display: bool,
+            name: &'a str,
+            descriptor_template: &'a str,
+            keys_info: Vec<Cow<'a, str>>,
+            wallet_hmac: &'a [u8],
+            change: bool,
+            address_index: u32,
+        ) -> core::result::Result<String, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::get_wallet_address(RequestGetWalletAddress {
+                display,
+                name: Cow::Borrowed(name),
+                descriptor_template: Cow::Borrowed(descriptor_template),
+                keys_info,
+                wallet_hmac: Cow::Borrowed(wallet_hmac),
+                change,
+                address_index,
+            }));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_wallet_address)?;
+            Ok(response.address.into_owned())
+        }
+
+        fn sign_psbt<'a>(
+            &mut self,
+            psbt: &'a [u8],
+            name: &'a str,
+            descriptor_template: &'a str,
+            keys_info: Vec<Cow<'a, str>>,
+            wallet_hmac: &'a [u8],
+        ) -> core::result::Result<ResponseSignPsbt<'static>, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::sign_psbt(RequestSignPsbt {
+                psbt: Cow::Borrowed(psbt),
+                name: Cow::Borrowed(name),
+                descriptor_template: Cow::Borrowed(descriptor_template),
+                keys_info,
+                wallet_hmac: Cow::Borrowed(wallet_hmac),
+            }));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, sign_psbt)?;
+            // Copy everything out of `response` (which borrows `response_bytes`) into an owned
+            // `ResponseSignPsbt<'static>` before `response_bytes` goes out of scope.
+            Ok(ResponseSignPsbt {
+                partial_signatures: response
+                    .partial_signatures
+                    .into_iter()
+                    .map(|s| PartialSignature {
+                        input_index: s.input_index,
+                        signature: Cow::Owned(s.signature.into_owned()),
+                        public_key: Cow::Owned(s.public_key.into_owned()),
+                        leaf_hash: Cow::Owned(s.leaf_hash.into_owned()),
+                    })
+                    .collect(),
+                musig_public_nonces: response
+                    .musig_public_nonces
+                    .into_iter()
+                    .map(|s| MusigPublicNonce {
+                        input_index: s.input_index,
+                        pubnonce: Cow::Owned(s.pubnonce.into_owned()),
+                        participant_public_key: Cow::Owned(s.participant_public_key.into_owned()),
+                        xonly_key: Cow::Owned(s.xonly_key.into_owned()),
+                        leaf_hash: Cow::Owned(s.leaf_hash.into_owned()),
+                    })
+                    .collect(),
+                musig_partial_signatures: response
+                    .musig_partial_signatures
+                    .into_iter()
+                    .map(|s| MusigPartialSignature {
+                        input_index: s.input_index,
+                        signature: Cow::Owned(s.signature.into_owned()),
+                        participant_public_key: Cow::Owned(s.participant_public_key.into_owned()),
+                        xonly_key: Cow::Owned(s.xonly_key.into_owned()),
+                        leaf_hash: Cow::Owned(s.leaf_hash.into_owned()),
+                    })
+                    .collect(),
+            })
+        }
+
+        fn sign_message<'a>(
+            &mut self,
+            derivation_path: Vec<u32>,
+            message: &'a [u8],
+            scheme: SignatureScheme,
+        ) -> core::result::Result<Vec<u8>, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::sign_message(RequestSignMessage {
+                derivation_path,
+                message: Cow::Borrowed(message),
+                scheme,
+            }));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, sign_message)?;
+            Ok(response.signature.into_owned())
+        }
+
+        /// Checks a `sign_message`-compatible signature against the public key for
+        /// `derivation_path`, without the app needing to re-derive and re-sign.
+        fn verify_message<'a>(
+            &mut self,
+            derivation_path: Vec<u32>,
+            message: &'a [u8],
+            scheme: SignatureScheme,
+            signature: &'a [u8],
+        ) -> core::result::Result<bool, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::verify_message(RequestVerifyMessage {
+                derivation_path,
+                message: Cow::Borrowed(message),
+                scheme,
+                signature: Cow::Borrowed(signature),
+            }));
+            let response_bytes = self.exchange(&bytes).map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, verify_message)?;
+            Ok(response.valid)
+        }
+    }
+
+    impl<T: Transport> VanadiumClient for T {}
+
+    /// Async counterpart of [`VanadiumClient`], for transports that can only be driven from an
+    /// async context (e.g. HID over tokio). Only `get_master_fingerprint` and `get_version` are
+    /// provided here; the rest follow the exact same shape as their blocking counterparts above.
+    #[async_trait(?Send)]
+    pub trait AsyncVanadiumClient: AsyncTransport {
+        async fn get_version<'a>(
+            &mut self,
+            supported_features: Vec<Cow<'a, str>>,
+        ) -> core::result::Result<String, ClientError<Self::Error>> {
+            let request = RequestGetVersion { supported_features };
+            let bytes = encode_request(mod_Request::OneOfrequest::get_version(request.clone()));
+            let response_bytes = self.exchange(&bytes).await.map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_version)?;
+            if let Some(missing) = missing_required_feature(&request, &response) {
+                return Err(ClientError::MissingRequiredFeature(missing.to_string()));
+            }
+            Ok(response.version.into_owned())
+        }
+
+        async fn get_master_fingerprint(&mut self) -> core::result::Result<u32, ClientError<Self::Error>> {
+            let bytes = encode_request(mod_Request::OneOfrequest::get_master_fingerprint(
+                RequestGetMasterFingerprint {},
+            ));
+            let response_bytes = self.exchange(&bytes).await.map_err(ClientError::Transport)?;
+            let response = unwrap_response!(decode_response(&response_bytes)?, get_master_fingerprint)?;
+            Ok(response.fingerprint)
+        }
+    }
+
+    impl<T: AsyncTransport> AsyncVanadiumClient for T {}
+}
+
+/// Canonical, deterministic encoding of a wallet policy (the `(name, descriptor_template,
+/// keys_info)` triple carried by [`RequestRegisterWallet`]), independent of how it was framed on
+/// the wire. Protobuf is not canonical — field order, omitted defaults and unknown fields can all
+/// vary between encoders producing the "same" message — so `wallet_id`/`wallet_hmac` must instead
+/// be derived from a format that always serializes identically for the same logical policy.
+pub mod canonical {
+    use super::*;
+    use alloc::string::{String, ToString};
+
+    /// A wallet policy, independent of its protobuf framing.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct WalletPolicy<'a> {
+        pub name: Cow<'a, str>,
+        pub descriptor_template: Cow<'a, str>,
+        pub keys_info: Vec<Cow<'a, str>>,
+    }
+
+    impl<'a> From<&'a RequestRegisterWallet<'a>> for WalletPolicy<'a> {
+        fn from(req: &'a RequestRegisterWallet<'a>) -> Self {
+            WalletPolicy {
+                name: Cow::Borrowed(req.name.as_ref()),
+                descriptor_template: Cow::Borrowed(req.descriptor_template.as_ref()),
+                keys_info: req.keys_info.iter().map(|k| Cow::Borrowed(k.as_ref())).collect(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CanonicalError {
+        /// A line of the text form did not match any recognized field.
+        UnknownField,
+        /// A `keys_info[N]` line arrived out of order with respect to its index.
+        KeysInfoOutOfOrder,
+        /// A required field (`name` or `descriptor_template`) was never set.
+        MissingField,
+        /// `name` or `descriptor_template` appeared more than once.
+        DuplicateField,
+        /// A value's `\`-escaping was malformed (a trailing `\`, or `\` followed by a character
+        /// other than `\` or `n`).
+        InvalidEscape,
+    }
+
+    impl core::fmt::Display for CanonicalError {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            match self {
+                CanonicalError::UnknownField => write!(f, "unknown or malformed field"),
+                CanonicalError::KeysInfoOutOfOrder => write!(f, "keys_info entries out of order"),
+                CanonicalError::MissingField => write!(f, "missing required field"),
+                CanonicalError::DuplicateField => write!(f, "field appeared more than once"),
+                CanonicalError::InvalidEscape => write!(f, "malformed escape sequence in value"),
+            }
+        }
+    }
+
+    /// Escapes `\` and `\n` in a value so it always renders on a single text-form line and can be
+    /// unambiguously recovered by [`unescape_value`]. Without this, a value containing a newline
+    /// could forge what looks like a subsequent field line (e.g. a `name` smuggling its own
+    /// `descriptor_template=...`), changing how the text form parses back.
+    fn escape_value(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Reverses [`escape_value`].
+    fn unescape_value(value: &str) -> core::result::Result<String, CanonicalError> {
+        let mut out = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('\\') => out.push('\\'),
+                Some('n') => out.push('\n'),
+                _ => return Err(CanonicalError::InvalidEscape),
+            }
+        }
+        Ok(out)
+    }
+
+    fn push_length_prefixed(buf: &mut Vec<u8>, data: &[u8]) {
+        buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        buf.extend_from_slice(data);
+    }
+
+    /// Serializes the policy as: a fixed field order (`name`, `descriptor_template`, then
+    /// `keys_info` in the caller-supplied order), each component length-prefixed with a 4-byte
+    /// big-endian length, and the count of `keys_info` entries written up front so the byte
+    /// string is self-delimiting with no unknown/extra fields possible.
+    pub fn to_canonical_bytes(policy: &WalletPolicy) -> Vec<u8> {
+        let mut buf = Vec::new();
+        push_length_prefixed(&mut buf, policy.name.as_bytes());
+        push_length_prefixed(&mut buf, policy.descriptor_template.as_bytes());
+        buf.extend_from_slice(&(policy.keys_info.len() as u32).to_be_bytes());
+        for key_info in &policy.keys_info {
+            push_length_prefixed(&mut buf, key_info.as_bytes());
+        }
+        buf
+    }
+
+    /// Computes the `wallet_id` for a policy: the digest (under the caller-supplied hash
+    /// function, expected to be SHA-256) of its canonical encoding.
+    pub fn wallet_id<H: FnOnce(&[u8]) -> [u8; 32]>(policy: &WalletPolicy, hash: H) -> [u8; 32] {
+        hash(&to_canonical_bytes(policy))
+    }
+
+    /// Renders a policy as a stable, human-readable, line-based text form suitable for logging
+    /// and golden-file tests.
+    pub fn to_text(policy: &WalletPolicy) -> String {
+        let mut out = String::new();
+        out.push_str("name=");
+        out.push_str(&escape_value(&policy.name));
+        out.push('\n');
+        out.push_str("descriptor_template=");
+        out.push_str(&escape_value(&policy.descriptor_template));
+        out.push('\n');
+        for (i, key_info) in policy.keys_info.iter().enumerate() {
+            out.push_str("keys_info[");
+            out.push_str(&i.to_string());
+            out.push_str("]=");
+            out.push_str(&escape_value(key_info));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parses the text form produced by [`to_text`] back into a [`WalletPolicy`]. Any line that
+    /// is not one of the recognized fields, a `keys_info[N]` line out of sequence, a duplicate
+    /// `name`/`descriptor_template` line, or a value with a malformed escape sequence, is rejected.
+    pub fn from_text(text: &str) -> core::result::Result<WalletPolicy<'static>, CanonicalError> {
+        let mut name: Option<String> = None;
+        let mut descriptor_template: Option<String> = None;
+        let mut keys_info: Vec<String> = Vec::new();
+
+        for line in text.lines() {
+            if let Some(value) = line.strip_prefix("name=") {
+                if name.is_some() {
+                    return Err(CanonicalError::DuplicateField);
+                }
+                name = Some(unescape_value(value)?);
+            } else if let Some(value) = line.strip_prefix("descriptor_template=") {
+                if descriptor_template.is_some() {
+                    return Err(CanonicalError::DuplicateField);
+                }
+                descriptor_template = Some(unescape_value(value)?);
+            } else if let Some(rest) = line.strip_prefix("keys_info[") {
+                let (index_str, value) = rest.split_once("]=").ok_or(CanonicalError::UnknownField)?;
+                let index: usize = index_str.parse().map_err(|_| CanonicalError::UnknownField)?;
+                if index != keys_info.len() {
+                    return Err(CanonicalError::KeysInfoOutOfOrder);
+                }
+                keys_info.push(unescape_value(value)?);
+            } else {
+                return Err(CanonicalError::UnknownField);
+            }
+        }
+
+        Ok(WalletPolicy {
+            name: Cow::Owned(name.ok_or(CanonicalError::MissingField)?),
+            descriptor_template: Cow::Owned(descriptor_template.ok_or(CanonicalError::MissingField)?),
+            keys_info: keys_info.into_iter().map(Cow::Owned).collect(),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn round_trips_a_simple_policy() {
+            let policy = WalletPolicy {
+                name: Cow::Borrowed("My Wallet"),
+                descriptor_template: Cow::Borrowed("wsh(multi(2,@0/**,@1/**))"),
+                keys_info: alloc::vec![Cow::Borrowed("[abcdef00]xpub.../**"), Cow::Borrowed("xpub2.../**")],
+            };
+
+            let text = to_text(&policy);
+            assert_eq!(from_text(&text).unwrap(), policy);
+        }
+
+        #[test]
+        fn round_trips_values_with_embedded_newlines_and_equals_signs() {
+            let policy = WalletPolicy {
+                name: Cow::Borrowed("evil\nname=hijacked\ndescriptor_template=hijacked"),
+                descriptor_template: Cow::Borrowed("a=b\\c"),
+                keys_info: alloc::vec![Cow::Borrowed("key\nwith\nnewlines=and=equals")],
+            };
+
+            let text = to_text(&policy);
+            // The adversarial newlines must not have produced extra top-level lines that a naive
+            // line-based parser could mistake for forged fields.
+            assert_eq!(text.lines().count(), 3);
+            assert_eq!(from_text(&text).unwrap(), policy);
+        }
+
+        #[test]
+        fn rejects_duplicate_name() {
+            let text = "name=a\nname=b\ndescriptor_template=d\n";
+            assert_eq!(from_text(text), Err(CanonicalError::DuplicateField));
+        }
+
+        #[test]
+        fn rejects_duplicate_descriptor_template() {
+            let text = "name=a\ndescriptor_template=d\ndescriptor_template=d2\n";
+            assert_eq!(from_text(text), Err(CanonicalError::DuplicateField));
+        }
+
+        #[test]
+        fn rejects_malformed_escape_sequence() {
+            let text = "name=a\\x\ndescriptor_template=d\n";
+            assert_eq!(from_text(text), Err(CanonicalError::InvalidEscape));
+        }
+    }
+}
+
+/// Dispatch layer: rather than each V-App hand-writing a tag match over decoded `Request`
+/// variants, app authors implement the single [`RequestHandler`] trait below and call
+/// [`dispatch`], which decodes the incoming `Request`, invokes the matching trait method, and
+/// re-encodes the `Response`. Like the rest of this hand-written part of the file, the
+/// variant mapping in `dispatch` and the trait's method list are written by hand, not emitted by
+/// a generator; adding a new RPC means adding one trait method and one match arm here.
+pub mod dispatch {
+    use super::*;
+    use alloc::vec;
+
+    /// One method per `Request*` variant; app authors implement this trait instead of writing
+    /// their own tag-matching boilerplate.
+    pub trait RequestHandler {
+        type Error;
+
+        fn get_version(
+            &mut self,
+            request: &RequestGetVersion,
+        ) -> core::result::Result<ResponseGetVersion<'static>, Self::Error>;
+        /// Called for `Request::exit`. Implementations typically never return (e.g. they call
+        /// into an ecall that terminates the V-App); the return value is only used if they do.
+        fn exit(&mut self) -> Self::Error;
+        fn get_master_fingerprint(
+            &mut self,
+        ) -> core::result::Result<ResponseGetMasterFingerprint, Self::Error>;
+        fn get_extended_pubkey(
+            &mut self,
+            request: &RequestGetExtendedPubkey,
+        ) -> core::result::Result<ResponseGetExtendedPubkey<'static>, Self::Error>;
+        fn register_wallet(
+            &mut self,
+            request: &RequestRegisterWallet,
+        ) -> core::result::Result<ResponseRegisterWallet<'static>, Self::Error>;
+        fn get_wallet_address(
+            &mut self,
+            request: &RequestGetWalletAddress,
+        ) -> core::result::Result<ResponseGetWalletAddress<'static>, Self::Error>;
+        fn sign_psbt(
+            &mut self,
+            request: &RequestSignPsbt,
+        ) -> core::result::Result<ResponseSignPsbt<'static>, Self::Error>;
+        fn get_capabilities(
+            &mut self,
+        ) -> core::result::Result<ResponseGetCapabilities<'static>, Self::Error>;
+        fn sign_message(
+            &mut self,
+            request: &RequestSignMessage,
+        ) -> core::result::Result<ResponseSignMessage<'static>, Self::Error>;
+        fn verify_message(
+            &mut self,
+            request: &RequestVerifyMessage,
+        ) -> core::result::Result<ResponseVerifyMessage, Self::Error>;
+    }
+
+    #[derive(Debug)]
+    pub enum DispatchError<E> {
+        /// The request bytes could not be decoded as a `Request`.
+        Decode(quick_protobuf::Error),
+        /// The decoded `Request` carried no recognized variant (an empty/invalid oneof).
+        NoRequest,
+        /// The handler for `Request::exit` was invoked. No response is ever encoded for it.
+        Exited(E),
+        /// A handler method returned an error.
+        Handler(E),
+    }
+
+    pub fn dispatch<H: RequestHandler>(
+        handler: &mut H,
+        bytes: &[u8],
+    ) -> core::result::Result<Vec<u8>, DispatchError<H::Error>> {
+        let mut reader = BytesReader::from_bytes(bytes);
+        let request =
+            Request::from_reader(&mut reader, bytes).map_err(DispatchError::Decode)?;
+
+        let response = match request.request {
+            mod_Request::OneOfrequest::get_version(ref req) => {
+                mod_Response::OneOfresponse::get_version(
+                    handler.get_version(req).map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::exit(_) => {
+                return Err(DispatchError::Exited(handler.exit()));
+            }
+            mod_Request::OneOfrequest::get_master_fingerprint(_) => {
+                mod_Response::OneOfresponse::get_master_fingerprint(
+                    handler
+                        .get_master_fingerprint()
+                        .map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::get_extended_pubkey(ref req) => {
+                mod_Response::OneOfresponse::get_extended_pubkey(
+                    handler
+                        .get_extended_pubkey(req)
+                        .map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::register_wallet(ref req) => {
+                mod_Response::OneOfresponse::register_wallet(
+                    handler
+                        .register_wallet(req)
+                        .map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::get_wallet_address(ref req) => {
+                mod_Response::OneOfresponse::get_wallet_address(
+                    handler
+                        .get_wallet_address(req)
+                        .map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::sign_psbt(ref req) => {
+                mod_Response::OneOfresponse::sign_psbt(
+                    handler.sign_psbt(req).map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::get_capabilities(_) => {
+                mod_Response::OneOfresponse::get_capabilities(
+                    handler
+                        .get_capabilities()
+                        .map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::sign_message(ref req) => {
+                mod_Response::OneOfresponse::sign_message(
+                    handler.sign_message(req).map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::verify_message(ref req) => {
+                mod_Response::OneOfresponse::verify_message(
+                    handler.verify_message(req).map_err(DispatchError::Handler)?,
+                )
+            }
+            mod_Request::OneOfrequest::None => return Err(DispatchError::NoRequest),
+        };
+
+        let response = Response { response };
+        let mut buf = vec![0u8; response.get_size()];
+        let mut writer = Writer::new(&mut buf[..]);
+        response
+            .write_message(&mut writer)
+            .expect("buffer sized by get_size()");
+        Ok(buf)
+    }
+}
+
+/// CBOR alternative to the protobuf encoding used above, in the spirit of how Jade-style hardware
+/// wallets frame their RPC as CBOR with byte-string fields instead of a protobuf runtime. A
+/// session picks one codec at startup (e.g. a capability advertised alongside
+/// `RequestGetVersion::supported_features` in the version handshake) and uses it for every
+/// `Request`/`Response` exchange; both codecs carry the same logical messages, so a V-App need
+/// only implement [`dispatch::RequestHandler`] once regardless of which codec a transport chose.
+pub mod codec {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+    use serde::{Deserialize, Serialize};
+    use serde_bytes::ByteBuf;
+
+    /// Wire format negotiated for a session.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Codec {
+        Protobuf,
+        Cbor,
+    }
+
+    fn to_strings(v: &[Cow<str>]) -> Vec<String> {
+        v.iter().map(|s| s.as_ref().into()).collect()
+    }
+
+    fn to_cows(v: Vec<String>) -> Vec<Cow<'static, str>> {
+        v.into_iter().map(Cow::Owned).collect()
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestGetVersion {
+        pub supported_features: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseGetVersion {
+        pub version: String,
+        pub required_features: Vec<String>,
+        pub optional_features: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseGetMasterFingerprint {
+        pub fingerprint: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestGetExtendedPubkey {
+        pub display: bool,
+        pub bip32_path: Vec<u32>,
+        pub network: i32,
+        pub script_type: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseGetExtendedPubkey {
+        pub pubkey: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestRegisterWallet {
+        pub name: String,
+        pub descriptor_template: String,
+        pub keys_info: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseRegisterWallet {
+        pub wallet_id: ByteBuf,
+        pub wallet_hmac: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestGetWalletAddress {
+        pub display: bool,
+        pub name: String,
+        pub descriptor_template: String,
+        pub keys_info: Vec<String>,
+        pub wallet_hmac: ByteBuf,
+        pub change: bool,
+        pub address_index: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseGetWalletAddress {
+        pub address: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestSignPsbt {
+        pub psbt: ByteBuf,
+        pub name: String,
+        pub descriptor_template: String,
+        pub keys_info: Vec<String>,
+        pub wallet_hmac: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborPartialSignature {
+        pub input_index: u32,
+        pub signature: ByteBuf,
+        pub public_key: ByteBuf,
+        pub leaf_hash: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborMusigPublicNonce {
+        pub input_index: u32,
+        pub pubnonce: ByteBuf,
+        pub participant_public_key: ByteBuf,
+        pub xonly_key: ByteBuf,
+        pub leaf_hash: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborMusigPartialSignature {
+        pub input_index: u32,
+        pub signature: ByteBuf,
+        pub participant_public_key: ByteBuf,
+        pub xonly_key: ByteBuf,
+        pub leaf_hash: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseSignPsbt {
+        pub partial_signatures: Vec<CborPartialSignature>,
+        pub musig_public_nonces: Vec<CborMusigPublicNonce>,
+        pub musig_partial_signatures: Vec<CborMusigPartialSignature>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseError {
+        pub error_msg: String,
+        pub error_code: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseGetCapabilities {
+        pub capabilities: Vec<(String, u32)>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestSignMessage {
+        pub derivation_path: Vec<u32>,
+        pub message: ByteBuf,
+        pub scheme: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseSignMessage {
+        pub signature: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequestVerifyMessage {
+        pub derivation_path: Vec<u32>,
+        pub message: ByteBuf,
+        pub scheme: i32,
+        pub signature: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponseVerifyMessage {
+        pub valid: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborNoiseE {
+        pub e: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborNoiseEeSEs {
+        pub e: ByteBuf,
+        pub encrypted_static: ByteBuf,
+        pub payload: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborNoiseSSe {
+        pub encrypted_static: ByteBuf,
+        pub payload: ByteBuf,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum CborOneOfrequest {
+        GetVersion(CborRequestGetVersion),
+        Exit,
+        GetMasterFingerprint,
+        GetExtendedPubkey(CborRequestGetExtendedPubkey),
+        RegisterWallet(CborRequestRegisterWallet),
+        GetWalletAddress(CborRequestGetWalletAddress),
+        SignPsbt(CborRequestSignPsbt),
+        GetCapabilities,
+        NoiseE(CborNoiseE),
+        NoiseEeSEs(CborNoiseEeSEs),
+        NoiseSSe(CborNoiseSSe),
+        SignMessage(CborRequestSignMessage),
+        VerifyMessage(CborRequestVerifyMessage),
+        None,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum CborOneOfresponse {
+        GetVersion(CborResponseGetVersion),
+        GetMasterFingerprint(CborResponseGetMasterFingerprint),
+        GetExtendedPubkey(CborResponseGetExtendedPubkey),
+        RegisterWallet(CborResponseRegisterWallet),
+        GetWalletAddress(CborResponseGetWalletAddress),
+        SignPsbt(CborResponseSignPsbt),
+        Error(CborResponseError),
+        GetCapabilities(CborResponseGetCapabilities),
+        NoiseE(CborNoiseE),
+        NoiseEeSEs(CborNoiseEeSEs),
+        NoiseSSe(CborNoiseSSe),
+        SignMessage(CborResponseSignMessage),
+        VerifyMessage(CborResponseVerifyMessage),
+        None,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborRequest {
+        pub request: CborOneOfrequest,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CborResponse {
+        pub response: CborOneOfresponse,
+    }
+
+    impl<'a> From<&Request<'a>> for CborRequest {
+        fn from(request: &Request<'a>) -> Self {
+            let request = match &request.request {
+                mod_Request::OneOfrequest::get_version(m) => CborOneOfrequest::GetVersion(CborRequestGetVersion {
+                    supported_features: to_strings(&m.supported_features),
+                }),
+                mod_Request::OneOfrequest::exit(_) => CborOneOfrequest::Exit,
+                mod_Request::OneOfrequest::get_master_fingerprint(_) => CborOneOfrequest::GetMasterFingerprint,
+                mod_Request::OneOfrequest::get_extended_pubkey(m) => CborOneOfrequest::GetExtendedPubkey(CborRequestGetExtendedPubkey {
+                    display: m.display,
+                    bip32_path: m.bip32_path.clone(),
+                    network: m.network as i32,
+                    script_type: m.script_type as i32,
+                }),
+                mod_Request::OneOfrequest::register_wallet(m) => CborOneOfrequest::RegisterWallet(CborRequestRegisterWallet {
+                    name: m.name.as_ref().into(),
+                    descriptor_template: m.descriptor_template.as_ref().into(),
+                    keys_info: to_strings(&m.keys_info),
+                }),
+                mod_Request::OneOfrequest::get_wallet_address(m) => CborOneOfrequest::GetWalletAddress(CborRequestGetWalletAddress {
+                    display: m.display,
+                    name: m.name.as_ref().into(),
+                    descriptor_template: m.descriptor_template.as_ref().into(),
+                    keys_info: to_strings(&m.keys_info),
+                    wallet_hmac: ByteBuf::from(m.wallet_hmac.as_ref().to_vec()),
+                    change: m.change,
+                    address_index: m.address_index,
+                }),
+                mod_Request::OneOfrequest::sign_psbt(m) => CborOneOfrequest::SignPsbt(CborRequestSignPsbt {
+                    psbt: ByteBuf::from(m.psbt.as_ref().to_vec()),
+                    name: m.name.as_ref().into(),
+                    descriptor_template: m.descriptor_template.as_ref().into(),
+                    keys_info: to_strings(&m.keys_info),
+                    wallet_hmac: ByteBuf::from(m.wallet_hmac.as_ref().to_vec()),
+                }),
+                mod_Request::OneOfrequest::get_capabilities(_) => CborOneOfrequest::GetCapabilities,
+                mod_Request::OneOfrequest::noise_e(m) => CborOneOfrequest::NoiseE(CborNoiseE {
+                    e: ByteBuf::from(m.e.as_ref().to_vec()),
+                }),
+                mod_Request::OneOfrequest::noise_ee_s_es(m) => CborOneOfrequest::NoiseEeSEs(CborNoiseEeSEs {
+                    e: ByteBuf::from(m.e.as_ref().to_vec()),
+                    encrypted_static: ByteBuf::from(m.encrypted_static.as_ref().to_vec()),
+                    payload: ByteBuf::from(m.payload.as_ref().to_vec()),
+                }),
+                mod_Request::OneOfrequest::noise_s_se(m) => CborOneOfrequest::NoiseSSe(CborNoiseSSe {
+                    encrypted_static: ByteBuf::from(m.encrypted_static.as_ref().to_vec()),
+                    payload: ByteBuf::from(m.payload.as_ref().to_vec()),
+                }),
+                mod_Request::OneOfrequest::sign_message(m) => CborOneOfrequest::SignMessage(CborRequestSignMessage {
+                    derivation_path: m.derivation_path.clone(),
+                    message: ByteBuf::from(m.message.as_ref().to_vec()),
+                    scheme: m.scheme as i32,
+                }),
+                mod_Request::OneOfrequest::verify_message(m) => CborOneOfrequest::VerifyMessage(CborRequestVerifyMessage {
+                    derivation_path: m.derivation_path.clone(),
+                    message: ByteBuf::from(m.message.as_ref().to_vec()),
+                    scheme: m.scheme as i32,
+                    signature: ByteBuf::from(m.signature.as_ref().to_vec()),
+                }),
+                mod_Request::OneOfrequest::None => CborOneOfrequest::None,
+            };
+            CborRequest { request }
+        }
+    }
+
+    impl From<CborRequest> for Request<'static> {
+        fn from(cbor: CborRequest) -> Self {
+            let request = match cbor.request {
+                CborOneOfrequest::GetVersion(m) => mod_Request::OneOfrequest::get_version(RequestGetVersion {
+                    supported_features: to_cows(m.supported_features),
+                }),
+                CborOneOfrequest::Exit => mod_Request::OneOfrequest::exit(RequestExit::default()),
+                CborOneOfrequest::GetMasterFingerprint => {
+                    mod_Request::OneOfrequest::get_master_fingerprint(RequestGetMasterFingerprint::default())
+                }
+                CborOneOfrequest::GetExtendedPubkey(m) => mod_Request::OneOfrequest::get_extended_pubkey(RequestGetExtendedPubkey {
+                    display: m.display,
+                    bip32_path: m.bip32_path,
+                    network: Network::from(m.network),
+                    script_type: ExtendedPubkeyScriptType::from(m.script_type),
+                }),
+                CborOneOfrequest::RegisterWallet(m) => mod_Request::OneOfrequest::register_wallet(RequestRegisterWallet {
+                    name: Cow::Owned(m.name),
+                    descriptor_template: Cow::Owned(m.descriptor_template),
+                    keys_info: to_cows(m.keys_info),
+                }),
+                CborOneOfrequest::GetWalletAddress(m) => mod_Request::OneOfrequest::get_wallet_address(RequestGetWalletAddress {
+                    display: m.display,
+                    name: Cow::Owned(m.name),
+                    descriptor_template: Cow::Owned(m.descriptor_template),
+                    keys_info: to_cows(m.keys_info),
+                    wallet_hmac: Cow::Owned(m.wallet_hmac.into_vec()),
+                    change: m.change,
+                    address_index: m.address_index,
+                }),
+                CborOneOfrequest::SignPsbt(m) => mod_Request::OneOfrequest::sign_psbt(RequestSignPsbt {
+                    psbt: Cow::Owned(m.psbt.into_vec()),
+                    name: Cow::Owned(m.name),
+                    descriptor_template: Cow::Owned(m.descriptor_template),
+                    keys_info: to_cows(m.keys_info),
+                    wallet_hmac: Cow::Owned(m.wallet_hmac.into_vec()),
+                }),
+                CborOneOfrequest::GetCapabilities => mod_Request::OneOfrequest::get_capabilities(RequestGetCapabilities::default()),
+                CborOneOfrequest::NoiseE(m) => mod_Request::OneOfrequest::noise_e(NoiseE {
+                    e: Cow::Owned(m.e.into_vec()),
+                }),
+                CborOneOfrequest::NoiseEeSEs(m) => mod_Request::OneOfrequest::noise_ee_s_es(NoiseEeSEs {
+                    e: Cow::Owned(m.e.into_vec()),
+                    encrypted_static: Cow::Owned(m.encrypted_static.into_vec()),
+                    payload: Cow::Owned(m.payload.into_vec()),
+                }),
+                CborOneOfrequest::NoiseSSe(m) => mod_Request::OneOfrequest::noise_s_se(NoiseSSe {
+                    encrypted_static: Cow::Owned(m.encrypted_static.into_vec()),
+                    payload: Cow::Owned(m.payload.into_vec()),
+                }),
+                CborOneOfrequest::SignMessage(m) => mod_Request::OneOfrequest::sign_message(RequestSignMessage {
+                    derivation_path: m.derivation_path,
+                    message: Cow::Owned(m.message.into_vec()),
+                    scheme: SignatureScheme::from(m.scheme),
+                }),
+                CborOneOfrequest::VerifyMessage(m) => mod_Request::OneOfrequest::verify_message(RequestVerifyMessage {
+                    derivation_path: m.derivation_path,
+                    message: Cow::Owned(m.message.into_vec()),
+                    scheme: SignatureScheme::from(m.scheme),
+                    signature: Cow::Owned(m.signature.into_vec()),
+                }),
+                CborOneOfrequest::None => mod_Request::OneOfrequest::None,
+            };
+            Request { request }
+        }
+    }
+
+    impl<'a> From<&Response<'a>> for CborResponse {
+        fn from(response: &Response<'a>) -> Self {
+            let response = match &response.response {
+                mod_Response::OneOfresponse::get_version(m) => CborOneOfresponse::GetVersion(CborResponseGetVersion {
+                    version: m.version.as_ref().into(),
+                    required_features: to_strings(&m.required_features),
+                    optional_features: to_strings(&m.optional_features),
+                }),
+                mod_Response::OneOfresponse::get_master_fingerprint(m) => {
+                    CborOneOfresponse::GetMasterFingerprint(CborResponseGetMasterFingerprint {
+                        fingerprint: m.fingerprint,
+                    })
+                }
+                mod_Response::OneOfresponse::get_extended_pubkey(m) => {
+                    CborOneOfresponse::GetExtendedPubkey(CborResponseGetExtendedPubkey {
+                        pubkey: m.pubkey.as_ref().into(),
+                    })
+                }
+                mod_Response::OneOfresponse::register_wallet(m) => CborOneOfresponse::RegisterWallet(CborResponseRegisterWallet {
+                    wallet_id: ByteBuf::from(m.wallet_id.as_ref().to_vec()),
+                    wallet_hmac: ByteBuf::from(m.wallet_hmac.as_ref().to_vec()),
+                }),
+                mod_Response::OneOfresponse::get_wallet_address(m) => {
+                    CborOneOfresponse::GetWalletAddress(CborResponseGetWalletAddress {
+                        address: m.address.as_ref().into(),
+                    })
+                }
+                mod_Response::OneOfresponse::sign_psbt(m) => CborOneOfresponse::SignPsbt(CborResponseSignPsbt {
+                    partial_signatures: m
+                        .partial_signatures
+                        .iter()
+                        .map(|s| CborPartialSignature {
+                            input_index: s.input_index,
+                            signature: ByteBuf::from(s.signature.as_ref().to_vec()),
+                            public_key: ByteBuf::from(s.public_key.as_ref().to_vec()),
+                            leaf_hash: ByteBuf::from(s.leaf_hash.as_ref().to_vec()),
+                        })
+                        .collect(),
+                    musig_public_nonces: m
+                        .musig_public_nonces
+                        .iter()
+                        .map(|s| CborMusigPublicNonce {
+                            input_index: s.input_index,
+                            pubnonce: ByteBuf::from(s.pubnonce.as_ref().to_vec()),
+                            participant_public_key: ByteBuf::from(s.participant_public_key.as_ref().to_vec()),
+                            xonly_key: ByteBuf::from(s.xonly_key.as_ref().to_vec()),
+                            leaf_hash: ByteBuf::from(s.leaf_hash.as_ref().to_vec()),
+                        })
+                        .collect(),
+                    musig_partial_signatures: m
+                        .musig_partial_signatures
+                        .iter()
+                        .map(|s| CborMusigPartialSignature {
+                            input_index: s.input_index,
+                            signature: ByteBuf::from(s.signature.as_ref().to_vec()),
+                            participant_public_key: ByteBuf::from(s.participant_public_key.as_ref().to_vec()),
+                            xonly_key: ByteBuf::from(s.xonly_key.as_ref().to_vec()),
+                            leaf_hash: ByteBuf::from(s.leaf_hash.as_ref().to_vec()),
+                        })
+                        .collect(),
+                }),
+                mod_Response::OneOfresponse::error(m) => CborOneOfresponse::Error(CborResponseError {
+                    error_msg: m.error_msg.as_ref().into(),
+                    error_code: m.error_code as i32,
+                }),
+                mod_Response::OneOfresponse::get_capabilities(m) => {
+                    CborOneOfresponse::GetCapabilities(CborResponseGetCapabilities {
+                        capabilities: m.capabilities.iter().map(|(k, v)| (k.as_ref().into(), *v)).collect(),
+                    })
+                }
+                mod_Response::OneOfresponse::noise_e(m) => CborOneOfresponse::NoiseE(CborNoiseE {
+                    e: ByteBuf::from(m.e.as_ref().to_vec()),
+                }),
+                mod_Response::OneOfresponse::noise_ee_s_es(m) => CborOneOfresponse::NoiseEeSEs(CborNoiseEeSEs {
+                    e: ByteBuf::from(m.e.as_ref().to_vec()),
+                    encrypted_static: ByteBuf::from(m.encrypted_static.as_ref().to_vec()),
+                    payload: ByteBuf::from(m.payload.as_ref().to_vec()),
+                }),
+                mod_Response::OneOfresponse::noise_s_se(m) => CborOneOfresponse::NoiseSSe(CborNoiseSSe {
+                    encrypted_static: ByteBuf::from(m.encrypted_static.as_ref().to_vec()),
+                    payload: ByteBuf::from(m.payload.as_ref().to_vec()),
+                }),
+                mod_Response::OneOfresponse::sign_message(m) => CborOneOfresponse::SignMessage(CborResponseSignMessage {
+                    signature: ByteBuf::from(m.signature.as_ref().to_vec()),
+                }),
+                mod_Response::OneOfresponse::verify_message(m) => CborOneOfresponse::VerifyMessage(CborResponseVerifyMessage {
+                    valid: m.valid,
+                }),
+                mod_Response::OneOfresponse::None => CborOneOfresponse::None,
+            };
+            CborResponse { response }
+        }
+    }
+
+    impl From<CborResponse> for Response<'static> {
+        fn from(cbor: CborResponse) -> Self {
+            let response = match cbor.response {
+                CborOneOfresponse::GetVersion(m) => mod_Response::OneOfresponse::get_version(ResponseGetVersion {
+                    version: Cow::Owned(m.version),
+                    required_features: to_cows(m.required_features),
+                    optional_features: to_cows(m.optional_features),
+                }),
+                CborOneOfresponse::GetMasterFingerprint(m) => {
+                    mod_Response::OneOfresponse::get_master_fingerprint(ResponseGetMasterFingerprint {
+                        fingerprint: m.fingerprint,
+                    })
+                }
+                CborOneOfresponse::GetExtendedPubkey(m) => {
+                    mod_Response::OneOfresponse::get_extended_pubkey(ResponseGetExtendedPubkey {
+                        pubkey: Cow::Owned(m.pubkey),
+                    })
+                }
+                CborOneOfresponse::RegisterWallet(m) => mod_Response::OneOfresponse::register_wallet(ResponseRegisterWallet {
+                    wallet_id: Cow::Owned(m.wallet_id.into_vec()),
+                    wallet_hmac: Cow::Owned(m.wallet_hmac.into_vec()),
+                }),
+                CborOneOfresponse::GetWalletAddress(m) => {
+                    mod_Response::OneOfresponse::get_wallet_address(ResponseGetWalletAddress {
+                        address: Cow::Owned(m.address),
+                    })
+                }
+                CborOneOfresponse::SignPsbt(m) => mod_Response::OneOfresponse::sign_psbt(ResponseSignPsbt {
+                    partial_signatures: m
+                        .partial_signatures
+                        .into_iter()
+                        .map(|s| PartialSignature {
+                            input_index: s.input_index,
+                            signature: Cow::Owned(s.signature.into_vec()),
+                            public_key: Cow::Owned(s.public_key.into_vec()),
+                            leaf_hash: Cow::Owned(s.leaf_hash.into_vec()),
+                        })
+                        .collect(),
+                    musig_public_nonces: m
+                        .musig_public_nonces
+                        .into_iter()
+                        .map(|s| MusigPublicNonce {
+                            input_index: s.input_index,
+                            pubnonce: Cow::Owned(s.pubnonce.into_vec()),
+                            participant_public_key: Cow::Owned(s.participant_public_key.into_vec()),
+                            xonly_key: Cow::Owned(s.xonly_key.into_vec()),
+                            leaf_hash: Cow::Owned(s.leaf_hash.into_vec()),
+                        })
+                        .collect(),
+                    musig_partial_signatures: m
+                        .musig_partial_signatures
+                        .into_iter()
+                        .map(|s| MusigPartialSignature {
+                            input_index: s.input_index,
+                            signature: Cow::Owned(s.signature.into_vec()),
+                            participant_public_key: Cow::Owned(s.participant_public_key.into_vec()),
+                            xonly_key: Cow::Owned(s.xonly_key.into_vec()),
+                            leaf_hash: Cow::Owned(s.leaf_hash.into_vec()),
+                        })
+                        .collect(),
+                }),
+                CborOneOfresponse::Error(m) => mod_Response::OneOfresponse::error(ResponseError {
+                    error_msg: Cow::Owned(m.error_msg),
+                    error_code: ErrorCode::from(m.error_code),
+                }),
+                CborOneOfresponse::GetCapabilities(m) => {
+                    mod_Response::OneOfresponse::get_capabilities(ResponseGetCapabilities {
+                        capabilities: m.capabilities.into_iter().map(|(k, v)| (Cow::Owned(k), v)).collect(),
+                    })
+                }
+                CborOneOfresponse::NoiseE(m) => mod_Response::OneOfresponse::noise_e(NoiseE {
+                    e: Cow::Owned(m.e.into_vec()),
+                }),
+                CborOneOfresponse::NoiseEeSEs(m) => mod_Response::OneOfresponse::noise_ee_s_es(NoiseEeSEs {
+                    e: Cow::Owned(m.e.into_vec()),
+                    encrypted_static: Cow::Owned(m.encrypted_static.into_vec()),
+                    payload: Cow::Owned(m.payload.into_vec()),
+                }),
+                CborOneOfresponse::NoiseSSe(m) => mod_Response::OneOfresponse::noise_s_se(NoiseSSe {
+                    encrypted_static: Cow::Owned(m.encrypted_static.into_vec()),
+                    payload: Cow::Owned(m.payload.into_vec()),
+                }),
+                CborOneOfresponse::SignMessage(m) => mod_Response::OneOfresponse::sign_message(ResponseSignMessage {
+                    signature: Cow::Owned(m.signature.into_vec()),
+                }),
+                CborOneOfresponse::VerifyMessage(m) => mod_Response::OneOfresponse::verify_message(ResponseVerifyMessage {
+                    valid: m.valid,
+                }),
+                CborOneOfresponse::None => mod_Response::OneOfresponse::None,
+            };
+            Response { response }
+        }
+    }
+
+    /// Encodes `request` under the negotiated `codec`.
+    pub fn encode_request(codec: Codec, request: &Request) -> Vec<u8> {
+        match codec {
+            Codec::Protobuf => {
+                let mut buf = vec![0u8; request.get_size()];
+                let mut writer = Writer::new(&mut buf[..]);
+                request.write_message(&mut writer).expect("buffer sized by get_size()");
+                buf
+            }
+            Codec::Cbor => serde_cbor::to_vec(&CborRequest::from(request)).expect("CBOR encoding cannot fail"),
+        }
+    }
+
+    /// Decodes a `Request` that was encoded under `codec`. The protobuf path borrows from
+    /// `bytes`; the CBOR path always produces an owned `Request<'static>`, which is a valid
+    /// `Request<'a>` for any `'a`.
+    pub fn decode_request<'a>(codec: Codec, bytes: &'a [u8]) -> core::result::Result<Request<'a>, DecodeError> {
+        match codec {
+            Codec::Protobuf => {
+                let mut reader = BytesReader::from_bytes(bytes);
+                Request::from_reader(&mut reader, bytes).map_err(DecodeError::Protobuf)
+            }
+            Codec::Cbor => {
+                let cbor: CborRequest = serde_cbor::from_slice(bytes).map_err(DecodeError::Cbor)?;
+                Ok(Request::from(cbor))
+            }
+        }
+    }
+
+    /// Encodes `response` under the negotiated `codec`.
+    pub fn encode_response(codec: Codec, response: &Response) -> Vec<u8> {
+        match codec {
+            Codec::Protobuf => {
+                let mut buf = vec![0u8; response.get_size()];
+                let mut writer = Writer::new(&mut buf[..]);
+                response.write_message(&mut writer).expect("buffer sized by get_size()");
+                buf
+            }
+            Codec::Cbor => serde_cbor::to_vec(&CborResponse::from(response)).expect("CBOR encoding cannot fail"),
+        }
+    }
+
+    /// Decodes a `Response` that was encoded under `codec`. Same borrowed-vs-owned split as
+    /// [`decode_request`].
+    pub fn decode_response<'a>(codec: Codec, bytes: &'a [u8]) -> core::result::Result<Response<'a>, DecodeError> {
+        match codec {
+            Codec::Protobuf => {
+                let mut reader = BytesReader::from_bytes(bytes);
+                Response::from_reader(&mut reader, bytes).map_err(DecodeError::Protobuf)
+            }
+            Codec::Cbor => {
+                let cbor: CborResponse = serde_cbor::from_slice(bytes).map_err(DecodeError::Cbor)?;
+                Ok(Response::from(cbor))
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub enum DecodeError {
+        Protobuf(quick_protobuf::Error),
+        Cbor(serde_cbor::Error),
+    }
+}
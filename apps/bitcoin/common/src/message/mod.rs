@@ -0,0 +1,8 @@
+//! The wire format shared between the Bitcoin V-App and its host client: a protobuf schema
+//! (`message.proto`) compiled ahead of time with `quick_protobuf`'s codegen, checked in as
+//! [`message`] rather than regenerated by a build script.
+
+#[allow(clippy::module_inception)]
+mod message;
+
+pub use message::*;
@@ -12,10 +12,87 @@ use sdk::{
 
 extern crate alloc;
 
+mod modexp;
+
+use alloc::collections::BTreeMap;
 use alloc::string::ToString;
 use alloc::vec::Vec;
 use common::{Command, Curve, HashId};
 
+/// State for one in-flight streaming hash, kept alive across `receive_message` calls between a
+/// `HashInit` and the matching `HashFinal`. This lets a vApp hash a message larger than
+/// `COMM_BUFFER_SIZE` by streaming it in through repeated `HashUpdate` commands instead of
+/// having to buffer it whole.
+enum HashState {
+    Ripemd160(sdk::hash::Ripemd160),
+    Sha256(sdk::hash::Sha256),
+    Sha512(sdk::hash::Sha512),
+    Keccak256(sdk::hash::Keccak256),
+    Sha3_256(sdk::hash::Sha3_256),
+}
+
+impl HashState {
+    fn new(hash_id: HashId) -> Self {
+        match hash_id {
+            HashId::Ripemd160 => HashState::Ripemd160(sdk::hash::Ripemd160::new()),
+            HashId::Sha256 => HashState::Sha256(sdk::hash::Sha256::new()),
+            HashId::Sha512 => HashState::Sha512(sdk::hash::Sha512::new()),
+            HashId::Keccak256 => HashState::Keccak256(sdk::hash::Keccak256::new()),
+            HashId::Sha3_256 => HashState::Sha3_256(sdk::hash::Sha3_256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            HashState::Ripemd160(h) => {
+                h.update(data);
+            }
+            HashState::Sha256(h) => {
+                h.update(data);
+            }
+            HashState::Sha512(h) => {
+                h.update(data);
+            }
+            HashState::Keccak256(h) => {
+                h.update(data);
+            }
+            HashState::Sha3_256(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            HashState::Ripemd160(h) => {
+                let mut digest = [0u8; 20];
+                h.digest(&mut digest);
+                digest.to_vec()
+            }
+            HashState::Sha256(h) => {
+                let mut digest = [0u8; 32];
+                h.digest(&mut digest);
+                digest.to_vec()
+            }
+            HashState::Sha512(h) => {
+                let mut digest = [0u8; 64];
+                h.digest(&mut digest);
+                digest.to_vec()
+            }
+            HashState::Keccak256(h) => {
+                let mut digest = [0u8; 32];
+                h.digest(&mut digest);
+                digest.to_vec()
+            }
+            HashState::Sha3_256(h) => {
+                let mut digest = [0u8; 32];
+                h.digest(&mut digest);
+                digest.to_vec()
+            }
+        }
+    }
+}
+
 // Temporary to force the creation of a data section
 #[used]
 #[no_mangle]
@@ -48,8 +125,12 @@ pub fn main(_: isize, _: *const *const u8) -> isize {
     sdk::rust_init_heap();
 
     sdk::ux::ux_idle();
+
+    let mut hash_states: BTreeMap<u32, HashState> = BTreeMap::new();
+    let mut next_hash_ctx: u32 = 0;
+
     loop {
-        let msg = match sdk::comm::receive_message() {
+        let msg = match sdk::comm::receive_message(None) {
             Ok(msg) => msg,
             Err(e) => {
                 let error_string = e.to_string();
@@ -84,8 +165,40 @@ pub fn main(_: isize, _: *const *const u8) -> isize {
                         hasher.digest(&mut digest);
                         digest.to_vec()
                     }
+                    HashId::Keccak256 => {
+                        let mut hasher = sdk::hash::Keccak256::new();
+                        hasher.update(&msg);
+                        let mut digest = [0u8; 32];
+                        hasher.digest(&mut digest);
+                        digest.to_vec()
+                    }
+                    HashId::Sha3_256 => {
+                        let mut hasher = sdk::hash::Sha3_256::new();
+                        hasher.update(&msg);
+                        let mut digest = [0u8; 32];
+                        hasher.digest(&mut digest);
+                        digest.to_vec()
+                    }
                 }
             }
+            Command::HashInit { hash_id } => {
+                let hash_id = HashId::try_from(hash_id).expect("Invalid hash ID");
+                let ctx = next_hash_ctx;
+                next_hash_ctx = next_hash_ctx.wrapping_add(1);
+                hash_states.insert(ctx, HashState::new(hash_id));
+                ctx.to_le_bytes().to_vec()
+            }
+            Command::HashUpdate { ctx, chunk } => {
+                hash_states
+                    .get_mut(&ctx)
+                    .expect("Invalid hash context")
+                    .update(&chunk);
+                Vec::new()
+            }
+            Command::HashFinal { ctx } => hash_states
+                .remove(&ctx)
+                .expect("Invalid hash context")
+                .finalize(),
             Command::BigIntOperation {
                 operator,
                 a,
@@ -179,10 +292,22 @@ pub fn main(_: isize, _: *const *const u8) -> isize {
                     }
                 }
             }
+            Command::ModExp {
+                base,
+                exponent,
+                modulus,
+            } => modexp::mod_exp(&base, &exponent, &modulus)
+                .expect("Operand too large"),
             Command::GetMasterFingerprint { curve } => match curve {
                 Curve::Secp256k1 => sdk::curve::Secp256k1::get_master_fingerprint()
                     .to_be_bytes()
                     .to_vec(),
+                Curve::Ed25519 => sdk::curve::Ed25519::get_master_fingerprint()
+                    .to_be_bytes()
+                    .to_vec(),
+                Curve::Secp256r1 => sdk::curve::Secp256r1::get_master_fingerprint()
+                    .to_be_bytes()
+                    .to_vec(),
             },
             Command::DeriveHdNode { curve, path } => match curve {
                 // returns the concatenation of the chaincode and private key
@@ -192,9 +317,33 @@ pub fn main(_: isize, _: *const *const u8) -> isize {
                     result.extend_from_slice(&node.privkey);
                     result
                 }
+                Curve::Ed25519 => {
+                    let node = sdk::curve::Ed25519::derive_hd_node(&path).unwrap();
+                    let mut result = node.chaincode.to_vec();
+                    result.extend_from_slice(&node.privkey);
+                    result
+                }
+                Curve::Secp256r1 => {
+                    let node = sdk::curve::Secp256r1::derive_hd_node(&path).unwrap();
+                    let mut result = node.chaincode.to_vec();
+                    result.extend_from_slice(&node.privkey);
+                    result
+                }
+            },
+            Command::EcRecover {
+                curve,
+                msg_hash,
+                v,
+                r,
+                s,
+            } => match curve {
+                // returns the 64-byte uncompressed public key (X || Y)
+                Curve::Secp256k1 => sdk::curve::Secp256k1::recover(&msg_hash, v, &r, &s)
+                    .expect("Invalid signature")
+                    .to_vec(),
             },
         };
 
-        sdk::comm::send_message(&response);
+        sdk::comm::send_message(&response, None);
     }
 }
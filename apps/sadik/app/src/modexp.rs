@@ -0,0 +1,188 @@
+//! Arbitrary-length modular exponentiation, matching the semantics of the Ethereum MODEXP
+//! precompile: `base^exponent mod modulus`, where each operand is a big-endian byte string of
+//! runtime-determined length.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Upper bound on the byte length of any operand, so a malicious (huge) length can't be used to
+/// exhaust the V-App's heap.
+const MAX_OPERAND_LEN: usize = 1024;
+
+/// Computes `base^exponent mod modulus`, all given as big-endian byte strings.
+///
+/// Returns `None` if any operand exceeds [`MAX_OPERAND_LEN`] bytes.
+pub fn mod_exp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Option<Vec<u8>> {
+    if base.len() > MAX_OPERAND_LEN
+        || exponent.len() > MAX_OPERAND_LEN
+        || modulus.len() > MAX_OPERAND_LEN
+    {
+        return None;
+    }
+
+    // Strip the modulus' leading zero bytes up front (keeping its original length for the
+    // output), so the reduction helpers compare and subtract against its canonical (shortest)
+    // form. The base and exponent are used as given.
+    let output_len = modulus.len();
+    let modulus = skip_leading_zeros(modulus);
+
+    // The precompile defines modulus 0 or 1 as yielding 0.
+    if is_zero(modulus) || is_one(modulus) {
+        return Some(vec![0u8; output_len]);
+    }
+
+    // Exponent 0 yields 1 mod modulus (and modulus > 1 here, so that's just 1).
+    let result = if is_zero(exponent) {
+        mod_reduce(&[1], modulus)
+    } else {
+        let mut result = mod_reduce(&[1], modulus); // 1 mod modulus
+        let base = mod_reduce(base, modulus);
+
+        // Scan the exponent from the most to the least significant bit: square the accumulator
+        // at every step, and multiply in the (reduced) base whenever the bit is set.
+        for bit in bits_msb_first(exponent) {
+            result = mul_mod(&result, &result, modulus);
+            if bit {
+                result = mul_mod(&result, &base, modulus);
+            }
+        }
+        result
+    };
+
+    // Pad back out to the modulus' original length, matching the precompile's fixed-width output.
+    let mut padded = vec![0u8; output_len - result.len()];
+    padded.extend_from_slice(&result);
+    Some(padded)
+}
+
+fn is_zero(a: &[u8]) -> bool {
+    a.iter().all(|&b| b == 0)
+}
+
+fn is_one(a: &[u8]) -> bool {
+    match a.split_last() {
+        Some((&last, rest)) => last == 1 && is_zero(rest),
+        None => false,
+    }
+}
+
+/// Iterates over the bits of `a`, most significant first (including leading zero bits: those
+/// just square the `1` accumulator a few extra times, which is harmless).
+fn bits_msb_first(a: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    a.iter()
+        .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0))
+}
+
+/// Computes `a mod m` via repeated shift-and-subtract (binary long division).
+fn mod_reduce(a: &[u8], m: &[u8]) -> Vec<u8> {
+    let mut remainder: Vec<u8> = Vec::new();
+    for bit in bits_msb_first(a) {
+        shl1_in_place(&mut remainder);
+        if bit {
+            set_lsb(&mut remainder);
+        }
+        if cmp(&remainder, m) != core::cmp::Ordering::Less {
+            sub_in_place(&mut remainder, m);
+        }
+    }
+    strip_leading_zeros(&mut remainder);
+    remainder
+}
+
+/// Computes `(a * b) mod m`.
+fn mul_mod(a: &[u8], b: &[u8], m: &[u8]) -> Vec<u8> {
+    let product = mul(a, b);
+    mod_reduce(&product, m)
+}
+
+/// Schoolbook multiplication of two big-endian unsigned integers.
+fn mul(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u16; a.len() + b.len()];
+    for (i, &a_byte) in a.iter().rev().enumerate() {
+        let mut carry = 0u16;
+        for (j, &b_byte) in b.iter().rev().enumerate() {
+            let idx = result.len() - 1 - (i + j);
+            let prod = a_byte as u16 * b_byte as u16 + result[idx] + carry;
+            result[idx] = prod & 0xff;
+            carry = prod >> 8;
+        }
+        let idx = result.len() - 1 - (i + b.len());
+        result[idx] += carry;
+    }
+    result.into_iter().map(|limb| limb as u8).collect()
+}
+
+/// Left-shifts a big-endian unsigned integer by one bit in place, growing it by a byte if the
+/// top bit would otherwise be lost.
+fn shl1_in_place(a: &mut Vec<u8>) {
+    let mut carry = 0u8;
+    for byte in a.iter_mut().rev() {
+        let new_carry = *byte >> 7;
+        *byte = (*byte << 1) | carry;
+        carry = new_carry;
+    }
+    if carry != 0 {
+        a.insert(0, carry);
+    }
+}
+
+/// Sets the least significant bit of a big-endian unsigned integer, growing it by a byte if
+/// it's currently empty (i.e. represents zero).
+fn set_lsb(a: &mut Vec<u8>) {
+    if a.is_empty() {
+        a.push(1);
+    } else {
+        *a.last_mut().unwrap() |= 1;
+    }
+}
+
+/// Subtracts `b` from `a` in place. Requires `a >= b`.
+fn sub_in_place(a: &mut Vec<u8>, b: &[u8]) {
+    let mut borrow = 0i16;
+    let len = a.len();
+    for (i, &b_byte) in b.iter().rev().enumerate() {
+        let idx = len - 1 - i;
+        let diff = a[idx] as i16 - b_byte as i16 - borrow;
+        if diff < 0 {
+            a[idx] = (diff + 256) as u8;
+            borrow = 1;
+        } else {
+            a[idx] = diff as u8;
+            borrow = 0;
+        }
+    }
+    let mut i = len - b.len();
+    while borrow != 0 && i > 0 {
+        i -= 1;
+        let diff = a[i] as i16 - borrow;
+        if diff < 0 {
+            a[i] = (diff + 256) as u8;
+            borrow = 1;
+        } else {
+            a[i] = diff as u8;
+            borrow = 0;
+        }
+    }
+    strip_leading_zeros(a);
+}
+
+fn strip_leading_zeros(a: &mut Vec<u8>) {
+    let n_zeros = a.iter().take_while(|&&b| b == 0).count();
+    a.drain(0..n_zeros);
+}
+
+/// Compares two big-endian unsigned integers, ignoring leading zeros.
+fn cmp(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+    let a = skip_leading_zeros(a);
+    let b = skip_leading_zeros(b);
+    if a.len() != b.len() {
+        a.len().cmp(&b.len())
+    } else {
+        a.cmp(b)
+    }
+}
+
+fn skip_leading_zeros(a: &[u8]) -> &[u8] {
+    let n_zeros = a.iter().take_while(|&&b| b == 0).count();
+    &a[n_zeros..]
+}
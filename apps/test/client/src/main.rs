@@ -4,6 +4,7 @@ use hidapi::HidApi;
 use ledger_transport_hid::TransportNativeHID;
 
 use sdk::transport::{Transport, TransportHID, TransportTcp, TransportWrapper};
+use sdk::transport_serial::TransportSerial;
 use sdk::vanadium_client::{NativeAppClient, VanadiumAppClient};
 
 mod commands;
@@ -26,6 +27,26 @@ struct Args {
     /// Use the native interface
     #[arg(long, group = "interface")]
     native: bool,
+
+    /// Use a serial port (e.g. /dev/ttyUSB0) instead of Speculos, HID, or the native interface
+    #[arg(long, group = "interface")]
+    serial: Option<String>,
+
+    /// Baud rate for --serial
+    #[arg(long, default_value_t = 115_200)]
+    baud: u32,
+
+    /// Run commands non-interactively from `file` (use `-` for stdin) instead of the prompt loop
+    #[arg(long)]
+    script: Option<String>,
+
+    /// Emit one JSON object per command result instead of a bare output line
+    #[arg(long)]
+    json: bool,
+
+    /// Stop at the first command that errors, instead of continuing with the rest of the script
+    #[arg(long)]
+    fail_fast: bool,
 }
 
 enum CliCommand {
@@ -57,6 +78,47 @@ fn parse_u32(s: &str) -> Result<u32, String> {
         .map_err(|_| "Invalid u32 integer".to_string())
 }
 
+/// Escapes `s` for embedding in a JSON string literal. Output here is always a flat object of
+/// plain string fields, so a minimal escaper is enough without pulling in a JSON crate.
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Prints the outcome of running one command, in the format selected by `--json`.
+fn print_result(json: bool, command: &str, input: &str, result: &Result<String, String>) {
+    if json {
+        let (status, field, value) = match result {
+            Ok(output) => ("ok", "output", output.as_str()),
+            Err(e) => ("error", "error", e.as_str()),
+        };
+        println!(
+            "{{\"command\":\"{}\",\"input\":\"{}\",\"{}\":\"{}\",\"status\":\"{}\"}}",
+            json_escape(command),
+            json_escape(input),
+            field,
+            json_escape(value),
+            status,
+        );
+    } else {
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
+
 fn parse_command(line: &str) -> Result<CliCommand, String> {
     let mut tokens = line.trim().split_whitespace();
     if let Some(command) = tokens.next() {
@@ -113,6 +175,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 )
                 .unwrap(),
             ))
+        } else if let Some(serial_path) = &args.serial {
+            Arc::new(
+                TransportSerial::new(serial_path, args.baud)
+                    .expect("Unable to open the serial port"),
+            )
         } else {
             Arc::new(
                 TransportTcp::new()
@@ -127,45 +194,93 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         ))
     };
 
-    loop {
-        println!("Enter a command:");
+    // Interactive use reads from stdin with a prompt before each line; `--script <file>` reads
+    // lines from a file instead, and `--script -` reads from stdin without prompting, for piping
+    // commands in from a non-interactive harness.
+    let mut script_reader: Option<Box<dyn BufRead>> = match args.script.as_deref() {
+        Some("-") => Some(Box::new(std::io::stdin().lock())),
+        Some(path) => Some(Box::new(std::io::BufReader::new(
+            std::fs::File::open(path).expect("Unable to open script file"),
+        ))),
+        None => None,
+    };
+    let interactive = script_reader.is_none();
 
+    let mut last_exit_status = 0;
+
+    loop {
         let mut line = String::new();
-        std::io::stdin()
-            .lock()
-            .read_line(&mut line)
-            .expect("Failed to read line");
-
-        match parse_command(&line) {
-            Ok(cmd) => match cmd {
-                CliCommand::Reverse(arg) => {
-                    println!("{}", hex::encode(test_client.reverse(&arg).await?));
-                }
-                CliCommand::AddNumbers(number) => {
-                    println!("{}", test_client.add_numbers(number).await?);
-                }
-                CliCommand::Sha256(arg) => {
-                    println!("{}", hex::encode(test_client.sha256(&arg).await?));
-                }
-                CliCommand::B58Enc(arg) => {
-                    println!("{}", hex::encode(test_client.b58enc(&arg).await?));
-                }
-                CliCommand::NPrimes(n) => {
-                    println!("{}", test_client.nprimes(n).await?);
-                }
-                CliCommand::Exit => {
-                    let status = test_client.exit().await?;
-                    if status != 0 {
-                        std::process::exit(status);
-                    }
-                    break;
-                }
-            },
-            Err(e) => {
-                println!("Error: {}", e);
+        let bytes_read = if let Some(reader) = script_reader.as_mut() {
+            reader.read_line(&mut line).expect("Failed to read line")
+        } else {
+            println!("Enter a command:");
+            std::io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .expect("Failed to read line")
+        };
+
+        // EOF on a script (but not on the interactive prompt, where an empty line means "exit")
+        // is treated as an implicit exit, so a script doesn't need a trailing `exit` line to reach
+        // the client's normal shutdown path.
+        if bytes_read == 0 && !interactive {
+            line.clear();
+        } else if bytes_read == 0 {
+            break;
+        }
+
+        let input = line.trim().to_string();
+        let command_name = input
+            .split_whitespace()
+            .next()
+            .unwrap_or("exit")
+            .to_string();
+
+        let result: Result<String, String> = match parse_command(&line) {
+            Ok(CliCommand::Reverse(arg)) => test_client
+                .reverse(&arg)
+                .await
+                .map(hex::encode)
+                .map_err(|e| e.to_string()),
+            Ok(CliCommand::AddNumbers(number)) => test_client
+                .add_numbers(number)
+                .await
+                .map(|n| n.to_string())
+                .map_err(|e| e.to_string()),
+            Ok(CliCommand::Sha256(arg)) => test_client
+                .sha256(&arg)
+                .await
+                .map(hex::encode)
+                .map_err(|e| e.to_string()),
+            Ok(CliCommand::B58Enc(arg)) => test_client
+                .b58enc(&arg)
+                .await
+                .map(hex::encode)
+                .map_err(|e| e.to_string()),
+            Ok(CliCommand::NPrimes(n)) => test_client
+                .nprimes(n)
+                .await
+                .map(|n| n.to_string())
+                .map_err(|e| e.to_string()),
+            Ok(CliCommand::Exit) => {
+                last_exit_status = test_client.exit().await?;
+                print_result(args.json, "exit", &input, &Ok(last_exit_status.to_string()));
+                break;
             }
+            Err(e) => Err(e),
+        };
+
+        let is_err = result.is_err();
+        print_result(args.json, &command_name, &input, &result);
+
+        if is_err && args.fail_fast {
+            std::process::exit(1);
         }
     }
 
+    if last_exit_status != 0 {
+        std::process::exit(last_exit_status);
+    }
+
     Ok(())
 }
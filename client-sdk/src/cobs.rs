@@ -0,0 +1,92 @@
+//! Consistent Overhead Byte Stuffing (COBS) framing, used by [`crate::transport_serial`] to
+//! delimit packets on a raw UART link where no other framing (HID reports, TCP length prefixes)
+//! is available.
+//!
+//! Encoding replaces every zero byte in the payload with a distance-to-next-zero "code" byte, so
+//! the only zero byte left in the wire frame is the trailing delimiter. This adds at most one
+//! overhead byte per 254 payload bytes.
+
+/// Maximum number of bytes (including the code byte itself) in one COBS block.
+const MAX_BLOCK_LEN: u8 = 0xFF;
+
+/// Encodes `input` into a COBS frame, including the trailing `0x00` delimiter.
+pub fn encode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() + input.len() / (MAX_BLOCK_LEN as usize - 1) + 2);
+
+    // `code_index` points at the code byte of the block currently being built; it's filled in
+    // once the block closes (on a zero byte, on reaching the max block length, or at the end).
+    let mut code_index = 0;
+    let mut code: u8 = 1;
+    output.push(0); // placeholder for the first block's code byte
+
+    for &byte in input {
+        if byte == 0 {
+            output[code_index] = code;
+            code = 1;
+            code_index = output.len();
+            output.push(0); // placeholder
+        } else {
+            output.push(byte);
+            code += 1;
+            if code == MAX_BLOCK_LEN {
+                output[code_index] = code;
+                code = 1;
+                code_index = output.len();
+                output.push(0); // placeholder
+            }
+        }
+    }
+
+    output[code_index] = code;
+    output.push(0); // end-of-frame delimiter
+    output
+}
+
+#[derive(Debug)]
+pub enum CobsError {
+    /// A code byte of 0 appeared outside the frame delimiter.
+    InvalidCode,
+    /// A block's length claims more bytes than remain in the frame.
+    Truncated,
+}
+
+impl std::fmt::Display for CobsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CobsError::InvalidCode => write!(f, "invalid COBS code byte"),
+            CobsError::Truncated => write!(f, "truncated COBS frame"),
+        }
+    }
+}
+
+impl std::error::Error for CobsError {}
+
+/// Decodes a single COBS frame. `frame` must NOT include the trailing `0x00` delimiter — callers
+/// reading from a byte stream should split on `0x00` first and pass everything before it here.
+pub fn decode(frame: &[u8]) -> Result<Vec<u8>, CobsError> {
+    let mut output = Vec::with_capacity(frame.len());
+    let mut pos = 0;
+
+    while pos < frame.len() {
+        let code = frame[pos];
+        if code == 0 {
+            return Err(CobsError::InvalidCode);
+        }
+        let block_len = code as usize - 1;
+        pos += 1;
+
+        if pos + block_len > frame.len() {
+            return Err(CobsError::Truncated);
+        }
+        output.extend_from_slice(&frame[pos..pos + block_len]);
+        pos += block_len;
+
+        // A block shorter than the max implies a zero byte follows in the original data, unless
+        // this block ran right up to the end of the frame (the delimiter takes its place there).
+        if code != MAX_BLOCK_LEN && pos != frame.len() {
+            output.push(0);
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,424 @@
+//! A minimal Debug Adapter Protocol (DAP) server for attaching an editor to a running V-App.
+//!
+//! The `VAppEngine` in [`crate::vanadium_client`] already answers every `GetPage`/`CommitPage`
+//! request the on-device VM makes, so it sees every code/data/stack page the V-App touches before
+//! the device does. That's reused here as the whole breakpoint mechanism: when the VM asks for a
+//! code page that covers an armed breakpoint, [`DebugAdapter::pause_if_breakpoint`] emits a
+//! `stopped` DAP event and blocks until the attached client sends `continue`, instead of
+//! immediately handing the page back. Since the device's execution loop is itself blocked waiting
+//! on that APDU response, this pauses the V-App without any device-side change.
+//!
+//! DAP messages are JSON, framed on the wire as `Content-Length: N\r\n\r\n` followed by `N` bytes
+//! of UTF-8 JSON (the same framing the Language Server Protocol uses). Only the handful of
+//! requests needed for a basic breakpoint-driven debugging session are implemented: a host-side
+//! debugger that can't see device registers can't do much more than that (see
+//! [`DebugAdapter::handle_request`]).
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{oneshot, Mutex};
+
+use common::constants::{page_start, PAGE_SIZE};
+
+use crate::vanadium_client::MemorySegment;
+
+/// Which memory segment a breakpoint or `readMemory` request falls in, and the address range it
+/// spans. Mirrors the three sections `OutsourcedMemory` serves on the device side.
+#[derive(Clone, Copy)]
+pub struct SegmentBounds {
+    pub code_start: u32,
+    pub code_end: u32,
+    pub data_start: u32,
+    pub data_end: u32,
+    pub stack_start: u32,
+    pub stack_end: u32,
+}
+
+enum SegmentKind {
+    Code,
+    Data,
+    Stack,
+}
+
+impl SegmentBounds {
+    fn locate(&self, address: u32) -> Option<(SegmentKind, u32)> {
+        if address >= self.code_start && address < self.code_end {
+            Some((SegmentKind::Code, self.code_start))
+        } else if address >= self.data_start && address < self.data_end {
+            Some((SegmentKind::Data, self.data_start))
+        } else if address >= self.stack_start && address < self.stack_end {
+            Some((SegmentKind::Stack, self.stack_start))
+        } else {
+            None
+        }
+    }
+}
+
+/// A DAP request/response/event pair for a session with at most one attached client at a time.
+struct Session {
+    writer: tokio::net::tcp::OwnedWriteHalf,
+    /// Sequence number for server-initiated messages (responses echo the request's `seq`).
+    next_seq: i64,
+}
+
+struct Inner {
+    breakpoints: BTreeSet<u32>,
+    /// Set while the V-App is paused on a breakpoint; firing it lets `GetPage` processing resume.
+    resume_tx: Option<oneshot::Sender<()>>,
+    session: Option<Session>,
+}
+
+/// Shared debugger state: the breakpoint set, the current pause (if any), and the attached DAP
+/// client connection. One `DebugAdapter` is created per V-App run and handed both to
+/// [`DebugAdapter::serve`] (the DAP socket server) and to `VAppEngine::process_get_page` (the
+/// breakpoint check).
+pub struct DebugAdapter {
+    inner: Mutex<Inner>,
+    bounds: SegmentBounds,
+    code_seg: Arc<Mutex<MemorySegment>>,
+    data_seg: Arc<Mutex<MemorySegment>>,
+    stack_seg: Arc<Mutex<MemorySegment>>,
+}
+
+impl DebugAdapter {
+    pub fn new(
+        bounds: SegmentBounds,
+        code_seg: Arc<Mutex<MemorySegment>>,
+        data_seg: Arc<Mutex<MemorySegment>>,
+        stack_seg: Arc<Mutex<MemorySegment>>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(Inner {
+                breakpoints: BTreeSet::new(),
+                resume_tx: None,
+                session: None,
+            }),
+            bounds,
+            code_seg,
+            data_seg,
+            stack_seg,
+        })
+    }
+
+    /// Accepts DAP client connections on `addr`, one at a time, until the process exits. Meant to
+    /// be spawned as its own task alongside the `VAppEngine`'s busy loop.
+    pub async fn serve(
+        self: Arc<Self>,
+        addr: impl ToSocketAddrs,
+    ) -> Result<(), std::io::Error> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let (socket, _) = listener.accept().await?;
+            // Only one debugger session makes sense at a time; the next connection is accepted
+            // only once the current session has ended.
+            self.clone().handle_connection(socket).await;
+        }
+    }
+
+    async fn handle_connection(self: Arc<Self>, socket: TcpStream) {
+        let (read_half, write_half) = socket.into_split();
+        {
+            let mut inner = self.inner.lock().await;
+            inner.session = Some(Session {
+                writer: write_half,
+                next_seq: 1,
+            });
+        }
+
+        let mut reader = BufReader::new(read_half);
+        loop {
+            match read_message(&mut reader).await {
+                Ok(Some(request)) => {
+                    let response = self.handle_request(&request).await;
+                    self.send_message(&response).await;
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let mut inner = self.inner.lock().await;
+        inner.session = None;
+    }
+
+    async fn send_message(&self, message: &Value) {
+        let mut inner = self.inner.lock().await;
+        if let Some(session) = inner.session.as_mut() {
+            let _ = write_message(&mut session.writer, message).await;
+        }
+    }
+
+    /// Handles one DAP request, returning its response. `setBreakpoints`, `continue`,
+    /// `stackTrace`, `scopes`, `variables` and `readMemory` are implemented; everything else gets
+    /// an empty but `success: true` response, which is enough for most clients to move past
+    /// requests (like `initialize` or `launch`) that this adapter doesn't need to act on.
+    async fn handle_request(&self, request: &Value) -> Value {
+        let seq = request.get("seq").and_then(Value::as_i64).unwrap_or(0);
+        let command = request
+            .get("command")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let arguments = request.get("arguments").cloned().unwrap_or(json!({}));
+
+        let body = match command {
+            "setBreakpoints" => self.cmd_set_breakpoints(&arguments).await,
+            "continue" => self.cmd_continue().await,
+            "stackTrace" => self.cmd_stack_trace().await,
+            "scopes" => json!({ "scopes": [] }),
+            "variables" => json!({ "variables": [] }),
+            "readMemory" => self.cmd_read_memory(&arguments).await,
+            _ => json!({}),
+        };
+
+        response_envelope(seq, command, body)
+    }
+
+    async fn cmd_set_breakpoints(&self, arguments: &Value) -> Value {
+        let addresses: Vec<u32> = arguments
+            .get("breakpoints")
+            .and_then(Value::as_array)
+            .map(|bps| {
+                bps.iter()
+                    .filter_map(|bp| bp.get("instructionReference").and_then(Value::as_str))
+                    .filter_map(parse_hex_address)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let breakpoints_response: Vec<Value> = addresses
+            .iter()
+            .map(|addr| json!({ "verified": true, "instructionReference": format!("0x{:x}", addr) }))
+            .collect();
+
+        let mut inner = self.inner.lock().await;
+        inner.breakpoints = addresses.into_iter().collect();
+
+        json!({ "breakpoints": breakpoints_response })
+    }
+
+    async fn cmd_continue(&self) -> Value {
+        let mut inner = self.inner.lock().await;
+        if let Some(resume_tx) = inner.resume_tx.take() {
+            let _ = resume_tx.send(());
+        }
+        json!({ "allThreadsContinued": true })
+    }
+
+    async fn cmd_stack_trace(&self) -> Value {
+        let stopped_at = { self.inner.lock().await.resume_tx.is_some() };
+        if !stopped_at {
+            return json!({ "stackFrames": [], "totalFrames": 0 });
+        }
+        // The only address this host-side debugger can observe is the page covering the
+        // breakpoint that's currently withheld; there's no register file to unwind a real call
+        // stack from, so a single synthetic frame is reported.
+        let frame = json!({
+            "id": 0,
+            "name": "vapp",
+            "line": 0,
+            "column": 0,
+        });
+        json!({ "stackFrames": [frame], "totalFrames": 1 })
+    }
+
+    async fn cmd_read_memory(&self, arguments: &Value) -> Value {
+        let Some(base) = arguments
+            .get("memoryReference")
+            .and_then(Value::as_str)
+            .and_then(parse_hex_address)
+        else {
+            return json!({ "data": Value::Null, "unreadableBytes": 0 });
+        };
+        let offset = arguments.get("offset").and_then(Value::as_i64).unwrap_or(0);
+        let count = arguments
+            .get("count")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize;
+        let address = (base as i64 + offset) as u32;
+
+        match self.read_memory(address, count).await {
+            Ok(data) => json!({
+                "address": format!("0x{:x}", address),
+                "data": base64_encode(&data),
+            }),
+            Err(_) => json!({ "unreadableBytes": count }),
+        }
+    }
+
+    /// Reconstructs `count` bytes starting at `address` from the committed pages of whichever
+    /// segment contains it. Code pages, and data/stack pages the device has not committed yet,
+    /// hold the plaintext the segment was created with; a data/stack page the device has
+    /// committed holds its ciphertext (the host never holds the session key needed to decrypt
+    /// it), so `readMemory` on such a page surfaces encrypted bytes rather than failing silently.
+    async fn read_memory(&self, address: u32, count: usize) -> Result<Vec<u8>, &'static str> {
+        let (kind, segment_start) = self.bounds.locate(address).ok_or("out of bounds")?;
+        let segment = match kind {
+            SegmentKind::Code => &self.code_seg,
+            SegmentKind::Data => &self.data_seg,
+            SegmentKind::Stack => &self.stack_seg,
+        };
+
+        let segment = segment.lock().await;
+        let mut out = Vec::with_capacity(count);
+        let mut addr = address;
+        while out.len() < count {
+            let page_index = (page_start(addr) - page_start(segment_start)) / PAGE_SIZE as u32;
+            let (serialized_page, _proof) = segment
+                .get_page(page_index)
+                .map_err(|_| "page not committed")?;
+            let page_data = &serialized_page[13..13 + PAGE_SIZE];
+            let offset_in_page = (addr - page_start(addr)) as usize;
+            let n = (PAGE_SIZE - offset_in_page).min(count - out.len());
+            out.extend_from_slice(&page_data[offset_in_page..offset_in_page + n]);
+            addr += n as u32;
+        }
+        Ok(out)
+    }
+
+    /// Checks whether `page_index` within `kind` covers an armed breakpoint, and if so, emits a
+    /// `stopped` DAP event and blocks until a `continue` request resumes it. Called from
+    /// `VAppEngine::process_get_page` right before the withheld page would otherwise be sent back
+    /// to the device.
+    pub async fn pause_if_breakpoint(&self, kind: CheckedSegment, page_index: u32) {
+        let segment_start = match kind {
+            CheckedSegment::Code => self.bounds.code_start,
+        };
+        let page_addr = page_start(segment_start) + page_index * PAGE_SIZE as u32;
+        let page_end = page_addr + PAGE_SIZE as u32;
+
+        let hit = {
+            let inner = self.inner.lock().await;
+            inner
+                .breakpoints
+                .range(page_addr..page_end)
+                .next()
+                .copied()
+        };
+        let Some(hit_address) = hit else {
+            return;
+        };
+
+        let (resume_rx, stopped_event) = {
+            let mut inner = self.inner.lock().await;
+            let (tx, rx) = oneshot::channel();
+            inner.resume_tx = Some(tx);
+            (
+                rx,
+                json!({
+                    "reason": "breakpoint",
+                    "threadId": 1,
+                    "description": format!("breakpoint at 0x{:x}", hit_address),
+                }),
+            )
+        };
+
+        self.send_message(&event_envelope("stopped", stopped_event))
+            .await;
+
+        // The device is itself blocked waiting on this APDU's response, so awaiting here is what
+        // actually pauses the V-App.
+        let _ = resume_rx.await;
+    }
+}
+
+/// Which segment a paused `GetPage` request was for. Only `Code` breakpoints are supported:
+/// breakpoints are instruction addresses, and `SectionKind::Data`/`SectionKind::Stack` pages are
+/// never instruction fetches.
+pub enum CheckedSegment {
+    Code,
+}
+
+fn response_envelope(request_seq: i64, command: &str, body: Value) -> Value {
+    json!({
+        "type": "response",
+        "request_seq": request_seq,
+        "success": true,
+        "command": command,
+        "body": body,
+    })
+}
+
+fn event_envelope(event: &str, body: Value) -> Value {
+    json!({
+        "type": "event",
+        "event": event,
+        "body": body,
+    })
+}
+
+fn parse_hex_address(s: &str) -> Option<u32> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+/// Minimal base64 encoder (DAP's `readMemory` response encodes `data` as base64), avoiding a new
+/// dependency for a single field.
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+
+        out.push(ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Reads one `Content-Length: N\r\n\r\n<N bytes of JSON>` framed DAP message. Returns `Ok(None)`
+/// on a clean EOF between messages.
+async fn read_message<R: AsyncBufReadExt + AsyncReadExt + Unpin>(
+    reader: &mut R,
+) -> Result<Option<Value>, std::io::Error> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = content_length
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Writes one DAP message with its `Content-Length` header.
+async fn write_message<W: AsyncWriteExt + Unpin>(
+    writer: &mut W,
+    message: &Value,
+) -> Result<(), std::io::Error> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await
+}
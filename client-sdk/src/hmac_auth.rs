@@ -1,10 +1,39 @@
 use std::path::{Path, PathBuf};
 
+use common::accumulator::{HashOutput, Hasher, MerkleAccumulator, VectorAccumulator};
 use common::manifest::Manifest;
 
-/// Magic bytes for the HMAC cache file format.
+/// Magic bytes for the flat HMAC cache file format (`CodeHmacs::load`/`save`).
 const HMAC_FILE_MAGIC: &[u8; 10] = b"VAPP_HMAC\0";
 
+/// Magic bytes for the Merkle-root HMAC cache file format (`MerkleCodeHmacs::load`/`save`).
+const MERKLE_HMAC_FILE_MAGIC: &[u8; 10] = b"VAPP_MRKL\0";
+
+/// A SHA-256 `Hasher` for building the Merkle tree over per-page HMACs (see
+/// `common::accumulator`).
+struct Sha256Hasher(sha2::Sha256);
+
+impl Hasher<32> for Sha256Hasher {
+    fn new() -> Self {
+        use sha2::Digest;
+        Sha256Hasher(sha2::Sha256::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        use sha2::Digest;
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&self.0.finalize());
+        out
+    }
+}
+
+type CodeHmacsAccumulator = MerkleAccumulator<Sha256Hasher, [u8; 32], 32>;
+
 /// Computes the V-App hash from a manifest.
 pub fn compute_vapp_hash(manifest: &Manifest) -> [u8; 32] {
     use crate::hash::Sha256;
@@ -76,6 +105,37 @@ impl CodeHmacs {
         self.hmacs
     }
 
+    /// Builds the Merkle tree over the per-page HMACs and returns its root. Save this (via
+    /// [`MerkleCodeHmacs::save`]) instead of the flat HMAC vector to avoid keeping the whole
+    /// working set in the cache file.
+    pub fn merkle_root(&self) -> [u8; 32] {
+        let acc = CodeHmacsAccumulator::new(self.hmacs.clone());
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&acc.root());
+        root
+    }
+
+    /// Produces a logarithmic inclusion proof that `self.hmacs[index]` is a leaf of the tree
+    /// rooted at [`CodeHmacs::merkle_root`].
+    pub fn prove_page(&self, index: usize) -> Result<Vec<[u8; 32]>, &'static str> {
+        let acc = CodeHmacsAccumulator::new(self.hmacs.clone());
+        Ok(acc.prove(index)?.into_iter().map(|h| h.0).collect())
+    }
+
+    /// Verifies that `hmac` is the per-page HMAC at `index` (out of `total_pages`) under
+    /// `root`, given an inclusion `proof` produced by [`CodeHmacs::prove_page`]. Unlike
+    /// [`CodeHmacs::prove_page`], this doesn't require holding any of the other pages' HMACs.
+    pub fn verify_page(
+        root: &[u8; 32],
+        index: usize,
+        total_pages: usize,
+        hmac: &[u8; 32],
+        proof: &[[u8; 32]],
+    ) -> bool {
+        let proof: Vec<HashOutput<32>> = proof.iter().map(|h| HashOutput(*h)).collect();
+        CodeHmacsAccumulator::verify_inclusion_proof(root, &proof, hmac, index, total_pages)
+    }
+
     /// Loads HMACs from a cache file, validating the magic, `vapp_hash` and `vanadium_app_id`.
     ///
     /// Returns an error if the file can't be read, has wrong format, or has mismatched identifiers.
@@ -143,3 +203,116 @@ impl CodeHmacs {
         Ok(())
     }
 }
+
+/// The compact, root-only counterpart to [`CodeHmacs`]: a cache file that keeps the same
+/// `vapp_hash`/`vanadium_app_id` header but stores only the Merkle root over the per-page
+/// HMACs, instead of the full flat vector. Individual pages are authenticated on demand with
+/// [`CodeHmacs::verify_page`] and an inclusion proof, without ever materializing the rest of the
+/// tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MerkleCodeHmacs {
+    root: [u8; 32],
+}
+
+impl MerkleCodeHmacs {
+    /// Wraps an existing Merkle root, e.g. one produced by [`CodeHmacs::merkle_root`].
+    pub fn new(root: [u8; 32]) -> Self {
+        Self { root }
+    }
+
+    /// The Merkle root over the per-page HMACs.
+    pub fn root(&self) -> &[u8; 32] {
+        &self.root
+    }
+
+    /// Loads a Merkle-root cache file, validating the magic, `vapp_hash` and `vanadium_app_id`.
+    pub fn load(
+        path: &Path,
+        expected_vapp_hash: &[u8; 32],
+        expected_app_id: &[u8; 32],
+    ) -> Result<Self, CodeHmacsLoadError> {
+        let data = std::fs::read(path).map_err(CodeHmacsLoadError::Io)?;
+
+        // Header: 10 (magic) + 32 (vapp_hash) + 32 (app_id) + 32 (root) = 106 bytes
+        if data.len() < 106 {
+            return Err(CodeHmacsLoadError::FileTooShort);
+        }
+
+        if &data[0..10] != MERKLE_HMAC_FILE_MAGIC.as_slice() {
+            return Err(CodeHmacsLoadError::InvalidMagic);
+        }
+
+        if &data[10..42] != expected_vapp_hash {
+            return Err(CodeHmacsLoadError::VappHashMismatch);
+        }
+
+        if &data[42..74] != expected_app_id {
+            return Err(CodeHmacsLoadError::AppIdMismatch);
+        }
+
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&data[74..106]);
+        Ok(Self { root })
+    }
+
+    /// Saves the Merkle root to a cache file with format:
+    ///
+    /// `"VAPP_MRKL\0"` (10 bytes) || `vapp_hash` (32 bytes) || `vanadium_app_id` (32 bytes) || root (32 bytes).
+    pub fn save(
+        &self,
+        path: &Path,
+        vapp_hash: &[u8; 32],
+        vanadium_app_id: &[u8; 32],
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(MERKLE_HMAC_FILE_MAGIC)?;
+        file.write_all(vapp_hash)?;
+        file.write_all(vanadium_app_id)?;
+        file.write_all(&self.root)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_hmacs(n: usize) -> CodeHmacs {
+        let hmacs = (0..n)
+            .map(|i| {
+                let mut hmac = [0u8; 32];
+                hmac[0] = i as u8;
+                hmac
+            })
+            .collect();
+        CodeHmacs::new(hmacs)
+    }
+
+    #[test]
+    fn test_merkle_root_and_prove_page_roundtrip() {
+        let hmacs = sample_hmacs(8);
+        let root = hmacs.merkle_root();
+
+        for i in 0..8 {
+            let proof = hmacs.prove_page(i).unwrap();
+            assert!(CodeHmacs::verify_page(
+                &root,
+                i,
+                8,
+                &hmacs.as_slice()[i],
+                &proof
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_page_rejects_wrong_hmac() {
+        let hmacs = sample_hmacs(8);
+        let root = hmacs.merkle_root();
+        let proof = hmacs.prove_page(3).unwrap();
+
+        let wrong_hmac = [0xffu8; 32];
+        assert!(!CodeHmacs::verify_page(&root, 3, 8, &wrong_hmac, &proof));
+    }
+}
@@ -0,0 +1,317 @@
+//! Resolves raw program counters from a V-App panic into `function @ file:line` frames, using the
+//! line-number program embedded in the ELF's `.debug_line` DWARF section.
+//!
+//! This is a minimal, dependency-free DWARF line-number program interpreter (DWARF versions 2-4,
+//! 32-bit format only) rather than pulling in a full DWARF crate — it only needs to answer "what
+//! source line is this address in", not the rest of what `.debug_info` can offer. Anything it
+//! doesn't understand (a newer DWARF version, a malformed unit) is treated as "no debug info" so
+//! callers degrade to the plain panic string instead of erroring out.
+
+use crate::elf::ElfFile;
+
+/// A single resolved stack frame.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    pub function: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} @ {}:{}", self.function, self.file, self.line)
+    }
+}
+
+struct LineRow {
+    address: u64,
+    file: String,
+    line: u32,
+    end_sequence: bool,
+}
+
+/// Resolves program counters against the DWARF line table and ELF symbol table of a single ELF
+/// file, built once via [`SymbolTable::from_elf`] and reused across a whole panic payload.
+pub struct SymbolTable {
+    // Sorted by `address`, ascending.
+    rows: Vec<LineRow>,
+    // Sorted by `address`, ascending.
+    functions: Vec<(u64, u64, String)>, // (address, size, name)
+}
+
+impl SymbolTable {
+    /// Builds a symbol table from `elf`'s `.debug_line` and symbol table sections. Returns `None`
+    /// if the ELF has no debug info, or if it's in a form this parser doesn't understand (e.g.
+    /// DWARF 5, or 64-bit DWARF) — callers should fall back to undecorated panic messages.
+    pub fn from_elf(elf: &ElfFile) -> Option<Self> {
+        let debug_line = elf.section_data(".debug_line")?;
+
+        let mut rows = parse_debug_line(debug_line)?;
+        rows.sort_by_key(|r| r.address);
+
+        let mut functions: Vec<(u64, u64, String)> = elf
+            .function_symbols()
+            .iter()
+            .map(|s| (s.address, s.size, s.name.clone()))
+            .collect();
+        functions.sort_by_key(|(addr, _, _)| *addr);
+
+        Some(Self { rows, functions })
+    }
+
+    /// Resolves `pc` to a frame, or `None` if it falls outside every line-table entry (e.g. it
+    /// points into code with no debug info, such as a precompiled dependency).
+    pub fn resolve(&self, pc: u64) -> Option<Frame> {
+        let row_idx = match self.rows.binary_search_by_key(&pc, |r| r.address) {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1,
+        };
+        let row = &self.rows[row_idx];
+        if row.end_sequence {
+            return None;
+        }
+
+        let function = self
+            .functions
+            .iter()
+            .rev()
+            .find(|(addr, size, _)| *addr <= pc && pc < *addr + *size)
+            .map(|(_, _, name)| name.clone())
+            .unwrap_or_else(|| "??".to_string());
+
+        Some(Frame {
+            function,
+            file: row.file.clone(),
+            line: row.line,
+        })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        let b = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.data.get(self.pos..self.pos + 2)?;
+        self.pos += 2;
+        Some(u16::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let bytes = self.data.get(self.pos..self.pos + 8)?;
+        self.pos += 8;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn uleb128(&mut self) -> Option<u64> {
+        let mut result: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+        }
+    }
+
+    fn sleb128(&mut self) -> Option<i64> {
+        let mut result: i64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            result |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && (byte & 0x40) != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Some(result);
+            }
+        }
+    }
+
+    fn cstr(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self.u8()? != 0 {}
+        String::from_utf8(self.data[start..self.pos - 1].to_vec()).ok()
+    }
+
+}
+
+/// Parses every compilation unit in a `.debug_line` section, concatenating their rows. Only
+/// DWARF versions 2-4 in the 32-bit format are understood; a unit in any other form aborts the
+/// whole parse (`None`) rather than risk silently misattributing frames.
+fn parse_debug_line(data: &[u8]) -> Option<Vec<LineRow>> {
+    let mut reader = Reader::new(data);
+    let mut rows = Vec::new();
+
+    while reader.remaining() > 4 {
+        let unit_start = reader.pos;
+        let unit_length = reader.u32()? as usize;
+        let unit_end = reader.pos + unit_length;
+
+        let version = reader.u16()?;
+        if !(2..=4).contains(&version) {
+            return None;
+        }
+
+        let header_length = reader.u32()? as usize;
+        let program_start = reader.pos + header_length;
+
+        let minimum_instruction_length = reader.u8()?;
+        let maximum_operations_per_instruction = if version >= 4 { reader.u8()? } else { 1 };
+        let _ = maximum_operations_per_instruction;
+        let default_is_stmt = reader.u8()? != 0;
+        let line_base = reader.u8()? as i8;
+        let line_range = reader.u8()?;
+        let opcode_base = reader.u8()?;
+
+        let mut standard_opcode_lengths = Vec::with_capacity(opcode_base as usize - 1);
+        for _ in 0..opcode_base.saturating_sub(1) {
+            standard_opcode_lengths.push(reader.u8()?);
+        }
+
+        // Include directories: a sequence of non-empty strings, terminated by an empty one.
+        loop {
+            let dir = reader.cstr()?;
+            if dir.is_empty() {
+                break;
+            }
+        }
+
+        // File names: (name, dir_index, mtime, length) tuples, terminated by an empty name.
+        let mut file_names = vec!["<unknown>".to_string()]; // index 0 unused pre-DWARF5, keep 1-based
+        loop {
+            let name = reader.cstr()?;
+            if name.is_empty() {
+                break;
+            }
+            reader.uleb128()?; // directory index
+            reader.uleb128()?; // mtime
+            reader.uleb128()?; // file length
+            file_names.push(name);
+        }
+
+        reader.pos = program_start;
+
+        let mut address: u64 = 0;
+        let mut file: usize = 1;
+        let mut line: u32 = 1;
+        let mut is_stmt = default_is_stmt;
+        let _ = is_stmt;
+
+        while reader.pos < unit_end {
+            let opcode = reader.u8()?;
+
+            if opcode == 0 {
+                // Extended opcode.
+                let len = reader.uleb128()? as usize;
+                let next_pos = reader.pos + len;
+                let sub_opcode = reader.u8()?;
+                match sub_opcode {
+                    1 => {
+                        // DW_LNE_end_sequence
+                        rows.push(LineRow {
+                            address,
+                            file: file_names.get(file).cloned().unwrap_or_default(),
+                            line,
+                            end_sequence: true,
+                        });
+                        address = 0;
+                        file = 1;
+                        line = 1;
+                        is_stmt = default_is_stmt;
+                    }
+                    2 => {
+                        // DW_LNE_set_address
+                        address = if next_pos - reader.pos >= 8 {
+                            reader.u64()?
+                        } else {
+                            reader.u32()? as u64
+                        };
+                    }
+                    _ => {} // DW_LNE_define_file and vendor extensions: skip operand bytes below.
+                }
+                reader.pos = next_pos;
+            } else if opcode < opcode_base {
+                match opcode {
+                    1 => {
+                        // DW_LNS_copy
+                        rows.push(LineRow {
+                            address,
+                            file: file_names.get(file).cloned().unwrap_or_default(),
+                            line,
+                            end_sequence: false,
+                        });
+                    }
+                    2 => address += reader.uleb128()? * minimum_instruction_length as u64,
+                    3 => line = (line as i64 + reader.sleb128()?) as u32,
+                    4 => file = reader.uleb128()? as usize,
+                    5 => {
+                        reader.uleb128()?;
+                    }
+                    6 => is_stmt = !is_stmt,
+                    7 => {}
+                    8 => {
+                        let adjusted = 255 - opcode_base;
+                        address += (adjusted / line_range) as u64 * minimum_instruction_length as u64;
+                    }
+                    9 => address += reader.u16()? as u64,
+                    10 | 11 => {}
+                    12 => {
+                        reader.uleb128()?;
+                    }
+                    _ => {
+                        // Unknown standard opcode for this unit's opcode_base: skip its operands.
+                        let n = *standard_opcode_lengths.get(opcode as usize - 1)? as usize;
+                        for _ in 0..n {
+                            reader.uleb128()?;
+                        }
+                    }
+                }
+            } else {
+                // Special opcode.
+                let adjusted = opcode - opcode_base;
+                address += (adjusted / line_range) as u64 * minimum_instruction_length as u64;
+                line = (line as i64 + line_base as i64 + (adjusted % line_range) as i64) as u32;
+                rows.push(LineRow {
+                    address,
+                    file: file_names.get(file).cloned().unwrap_or_default(),
+                    line,
+                    end_sequence: false,
+                });
+            }
+        }
+
+        // Resync exactly on the unit boundary in case the program read past or stopped short of it.
+        reader.pos = unit_start + 4 + unit_length;
+    }
+
+    Some(rows)
+}
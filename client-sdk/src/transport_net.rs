@@ -0,0 +1,167 @@
+//! A [`Transport`] that proxies APDU exchanges to a Vanadium device attached to a remote host.
+//!
+//! The local side speaks this module's protocol over a plain TCP connection; the remote side is
+//! expected to run a thin daemon (out of scope for this crate) that decodes each request, forwards
+//! it to whatever local transport is actually wired to the device, and relays the response back.
+//! This lets a developer run a V-App ELF against a device attached to a different machine — useful
+//! for CI fleets and shared signing devices — without touching any V-App or engine code, since
+//! [`TransportNet`] is just another `Arc<dyn Transport<Error = E>>`.
+//!
+//! Requests and responses are framed as `[4-byte big-endian length][payload]`. The payload is the
+//! [`APDUCommand`], serialized the same way every other structured message in this crate is
+//! (`postcard`), on the way out, and `[2-byte big-endian status word][data]` on the way back.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use crate::apdu::{APDUCommand, StatusWord};
+use crate::transport::Transport;
+
+/// Delay before the first reconnect attempt; doubles on each consecutive failure up to
+/// [`MAX_RECONNECT_DELAY`].
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Reconnect attempts before giving up and surfacing [`TransportNetError::Io`].
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+#[derive(Debug)]
+pub enum TransportNetError {
+    Io(std::io::Error),
+    ConnectionLost,
+    InvalidStatusWord(u16),
+}
+
+impl std::fmt::Display for TransportNetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportNetError::Io(e) => write!(f, "I/O error: {}", e),
+            TransportNetError::ConnectionLost => {
+                write!(f, "connection to the remote daemon was lost")
+            }
+            TransportNetError::InvalidStatusWord(sw) => {
+                write!(f, "invalid status word: {:#06x}", sw)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransportNetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransportNetError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TransportNetError {
+    fn from(e: std::io::Error) -> Self {
+        TransportNetError::Io(e)
+    }
+}
+
+/// A [`Transport`] that forwards APDU exchanges over TCP to a remote daemon proxying a physically
+/// attached device. Connects with exponential backoff (up to [`MAX_RECONNECT_ATTEMPTS`]), and if
+/// the connection drops mid-exchange, retries that exchange once on a fresh connection. If
+/// connecting fails every attempt, or the retried exchange fails too, the error surfaces as an
+/// ordinary `Transport::Error` rather than a panic.
+pub struct TransportNet {
+    addr: SocketAddr,
+    stream: Mutex<Option<TcpStream>>,
+}
+
+impl TransportNet {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            stream: Mutex::new(None),
+        }
+    }
+
+    async fn connect_with_backoff(&self) -> Result<TcpStream, TransportNetError> {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        for attempt in 0..MAX_RECONNECT_ATTEMPTS {
+            match TcpStream::connect(self.addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    if attempt + 1 == MAX_RECONNECT_ATTEMPTS {
+                        return Err(TransportNetError::Io(e));
+                    }
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+        unreachable!("loop always returns before exhausting MAX_RECONNECT_ATTEMPTS")
+    }
+
+    async fn send_frame(stream: &mut TcpStream, payload: &[u8]) -> Result<(), TransportNetError> {
+        stream
+            .write_all(&(payload.len() as u32).to_be_bytes())
+            .await?;
+        stream.write_all(payload).await?;
+        stream.flush().await?;
+        Ok(())
+    }
+
+    async fn recv_frame(stream: &mut TcpStream) -> Result<Vec<u8>, TransportNetError> {
+        let mut len_bytes = [0u8; 4];
+        stream.read_exact(&mut len_bytes).await?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await?;
+        Ok(payload)
+    }
+}
+
+#[async_trait]
+impl Transport for TransportNet {
+    type Error = TransportNetError;
+
+    async fn exchange(&self, command: &APDUCommand) -> Result<(StatusWord, Vec<u8>), Self::Error> {
+        let request =
+            postcard::to_allocvec(command).map_err(|_| TransportNetError::ConnectionLost)?;
+
+        for attempt in 0..2 {
+            let mut guard = self.stream.lock().await;
+            if guard.is_none() {
+                *guard = Some(self.connect_with_backoff().await?);
+            }
+            let stream = guard.as_mut().expect("just connected");
+
+            let outcome: Result<Vec<u8>, TransportNetError> = async {
+                Self::send_frame(stream, &request).await?;
+                Self::recv_frame(stream).await
+            }
+            .await;
+
+            match outcome {
+                Ok(response) => {
+                    if response.len() < 2 {
+                        return Err(TransportNetError::ConnectionLost);
+                    }
+                    let (sw_bytes, data) = response.split_at(2);
+                    let sw = u16::from_be_bytes([sw_bytes[0], sw_bytes[1]]);
+                    let status = StatusWord::try_from(sw)
+                        .map_err(|_| TransportNetError::InvalidStatusWord(sw))?;
+                    return Ok((status, data.to_vec()));
+                }
+                Err(e) => {
+                    // The connection dropped mid-exchange: drop it and retry once against a
+                    // fresh connection rather than surfacing a transient blip as a hard error.
+                    *guard = None;
+                    if attempt == 1 {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        unreachable!("loop always returns within two attempts")
+    }
+}
@@ -0,0 +1,128 @@
+//! A [`Transport`] that drives a Vanadium device over a raw UART, for embedded targets that
+//! expose only a serial port rather than an HID stack or a TCP-reachable host.
+//!
+//! Requests and responses are framed with COBS (see [`crate::cobs`]) rather than a length prefix,
+//! since a raw serial link has no out-of-band way to signal "this many bytes follow" the way a
+//! stream socket's read does. The payload convention is otherwise the same as [`crate::transport_net`]:
+//! the request is a `postcard`-serialized [`APDUCommand`], and the response is
+//! `[2-byte big-endian status word][data]`.
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use tokio_serial::SerialStream;
+
+use crate::apdu::{APDUCommand, StatusWord};
+use crate::cobs;
+use crate::transport::Transport;
+
+#[derive(Debug)]
+pub enum TransportSerialError {
+    Io(std::io::Error),
+    Cobs(cobs::CobsError),
+    Serialization,
+    InvalidResponse,
+    InvalidStatusWord(u16),
+}
+
+impl std::fmt::Display for TransportSerialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportSerialError::Io(e) => write!(f, "I/O error: {}", e),
+            TransportSerialError::Cobs(e) => write!(f, "framing error: {}", e),
+            TransportSerialError::Serialization => write!(f, "APDU serialization failed"),
+            TransportSerialError::InvalidResponse => {
+                write!(f, "response frame shorter than the status word")
+            }
+            TransportSerialError::InvalidStatusWord(sw) => {
+                write!(f, "invalid status word: {:#06x}", sw)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransportSerialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransportSerialError::Io(e) => Some(e),
+            TransportSerialError::Cobs(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TransportSerialError {
+    fn from(e: std::io::Error) -> Self {
+        TransportSerialError::Io(e)
+    }
+}
+
+impl From<cobs::CobsError> for TransportSerialError {
+    fn from(e: cobs::CobsError) -> Self {
+        TransportSerialError::Cobs(e)
+    }
+}
+
+/// A [`Transport`] that speaks the Vanadium APDU protocol over a serial port, delimiting frames
+/// with COBS instead of relying on a framing primitive the link doesn't have.
+pub struct TransportSerial {
+    port: Mutex<SerialStream>,
+}
+
+impl TransportSerial {
+    /// Opens the serial device at `path` (e.g. `/dev/ttyUSB0`) at `baud_rate`.
+    pub fn new(path: &str, baud_rate: u32) -> Result<Self, TransportSerialError> {
+        let port = tokio_serial::new(path, baud_rate)
+            .open_native_async()
+            .map_err(|e| TransportSerialError::Io(e.into()))?;
+        Ok(Self {
+            port: Mutex::new(port),
+        })
+    }
+
+    async fn read_frame(port: &mut SerialStream) -> Result<Vec<u8>, TransportSerialError> {
+        let mut frame = Vec::new();
+        loop {
+            let byte = port.read_u8().await?;
+            if byte == 0 {
+                return Ok(cobs::decode(&frame)?);
+            }
+            frame.push(byte);
+        }
+    }
+
+    async fn do_exchange(
+        &self,
+        command: &APDUCommand,
+    ) -> Result<(StatusWord, Vec<u8>), TransportSerialError> {
+        let request =
+            postcard::to_allocvec(command).map_err(|_| TransportSerialError::Serialization)?;
+        let encoded = cobs::encode(&request);
+
+        let mut port = self.port.lock().await;
+        port.write_all(&encoded).await?;
+        port.flush().await?;
+
+        let response = Self::read_frame(&mut port).await?;
+        if response.len() < 2 {
+            return Err(TransportSerialError::InvalidResponse);
+        }
+        let (sw_bytes, data) = response.split_at(2);
+        let sw = u16::from_be_bytes([sw_bytes[0], sw_bytes[1]]);
+        let status =
+            StatusWord::try_from(sw).map_err(|_| TransportSerialError::InvalidStatusWord(sw))?;
+        Ok((status, data.to_vec()))
+    }
+}
+
+// Boxes `TransportSerialError` into the common `Box<dyn Error + Send + Sync>` used as the
+// associated `Error` type of the other concrete transports (`TransportHID`, `TransportTcp`), so
+// client code can hold a `Vec`/`Arc` of mixed transports without a generic parameter per kind.
+#[async_trait]
+impl Transport for TransportSerial {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn exchange(&self, command: &APDUCommand) -> Result<(StatusWord, Vec<u8>), Self::Error> {
+        self.do_exchange(command).await.map_err(|e| e.into())
+    }
+}
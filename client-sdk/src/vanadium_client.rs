@@ -1,11 +1,13 @@
 use async_trait::async_trait;
 use common::vm::MemoryError;
 use std::cmp::min;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{ChildStdin, ChildStdout};
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, Mutex, Notify};
 use tokio::task::JoinHandle;
 
 use common::accumulator::{
@@ -17,17 +19,20 @@ use common::client_commands::{
     GetPageMessage, GetPageProofContinuedMessage, GetPageProofContinuedResponse,
     GetPageProofMessage, GetPageProofResponse, Message, MessageDeserializationError,
     ReceiveBufferMessage, ReceiveBufferResponse, SectionKind, SendBufferMessage,
-    SendPanicBufferMessage,
+    SendPanicBufferMessage, YieldMessage,
 };
+use common::compress;
 use common::constants::{page_start, DEFAULT_STACK_START, PAGE_SIZE};
 use common::manifest::Manifest;
 use sha2::{Digest, Sha256};
 
 use crate::apdu::{
-    apdu_continue, apdu_continue_with_p1, apdu_register_vapp, apdu_run_vapp, APDUCommand,
-    StatusWord,
+    apdu_continue, apdu_continue_with_p1, apdu_continue_with_p2, apdu_register_vapp,
+    apdu_run_vapp, APDUCommand, StatusWord,
 };
+use crate::dap::{CheckedSegment, DebugAdapter, SegmentBounds};
 use crate::elf::{self, ElfFile};
+use crate::symbolize::{Frame, SymbolTable};
 use crate::transport::Transport;
 
 pub struct Sha256Hasher {
@@ -153,7 +158,7 @@ impl MemorySegment {
         }
     }
 
-    fn get_page(
+    pub(crate) fn get_page(
         &self,
         page_index: u32,
     ) -> Result<(Vec<u8>, Vec<HashOutput<32>>), MemorySegmentError> {
@@ -183,11 +188,24 @@ impl MemorySegment {
     pub fn get_content_root(&self) -> &HashOutput<32> {
         self.content.root()
     }
+
+    /// Number of pages in this segment, for code that needs to walk every page (e.g. panic
+    /// backtrace symbolization scanning the stack for return addresses).
+    pub(crate) fn page_count(&self) -> usize {
+        self.content.size()
+    }
 }
 
+#[derive(Clone)]
 enum VAppMessage {
     SendBuffer(Vec<u8>),
-    SendPanicBuffer(String),
+    SendPanicBuffer {
+        message: String,
+        /// Symbolized return addresses recovered from the stack, if debug info was available and
+        /// a plaintext stack page held any; empty otherwise (see [`crate::symbolize`] and
+        /// `VAppEngine::recover_stack_frames`).
+        frames: Vec<Frame>,
+    },
     VAppExited { status: i32 },
 }
 
@@ -274,12 +292,30 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> From<Box<dyn std::error::Error
 
 struct VAppEngine<E: std::fmt::Debug + Send + Sync + 'static> {
     manifest: Manifest,
-    code_seg: MemorySegment,
-    data_seg: MemorySegment,
-    stack_seg: MemorySegment,
+    code_seg: Arc<Mutex<MemorySegment>>,
+    data_seg: Arc<Mutex<MemorySegment>>,
+    stack_seg: Arc<Mutex<MemorySegment>>,
     transport: Arc<dyn Transport<Error = E>>,
     engine_to_client_sender: mpsc::Sender<VAppMessage>,
     client_to_engine_receiver: mpsc::Receiver<ClientMessage>,
+    /// DAP debugger attached to this run, if any (see [`crate::dap`]). `process_get_page` checks
+    /// it for an armed breakpoint before handing a requested code page back to the device.
+    debugger: Option<Arc<DebugAdapter>>,
+    /// Router for `SendBuffer` output addressed to another V-App instead of the external client,
+    /// if one is attached. `process_send_buffer` consults it before forwarding to
+    /// `engine_to_client_sender`.
+    router: Option<Arc<VAppRouter>>,
+    /// Reply from a `SendBuffer` that `router` routed to another V-App, waiting to be delivered
+    /// as the next `ReceiveBuffer` instead of one coming from the external client.
+    pending_reply: Option<Vec<u8>>,
+    /// DWARF line-table symbols for this V-App's ELF, if built with debug info (see
+    /// [`crate::symbolize`]). `process_send_panic_buffer` uses this to resolve return addresses
+    /// recovered from the stack into `function @ file:line` frames; `None` degrades to a plain
+    /// panic string, same as a release build with no debug info.
+    symbols: Option<Arc<SymbolTable>>,
+    /// Set by [`GenericVanadiumClient::request_cancel`]; `process_yield` reads it on every
+    /// `ECALL_YIELD` heartbeat and tells the V-App to cancel as soon as one arrives.
+    cancel_requested: Arc<AtomicBool>,
 }
 
 impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
@@ -340,10 +376,21 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
         };
 
         // Get the serialized page content and its proof
-        let (mut serialized_page, proof) = segment.get_page(page_index)?;
+        let (mut serialized_page, proof) = segment.lock().await.get_page(page_index)?;
 
         assert!(serialized_page.len() == 1 + 12 + PAGE_SIZE);
 
+        // If a debugger is attached and this code page covers an armed breakpoint, withhold the
+        // page response (and so stall the device's execution loop, which is blocked waiting on
+        // it) until the debugger sends `continue`.
+        if matches!(section_kind, SectionKind::Code) {
+            if let Some(debugger) = &self.debugger {
+                debugger
+                    .pause_if_breakpoint(CheckedSegment::Code, page_index)
+                    .await;
+            }
+        }
+
         // split the first 13 bytes from the actual page data:
         let (header, data) = serialized_page.split_at_mut(13);
 
@@ -457,8 +504,8 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
             SectionKind::Code => {
                 return Err(VAppEngineError::AccessViolation);
             }
-            SectionKind::Data => &mut self.data_seg,
-            SectionKind::Stack => &mut self.stack_seg,
+            SectionKind::Data => &self.data_seg,
+            SectionKind::Stack => &self.stack_seg,
         };
 
         // get the next message, which contains the content of the page
@@ -486,7 +533,10 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
         serialized_page.extend_from_slice(&data);
 
         // Store page and get proof
-        let (proof, new_root) = segment.store_page(msg.page_index, &serialized_page)?;
+        let (proof, new_root) = segment
+            .lock()
+            .await
+            .store_page(msg.page_index, &serialized_page)?;
 
         // Convert HashOutput<32> to [u8; 32]
         let proof: Vec<[u8; 32]> = proof.into_iter().map(|h| h.into()).collect();
@@ -573,6 +623,25 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
         Ok((status, result))
     }
 
+    // Decompresses one xsend/xrecv chunk if the V-App's manifest advertises support for it (see
+    // `Manifest::supports_compression`), passing it through unchanged otherwise. `bound` is the
+    // total byte count not yet delivered as of before this chunk, a safe upper bound for its
+    // decompressed size since it's known from the surrounding transfer's own bookkeeping.
+    fn decompress_xfer_chunk(
+        &self,
+        data: &[u8],
+        bound: u32,
+    ) -> Result<Vec<u8>, VAppEngineError<E>> {
+        if !self.manifest.supports_compression() {
+            return Ok(data.to_vec());
+        }
+        let mut out = vec![0u8; bound as usize];
+        let written =
+            compress::decompress_into(data, &mut out).map_err(VAppEngineError::ResponseError)?;
+        out.truncate(written);
+        Ok(out)
+    }
+
     // receive a buffer sent by the V-App via xsend; send it to the VappEngine
     async fn process_send_buffer(
         &mut self,
@@ -581,9 +650,11 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
         let SendBufferMessage {
             command_code: _,
             total_remaining_size: mut remaining_len,
-            data: mut buf,
+            data: raw,
         } = SendBufferMessage::deserialize(command)?;
 
+        let mut buf = self.decompress_xfer_chunk(&raw, remaining_len)?;
+
         if (buf.len() as u32) > remaining_len {
             return Err(VAppEngineError::ResponseError(
                 "Received data length exceeds expected remaining length",
@@ -612,8 +683,22 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
                 ));
             }
 
-            buf.extend_from_slice(&msg.data);
-            remaining_len -= msg.data.len() as u32;
+            let chunk = self.decompress_xfer_chunk(&msg.data, remaining_len)?;
+            buf.extend_from_slice(&chunk);
+            remaining_len -= chunk.len() as u32;
+        }
+
+        // If a router is attached and this buffer is addressed to another registered V-App,
+        // forward it there and stash the reply instead of surfacing it to the external client.
+        if let Some(router) = &self.router {
+            if let Some(result) = router.route(&buf).await {
+                self.pending_reply = Some(result.map_err(|e| {
+                    VAppEngineError::GenericError(Box::new(RoutedAppError(e.to_string())))
+                })?);
+                return self
+                    .exchange_and_process_page_requests(&apdu_continue(vec![]))
+                    .await;
+            }
         }
 
         // Send the buffer back to the client via engine_to_client_sender
@@ -633,26 +718,42 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
     ) -> Result<(StatusWord, Vec<u8>), VAppEngineError<E>> {
         ReceiveBufferMessage::deserialize(command)?;
 
-        // Wait for the message from the client
-        let ClientMessage::ReceiveBuffer(bytes) = self
-            .client_to_engine_receiver
-            .recv()
-            .await
-            .ok_or(VAppEngineError::ResponseError(
-                "Failed to receive buffer from client",
-            ))?;
+        // If the previous SendBuffer was routed to another V-App, its reply is already in hand;
+        // otherwise wait for the message from the external client.
+        let bytes = if let Some(reply) = self.pending_reply.take() {
+            reply
+        } else {
+            let ClientMessage::ReceiveBuffer(bytes) = self
+                .client_to_engine_receiver
+                .recv()
+                .await
+                .ok_or(VAppEngineError::ResponseError(
+                    "Failed to receive buffer from client",
+                ))?;
+            bytes
+        };
 
         let mut remaining_len = bytes.len() as u32;
         let mut offset: usize = 0;
+        let compressing = self.manifest.supports_compression();
 
         loop {
             // TODO: check if correct when the buffer is long
-            let chunk_len = min(remaining_len, 255 - 4);
-            let data = ReceiveBufferResponse::new(
-                remaining_len,
-                bytes[offset..offset + chunk_len as usize].to_vec(),
-            )
-            .serialize();
+            // Leave a byte of headroom when compressing: a chunk that doesn't shrink falls back
+            // to a verbatim encoding with a 1-byte tag, which must still fit in 251 bytes.
+            let max_chunk = if compressing { 255 - 4 - 1 } else { 255 - 4 };
+            let chunk_len = min(remaining_len, max_chunk);
+            let raw_chunk = &bytes[offset..offset + chunk_len as usize];
+
+            let payload = if compressing {
+                let mut compressed = Vec::new();
+                compress::compress(raw_chunk, &mut compressed);
+                compressed
+            } else {
+                raw_chunk.to_vec()
+            };
+
+            let data = ReceiveBufferResponse::new(remaining_len, payload).serialize();
 
             let (status, result) = self
                 .exchange_and_process_page_requests(&apdu_continue(data))
@@ -722,10 +823,14 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
 
         let panic_message =
             String::from_utf8(buf).map_err(|e| VAppEngineError::GenericError(Box::new(e)))?;
+        let frames = self.recover_stack_frames().await;
 
         // Send the panic message back to the client via engine_to_client_sender
         self.engine_to_client_sender
-            .send(VAppMessage::SendPanicBuffer(panic_message))
+            .send(VAppMessage::SendPanicBuffer {
+                message: panic_message,
+                frames,
+            })
             .await
             .map_err(|e| VAppEngineError::GenericError(Box::new(e)))?;
 
@@ -734,6 +839,62 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
             .await
     }
 
+    // Heartbeat from a long-running ECALL_YIELD loop; log the V-App's progress string and tell
+    // it whether the host wants it to cancel, via the Continue APDU's P2 byte.
+    async fn process_yield(
+        &mut self,
+        command: &[u8],
+    ) -> Result<(StatusWord, Vec<u8>), VAppEngineError<E>> {
+        let YieldMessage {
+            command_code: _,
+            progress,
+        } = YieldMessage::deserialize(command)?;
+
+        if let Ok(progress) = core::str::from_utf8(&progress) {
+            println!("V-App progress: {}", progress);
+        }
+
+        let cancel = self.cancel_requested.load(Ordering::Relaxed);
+        self.exchange_and_process_page_requests(&apdu_continue_with_p2(vec![], cancel as u8))
+            .await
+    }
+
+    /// Best-effort scan of the stack pages held in plaintext, looking for values that land inside
+    /// the code segment — i.e. plausible return addresses — and resolving each one through
+    /// `self.symbols`. An encrypted page is skipped, since reading its plaintext would need the
+    /// session key this host-side engine never sees. Every stack page the device commits is
+    /// encrypted (see `process_commit_page`), and a page it has not committed still holds the
+    /// zeros it started with, so the scan finds no return addresses. Returns an empty `Vec` if
+    /// there's no symbol table (no debug info).
+    async fn recover_stack_frames(&self) -> Vec<Frame> {
+        let Some(symbols) = &self.symbols else {
+            return Vec::new();
+        };
+
+        let stack = self.stack_seg.lock().await;
+        let mut frames = Vec::new();
+        for page_index in 0..stack.page_count() as u32 {
+            let Ok((page, _)) = stack.get_page(page_index) else {
+                continue;
+            };
+            if page.first() != Some(&0) {
+                continue; // encrypted page
+            }
+            for word in page[13..].chunks_exact(4) {
+                let candidate = u32::from_le_bytes(word.try_into().unwrap()) as u64;
+                if candidate < self.manifest.code_start as u64
+                    || candidate >= self.manifest.code_end as u64
+                {
+                    continue;
+                }
+                if let Some(frame) = symbols.resolve(candidate) {
+                    frames.push(frame);
+                }
+            }
+        }
+        frames
+    }
+
     async fn busy_loop(
         &mut self,
         first_sw: StatusWord,
@@ -785,6 +946,7 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VAppEngine<E> {
                 ClientCommandCode::SendPanicBuffer => {
                     self.process_send_panic_buffer(&result).await?
                 }
+                ClientCommandCode::Yield => self.process_yield(&result).await?,
                 ClientCommandCode::CommitPageContent
                 | ClientCommandCode::GetPageProof
                 | ClientCommandCode::GetPageProofContinued
@@ -801,11 +963,22 @@ struct GenericVanadiumClient<E: std::fmt::Debug + Send + Sync + 'static> {
     client_to_engine_sender: Option<mpsc::Sender<ClientMessage>>,
     engine_to_client_receiver: Option<Mutex<mpsc::Receiver<VAppMessage>>>,
     vapp_engine_handle: Option<JoinHandle<Result<(), VAppEngineError<E>>>>,
+    /// Address to serve a DAP debugger on for the next `run_vapp` call, set via
+    /// [`GenericVanadiumClient::enable_debugger`]. `None` (the default) runs the V-App
+    /// undebugged, with no overhead beyond the `Option` check on each `GetPage`.
+    debug_listen_addr: Option<std::net::SocketAddr>,
+    /// Router to attach to the next `run_vapp` call, set via
+    /// [`GenericVanadiumClient::attach_router`]. `None` (the default) delivers all `SendBuffer`
+    /// output to the external client, as today.
+    router: Option<Arc<VAppRouter>>,
+    /// Shared with the running `VAppEngine`; flipped by [`GenericVanadiumClient::request_cancel`]
+    /// and read back on every `ECALL_YIELD` heartbeat (see `VAppEngine::process_yield`).
+    cancel_requested: Arc<AtomicBool>,
 }
 
 #[derive(Debug)]
 enum VanadiumClientError {
-    VAppPanicked(String),
+    VAppPanicked { message: String, frames: Vec<Frame> },
     VAppExited(i32),
     GenericError(String),
 }
@@ -819,7 +992,13 @@ impl From<&str> for VanadiumClientError {
 impl std::fmt::Display for VanadiumClientError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            VanadiumClientError::VAppPanicked(msg) => write!(f, "VApp panicked: {}", msg),
+            VanadiumClientError::VAppPanicked { message, frames } => {
+                write!(f, "VApp panicked: {}", message)?;
+                for frame in frames {
+                    write!(f, "\n  at {}", frame)?;
+                }
+                Ok(())
+            }
             VanadiumClientError::VAppExited(code) => write!(f, "VApp exited with code: {}", code),
             VanadiumClientError::GenericError(msg) => write!(f, "Generic error: {}", msg),
         }
@@ -838,9 +1017,32 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> GenericVanadiumClient<E> {
             client_to_engine_sender: None,
             engine_to_client_receiver: None,
             vapp_engine_handle: None,
+            debug_listen_addr: None,
+            router: None,
+            cancel_requested: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Serves a DAP debugger for the V-App started by the next [`Self::run_vapp`] call, letting
+    /// an editor attach and set breakpoints on `addr`. See [`crate::dap`].
+    pub fn enable_debugger(&mut self, addr: std::net::SocketAddr) {
+        self.debug_listen_addr = Some(addr);
+    }
+
+    /// Asks the running V-App to cancel at its next `ECALL_YIELD` heartbeat. Has no effect if the
+    /// V-App never yields (e.g. it's not running a long computation, or doesn't call
+    /// `ECALL_YIELD` at all); the cancellation is cooperative, not forced.
+    pub fn request_cancel(&self) {
+        self.cancel_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Attaches `router` to the V-App started by the next [`Self::run_vapp`] call, so its
+    /// `SendBuffer` output addressed to another registered V-App is routed there instead of
+    /// being delivered to the external client. See [`VAppRouter`].
+    pub fn attach_router(&mut self, router: Arc<VAppRouter>) {
+        self.router = Some(router);
+    }
+
     pub async fn register_vapp(
         &self,
         transport: Arc<dyn Transport<Error = E>>,
@@ -878,12 +1080,45 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> GenericVanadiumClient<E> {
         data.extend_from_slice(app_hmac);
 
         // Create the memory segments for the code, data, and stack sections
-        let code_seg = MemorySegment::new(elf.code_segment.start, &elf.code_segment.data);
-        let data_seg = MemorySegment::new(elf.data_segment.start, &elf.data_segment.data);
-        let stack_seg = MemorySegment::new(
+        let code_seg = Arc::new(Mutex::new(MemorySegment::new(
+            elf.code_segment.start,
+            &elf.code_segment.data,
+        )));
+        let data_seg = Arc::new(Mutex::new(MemorySegment::new(
+            elf.data_segment.start,
+            &elf.data_segment.data,
+        )));
+        let stack_seg = Arc::new(Mutex::new(MemorySegment::new(
             manifest.stack_start,
             &vec![0; (manifest.stack_end - manifest.stack_start) as usize],
-        );
+        )));
+
+        let debugger = self.debug_listen_addr.take().map(|addr| {
+            let bounds = SegmentBounds {
+                code_start: manifest.code_start,
+                code_end: manifest.code_end,
+                data_start: manifest.data_start,
+                data_end: manifest.data_end,
+                stack_start: manifest.stack_start,
+                stack_end: manifest.stack_end,
+            };
+            let debugger = DebugAdapter::new(bounds, code_seg.clone(), data_seg.clone(), stack_seg.clone());
+            let debugger_for_server = debugger.clone();
+            tokio::spawn(async move {
+                if let Err(e) = debugger_for_server.serve(addr).await {
+                    println!("DAP server error: {:?}", e);
+                }
+            });
+            debugger
+        });
+
+        // Parsing DWARF debug info only makes sense for a locally built V-App with a Cargo.toml
+        // to compile debug symbols from in the first place; a packaged, debug-info-stripped ELF
+        // simply yields no symbol table, and panics fall back to the plain message.
+        #[cfg(feature = "cargo_toml")]
+        let symbols = SymbolTable::from_elf(elf).map(Arc::new);
+        #[cfg(not(feature = "cargo_toml"))]
+        let symbols = None;
 
         let (client_to_engine_sender, client_to_engine_receiver) =
             mpsc::channel::<ClientMessage>(10);
@@ -897,6 +1132,11 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> GenericVanadiumClient<E> {
             transport,
             engine_to_client_sender,
             client_to_engine_receiver,
+            debugger,
+            router: self.router.take(),
+            pending_reply: None,
+            symbols,
+            cancel_requested: self.cancel_requested.clone(),
         };
 
         // Start the VAppEngine in a task
@@ -932,8 +1172,8 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> GenericVanadiumClient<E> {
                 let mut receiver = engine_to_client_receiver.lock().await;
                 match receiver.recv().await {
                     Some(VAppMessage::SendBuffer(buf)) => Ok(buf),
-                    Some(VAppMessage::SendPanicBuffer(panic_msg)) => {
-                        Err(VanadiumClientError::VAppPanicked(panic_msg))
+                    Some(VAppMessage::SendPanicBuffer { message, frames }) => {
+                        Err(VanadiumClientError::VAppPanicked { message, frames })
                     }
                     Some(VAppMessage::VAppExited { status }) => {
                         Err(VanadiumClientError::VAppExited(status))
@@ -944,6 +1184,206 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> GenericVanadiumClient<E> {
             None => Err("VAppEngine not running".into()),
         }
     }
+
+    /// Hands this client's running engine off to `manager` under `app_hmac`, so it keeps running
+    /// after `self` (or the process that owns it) goes away. After this call, `send_message` on
+    /// `self` no longer works; reconnect to the engine via [`VAppSessionManager::attach`].
+    pub fn detach_to(
+        &mut self,
+        manager: &VAppSessionManager<E>,
+        app_hmac: [u8; 32],
+    ) -> Result<(), &'static str> {
+        let client_to_engine_sender = self
+            .client_to_engine_sender
+            .take()
+            .ok_or("VAppEngine not running")?;
+        let engine_to_client_receiver = self
+            .engine_to_client_receiver
+            .take()
+            .ok_or("VAppEngine not running")?
+            .into_inner();
+        let vapp_engine_handle = self
+            .vapp_engine_handle
+            .take()
+            .ok_or("VAppEngine not running")?;
+
+        manager.register(
+            app_hmac,
+            client_to_engine_sender,
+            vapp_engine_handle,
+            engine_to_client_receiver,
+        );
+        Ok(())
+    }
+}
+
+/// Number of buffered [`VAppMessage`]s a detached [`VAppSessionManager`] session keeps before
+/// dropping the oldest one. Bounds memory use for a V-App left detached indefinitely; a
+/// reattaching client only needs recent output, not a full replay since registration.
+const SESSION_BUFFER_CAPACITY: usize = 64;
+
+struct SessionInner {
+    pending: VecDeque<VAppMessage>,
+    attached: bool,
+}
+
+struct VAppSession<E: std::fmt::Debug + Send + Sync + 'static> {
+    client_to_engine_sender: mpsc::Sender<ClientMessage>,
+    vapp_engine_handle: JoinHandle<Result<(), VAppEngineError<E>>>,
+    inner: Arc<std::sync::Mutex<SessionInner>>,
+    notify: Arc<Notify>,
+}
+
+/// Keeps V-App engine tasks alive across client disconnects, keyed by the `app_hmac` issued at
+/// registration. Borrows the pty-reconnect model: a long-lived host keeps the device I/O alive
+/// (the `VAppEngine` task and its channels) while front-end clients come and go. A session is
+/// added with [`GenericVanadiumClient::detach_to`] (or [`VanadiumAppClient::detach_to`]); from
+/// then on, [`Self::detach`] marks the session as having no attached client without touching the
+/// engine (a client already handed out keeps working until it is dropped), and
+/// [`Self::attach`] rebinds a new one, replaying whatever output was buffered in the meantime.
+pub struct VAppSessionManager<E: std::fmt::Debug + Send + Sync + 'static> {
+    sessions: std::sync::Mutex<HashMap<[u8; 32], VAppSession<E>>>,
+}
+
+impl<E: std::fmt::Debug + Send + Sync + 'static> VAppSessionManager<E> {
+    pub fn new() -> Self {
+        Self {
+            sessions: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a running engine under `app_hmac`, and starts a background task pumping its
+    /// output into a ring buffer of capacity [`SESSION_BUFFER_CAPACITY`] so it keeps accumulating
+    /// while no client is attached. Called by [`GenericVanadiumClient::detach_to`].
+    fn register(
+        &self,
+        app_hmac: [u8; 32],
+        client_to_engine_sender: mpsc::Sender<ClientMessage>,
+        vapp_engine_handle: JoinHandle<Result<(), VAppEngineError<E>>>,
+        mut engine_to_client_receiver: mpsc::Receiver<VAppMessage>,
+    ) {
+        let inner = Arc::new(std::sync::Mutex::new(SessionInner {
+            pending: VecDeque::new(),
+            attached: false,
+        }));
+        let notify = Arc::new(Notify::new());
+
+        let pump_inner = inner.clone();
+        let pump_notify = notify.clone();
+        tokio::spawn(async move {
+            while let Some(message) = engine_to_client_receiver.recv().await {
+                {
+                    let mut inner = pump_inner.lock().unwrap();
+                    if inner.pending.len() >= SESSION_BUFFER_CAPACITY {
+                        inner.pending.pop_front();
+                    }
+                    inner.pending.push_back(message);
+                }
+                pump_notify.notify_waiters();
+            }
+        });
+
+        self.sessions.lock().unwrap().insert(
+            app_hmac,
+            VAppSession {
+                client_to_engine_sender,
+                vapp_engine_handle,
+                inner,
+                notify,
+            },
+        );
+    }
+
+    /// Marks `app_hmac`'s session as having no attached client. The engine keeps running and its
+    /// output keeps accumulating in the ring buffer for a future [`Self::attach`].
+    pub fn detach(&self, app_hmac: &[u8; 32]) {
+        if let Some(session) = self.sessions.lock().unwrap().get(app_hmac) {
+            session.inner.lock().unwrap().attached = false;
+        }
+    }
+
+    /// Rebinds a new client to the running engine registered under `app_hmac`, if any. The
+    /// returned [`AttachedVAppClient`] first replays whatever output was buffered since the last
+    /// attach before waiting on new messages. Returns `None` if there is no such session, or if a
+    /// client is already attached to it.
+    pub fn attach(&self, app_hmac: &[u8; 32]) -> Option<AttachedVAppClient<E>> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(app_hmac)?;
+
+        let mut inner = session.inner.lock().unwrap();
+        if inner.attached {
+            return None;
+        }
+        inner.attached = true;
+        drop(inner);
+
+        Some(AttachedVAppClient {
+            client_to_engine_sender: session.client_to_engine_sender.clone(),
+            inner: session.inner.clone(),
+            notify: session.notify.clone(),
+        })
+    }
+
+    /// Removes `app_hmac`'s session entirely, returning the engine task's `JoinHandle` so the
+    /// caller can await its exit status. Use once the V-App has exited and the session is no
+    /// longer needed.
+    pub fn remove(
+        &self,
+        app_hmac: &[u8; 32],
+    ) -> Option<JoinHandle<Result<(), VAppEngineError<E>>>> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(app_hmac)
+            .map(|session| session.vapp_engine_handle)
+    }
+}
+
+/// A client bound to a running V-App engine via [`VAppSessionManager::attach`]. Unlike
+/// [`GenericVanadiumClient`], it does not own the engine task: dropping it only severs this
+/// client's view of the session (equivalent to calling [`VAppSessionManager::detach`]), leaving
+/// the engine running and buffering output for a future `attach`.
+pub struct AttachedVAppClient<E: std::fmt::Debug + Send + Sync + 'static> {
+    client_to_engine_sender: mpsc::Sender<ClientMessage>,
+    inner: Arc<std::sync::Mutex<SessionInner>>,
+    notify: Arc<Notify>,
+}
+
+impl<E: std::fmt::Debug + Send + Sync + 'static> AttachedVAppClient<E> {
+    pub async fn send_message(&mut self, message: &[u8]) -> Result<Vec<u8>, VanadiumClientError> {
+        self.client_to_engine_sender
+            .send(ClientMessage::ReceiveBuffer(message.to_vec()))
+            .await
+            .map_err(|_| "Failed to send message to VAppEngine")?;
+
+        self.next_message().await
+    }
+
+    /// Returns the next buffered or incoming message, without sending anything first. Useful to
+    /// drain output the V-App produced (e.g. via `xsend`) while this client was detached.
+    pub async fn next_message(&mut self) -> Result<Vec<u8>, VanadiumClientError> {
+        loop {
+            let popped = { self.inner.lock().unwrap().pending.pop_front() };
+            if let Some(message) = popped {
+                return match message {
+                    VAppMessage::SendBuffer(buf) => Ok(buf),
+                    VAppMessage::SendPanicBuffer { message, frames } => {
+                        Err(VanadiumClientError::VAppPanicked { message, frames })
+                    }
+                    VAppMessage::VAppExited { status } => {
+                        Err(VanadiumClientError::VAppExited(status))
+                    }
+                };
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + Send + Sync + 'static> Drop for AttachedVAppClient<E> {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().attached = false;
+    }
 }
 
 /// Represents errors that can occur during the execution of a V-App.
@@ -994,6 +1434,59 @@ pub trait VAppClient {
     async fn send_message(&mut self, msg: &[u8]) -> Result<Vec<u8>, VAppExecutionError>;
 }
 
+/// Separator between the target app name and the payload in a routed `SendBuffer`. A V-App that
+/// wants to call another registered app prefixes its output with `"<name>\0"`.
+const ROUTING_ENVELOPE_SEPARATOR: u8 = 0;
+
+/// Routes `SendBuffer` output between cooperating V-Apps instead of always delivering it to the
+/// external client, so e.g. a signing app can call out to a key-derivation app entirely host-side.
+///
+/// A buffer is addressed to another app by prefixing it with `"<app_name>\0"` (see
+/// [`ROUTING_ENVELOPE_SEPARATOR`]); anything not matching a registered name is left alone and
+/// falls through to the client as usual.
+#[derive(Default)]
+pub struct VAppRouter {
+    apps: Mutex<HashMap<String, Box<dyn VAppClient + Send + Sync>>>,
+}
+
+impl VAppRouter {
+    pub fn new() -> Self {
+        Self {
+            apps: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers `app` under `name`, making it a valid routing target for other V-Apps.
+    pub async fn register(&self, name: impl Into<String>, app: Box<dyn VAppClient + Send + Sync>) {
+        self.apps.lock().await.insert(name.into(), app);
+    }
+
+    /// If `buffer` is addressed to a registered app (`"<name>\0<payload>"`), forwards the payload
+    /// to it and returns its reply. Returns `None` if `buffer` doesn't name a registered app, so
+    /// the caller can fall back to delivering it to the external client unchanged.
+    async fn route(&self, buffer: &[u8]) -> Option<Result<Vec<u8>, VAppExecutionError>> {
+        let sep = buffer.iter().position(|&b| b == ROUTING_ENVELOPE_SEPARATOR)?;
+        let name = std::str::from_utf8(&buffer[..sep]).ok()?;
+
+        let mut apps = self.apps.lock().await;
+        let app = apps.get_mut(name)?;
+        Some(app.send_message(&buffer[sep + 1..]).await)
+    }
+}
+
+/// Bridges a [`VAppExecutionError`] (not `Send + Sync`, since it can wrap an arbitrary boxed
+/// error) into [`VAppEngineError::GenericError`] once routing has reduced it to a message.
+#[derive(Debug)]
+struct RoutedAppError(String);
+
+impl std::fmt::Display for RoutedAppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "routed V-App call failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for RoutedAppError {}
+
 /// Implementation of a VAppClient using the Vanadium VM.
 pub struct VanadiumAppClient<E: std::fmt::Debug + Send + Sync + 'static> {
     client: GenericVanadiumClient<E>,
@@ -1123,6 +1616,7 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VanadiumAppClient<E> {
                     stack_start,
                     stack_end,
                     stack_merkle_root,
+                    0, // capabilities
                 )?
             }
         };
@@ -1138,6 +1632,23 @@ impl<E: std::fmt::Debug + Send + Sync + 'static> VanadiumAppClient<E> {
 
         Ok((Self { client }, app_hmac))
     }
+
+    /// Hands this V-App's running engine off to `manager` under `app_hmac`, so it keeps running
+    /// after this `VanadiumAppClient` is dropped (e.g. because a CLI or GUI front-end is
+    /// restarting). Reconnect to it via [`VAppSessionManager::attach`].
+    pub fn detach_to(
+        &mut self,
+        manager: &VAppSessionManager<E>,
+        app_hmac: [u8; 32],
+    ) -> Result<(), &'static str> {
+        self.client.detach_to(manager, app_hmac)
+    }
+
+    /// Asks the running V-App to cancel at its next `ECALL_YIELD` heartbeat. See
+    /// [`GenericVanadiumClient::request_cancel`].
+    pub fn request_cancel(&self) {
+        self.client.request_cancel();
+    }
 }
 
 #[async_trait]
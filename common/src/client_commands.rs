@@ -1,6 +1,8 @@
 // Vanadium VM client commands (responsed to InterruptedExecution status word), and other related types
 
-use crate::constants::PAGE_SIZE;
+use crate::constants::{AEAD_TAG_SIZE, PAGE_SIZE};
+use crate::metrics::VAppMetrics;
+use crate::tlv::{self, TlvReader, TlvValue};
 use alloc::vec::Vec;
 
 #[cfg(feature = "device_sdk")]
@@ -33,6 +35,21 @@ pub enum ClientCommandCode {
     CommitPageContent = 2,
     SendBuffer = 3,
     ReceiveBuffer = 4,
+    /// Asks the host whether it's holding a write-ahead journal entry for a segment left behind
+    /// by an interrupted [`ClientCommandCode::CommitPage`]/[`ClientCommandCode::CommitPageContent`]
+    /// exchange, so it can be replayed or discarded on VM resume.
+    QueryJournal = 5,
+    /// Tells the host a page commit finished (normally, or by resolving a replayed journal entry),
+    /// so it can drop the corresponding journal entry.
+    CommitPageDone = 6,
+    /// Streams a snapshot of the V-App's metrics to the host mid-run, so a host tool can collect
+    /// per-run telemetry without waiting for the V-App to exit (see
+    /// [`ReportMetricsMessage`]).
+    ReportMetrics = 7,
+    /// Periodic heartbeat sent while a long-running `ECALL_YIELD` loop is in progress, so the
+    /// host can refresh a progress indicator and the V-App can cooperatively poll for a
+    /// user-requested cancellation (see [`YieldMessage`]).
+    Yield = 8,
 }
 
 impl TryFrom<u8> for ClientCommandCode {
@@ -45,6 +62,10 @@ impl TryFrom<u8> for ClientCommandCode {
             2 => Ok(ClientCommandCode::CommitPageContent),
             3 => Ok(ClientCommandCode::SendBuffer),
             4 => Ok(ClientCommandCode::ReceiveBuffer),
+            5 => Ok(ClientCommandCode::QueryJournal),
+            6 => Ok(ClientCommandCode::CommitPageDone),
+            7 => Ok(ClientCommandCode::ReportMetrics),
+            8 => Ok(ClientCommandCode::Yield),
             _ => Err("Invalid value for ClientCommandCode"),
         }
     }
@@ -78,15 +99,29 @@ pub struct CommitPageMessage {
     pub command_code: ClientCommandCode,
     pub section_kind: SectionKind,
     pub page_index: u32,
+    /// Version the page is being bumped to by this commit; journaled ahead of the page content
+    /// itself so a host crash between this message and [`CommitPageContentMessage`] can be
+    /// detected and replayed/discarded via `ClientCommandCode::QueryJournal`.
+    pub new_version: u32,
+    /// Merkle root the segment's authentication tree will have once this commit lands, likewise
+    /// journaled ahead of time for the same reason.
+    pub new_root: [u8; 32],
 }
 
 impl CommitPageMessage {
     #[inline]
-    pub fn new(section_kind: SectionKind, page_index: u32) -> Self {
+    pub fn new(
+        section_kind: SectionKind,
+        page_index: u32,
+        new_version: u32,
+        new_root: [u8; 32],
+    ) -> Self {
         CommitPageMessage {
             command_code: ClientCommandCode::CommitPage,
             section_kind,
             page_index,
+            new_version,
+            new_root,
         }
     }
 }
@@ -97,10 +132,12 @@ impl Message for CommitPageMessage {
         f(&[self.command_code as u8]);
         f(&[self.section_kind as u8]);
         f(&self.page_index.to_be_bytes());
+        f(&self.new_version.to_be_bytes());
+        f(&self.new_root);
     }
 
     fn deserialize(data: &[u8]) -> Result<Self, &'static str> {
-        if data.len() != 6 {
+        if data.len() != 1 + 1 + 4 + 4 + 32 {
             return Err("Invalid data for CommitPageMessage");
         }
         let command_code = ClientCommandCode::try_from(data[0])?;
@@ -109,12 +146,80 @@ impl Message for CommitPageMessage {
         }
 
         let section_kind = SectionKind::try_from(data[1])?;
-        let page_index = u32::from_be_bytes([data[2], data[3], data[4], data[5]]);
+        let page_index = u32::from_be_bytes(data[2..6].try_into().expect("4 bytes"));
+        let new_version = u32::from_be_bytes(data[6..10].try_into().expect("4 bytes"));
+        let mut new_root = [0u8; 32];
+        new_root.copy_from_slice(&data[10..42]);
 
         Ok(CommitPageMessage {
             command_code,
             section_kind,
             page_index,
+            new_version,
+            new_root,
+        })
+    }
+}
+
+/// TLV tag numbers for [`CommitPageMessage`]'s fields, shared between `serialize_tlv` and
+/// `deserialize_tlv`.
+impl CommitPageMessage {
+    const TAG_SECTION_KIND: u8 = 1;
+    const TAG_PAGE_INDEX: u8 = 2;
+    const TAG_NEW_VERSION: u8 = 3;
+    const TAG_NEW_ROOT: u8 = 4;
+
+    /// Forward-compatible encoding of this message: the mandatory leading `ClientCommandCode`
+    /// byte, followed by its fields as TLV elements (see [`crate::tlv`]). Prefer this over
+    /// [`Message::serialize`] for peers that also understand TLV framing, since it lets either
+    /// side add fields later without a breaking change.
+    pub fn serialize_tlv(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.command_code as u8);
+        tlv::write_u8(Self::TAG_SECTION_KIND, self.section_kind as u8, &mut out);
+        tlv::write_u32(Self::TAG_PAGE_INDEX, self.page_index, &mut out);
+        tlv::write_u32(Self::TAG_NEW_VERSION, self.new_version, &mut out);
+        tlv::write_bytes(Self::TAG_NEW_ROOT, &self.new_root, &mut out);
+        out
+    }
+
+    /// Decodes the TLV encoding produced by [`Self::serialize_tlv`]. Unrecognized tags (e.g. ones
+    /// added by a newer VM) are skipped rather than rejected.
+    pub fn deserialize_tlv(data: &[u8]) -> Result<Self, &'static str> {
+        let Some((&code_byte, rest)) = data.split_first() else {
+            return Err("Invalid data for CommitPageMessage");
+        };
+        let command_code = ClientCommandCode::try_from(code_byte)?;
+        if !matches!(command_code, ClientCommandCode::CommitPage) {
+            return Err("Invalid data for CommitPageMessage");
+        }
+
+        let mut section_kind = None;
+        let mut page_index = None;
+        let mut new_version = None;
+        let mut new_root = None;
+        for element in TlvReader::new(rest) {
+            match element? {
+                (Self::TAG_SECTION_KIND, TlvValue::Uint8(v)) => {
+                    section_kind = Some(SectionKind::try_from(v)?)
+                }
+                (Self::TAG_PAGE_INDEX, TlvValue::Uint32(v)) => page_index = Some(v),
+                (Self::TAG_NEW_VERSION, TlvValue::Uint32(v)) => new_version = Some(v),
+                (Self::TAG_NEW_ROOT, TlvValue::Bytes(v)) if v.len() == 32 => {
+                    let mut root = [0u8; 32];
+                    root.copy_from_slice(v);
+                    new_root = Some(root);
+                }
+                _ => {} // unknown tag (or unexpected wire type for a known one): ignore
+            }
+        }
+
+        Ok(CommitPageMessage {
+            command_code,
+            section_kind: section_kind.ok_or("Missing section_kind in CommitPageMessage")?,
+            page_index: page_index.ok_or("Missing page_index in CommitPageMessage")?,
+            new_version: new_version.ok_or("Missing new_version in CommitPageMessage")?,
+            new_root: new_root.ok_or("Missing new_root in CommitPageMessage")?,
         })
     }
 }
@@ -122,18 +227,27 @@ impl Message for CommitPageMessage {
 #[derive(Debug, Clone)]
 pub struct CommitPageContentMessage {
     pub command_code: ClientCommandCode,
+    /// AEAD ciphertext of the page content (see `AEAD_TAG_SIZE`-byte `tag` below). The VM never
+    /// sends plaintext page content to the host; see `vm::handlers::lib::outsourced_mem`.
     pub data: Vec<u8>,
+    /// AEAD authentication tag produced alongside `data`.
+    pub tag: [u8; AEAD_TAG_SIZE],
+    /// The page's new version, repeated here (it was already journaled by the preceding
+    /// [`CommitPageMessage`]) so this message is self-describing on its own.
+    pub new_version: u32,
 }
 
 impl CommitPageContentMessage {
     #[inline]
-    pub fn new(data: Vec<u8>) -> Self {
+    pub fn new(data: Vec<u8>, tag: [u8; AEAD_TAG_SIZE], new_version: u32) -> Self {
         if data.len() != PAGE_SIZE {
             panic!("Invalid data length for CommitPageContentMessage");
         }
         CommitPageContentMessage {
             command_code: ClientCommandCode::CommitPageContent,
             data,
+            tag,
+            new_version,
         }
     }
 }
@@ -143,10 +257,12 @@ impl Message for CommitPageContentMessage {
     fn serialize_with<F: FnMut(&[u8])>(&self, mut f: F) {
         f(&[self.command_code as u8]);
         f(&self.data);
+        f(&self.tag);
+        f(&self.new_version.to_be_bytes());
     }
 
     fn deserialize(data: &[u8]) -> Result<Self, &'static str> {
-        if data.len() != PAGE_SIZE + 1 {
+        if data.len() != 1 + PAGE_SIZE + AEAD_TAG_SIZE + 4 {
             return Err("Invalid data for CommitPageContentMessage");
         }
 
@@ -154,9 +270,18 @@ impl Message for CommitPageContentMessage {
         if !matches!(command_code, ClientCommandCode::CommitPageContent) {
             return Err("Invalid data for CommitPageContentMessage");
         }
+
+        let (page_data, rest) = data[1..].split_at(PAGE_SIZE);
+        let (tag_bytes, version_bytes) = rest.split_at(AEAD_TAG_SIZE);
+
+        let mut tag = [0u8; AEAD_TAG_SIZE];
+        tag.copy_from_slice(tag_bytes);
+
         Ok(CommitPageContentMessage {
             command_code,
-            data: data[1..].to_vec(),
+            data: page_data.to_vec(),
+            tag,
+            new_version: u32::from_be_bytes(version_bytes.try_into().expect("4 bytes")),
         })
     }
 }
@@ -206,6 +331,52 @@ impl Message for GetPageMessage {
     }
 }
 
+/// TLV tag numbers for [`GetPageMessage`]'s fields; see [`CommitPageMessage`]'s TLV methods for
+/// the rationale.
+impl GetPageMessage {
+    const TAG_SECTION_KIND: u8 = 1;
+    const TAG_PAGE_INDEX: u8 = 2;
+
+    /// Forward-compatible encoding of this message; see [`CommitPageMessage::serialize_tlv`].
+    pub fn serialize_tlv(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.command_code as u8);
+        tlv::write_u8(Self::TAG_SECTION_KIND, self.section_kind as u8, &mut out);
+        tlv::write_u32(Self::TAG_PAGE_INDEX, self.page_index, &mut out);
+        out
+    }
+
+    /// Decodes the TLV encoding produced by [`Self::serialize_tlv`]; see
+    /// [`CommitPageMessage::deserialize_tlv`].
+    pub fn deserialize_tlv(data: &[u8]) -> Result<Self, &'static str> {
+        let Some((&code_byte, rest)) = data.split_first() else {
+            return Err("Invalid data for GetPageMessage");
+        };
+        let command_code = ClientCommandCode::try_from(code_byte)?;
+        if !matches!(command_code, ClientCommandCode::GetPage) {
+            return Err("Invalid data for GetPageMessage");
+        }
+
+        let mut section_kind = None;
+        let mut page_index = None;
+        for element in TlvReader::new(rest) {
+            match element? {
+                (Self::TAG_SECTION_KIND, TlvValue::Uint8(v)) => {
+                    section_kind = Some(SectionKind::try_from(v)?)
+                }
+                (Self::TAG_PAGE_INDEX, TlvValue::Uint32(v)) => page_index = Some(v),
+                _ => {}
+            }
+        }
+
+        Ok(GetPageMessage {
+            command_code,
+            section_kind: section_kind.ok_or("Missing section_kind in GetPageMessage")?,
+            page_index: page_index.ok_or("Missing page_index in GetPageMessage")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SendBufferMessage {
     pub command_code: ClientCommandCode,
@@ -256,6 +427,63 @@ impl Message for SendBufferMessage {
     }
 }
 
+/// TLV tag numbers for [`SendBufferMessage`]'s fields; see [`CommitPageMessage`]'s TLV methods
+/// for the rationale.
+impl SendBufferMessage {
+    const TAG_TOTAL_REMAINING_SIZE: u8 = 1;
+    const TAG_DATA: u8 = 2;
+
+    /// Forward-compatible encoding of this message; see [`CommitPageMessage::serialize_tlv`].
+    pub fn serialize_tlv(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.push(self.command_code as u8);
+        tlv::write_u32(
+            Self::TAG_TOTAL_REMAINING_SIZE,
+            self.total_remaining_size,
+            &mut out,
+        );
+        tlv::write_bytes(Self::TAG_DATA, &self.data, &mut out);
+        out
+    }
+
+    /// Decodes the TLV encoding produced by [`Self::serialize_tlv`]; see
+    /// [`CommitPageMessage::deserialize_tlv`].
+    pub fn deserialize_tlv(data: &[u8]) -> Result<Self, &'static str> {
+        let Some((&code_byte, rest)) = data.split_first() else {
+            return Err("Invalid data for SendBufferMessage");
+        };
+        let command_code = ClientCommandCode::try_from(code_byte)?;
+        if !matches!(command_code, ClientCommandCode::SendBuffer) {
+            return Err("Invalid data for SendBufferMessage");
+        }
+
+        let mut total_remaining_size = None;
+        let mut buffer = None;
+        for element in TlvReader::new(rest) {
+            match element? {
+                (Self::TAG_TOTAL_REMAINING_SIZE, TlvValue::Uint32(v)) => {
+                    total_remaining_size = Some(v)
+                }
+                (Self::TAG_DATA, TlvValue::Bytes(v)) => buffer = Some(v.to_vec()),
+                _ => {}
+            }
+        }
+
+        let total_remaining_size =
+            total_remaining_size.ok_or("Missing total_remaining_size in SendBufferMessage")?;
+        let data = buffer.ok_or("Missing data in SendBufferMessage")?;
+        if data.len() > total_remaining_size as usize {
+            return Err("Data size exceeds total remaining size");
+        }
+
+        Ok(SendBufferMessage {
+            command_code,
+            total_remaining_size,
+            data,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ReceiveBufferMessage {
     pub command_code: ClientCommandCode,
@@ -326,3 +554,102 @@ impl Message for ReceiveBufferResponse {
         })
     }
 }
+
+/// Streams a [`VAppMetrics`] snapshot to the host mid-run (see
+/// [`ClientCommandCode::ReportMetrics`]). Uses the same field layout as
+/// `vm::handlers::get_metrics`'s `GetMetrics` response, so both paths can be decoded the same way
+/// on the host.
+#[derive(Debug, Clone)]
+pub struct ReportMetricsMessage {
+    pub command_code: ClientCommandCode,
+    pub metrics: VAppMetrics,
+}
+
+impl ReportMetricsMessage {
+    #[inline]
+    pub fn new(metrics: VAppMetrics) -> Self {
+        ReportMetricsMessage {
+            command_code: ClientCommandCode::ReportMetrics,
+            metrics,
+        }
+    }
+}
+
+impl Message for ReportMetricsMessage {
+    #[inline]
+    fn serialize_with<F: FnMut(&[u8])>(&self, mut f: F) {
+        f(&[self.command_code as u8]);
+        f(&self.metrics.vapp_name);
+        f(&self.metrics.vapp_hash);
+        f(&self.metrics.instruction_count.to_be_bytes());
+        f(&self.metrics.page_loads.to_be_bytes());
+        f(&self.metrics.page_commits.to_be_bytes());
+        f(&self.metrics.cache_hits.to_be_bytes());
+        f(&self.metrics.cache_misses.to_be_bytes());
+    }
+
+    fn deserialize(data: &[u8]) -> Result<Self, &'static str> {
+        if data.len() != 1 + 32 + 32 + 8 + 4 + 4 + 4 + 4 {
+            return Err("Invalid data for ReportMetricsMessage");
+        }
+        let command_code = ClientCommandCode::try_from(data[0])?;
+        if !matches!(command_code, ClientCommandCode::ReportMetrics) {
+            return Err("Invalid data for ReportMetricsMessage");
+        }
+
+        let mut metrics = VAppMetrics::new();
+        metrics.vapp_name.copy_from_slice(&data[1..33]);
+        metrics.vapp_hash.copy_from_slice(&data[33..65]);
+        metrics.instruction_count = u64::from_be_bytes(data[65..73].try_into().expect("8 bytes"));
+        metrics.page_loads = u32::from_be_bytes(data[73..77].try_into().expect("4 bytes"));
+        metrics.page_commits = u32::from_be_bytes(data[77..81].try_into().expect("4 bytes"));
+        metrics.cache_hits = u32::from_be_bytes(data[81..85].try_into().expect("4 bytes"));
+        metrics.cache_misses = u32::from_be_bytes(data[85..89].try_into().expect("4 bytes"));
+
+        Ok(ReportMetricsMessage {
+            command_code,
+            metrics,
+        })
+    }
+}
+
+/// Periodic heartbeat sent from the VM to the host during a long-running `ECALL_YIELD` loop,
+/// carrying a UTF-8 progress string the host can display while the V-App is busy. There's no
+/// separate response type: the host signals a user-requested cancellation back through the
+/// following `Continue` APDU's `P2` byte (nonzero means "cancel"), since no other data needs to
+/// travel in that direction.
+#[derive(Debug, Clone)]
+pub struct YieldMessage {
+    pub command_code: ClientCommandCode,
+    pub progress: Vec<u8>,
+}
+
+impl YieldMessage {
+    #[inline]
+    pub fn new(progress: Vec<u8>) -> Self {
+        YieldMessage {
+            command_code: ClientCommandCode::Yield,
+            progress,
+        }
+    }
+}
+
+impl Message for YieldMessage {
+    #[inline]
+    fn serialize_with<F: FnMut(&[u8])>(&self, mut f: F) {
+        f(&[self.command_code as u8]);
+        f(&self.progress);
+    }
+
+    fn deserialize(data: &[u8]) -> Result<Self, &'static str> {
+        let command_code = ClientCommandCode::try_from(data[0])?;
+        if !matches!(command_code, ClientCommandCode::Yield) {
+            return Err("Invalid data for YieldMessage");
+        }
+
+        Ok(YieldMessage {
+            command_code,
+            progress: data[1..].to_vec(),
+        })
+    }
+}
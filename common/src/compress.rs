@@ -0,0 +1,228 @@
+//! A small LZ77-style byte compressor for the host buffer-transfer protocol (see
+//! `Manifest::CAP_COMPRESSION`). Used to shrink the chunks carried by
+//! [`crate::client_commands::SendBufferMessage`]/[`crate::client_commands::ReceiveBufferResponse`]
+//! so a large `ECALL_XSEND`/`ECALL_XRECV` transfer costs fewer `InterruptedExecution`/`Continue`
+//! round-trips.
+//!
+//! The encoder emits a stream of tokens, each either a literal run or a back-reference into a
+//! sliding window of previously-emitted bytes:
+//!
+//! ```text
+//! control byte 0x00-0x7F: literal run of (control + 1) bytes follows verbatim
+//! control byte 0x80-0xFF: back-reference, length = (control & 0x7F) + MIN_MATCH,
+//!                         distance = next 2 bytes, big-endian
+//! ```
+//!
+//! [`decompress_into`] writes into a caller-provided buffer rather than growing a `Vec` to a size
+//! read off the wire, so a malformed or hostile stream can't be used to force unbounded
+//! allocation during decode.
+
+use alloc::vec::Vec;
+use core::cmp::min;
+
+/// Matches shorter than this aren't worth a back-reference: a token costs a control byte plus a
+/// 2-byte distance, so a match shorter than 3 bytes would cost more than the literal bytes it
+/// replaces.
+const MIN_MATCH: usize = 3;
+/// Longest match a single token can encode (a control byte's low 7 bits, plus `MIN_MATCH`).
+const MAX_MATCH: usize = MIN_MATCH + 0x7F;
+/// Longest literal run a single control byte can introduce.
+const MAX_LITERAL_RUN: usize = 0x80;
+/// How far back a back-reference can point.
+const WINDOW_SIZE: usize = 4096;
+
+const TAG_VERBATIM: u8 = 0;
+const TAG_COMPRESSED: u8 = 1;
+
+/// Compresses `input`, appending the result (including its leading format tag) to `out`. Falls
+/// back to storing `input` verbatim, tagged so [`decompress_into`] knows not to touch it, whenever
+/// the LZ encoding would not actually be smaller.
+pub fn compress(input: &[u8], out: &mut Vec<u8>) {
+    let start = out.len();
+    out.push(TAG_COMPRESSED);
+    encode(input, out);
+
+    if out.len() - start >= input.len() + 1 {
+        out.truncate(start);
+        out.push(TAG_VERBATIM);
+        out.extend_from_slice(input);
+    }
+}
+
+fn encode(input: &[u8], out: &mut Vec<u8>) {
+    let mut pos = 0;
+    let mut literal_start = 0;
+    while pos < input.len() {
+        match find_match(input, pos) {
+            Some((distance, length)) => {
+                flush_literals(input, literal_start, pos, out);
+                out.push(0x80 | (length - MIN_MATCH) as u8);
+                out.extend_from_slice(&(distance as u16).to_be_bytes());
+                pos += length;
+                literal_start = pos;
+            }
+            None => pos += 1,
+        }
+    }
+    flush_literals(input, literal_start, pos, out);
+}
+
+fn flush_literals(input: &[u8], start: usize, end: usize, out: &mut Vec<u8>) {
+    let mut i = start;
+    while i < end {
+        let run = min(end - i, MAX_LITERAL_RUN);
+        out.push((run - 1) as u8);
+        out.extend_from_slice(&input[i..i + run]);
+        i += run;
+    }
+}
+
+/// Finds the longest match for the bytes starting at `pos` among the preceding `WINDOW_SIZE`
+/// bytes, returning `(distance, length)` if one at least `MIN_MATCH` bytes long exists.
+fn find_match(input: &[u8], pos: usize) -> Option<(usize, usize)> {
+    if pos + MIN_MATCH > input.len() {
+        return None;
+    }
+
+    let window_start = pos.saturating_sub(WINDOW_SIZE);
+    let max_len = min(MAX_MATCH, input.len() - pos);
+    let mut best: Option<(usize, usize)> = None;
+    for cand in window_start..pos {
+        let mut len = 0;
+        while len < max_len && input[cand + len] == input[pos + len] {
+            len += 1;
+        }
+        let is_better = match best {
+            Some((_, best_len)) => len > best_len,
+            None => true,
+        };
+        if len >= MIN_MATCH && is_better {
+            best = Some((pos - cand, len));
+        }
+    }
+    best
+}
+
+/// Decompresses a buffer produced by [`compress`] into `out`, returning the number of bytes
+/// written. `out`'s length bounds the allocation: if the stream would produce more bytes than
+/// `out` can hold, this returns an error instead of growing any buffer to fit.
+pub fn decompress_into(data: &[u8], out: &mut [u8]) -> Result<usize, &'static str> {
+    let (&tag, rest) = data.split_first().ok_or("Empty compressed buffer")?;
+    match tag {
+        TAG_VERBATIM => {
+            if rest.len() > out.len() {
+                return Err("Decompressed data does not fit in output buffer");
+            }
+            out[..rest.len()].copy_from_slice(rest);
+            Ok(rest.len())
+        }
+        TAG_COMPRESSED => decode(rest, out),
+        _ => Err("Unknown compression tag"),
+    }
+}
+
+fn decode(mut data: &[u8], out: &mut [u8]) -> Result<usize, &'static str> {
+    let mut written = 0;
+    while let Some((&control, rest)) = data.split_first() {
+        data = rest;
+        if control & 0x80 == 0 {
+            let run = control as usize + 1;
+            let Some(literal) = data.get(..run) else {
+                return Err("Truncated literal run");
+            };
+            if written + run > out.len() {
+                return Err("Decompressed data does not fit in output buffer");
+            }
+            out[written..written + run].copy_from_slice(literal);
+            written += run;
+            data = &data[run..];
+        } else {
+            let Some(distance_bytes) = data.get(..2) else {
+                return Err("Truncated back-reference");
+            };
+            let length = (control & 0x7F) as usize + MIN_MATCH;
+            let distance = u16::from_be_bytes(distance_bytes.try_into().expect("2 bytes")) as usize;
+            data = &data[2..];
+
+            if distance == 0 || distance > written {
+                return Err("Back-reference points outside decoded output");
+            }
+            if written + length > out.len() {
+                return Err("Decompressed data does not fit in output buffer");
+            }
+
+            let src_start = written - distance;
+            for i in 0..length {
+                out[written + i] = out[src_start + i];
+            }
+            written += length;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn roundtrip(input: &[u8]) {
+        let mut compressed = Vec::new();
+        compress(input, &mut compressed);
+
+        let mut out = vec![0u8; input.len()];
+        let written = decompress_into(&compressed, &mut out).unwrap();
+        assert_eq!(written, input.len());
+        assert_eq!(&out[..written], input);
+    }
+
+    #[test]
+    fn test_roundtrip_empty() {
+        roundtrip(&[]);
+    }
+
+    #[test]
+    fn test_roundtrip_literal_only() {
+        roundtrip(b"the quick brown fox");
+    }
+
+    #[test]
+    fn test_roundtrip_repetitive() {
+        roundtrip(&[0x42; 300]);
+    }
+
+    #[test]
+    fn test_roundtrip_mixed() {
+        let mut input = Vec::new();
+        input.extend_from_slice(b"abcdefgh");
+        input.extend_from_slice(b"abcdefgh");
+        input.extend_from_slice(b"ijklmnop");
+        input.extend_from_slice(b"abcdefgh");
+        roundtrip(&input);
+    }
+
+    #[test]
+    fn test_incompressible_falls_back_to_verbatim() {
+        let input: Vec<u8> = (0..=255u8).collect();
+        let mut compressed = Vec::new();
+        compress(&input, &mut compressed);
+        assert_eq!(compressed[0], TAG_VERBATIM);
+        assert_eq!(&compressed[1..], &input[..]);
+    }
+
+    #[test]
+    fn test_decompress_into_undersized_buffer_errors() {
+        let mut compressed = Vec::new();
+        compress(&[0x42; 50], &mut compressed);
+        let mut out = [0u8; 10];
+        assert!(decompress_into(&compressed, &mut out).is_err());
+    }
+
+    #[test]
+    fn test_decompress_rejects_out_of_range_back_reference() {
+        // A back-reference token whose distance exceeds what's been decoded so far.
+        let malformed = [TAG_COMPRESSED, 0x80, 0x00, 0x05];
+        let mut out = [0u8; 16];
+        assert!(decompress_into(&malformed, &mut out).is_err());
+    }
+}
@@ -2,6 +2,10 @@
 /// of this value.
 pub const PAGE_SIZE: usize = 256;
 
+/// Size, in bytes, of the AEAD authentication tag attached to an encrypted, outsourced page (see
+/// `CommitPageContentMessage`).
+pub const AEAD_TAG_SIZE: usize = 16;
+
 pub const PAGE_MASK: u32 = !(PAGE_SIZE as u32 - 1);
 
 /// Calculates the start address of the page containing the given address.
@@ -0,0 +1,208 @@
+//! HID-style chunked framing for payloads that don't fit in a single APDU/HID packet, used to
+//! carry an out-of-line event payload (see [`crate::ux::IndirectPayload`]) across the host↔device
+//! boundary without the client having to invent its own chunking scheme.
+//!
+//! This follows the same framing Ledger devices already use for device signing over HID: each
+//! packet starts with a 5-byte header, `channel` (big-endian `u16`), `tag` (a fixed `u8`
+//! identifying this as an APDU/event-chunk packet rather than some other HID usage sharing the
+//! same channel) and `seq` (big-endian `u16`, the 0-based index of this packet within the
+//! message). The first packet (`seq == 0`) additionally carries a 2-byte big-endian total
+//! message length right after the header; later packets carry only payload bytes.
+
+use alloc::vec::Vec;
+
+/// Size, in bytes, of the `channel`/`tag`/`seq` header prepended to every packet.
+pub const HEADER_SIZE: usize = 5;
+
+/// The fixed tag identifying an event-chunk packet, mirroring the APDU tag used for device
+/// signing over HID.
+pub const CHUNK_TAG: u8 = 0x05;
+
+/// Splits `payload` into a sequence of `packet_size`-byte packets (the last one zero-padded),
+/// each framed with the `channel`/[`CHUNK_TAG`]/`seq` header described at the module level.
+///
+/// `packet_size` must be greater than [`HEADER_SIZE`] plus 2 (room for the first packet's length
+/// prefix), or this panics, since a packet that can't even hold the header can't carry any frame.
+pub fn encode_chunks(channel: u16, payload: &[u8], packet_size: usize) -> Vec<Vec<u8>> {
+    assert!(
+        packet_size > HEADER_SIZE + 2,
+        "packet_size must leave room for the header and the first packet's length prefix"
+    );
+
+    let mut packets = Vec::new();
+    let mut seq: u16 = 0;
+    let mut offset = 0;
+
+    loop {
+        let mut packet = Vec::with_capacity(packet_size);
+        packet.extend_from_slice(&channel.to_be_bytes());
+        packet.push(CHUNK_TAG);
+        packet.extend_from_slice(&seq.to_be_bytes());
+
+        if seq == 0 {
+            packet.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        }
+
+        let room = packet_size - packet.len();
+        let end = (offset + room).min(payload.len());
+        packet.extend_from_slice(&payload[offset..end]);
+        offset = end;
+
+        packet.resize(packet_size, 0);
+        packets.push(packet);
+
+        if offset >= payload.len() {
+            break;
+        }
+        seq += 1;
+    }
+
+    packets
+}
+
+/// Reassembles packets produced by [`encode_chunks`] back into the original payload.
+///
+/// Feed packets to [`Reassembler::feed`] in arrival order; it returns the completed payload once
+/// enough bytes have been collected.
+pub struct Reassembler {
+    channel: u16,
+    expected_seq: u16,
+    total_len: Option<usize>,
+    buffer: Vec<u8>,
+}
+
+impl Reassembler {
+    /// Creates a reassembler that only accepts packets for the given `channel`.
+    pub fn new(channel: u16) -> Self {
+        Self {
+            channel,
+            expected_seq: 0,
+            total_len: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Feeds one packet. Returns `Ok(Some(payload))` once the full message has been reassembled,
+    /// `Ok(None)` if more packets are still expected, or `Err` if `packet` is malformed:
+    /// shorter than [`HEADER_SIZE`], on the wrong channel, tagged with something other than
+    /// [`CHUNK_TAG`], or out of sequence (sequence numbers must start at 0 and increase by
+    /// exactly 1 each packet).
+    pub fn feed(&mut self, packet: &[u8]) -> Result<Option<Vec<u8>>, &'static str> {
+        if packet.len() < HEADER_SIZE {
+            return Err("HID chunk packet is shorter than its header");
+        }
+
+        let channel = u16::from_be_bytes([packet[0], packet[1]]);
+        let tag = packet[2];
+        let seq = u16::from_be_bytes([packet[3], packet[4]]);
+
+        if channel != self.channel {
+            return Err("HID chunk packet is on the wrong channel");
+        }
+        if tag != CHUNK_TAG {
+            return Err("HID chunk packet has an unexpected tag");
+        }
+        if seq != self.expected_seq {
+            return Err("HID chunk packet is out of sequence");
+        }
+
+        let mut body = &packet[HEADER_SIZE..];
+        if seq == 0 {
+            if body.len() < 2 {
+                return Err("first HID chunk packet is too short to hold a length prefix");
+            }
+            let total_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+            self.total_len = Some(total_len);
+            self.buffer.reserve(total_len);
+            body = &body[2..];
+        }
+
+        let Some(total_len) = self.total_len else {
+            return Err("HID chunk reassembler has no total length yet");
+        };
+
+        let remaining = total_len - self.buffer.len();
+        let take = body.len().min(remaining);
+        self.buffer.extend_from_slice(&body[..take]);
+
+        self.expected_seq = self.expected_seq.wrapping_add(1);
+
+        if self.buffer.len() >= total_len {
+            Ok(Some(core::mem::take(&mut self.buffer)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn reassemble(channel: u16, packets: &[Vec<u8>]) -> Result<Vec<u8>, &'static str> {
+        let mut reassembler = Reassembler::new(channel);
+        for packet in packets {
+            if let Some(payload) = reassembler.feed(packet)? {
+                return Ok(payload);
+            }
+        }
+        Err("reassembly never completed")
+    }
+
+    #[test]
+    fn roundtrips_a_multi_chunk_payload() {
+        let payload: Vec<u8> = (0..300u32).map(|i| (i % 256) as u8).collect();
+        let packets = encode_chunks(0x0101, &payload, 64);
+        assert!(packets.len() > 1, "payload should need multiple packets");
+        for packet in &packets {
+            assert_eq!(packet.len(), 64);
+        }
+
+        let reassembled = reassemble(0x0101, &packets).unwrap();
+        assert_eq!(reassembled, payload);
+    }
+
+    #[test]
+    fn roundtrips_a_single_chunk_payload() {
+        let payload = vec![1u8, 2, 3, 4, 5];
+        let packets = encode_chunks(7, &payload, 64);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(reassemble(7, &packets).unwrap(), payload);
+    }
+
+    #[test]
+    fn rejects_packet_shorter_than_header() {
+        let mut reassembler = Reassembler::new(1);
+        assert!(reassembler.feed(&[0, 1, 0x05]).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_tag() {
+        let payload = vec![1u8, 2, 3];
+        let mut packets = encode_chunks(1, &payload, 64);
+        packets[0][2] = 0x00;
+        let mut reassembler = Reassembler::new(1);
+        assert!(reassembler.feed(&packets[0]).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_channel() {
+        let payload = vec![1u8, 2, 3];
+        let packets = encode_chunks(1, &payload, 64);
+        let mut reassembler = Reassembler::new(2);
+        assert!(reassembler.feed(&packets[0]).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_order_sequence_numbers() {
+        let payload: Vec<u8> = (0..300u32).map(|i| (i % 256) as u8).collect();
+        let packets = encode_chunks(1, &payload, 64);
+        assert!(packets.len() > 2);
+
+        let mut reassembler = Reassembler::new(1);
+        assert_eq!(reassembler.feed(&packets[0]), Ok(None));
+        // Skip packet 1, feed packet 2 instead.
+        assert!(reassembler.feed(&packets[2]).is_err());
+    }
+}
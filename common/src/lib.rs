@@ -6,11 +6,15 @@ pub mod accumulator;
 #[cfg(feature = "target_vanadium_ledger")]
 pub mod client_commands;
 pub mod comm;
+pub mod compress;
 pub mod constants;
 pub mod ecall_constants;
+pub mod hid_framing;
 pub mod manifest;
 #[cfg(feature = "target_vanadium_ledger")]
 pub mod metrics;
+pub mod poseidon;
+pub mod tlv;
 pub mod ux;
 pub mod vm;
 
@@ -22,6 +26,8 @@ pub enum BufferType {
     VAppMessage = 0, // data buffer sent from the VApp to the host
     Panic = 1,       // the VApp panicked
     Print = 2,       // the VApp printed a message
+    Fault = 3, // the VApp guest faulted and the fault was not handled by a guest trap handler;
+               // the buffer body is a `vm::FaultRecord` (see `vm::FaultRecord::to_bytes`)
 }
 
 impl TryFrom<u8> for BufferType {
@@ -32,6 +38,7 @@ impl TryFrom<u8> for BufferType {
             0 => Ok(BufferType::VAppMessage),
             1 => Ok(BufferType::Panic),
             2 => Ok(BufferType::Print),
+            3 => Ok(BufferType::Fault),
             _ => Err("Invalid buffer type"),
         }
     }
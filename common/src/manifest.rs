@@ -1,15 +1,30 @@
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use serde::{self, Deserialize, Serialize};
 
 use crate::accumulator::Hasher;
 use crate::constants::{page_start, PAGE_SIZE};
 
+/// Version of the [`Manifest::to_canonical_bytes`]/[`Manifest::from_canonical_bytes`] wire format.
+///
+/// This is independent of [`Manifest::manifest_version`] (which describes the V-App manifest
+/// itself): it guards the *encoding* of the canonical byte sequence, so that a future manifest
+/// version that adds new fields can introduce a new canonical format version alongside it, while
+/// [`Manifest::from_canonical_bytes`] keeps parsing older versions exactly as before.
+pub const CANONICAL_FORMAT_VERSION: u8 = 1;
+
 /// Maximum length for app name.
 pub const APP_NAME_MAX_LEN: usize = 32;
 
 /// Maximum length for app version.
 pub const APP_VERSION_MAX_LEN: usize = 32;
 
+/// Bit in [`Manifest::capabilities`] indicating the host understands the `compress` wire format
+/// (see [`crate::compress`]) for `SendBufferMessage`/`ReceiveBufferResponse` chunks. The VM only
+/// compresses ECALL_XSEND/ECALL_XRECV traffic for a V-App whose manifest advertises this bit, so
+/// older hosts keep working over the raw, uncompressed path.
+pub const CAP_COMPRESSION: u32 = 1 << 0;
+
 /// The manifest contains all the required info that the application needs in order to execute a V-App.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Manifest {
@@ -28,6 +43,9 @@ pub struct Manifest {
     pub stack_merkle_root: [u8; 32],
     #[serde(default)]
     pub n_storage_slots: u32,
+    /// Bitmask of `CAP_*` flags the host supports; see [`CAP_COMPRESSION`].
+    #[serde(default)]
+    pub capabilities: u32,
 }
 
 impl Manifest {
@@ -47,6 +65,7 @@ impl Manifest {
         stack_end: u32,
         stack_merkle_root: [u8; 32],
         n_storage_slots: u32,
+        capabilities: u32,
     ) -> Result<Self, &'static str> {
         let manifest = Self {
             manifest_version,
@@ -63,6 +82,7 @@ impl Manifest {
             stack_end,
             stack_merkle_root,
             n_storage_slots,
+            capabilities,
         };
 
         manifest.validate()?;
@@ -120,6 +140,13 @@ impl Manifest {
         &self.vapp_version
     }
 
+    /// Whether this manifest's host advertises support for the compressed buffer-transfer wire
+    /// format (see [`CAP_COMPRESSION`]).
+    #[inline]
+    pub fn supports_compression(&self) -> bool {
+        self.capabilities & CAP_COMPRESSION != 0
+    }
+
     #[inline]
     fn n_pages(start: u32, end: u32) -> u32 {
         1 + (page_start(end - 1) - page_start(start)) / PAGE_SIZE as u32
@@ -150,6 +177,114 @@ impl Manifest {
         serde_json::from_str(s)
     }
 
+    /// Serializes this manifest to the canonical byte sequence that [`Self::get_vapp_hash`]
+    /// digests: a 1-byte [`CANONICAL_FORMAT_VERSION`] tag, followed by every field that
+    /// determines how the V-App executes, in a fixed order, with big-endian integers and
+    /// single-byte length-prefixed strings.
+    ///
+    /// This is a compact alternative to [`Self::to_json`] for transmitting manifests to the
+    /// device, and is guaranteed to round-trip through [`Self::from_canonical_bytes`].
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.push(CANONICAL_FORMAT_VERSION);
+
+        out.extend_from_slice(&self.manifest_version.to_be_bytes());
+
+        out.push(self.vapp_name.len() as u8);
+        out.extend_from_slice(self.vapp_name.as_bytes());
+
+        out.push(self.vapp_version.len() as u8);
+        out.extend_from_slice(self.vapp_version.as_bytes());
+
+        out.extend_from_slice(&self.entrypoint.to_be_bytes());
+
+        out.extend_from_slice(&self.code_start.to_be_bytes());
+        out.extend_from_slice(&self.code_end.to_be_bytes());
+        out.extend_from_slice(&self.code_merkle_root);
+
+        out.extend_from_slice(&self.data_start.to_be_bytes());
+        out.extend_from_slice(&self.data_end.to_be_bytes());
+        out.extend_from_slice(&self.data_merkle_root);
+
+        out.extend_from_slice(&self.stack_start.to_be_bytes());
+        out.extend_from_slice(&self.stack_end.to_be_bytes());
+        out.extend_from_slice(&self.stack_merkle_root);
+
+        out.extend_from_slice(&self.n_storage_slots.to_be_bytes());
+
+        out.extend_from_slice(&self.capabilities.to_be_bytes());
+
+        out
+    }
+
+    /// Parses the canonical byte sequence produced by [`Self::to_canonical_bytes`].
+    ///
+    /// Returns an error if `bytes` is truncated, carries an unrecognized
+    /// [`CANONICAL_FORMAT_VERSION`], or contains a string whose length prefix overruns the
+    /// buffer. Note that this only parses the encoding; use [`Self::validate`] to check that the
+    /// resulting manifest is well-formed.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut pos = 0usize;
+
+        let mut take = |n: usize| -> Result<&[u8], &'static str> {
+            let slice = bytes.get(pos..pos + n).ok_or("canonical manifest bytes: unexpected end")?;
+            pos += n;
+            Ok(slice)
+        };
+
+        let version = take(1)?[0];
+        if version != CANONICAL_FORMAT_VERSION {
+            return Err("canonical manifest bytes: unsupported format version");
+        }
+
+        let manifest_version = u32::from_be_bytes(take(4)?.try_into().unwrap());
+
+        let name_len = take(1)?[0] as usize;
+        let vapp_name = String::from_utf8(take(name_len)?.to_vec())
+            .map_err(|_| "canonical manifest bytes: vapp_name is not valid UTF-8")?;
+
+        let version_len = take(1)?[0] as usize;
+        let vapp_version = String::from_utf8(take(version_len)?.to_vec())
+            .map_err(|_| "canonical manifest bytes: vapp_version is not valid UTF-8")?;
+
+        let entrypoint = u32::from_be_bytes(take(4)?.try_into().unwrap());
+
+        let code_start = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let code_end = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let code_merkle_root: [u8; 32] = take(32)?.try_into().unwrap();
+
+        let data_start = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let data_end = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let data_merkle_root: [u8; 32] = take(32)?.try_into().unwrap();
+
+        let stack_start = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let stack_end = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let stack_merkle_root: [u8; 32] = take(32)?.try_into().unwrap();
+
+        let n_storage_slots = u32::from_be_bytes(take(4)?.try_into().unwrap());
+
+        let capabilities = u32::from_be_bytes(take(4)?.try_into().unwrap());
+
+        Ok(Self {
+            manifest_version,
+            vapp_name,
+            vapp_version,
+            entrypoint,
+            code_start,
+            code_end,
+            code_merkle_root,
+            data_start,
+            data_end,
+            data_merkle_root,
+            stack_start,
+            stack_end,
+            stack_merkle_root,
+            n_storage_slots,
+            capabilities,
+        })
+    }
+
     /// Computes a hash of all the fields in the manifest.
     ///
     /// All the fields in any way for the execution of the V-App must be included in the hash.
@@ -157,45 +292,93 @@ impl Manifest {
     ///
     /// This function is generic over a hasher that implements the `Hasher` trait in order to allow compiling on any
     /// target, but should only be used with a hasher for SHA-256 in order to produce the expected hashes.
+    ///
+    /// Hashes [`Self::to_canonical_bytes`], so the two can never drift apart.
     pub fn get_vapp_hash<H: Hasher<OUTPUT_SIZE>, const OUTPUT_SIZE: usize>(
         &self,
     ) -> [u8; OUTPUT_SIZE] {
         let mut hasher = H::new();
+        hasher.update(&self.to_canonical_bytes());
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
 
-        // Hash manifest_version
-        hasher.update(&self.manifest_version.to_be_bytes());
+    struct Sha256Hasher {
+        hasher: Sha256,
+    }
 
-        // Hash vapp_name (length prefixed, as it's variable length)
-        let name_len = self.vapp_name.len() as u8;
-        hasher.update(&[name_len]);
-        hasher.update(self.vapp_name.as_bytes());
+    impl Hasher<32> for Sha256Hasher {
+        fn new() -> Self {
+            Sha256Hasher {
+                hasher: Sha256::new(),
+            }
+        }
 
-        // Hash vapp_version (length prefixed, as it's variable length)
-        let version_len = self.vapp_version.len() as u8;
-        hasher.update(&[version_len]);
-        hasher.update(self.vapp_version.as_bytes());
+        fn update(&mut self, data: &[u8]) {
+            self.hasher.update(data);
+        }
 
-        // Hash entrypoint
-        hasher.update(&self.entrypoint.to_be_bytes());
+        fn finalize(self) -> [u8; 32] {
+            let result = self.hasher.finalize();
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&result);
+            hash
+        }
+    }
 
-        // Hash code section information
-        hasher.update(&self.code_start.to_be_bytes());
-        hasher.update(&self.code_end.to_be_bytes());
-        hasher.update(&self.code_merkle_root);
+    fn sample_manifest() -> Manifest {
+        Manifest::new(
+            1,
+            "test app",
+            "1.0",
+            0x1000,
+            0x1000,
+            0x2000,
+            [1u8; 32],
+            0x2000,
+            0x3000,
+            [2u8; 32],
+            0x3000,
+            0x4000,
+            [3u8; 32],
+            4,
+            CAP_COMPRESSION,
+        )
+        .unwrap()
+    }
 
-        // Hash data section information
-        hasher.update(&self.data_start.to_be_bytes());
-        hasher.update(&self.data_end.to_be_bytes());
-        hasher.update(&self.data_merkle_root);
+    #[test]
+    fn canonical_bytes_round_trip() {
+        let manifest = sample_manifest();
+        let bytes = manifest.to_canonical_bytes();
+        let decoded = Manifest::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_canonical_bytes(), bytes);
+    }
 
-        // Hash stack section information
-        hasher.update(&self.stack_start.to_be_bytes());
-        hasher.update(&self.stack_end.to_be_bytes());
-        hasher.update(&self.stack_merkle_root);
+    #[test]
+    fn vapp_hash_matches_sha256_of_canonical_bytes() {
+        let manifest = sample_manifest();
+        let expected: [u8; 32] = Sha256::digest(manifest.to_canonical_bytes()).into();
+        assert_eq!(manifest.get_vapp_hash::<Sha256Hasher, 32>(), expected);
+    }
 
-        // Hash storage configuration
-        hasher.update(&self.n_storage_slots.to_be_bytes());
+    #[test]
+    fn from_canonical_bytes_rejects_truncated_input() {
+        let manifest = sample_manifest();
+        let bytes = manifest.to_canonical_bytes();
+        assert!(Manifest::from_canonical_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
 
-        hasher.finalize()
+    #[test]
+    fn from_canonical_bytes_rejects_unknown_format_version() {
+        let manifest = sample_manifest();
+        let mut bytes = manifest.to_canonical_bytes();
+        bytes[0] = CANONICAL_FORMAT_VERSION.wrapping_add(1);
+        assert!(Manifest::from_canonical_bytes(&bytes).is_err());
     }
 }
@@ -1,3 +1,58 @@
+use alloc::collections::BTreeMap;
+
+/// Coarse instruction-cost classes used to turn a raw instruction count into a weighted
+/// `gas_used` figure that's actually comparable to a resource budget, instead of treating every
+/// RISC-V instruction as equally expensive. See [`GasCostTable`] for the weight assigned to each
+/// class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionClass {
+    /// ALU, branch, and jump instructions.
+    Arithmetic,
+    /// Loads and stores, pricier than ALU ops since they touch memory.
+    LoadStore,
+    /// Multiply/divide instructions, reserved for when an M-extension op is decoded (this ISA
+    /// subset doesn't implement one yet).
+    MultiplyDivide,
+    /// `ECALL`, the only instruction that crosses into a host-provided syscall.
+    Ecall,
+}
+
+/// Per-class weights used to compute `gas_used` from executed instructions (see
+/// [`VAppMetrics::gas_used`]). The units are arbitrary; only the weights' relative size matters.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasCostTable {
+    pub arithmetic: u64,
+    pub load_store: u64,
+    pub multiply_divide: u64,
+    pub ecall: u64,
+}
+
+impl GasCostTable {
+    /// The weight charged for one instruction of `class`.
+    pub const fn cost(&self, class: InstructionClass) -> u64 {
+        match class {
+            InstructionClass::Arithmetic => self.arithmetic,
+            InstructionClass::LoadStore => self.load_store,
+            InstructionClass::MultiplyDivide => self.multiply_divide,
+            InstructionClass::Ecall => self.ecall,
+        }
+    }
+}
+
+impl Default for GasCostTable {
+    /// Weighs memory accesses above plain ALU ops, multiply/divide above that, and ECALLs
+    /// heaviest of all, per the example weighting this table is meant to approximate.
+    fn default() -> Self {
+        Self {
+            arithmetic: 1,
+            load_store: 2,
+            multiply_divide: 4,
+            ecall: 8,
+        }
+    }
+}
+
 /// Metrics collected during the execution of a V-App.
 #[derive(Clone, Copy, Default)]
 #[cfg_attr(feature = "serde_json", derive(serde::Serialize, serde::Deserialize))]
@@ -12,6 +67,16 @@ pub struct VAppMetrics {
     pub page_loads: u32,
     /// Number of page commits to the host
     pub page_commits: u32,
+    /// Number of page accesses served from the resident page cache, without a host round-trip
+    pub cache_hits: u32,
+    /// Number of page accesses that missed the resident page cache
+    pub cache_misses: u32,
+    /// Weighted instruction cost accumulated so far, per [`GasCostTable`] (see
+    /// [`MetricsSink::on_instruction_gas`]).
+    pub gas_used: u64,
+    /// Gas budget for this V-App invocation; `0` means unlimited. Once `gas_used` reaches this,
+    /// [`MetricsSink::on_instruction_gas`] reports that execution should abort.
+    pub gas_limit: u64,
 }
 
 impl VAppMetrics {
@@ -22,6 +87,10 @@ impl VAppMetrics {
             instruction_count: 0,
             page_loads: 0,
             page_commits: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+            gas_used: 0,
+            gas_limit: 0,
         }
     }
 
@@ -37,3 +106,97 @@ impl VAppMetrics {
         core::str::from_utf8(&self.vapp_name[..len]).unwrap_or("")
     }
 }
+
+/// Callbacks the VM invokes as metrics-relevant events occur during execution, decoupling where
+/// counters come from (page cache, instruction loop, ...) from how they're collected. See
+/// [`VAppMetrics`], which implements this trait directly as the default in-memory sink.
+pub trait MetricsSink {
+    /// Called each time a page is fetched from the host.
+    fn on_page_load(&mut self);
+    /// Called each time a page is committed back to the host.
+    fn on_page_commit(&mut self);
+    /// Called after executing a batch of `count` instructions. Batched rather than called per
+    /// instruction, since the VM only tallies the instruction count between host round-trips.
+    fn on_instruction_batch(&mut self, count: u64);
+    /// Charges `count` instructions of `class` against `gas_used`, weighted by `cost_table`.
+    /// Returns `true` once `gas_used` has reached a nonzero `gas_limit`, telling the caller to
+    /// abort execution; a `gas_limit` of `0` means unlimited, and this always returns `false`.
+    fn on_instruction_gas(
+        &mut self,
+        class: InstructionClass,
+        count: u64,
+        cost_table: &GasCostTable,
+    ) -> bool;
+}
+
+impl MetricsSink for VAppMetrics {
+    fn on_page_load(&mut self) {
+        self.page_loads += 1;
+    }
+
+    fn on_page_commit(&mut self) {
+        self.page_commits += 1;
+    }
+
+    fn on_instruction_batch(&mut self, count: u64) {
+        self.instruction_count += count;
+    }
+
+    fn on_instruction_gas(
+        &mut self,
+        class: InstructionClass,
+        count: u64,
+        cost_table: &GasCostTable,
+    ) -> bool {
+        self.gas_used = self
+            .gas_used
+            .saturating_add(cost_table.cost(class).saturating_mul(count));
+        self.gas_limit != 0 && self.gas_used >= self.gas_limit
+    }
+}
+
+/// Accumulates [`VAppMetrics`] across multiple V-App invocations, keyed by `vapp_hash`, so a host
+/// tool can report totals for a V-App across a whole run (e.g. a benchmark suite that restarts it
+/// several times) instead of only the counters from its last invocation.
+#[derive(Default)]
+pub struct MetricsAggregate {
+    by_vapp_hash: BTreeMap<[u8; 32], VAppMetrics>,
+}
+
+impl MetricsAggregate {
+    pub fn new() -> Self {
+        Self {
+            by_vapp_hash: BTreeMap::new(),
+        }
+    }
+
+    /// Folds `metrics` into the running total for its `vapp_hash`, summing all counters.
+    pub fn record(&mut self, metrics: &VAppMetrics) {
+        let entry = self
+            .by_vapp_hash
+            .entry(metrics.vapp_hash)
+            .or_insert_with(|| {
+                let mut seed = VAppMetrics::new();
+                seed.vapp_name = metrics.vapp_name;
+                seed.vapp_hash = metrics.vapp_hash;
+                seed.gas_limit = metrics.gas_limit;
+                seed
+            });
+        entry.instruction_count += metrics.instruction_count;
+        entry.page_loads += metrics.page_loads;
+        entry.page_commits += metrics.page_commits;
+        entry.cache_hits += metrics.cache_hits;
+        entry.cache_misses += metrics.cache_misses;
+        entry.gas_used += metrics.gas_used;
+    }
+
+    /// Returns the accumulated totals for `vapp_hash`, if any invocation has been recorded for it.
+    pub fn get(&self, vapp_hash: &[u8; 32]) -> Option<&VAppMetrics> {
+        self.by_vapp_hash.get(vapp_hash)
+    }
+
+    /// Iterates over the accumulated totals for every V-App seen so far.
+    pub fn iter(&self) -> impl Iterator<Item = &VAppMetrics> {
+        self.by_vapp_hash.values()
+    }
+}
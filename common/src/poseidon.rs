@@ -0,0 +1,345 @@
+//! A Poseidon-based implementation of [`accumulator::Hasher`](crate::accumulator::Hasher),
+//! for building `MerkleAccumulator`/`IncrementalMerkleAccumulator` instances whose inclusion
+//! and update proofs are cheap to verify inside a SNARK circuit. Poseidon's S-box (`x^5`) is a
+//! handful of field multiplications, unlike SHA-256's bit-level rotations and shifts, which are
+//! notoriously expensive to express as arithmetic-circuit constraints.
+//!
+//! Poseidon itself operates on field elements, not bytes, so [`PoseidonHasher`] adapts the
+//! byte-oriented `Hasher` trait on top of it: `update` buffers raw bytes, and `finalize` packs
+//! them into field elements (little-endian, 7 bytes per element so every chunk is guaranteed
+//! less than the modulus) with a standard "1 then zeros" padding marker so the packing is
+//! unambiguous regardless of length.
+//!
+//! `MerkleAccumulator`/`IncrementalMerkleAccumulator` tag leaf, internal-node and empty-leaf
+//! hashes with a `0x00`/`0x01`/`0x02` byte prefix ahead of the real data. Packed into field
+//! elements the same way as the rest of the input, that prefix would shift every subsequent
+//! byte by one and complicate chunk alignment for no benefit once inside a circuit. Instead,
+//! `finalize` recognizes that leading tag and routes it to the sponge's capacity element via a
+//! domain-specific IV, leaving every rate element pure data.
+//!
+//! The round constants and MDS matrix are exposed on [`PoseidonParams`] precisely so an
+//! off-device verifier circuit can be generated against the exact same numbers. The default
+//! instantiation returned by [`PoseidonParams::demo`] is *not* an audited parameter set (its
+//! constants are generated by a plain deterministic PRNG, and its field is a 61-bit toy prime
+//! rather than a real curve's scalar field such as BN254's) — swap in real constants via
+//! [`PoseidonHasher::with_params`] for anything beyond demonstrating the construction.
+
+use alloc::vec::Vec;
+
+use crate::accumulator::Hasher;
+
+/// State width: one capacity element plus `RATE` rate elements.
+const T: usize = 3;
+/// Number of field elements absorbed into, or squeezed out of, the state per permutation call.
+const RATE: usize = T - 1;
+/// Bytes packed into each field element. `7 * 8 = 56 < 61` bits, so every chunk is guaranteed
+/// to already be less than [`MODULUS`] and needs no reduction-induced ambiguity.
+const CHUNK_BYTES: usize = 7;
+
+/// The toy field Poseidon's arithmetic is performed over: `2^61 - 1`, a Mersenne prime that
+/// comfortably fits a `u64` and keeps every multiplication's intermediate product within a
+/// `u128`. A real deployment matching a SNARK circuit's native field (e.g. BN254's scalar
+/// field) would need a bigger, multi-limb field element type instead of this one.
+const MODULUS: u64 = (1u64 << 61) - 1;
+
+/// An element of the field Poseidon's permutation operates over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Fp(u64);
+
+impl Fp {
+    fn new(value: u64) -> Self {
+        Fp(value % MODULUS)
+    }
+
+    fn zero() -> Self {
+        Fp(0)
+    }
+
+    fn add(self, other: Self) -> Self {
+        let sum = self.0 + other.0;
+        Fp(if sum >= MODULUS { sum - MODULUS } else { sum })
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Fp(((self.0 as u128 * other.0 as u128) % MODULUS as u128) as u64)
+    }
+
+    /// The Poseidon S-box: `x^5`, computed as three multiplications.
+    fn pow5(self) -> Self {
+        let x2 = self.mul(self);
+        let x4 = x2.mul(x2);
+        x4.mul(self)
+    }
+
+    /// Modular exponentiation by repeated squaring, used only to build the MDS matrix's
+    /// Cauchy-matrix entries via [`Self::inverse`].
+    fn pow(self, mut exponent: u64) -> Self {
+        let mut base = self;
+        let mut result = Fp::new(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(base);
+            }
+            base = base.mul(base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// The multiplicative inverse, by Fermat's little theorem (`MODULUS` is prime).
+    fn inverse(self) -> Self {
+        self.pow(MODULUS - 2)
+    }
+
+    fn to_le_bytes(self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+}
+
+/// Distinct capacity-element IVs selecting the domain (leaf, internal node, or empty-leaf hash)
+/// that byte-oriented hashers instead tag with a `0x00`/`0x01`/`0x02` prefix byte. See the
+/// module docs for why the capacity slot is used instead of packing the tag as data.
+const IV_LEAF: u64 = 1;
+const IV_NODE: u64 = 2;
+const IV_EMPTY: u64 = 3;
+
+/// A Poseidon instantiation: how many full and partial rounds to run, and the round constants
+/// and MDS matrix each round uses. See the module docs for what this is, and is not, safe to
+/// use as-is.
+pub struct PoseidonParams {
+    full_rounds: usize,
+    partial_rounds: usize,
+    /// One `[Fp; T]` of round constants per round, `full_rounds + partial_rounds` of them.
+    round_constants: Vec<[Fp; T]>,
+    mds: [[Fp; T]; T],
+}
+
+impl PoseidonParams {
+    /// A demonstration instantiation with deterministically generated (not audited) round
+    /// constants and a [Cauchy matrix](https://en.wikipedia.org/wiki/Cauchy_matrix) MDS, which
+    /// is guaranteed to satisfy the MDS property over any field large enough that its entries'
+    /// denominators can't collide — true of our 61-bit `MODULUS` for `T = 3`.
+    pub fn demo() -> Self {
+        let full_rounds = 8;
+        let partial_rounds = 22;
+        let total_rounds = full_rounds + partial_rounds;
+
+        // A splitmix64-style PRNG: good enough to deterministically fill out round constants
+        // for this demonstration instantiation, not a substitute for vetted Poseidon constants.
+        let mut seed = 0x506f736569646f6eu64; // ASCII "Poseidon", truncated to 8 bytes
+        let mut next_u64 = || {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+
+        let round_constants = (0..total_rounds)
+            .map(|_| [Fp::new(next_u64()), Fp::new(next_u64()), Fp::new(next_u64())])
+            .collect();
+
+        let mut mds = [[Fp::zero(); T]; T];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                let denominator = Fp::new(i as u64).add(Fp::new((T + j) as u64));
+                *entry = denominator.inverse();
+            }
+        }
+
+        PoseidonParams { full_rounds, partial_rounds, round_constants, mds }
+    }
+
+    /// Runs the full Poseidon permutation over `state` in place: `full_rounds + partial_rounds`
+    /// rounds, each adding that round's constants, applying the `x^5` S-box (to every element
+    /// in a full round, only `state[0]` in a partial one — full rounds are split evenly before
+    /// and after the partial rounds, the standard Poseidon round schedule), then mixing with
+    /// the MDS matrix.
+    fn permute(&self, state: &mut [Fp; T]) {
+        let half_full = self.full_rounds / 2;
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            for (s, c) in state.iter_mut().zip(constants.iter()) {
+                *s = s.add(*c);
+            }
+
+            let is_full_round = round < half_full || round >= half_full + self.partial_rounds;
+            if is_full_round {
+                for s in state.iter_mut() {
+                    *s = s.pow5();
+                }
+            } else {
+                state[0] = state[0].pow5();
+            }
+
+            let mut next_state = [Fp::zero(); T];
+            for (i, out) in next_state.iter_mut().enumerate() {
+                let mut acc = Fp::zero();
+                for (j, s) in state.iter().enumerate() {
+                    acc = acc.add(self.mds[i][j].mul(*s));
+                }
+                *out = acc;
+            }
+            *state = next_state;
+        }
+    }
+}
+
+/// A byte-oriented adapter over a Poseidon sponge, implementing
+/// [`Hasher<32>`](crate::accumulator::Hasher).
+pub struct PoseidonHasher {
+    params: PoseidonParams,
+    buffer: Vec<u8>,
+}
+
+impl PoseidonHasher {
+    /// Creates a hasher using a caller-supplied parameter set, e.g. one matching an existing
+    /// off-device circuit, instead of [`PoseidonParams::demo`].
+    pub fn with_params(params: PoseidonParams) -> Self {
+        PoseidonHasher { params, buffer: Vec::new() }
+    }
+
+    /// Packs the buffered bytes into field elements and runs the sponge construction,
+    /// returning 32 squeezed bytes. See the module docs for the domain-tag and padding scheme.
+    fn finalize_inner(mut self) -> [u8; 32] {
+        let capacity_iv = match self.buffer.first() {
+            Some(0x00) => {
+                self.buffer.remove(0);
+                IV_LEAF
+            }
+            Some(0x01) => {
+                self.buffer.remove(0);
+                IV_NODE
+            }
+            Some(0x02) => {
+                self.buffer.remove(0);
+                IV_EMPTY
+            }
+            _ => 0,
+        };
+
+        // "1 then zeros" padding, out to a whole number of `CHUNK_BYTES`-byte field elements,
+        // so the packing below is unambiguous regardless of `self.buffer`'s length.
+        self.buffer.push(0x80);
+        while self.buffer.len() % CHUNK_BYTES != 0 {
+            self.buffer.push(0);
+        }
+
+        let elements: Vec<Fp> = self
+            .buffer
+            .chunks(CHUNK_BYTES)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                Fp::new(u64::from_le_bytes(bytes))
+            })
+            .collect();
+
+        let mut state = [Fp::new(capacity_iv), Fp::zero(), Fp::zero()];
+        for group in elements.chunks(RATE) {
+            for (slot, element) in state[1..].iter_mut().zip(group.iter()) {
+                *slot = *element;
+            }
+            self.params.permute(&mut state);
+        }
+
+        let mut output = Vec::with_capacity(CHUNK_BYTES * RATE);
+        loop {
+            for slot in &state[1..] {
+                output.extend_from_slice(&slot.to_le_bytes());
+            }
+            if output.len() >= 32 {
+                break;
+            }
+            self.params.permute(&mut state);
+        }
+
+        let mut result = [0u8; 32];
+        result.copy_from_slice(&output[..32]);
+        result
+    }
+}
+
+impl Hasher<32> for PoseidonHasher {
+    fn new() -> Self {
+        PoseidonHasher { params: PoseidonParams::demo(), buffer: Vec::new() }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.finalize_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accumulator::{MerkleAccumulator, VectorAccumulator};
+
+    #[test]
+    fn test_hash_is_deterministic() {
+        let mut a = PoseidonHasher::new();
+        a.update(&[0x00]);
+        a.update(b"hello");
+        let mut b = PoseidonHasher::new();
+        b.update(&[0x00]);
+        b.update(b"hello");
+        assert_eq!(a.finalize(), b.finalize());
+    }
+
+    #[test]
+    fn test_domain_tag_changes_output() {
+        let mut leaf = PoseidonHasher::new();
+        leaf.update(&[0x00]);
+        leaf.update(b"hello");
+
+        let mut node = PoseidonHasher::new();
+        node.update(&[0x01]);
+        node.update(b"hello");
+
+        assert_ne!(leaf.finalize(), node.finalize());
+    }
+
+    #[test]
+    fn test_different_data_changes_output() {
+        let mut a = PoseidonHasher::new();
+        a.update(&[0x00]);
+        a.update(b"hello");
+        let mut b = PoseidonHasher::new();
+        b.update(&[0x00]);
+        b.update(b"world");
+        assert_ne!(a.finalize(), b.finalize());
+    }
+
+    #[test]
+    fn test_incremental_updates_match_single_update() {
+        let mut incremental = PoseidonHasher::new();
+        incremental.update(&[0x01]);
+        incremental.update(b"left-half");
+        incremental.update(b"right-half");
+
+        let mut single = PoseidonHasher::new();
+        single.update(&[0x01]);
+        single.update(b"left-halfright-half");
+
+        assert_eq!(incremental.finalize(), single.finalize());
+    }
+
+    #[test]
+    fn test_merkle_accumulator_over_poseidon() {
+        let data = vec![b"data1".to_vec(), b"data2".to_vec(), b"data3".to_vec(), b"data4".to_vec()];
+        let ma = MerkleAccumulator::<PoseidonHasher, Vec<u8>, 32>::new(data.clone());
+        let root = ma.root();
+
+        let proof = ma.prove(2).unwrap();
+        assert!(MerkleAccumulator::<PoseidonHasher, Vec<u8>, 32>::verify_inclusion_proof(
+            &root,
+            &proof,
+            &data[2],
+            2,
+            data.len()
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+//! Self-describing tag-length-value field encoding, layered underneath the fixed-layout
+//! [`crate::client_commands::Message`] wire format so a V-App VM built against a newer
+//! `common` can add fields to a command without breaking an older host's `client-sdk` that
+//! doesn't know about them yet.
+//!
+//! Every element is written as:
+//!
+//! ```text
+//! control byte: bits 7-6 = wire type, bits 5-0 = tag number (0-63)
+//! [varint length]         -- only present for `WireType::Bytes`
+//! value                   -- 1, 4, or 8 bytes (big-endian), or `length` bytes
+//! ```
+//!
+//! [`TlvReader`] walks a buffer of such elements and yields every one it encounters, known or not;
+//! a caller simply ignores tags it doesn't recognize; because each element carries its own length
+//! (fixed for `Uint8`/`Uint32`/`Uint64`, varint-prefixed for `Bytes`), skipping one never requires
+//! understanding its meaning.
+//!
+//! This module only encodes the fields *after* a command's mandatory leading
+//! `ClientCommandCode` byte, which stays a plain, non-TLV first byte on the wire.
+
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WireType {
+    Uint8 = 0,
+    Uint32 = 1,
+    Bytes = 2,
+    Uint64 = 3,
+}
+
+impl TryFrom<u8> for WireType {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(WireType::Uint8),
+            1 => Ok(WireType::Uint32),
+            2 => Ok(WireType::Bytes),
+            3 => Ok(WireType::Uint64),
+            _ => Err("Invalid TLV wire type"),
+        }
+    }
+}
+
+/// Tag numbers are packed into the low 6 bits of the control byte, alongside the 2-bit wire type.
+const TAG_MASK: u8 = 0x3F;
+
+fn control_byte(tag: u8, wire_type: WireType) -> u8 {
+    debug_assert!(tag <= TAG_MASK, "TLV tag must fit in 6 bits");
+    ((wire_type as u8) << 6) | (tag & TAG_MASK)
+}
+
+/// Appends a ULEB128-encoded length (same variable-length encoding as DWARF's ULEB128).
+fn write_varint(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Converts a length field taken from a header-prefixed frame (an APDU, an event-buffer record,
+/// an indirect-payload descriptor, ...) into the number of payload bytes that follow the header.
+///
+/// Naively computing `raw_len - header_size` is the same class of bug that let an MP4
+/// `largesize` of 8 wrongly collapse to a length of 0: on an attacker-controlled `raw_len`
+/// smaller than `header_size`, the subtraction underflows and produces a bogus huge length that
+/// reads past the buffer. This helper rejects that case instead of wrapping:
+///
+/// - `raw_len == 0` is a sentinel meaning "no length given; the payload extends to the end of
+///   whatever buffer the caller is reading from" — returns `None`.
+/// - `0 < raw_len < header_size` is malformed (a frame can't be shorter than its own header) —
+///   returns `Err`.
+/// - Otherwise returns `Some(raw_len - header_size)`, the number of payload bytes after the
+///   header.
+pub fn read_framed_len(raw_len: u32, header_size: u32) -> Result<Option<usize>, &'static str> {
+    if raw_len == 0 {
+        return Ok(None);
+    }
+    if raw_len < header_size {
+        return Err("framed length is shorter than its own header");
+    }
+    Ok(Some((raw_len - header_size) as usize))
+}
+
+/// Reads a ULEB128-encoded length, returning `(value, bytes_consumed)`.
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut result: usize = 0;
+    let mut shift = 0;
+    for (i, &byte) in data.iter().enumerate() {
+        result |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some((result, i + 1));
+        }
+        shift += 7;
+        if shift >= usize::BITS as usize {
+            return None; // overflow: not a real-world length, treat as malformed
+        }
+    }
+    None
+}
+
+/// Appends a TLV-encoded `u8` field.
+pub fn write_u8(tag: u8, value: u8, out: &mut Vec<u8>) {
+    out.push(control_byte(tag, WireType::Uint8));
+    out.push(value);
+}
+
+/// Appends a TLV-encoded big-endian `u32` field.
+pub fn write_u32(tag: u8, value: u32, out: &mut Vec<u8>) {
+    out.push(control_byte(tag, WireType::Uint32));
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Appends a TLV-encoded big-endian `u64` field.
+pub fn write_u64(tag: u8, value: u64, out: &mut Vec<u8>) {
+    out.push(control_byte(tag, WireType::Uint64));
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Appends a TLV-encoded byte-string field, with a varint length prefix.
+pub fn write_bytes(tag: u8, value: &[u8], out: &mut Vec<u8>) {
+    out.push(control_byte(tag, WireType::Bytes));
+    write_varint(value.len(), out);
+    out.extend_from_slice(value);
+}
+
+/// One decoded TLV element, with its value keyed by its wire type.
+#[derive(Debug, Clone, Copy)]
+pub enum TlvValue<'a> {
+    Uint8(u8),
+    Uint32(u32),
+    Uint64(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Iterates the TLV elements of a buffer, yielding `(tag, value)` pairs in order. A tag this
+/// reader's caller doesn't recognize is simply dropped by the caller's `match` — `next` has
+/// already advanced past the whole element by the time it's yielded, so there's nothing extra to
+/// do to "skip" it.
+pub struct TlvReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TlvReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for TlvReader<'a> {
+    type Item = Result<(u8, TlvValue<'a>), &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+
+        let control = self.data[self.pos];
+        self.pos += 1;
+        let tag = control & TAG_MASK;
+        let wire_type = match WireType::try_from(control >> 6) {
+            Ok(t) => t,
+            Err(e) => return Some(Err(e)),
+        };
+
+        match wire_type {
+            WireType::Uint8 => {
+                let Some(&byte) = self.data.get(self.pos) else {
+                    return Some(Err("Truncated TLV uint8 field"));
+                };
+                self.pos += 1;
+                Some(Ok((tag, TlvValue::Uint8(byte))))
+            }
+            WireType::Uint32 => {
+                let Some(bytes) = self.data.get(self.pos..self.pos + 4) else {
+                    return Some(Err("Truncated TLV uint32 field"));
+                };
+                self.pos += 4;
+                Some(Ok((
+                    tag,
+                    TlvValue::Uint32(u32::from_be_bytes(bytes.try_into().expect("4 bytes"))),
+                )))
+            }
+            WireType::Uint64 => {
+                let Some(bytes) = self.data.get(self.pos..self.pos + 8) else {
+                    return Some(Err("Truncated TLV uint64 field"));
+                };
+                self.pos += 8;
+                Some(Ok((
+                    tag,
+                    TlvValue::Uint64(u64::from_be_bytes(bytes.try_into().expect("8 bytes"))),
+                )))
+            }
+            WireType::Bytes => {
+                let Some((len, len_size)) = read_varint(&self.data[self.pos..]) else {
+                    return Some(Err("Truncated TLV length varint"));
+                };
+                self.pos += len_size;
+                let Some(bytes) = self.data.get(self.pos..self.pos + len) else {
+                    return Some(Err("Truncated TLV byte-string field"));
+                };
+                self.pos += len;
+                Some(Ok((tag, TlvValue::Bytes(bytes))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_framed_len_zero_means_extends_to_end() {
+        assert_eq!(read_framed_len(0, 5), Ok(None));
+    }
+
+    #[test]
+    fn read_framed_len_rejects_degenerate_lengths() {
+        assert!(read_framed_len(1, 5).is_err());
+        assert!(read_framed_len(4, 5).is_err());
+    }
+
+    #[test]
+    fn read_framed_len_accepts_exact_header_size() {
+        assert_eq!(read_framed_len(5, 5), Ok(Some(0)));
+    }
+
+    #[test]
+    fn read_framed_len_subtracts_header_size() {
+        assert_eq!(read_framed_len(6, 5), Ok(Some(1)));
+        assert_eq!(read_framed_len(105, 5), Ok(Some(100)));
+    }
+
+    #[test]
+    fn read_framed_len_handles_max_u32() {
+        assert_eq!(read_framed_len(u32::MAX, 5), Ok(Some((u32::MAX - 5) as usize)));
+    }
+}
@@ -0,0 +1,437 @@
+//! Icons used by the NBGL-backed review/confirmation flows, and the [`Event`]/[`EventData`]
+//! types a V-App polls to find out what the user did. See
+//! `vm::handlers::lib::ecall::bitmaps::ToIconDetails` for how each [`Icon`] variant is turned
+//! into the `nbgl_icon_details_t` the BOLOS NBGL library actually draws, separately for
+//! large-screen (Stax/Flex) and small-screen (Nano X/Nano S+) devices.
+
+use alloc::vec::Vec;
+
+/// The bit-per-pixel format of a [`Icon::Custom`] bitmap, mirroring BOLOS's `NBGL_BPP_*`
+/// constants (`ledger_secure_sdk_sys`) without pulling that crate into `common`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconBpp {
+    Bpp1,
+    Bpp2,
+    Bpp4,
+}
+
+/// An icon shown alongside a review step or confirmation screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Icon {
+    None,
+    Success,
+    Failure,
+    Confirm,
+    Reject,
+    Processing,
+    /// A V-App-supplied icon: a gzip-compressed NBGL bitmap file, along with the dimensions and
+    /// bit depth needed to build its `nbgl_icon_details_t` at runtime. The host must not trust
+    /// `width`/`height` at face value; [`Icon::validate_custom_dimensions`] checks them against
+    /// the little-endian width/height header every NBGL bitmap file starts with before handing
+    /// the bitmap pointer to NBGL.
+    Custom {
+        width: u16,
+        height: u16,
+        bpp: IconBpp,
+        bitmap: Vec<u8>,
+    },
+}
+
+/// A button-like action reported by [`EventCode::Action`], e.g. a confirm/reject tap or a
+/// page-turn swipe on the step-based (Nano) UX model.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Reject = 0,
+    Confirm = 1,
+    NextPage = 2,
+    PreviousPage = 3,
+}
+
+/// A touch gesture reported alongside [`EventCode::Touch`] on Stax/Flex-class screens.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Tap = 0,
+    SwipeUp = 1,
+    SwipeDown = 2,
+    SwipeLeft = 3,
+    SwipeRight = 4,
+}
+
+/// A touch event on a Stax/Flex-class screen: the touch coordinates, the recognized gesture, and
+/// a byte of gesture-specific flags (e.g. swipe velocity/direction bits; unused bits are 0).
+///
+/// `#[repr(C)]` with explicit padding keeps the layout (and therefore
+/// `size_of::<TouchEvent>()`) the same across every build target, which the `EventData` union
+/// this is embedded in relies on.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchEvent {
+    pub x: u16,
+    pub y: u16,
+    pub gesture: Gesture,
+    pub flags: u8,
+}
+
+/// A descriptor for an event payload too large to fit inline in [`EventData`] (e.g. a pasted
+/// string, an NFC record, a multi-field APDU notification): `len` bytes starting at `offset` in
+/// a host-managed arena, reported alongside [`EventCode::Indirect`].
+///
+/// The host only stores this 8-byte descriptor in the event queue; it copies the actual payload
+/// out to the V-App lazily, via a follow-up `read_event_payload` ecall, the same way boxing a
+/// large enum variant keeps a hot queue storing fat pointers rather than
+/// `size_of(largest_variant) * N`. An event the V-App never reads (or only partially reads)
+/// therefore never costs more than these 8 bytes plus whatever arena slot the host already had
+/// to keep around.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndirectPayload {
+    pub offset: u32,
+    pub len: u32,
+}
+
+const _: () = assert!(core::mem::size_of::<TouchEvent>() <= 16);
+const _: () = assert!(core::mem::size_of::<Action>() <= 16);
+const _: () = assert!(core::mem::size_of::<IndirectPayload>() <= 16);
+
+/// The event data delivered by the `get_event` ecall: a 16-byte union whose active arm is told
+/// apart by the [`EventCode`] the ecall itself returns. Kept intentionally compact (in the spirit
+/// of a small tagged-value representation, e.g. Lua 5.0's 16-byte `Value`) so the event-polling
+/// loop in [`crate`]-using V-Apps never needs to allocate just to learn "what happened".
+///
+/// Adding a new variant here must keep `size_of::<EventData>() == 16`; the `const _: () =
+/// assert!(..)` checks above and the `event_data_stays_16_bytes` test below both fail to
+/// compile/pass if a future variant doesn't fit.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union EventData {
+    pub raw: [u8; 16],
+    pub action: Action,
+    pub touch: TouchEvent,
+    pub indirect: IndirectPayload,
+}
+
+impl Default for EventData {
+    fn default() -> Self {
+        EventData { raw: [0u8; 16] }
+    }
+}
+
+/// Tags which arm of [`EventData`] is populated, as returned by the `get_event` ecall.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventCode {
+    Ticker = 0,
+    Action = 1,
+    Touch = 2,
+    Indirect = 3,
+    Unknown = 0xffff_ffff,
+}
+
+impl From<u32> for EventCode {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => EventCode::Ticker,
+            1 => EventCode::Action,
+            2 => EventCode::Touch,
+            3 => EventCode::Indirect,
+            _ => EventCode::Unknown,
+        }
+    }
+}
+
+/// A decoded event, as returned by the V-App-facing `ux::get_event`/`get_action` helpers: the
+/// typed counterpart to a raw [`EventCode`] + [`EventData`] pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A periodic tick, used to implement polling/timeouts (see `ux::wait`).
+    Ticker,
+    /// A button-like confirm/reject/page-turn action.
+    Action(Action),
+    /// A touch gesture on a Stax/Flex-class screen. Only delivered on targets whose host-side
+    /// event loop reports raw touch input rather than pre-digesting it into an [`Action`].
+    Touch(TouchEvent),
+    /// An event whose payload didn't fit inline; read it out with `ux::read_event_payload`
+    /// before the next `get_event` call, since the host is free to recycle the arena slot once
+    /// the next event is delivered.
+    Indirect(IndirectPayload),
+    /// An event code this SDK version doesn't recognize, with its data passed through raw so a
+    /// V-App that knows about a newer event kind (e.g. via a patched SDK) can still decode it.
+    Unknown([u8; 16]),
+}
+
+/// The `u8` tag an [`Event`] is serialized under in [`EventWire`]. Distinct from [`EventCode`]
+/// (which is `u32`-wide, to leave room in the ecall ABI) since the wire form only ever needs to
+/// distinguish a handful of kinds and a `u8` keeps [`EventWire`] small.
+const WIRE_KIND_TICKER: u8 = 0;
+const WIRE_KIND_ACTION: u8 = 1;
+const WIRE_KIND_TOUCH: u8 = 2;
+const WIRE_KIND_INDIRECT: u8 = 3;
+const WIRE_KIND_UNKNOWN: u8 = 0xff;
+
+/// A `#[repr(C)]`, byte-stable serialization of an [`Event`], decoupled from [`EventData`]'s
+/// in-RAM union layout so the host↔device wire protocol doesn't break if that union's internal
+/// representation ever changes underneath it. `to_wire`/`from_wire` never reinterpret pointers or
+/// rely on native alignment, the same discipline [`crate::vm::FaultRecord::to_bytes`] already
+/// follows, which also makes `from_wire` safe to call on attacker-controlled bytes.
+///
+/// `payload`'s meaning depends on `kind`: for a [`WIRE_KIND_INDIRECT`] event it's a little-endian
+/// `offset` (bytes 0..4) followed by `len` (bytes 4..8), referencing an out-of-line buffer (see
+/// [`IndirectPayload`]); every other kind packs its fields into the low bytes of `payload` and
+/// leaves the rest zeroed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventWire {
+    pub kind: u8,
+    _padding: [u8; 3],
+    pub payload: [u8; 16],
+}
+
+const _: () = assert!(core::mem::size_of::<EventWire>() == 20);
+
+impl EventWire {
+    /// Encodes this wire struct as `kind (1 byte) || padding (3 bytes, always 0) || payload (16
+    /// bytes)`, via plain byte-wise copies rather than a pointer cast over the struct, so the
+    /// layout is pinned to this explicit order instead of whatever `repr(C)` happens to produce.
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let mut out = [0u8; 20];
+        out[0] = self.kind;
+        out[4..20].copy_from_slice(&self.payload);
+        out
+    }
+
+    /// Decodes the byte layout produced by [`EventWire::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; 20]) -> Self {
+        let mut payload = [0u8; 16];
+        payload.copy_from_slice(&bytes[4..20]);
+        EventWire {
+            kind: bytes[0],
+            _padding: [0; 3],
+            payload,
+        }
+    }
+}
+
+impl Event {
+    /// Encodes this event into its stable wire representation.
+    pub fn to_wire(&self) -> EventWire {
+        let mut payload = [0u8; 16];
+        let kind = match self {
+            Event::Ticker => WIRE_KIND_TICKER,
+            Event::Action(action) => {
+                payload[0] = *action as u8;
+                WIRE_KIND_ACTION
+            }
+            Event::Touch(touch) => {
+                payload[0..2].copy_from_slice(&touch.x.to_le_bytes());
+                payload[2..4].copy_from_slice(&touch.y.to_le_bytes());
+                payload[4] = touch.gesture as u8;
+                payload[5] = touch.flags;
+                WIRE_KIND_TOUCH
+            }
+            Event::Indirect(indirect) => {
+                payload[0..4].copy_from_slice(&indirect.offset.to_le_bytes());
+                payload[4..8].copy_from_slice(&indirect.len.to_le_bytes());
+                WIRE_KIND_INDIRECT
+            }
+            Event::Unknown(data) => {
+                payload.copy_from_slice(data);
+                WIRE_KIND_UNKNOWN
+            }
+        };
+        EventWire {
+            kind,
+            _padding: [0; 3],
+            payload,
+        }
+    }
+
+    /// Decodes an event from its wire representation, as produced by [`Event::to_wire`].
+    /// Rejects a `kind`/`payload` combination this SDK version doesn't recognize, rather than
+    /// guessing at a `Some` variant's meaning from unvalidated bytes.
+    pub fn from_wire(wire: &EventWire) -> Result<Event, &'static str> {
+        match wire.kind {
+            WIRE_KIND_TICKER => Ok(Event::Ticker),
+            WIRE_KIND_ACTION => {
+                let action = match wire.payload[0] {
+                    0 => Action::Reject,
+                    1 => Action::Confirm,
+                    2 => Action::NextPage,
+                    3 => Action::PreviousPage,
+                    _ => return Err("invalid Action discriminant in EventWire"),
+                };
+                Ok(Event::Action(action))
+            }
+            WIRE_KIND_TOUCH => {
+                let x = u16::from_le_bytes([wire.payload[0], wire.payload[1]]);
+                let y = u16::from_le_bytes([wire.payload[2], wire.payload[3]]);
+                let gesture = match wire.payload[4] {
+                    0 => Gesture::Tap,
+                    1 => Gesture::SwipeUp,
+                    2 => Gesture::SwipeDown,
+                    3 => Gesture::SwipeLeft,
+                    4 => Gesture::SwipeRight,
+                    _ => return Err("invalid Gesture discriminant in EventWire"),
+                };
+                let flags = wire.payload[5];
+                Ok(Event::Touch(TouchEvent { x, y, gesture, flags }))
+            }
+            WIRE_KIND_INDIRECT => {
+                let offset = u32::from_le_bytes(wire.payload[0..4].try_into().unwrap());
+                let len = u32::from_le_bytes(wire.payload[4..8].try_into().unwrap());
+                Ok(Event::Indirect(IndirectPayload { offset, len }))
+            }
+            WIRE_KIND_UNKNOWN => Ok(Event::Unknown(wire.payload)),
+            _ => Err("unrecognized EventWire kind"),
+        }
+    }
+}
+
+impl Icon {
+    /// Every NBGL bitmap file (the `isFile: true` form used by [`Icon::Custom`]) starts with a
+    /// little-endian `u16` width followed by a little-endian `u16` height, ahead of the
+    /// compressed pixel data. Returns `true` if `bitmap` is long enough to contain that header
+    /// and the header agrees with the declared `width`/`height`.
+    pub fn validate_custom_dimensions(width: u16, height: u16, bitmap: &[u8]) -> bool {
+        if width == 0 || height == 0 || bitmap.len() < 4 {
+            return false;
+        }
+        let file_width = u16::from_le_bytes([bitmap[0], bitmap[1]]);
+        let file_height = u16::from_le_bytes([bitmap[2], bitmap[3]]);
+        file_width == width && file_height == height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_data_stays_16_bytes() {
+        assert_eq!(core::mem::size_of::<EventData>(), 16);
+    }
+
+    #[test]
+    fn indirect_payload_roundtrips_through_event_data() {
+        let payload = IndirectPayload {
+            offset: 0x1234_5678,
+            len: 42,
+        };
+        let data = EventData { indirect: payload };
+        let roundtripped = unsafe { data.indirect };
+        assert_eq!(roundtripped.offset, payload.offset);
+        assert_eq!(roundtripped.len, payload.len);
+    }
+
+    #[test]
+    fn indirect_payload_roundtrips_through_raw_bytes() {
+        let payload = IndirectPayload {
+            offset: 0x0102_0304,
+            len: 0x0506_0708,
+        };
+        let data = EventData { indirect: payload };
+        let raw = unsafe { data.raw };
+
+        let reinterpreted = EventData { raw };
+        let roundtripped = unsafe { reinterpreted.indirect };
+        assert_eq!(roundtripped.offset, payload.offset);
+        assert_eq!(roundtripped.len, payload.len);
+    }
+
+    #[test]
+    fn event_code_round_trips_through_u32() {
+        assert_eq!(EventCode::from(0), EventCode::Ticker);
+        assert_eq!(EventCode::from(1), EventCode::Action);
+        assert_eq!(EventCode::from(2), EventCode::Touch);
+        assert_eq!(EventCode::from(3), EventCode::Indirect);
+        assert_eq!(EventCode::from(0xffff_ffff), EventCode::Unknown);
+        assert_eq!(EventCode::from(999), EventCode::Unknown);
+    }
+
+    #[test]
+    fn event_wire_stays_20_bytes() {
+        assert_eq!(core::mem::size_of::<EventWire>(), 20);
+    }
+
+    #[test]
+    fn ticker_event_has_exact_wire_layout() {
+        let mut expected = [0u8; 20];
+        expected[0] = WIRE_KIND_TICKER;
+        assert_eq!(Event::Ticker.to_wire().to_bytes(), expected);
+    }
+
+    #[test]
+    fn action_event_has_exact_wire_layout() {
+        let mut expected = [0u8; 20];
+        expected[0] = WIRE_KIND_ACTION;
+        expected[4] = Action::Confirm as u8;
+        assert_eq!(
+            Event::Action(Action::Confirm).to_wire().to_bytes(),
+            expected
+        );
+    }
+
+    #[test]
+    fn touch_event_has_exact_wire_layout() {
+        let touch = TouchEvent {
+            x: 0x0102,
+            y: 0x0304,
+            gesture: Gesture::SwipeLeft,
+            flags: 0x42,
+        };
+        let mut expected = [0u8; 20];
+        expected[0] = WIRE_KIND_TOUCH;
+        expected[4..6].copy_from_slice(&touch.x.to_le_bytes());
+        expected[6..8].copy_from_slice(&touch.y.to_le_bytes());
+        expected[8] = Gesture::SwipeLeft as u8;
+        expected[9] = 0x42;
+        assert_eq!(Event::Touch(touch).to_wire().to_bytes(), expected);
+    }
+
+    #[test]
+    fn indirect_event_has_exact_wire_layout() {
+        let indirect = IndirectPayload {
+            offset: 0x1122_3344,
+            len: 0x5566_7788,
+        };
+        let mut expected = [0u8; 20];
+        expected[0] = WIRE_KIND_INDIRECT;
+        expected[4..8].copy_from_slice(&indirect.offset.to_le_bytes());
+        expected[8..12].copy_from_slice(&indirect.len.to_le_bytes());
+        assert_eq!(Event::Indirect(indirect).to_wire().to_bytes(), expected);
+    }
+
+    #[test]
+    fn unknown_event_has_exact_wire_layout() {
+        let data = [7u8; 16];
+        let mut expected = [0u8; 20];
+        expected[0] = WIRE_KIND_UNKNOWN;
+        expected[4..20].copy_from_slice(&data);
+        assert_eq!(Event::Unknown(data).to_wire().to_bytes(), expected);
+    }
+
+    #[test]
+    fn event_wire_round_trips_through_to_wire_and_from_wire() {
+        let events = [
+            Event::Ticker,
+            Event::Action(Action::Reject),
+            Event::Touch(TouchEvent {
+                x: 10,
+                y: 20,
+                gesture: Gesture::Tap,
+                flags: 0,
+            }),
+            Event::Indirect(IndirectPayload {
+                offset: 100,
+                len: 200,
+            }),
+            Event::Unknown([9u8; 16]),
+        ];
+        for event in events {
+            let wire = event.to_wire();
+            let decoded = Event::from_wire(&EventWire::from_bytes(&wire.to_bytes())).unwrap();
+            assert_eq!(decoded, event);
+        }
+    }
+}
@@ -3,11 +3,174 @@
 
 use core::{
     fmt,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
 };
 
-use crate::{constants::PAGE_SIZE, riscv::op::Op};
-use alloc::{format, vec::Vec};
+use crate::{
+    accumulator::Hasher,
+    constants::PAGE_SIZE,
+    metrics::{GasCostTable, InstructionClass},
+    riscv::op::Op,
+};
+use alloc::{boxed::Box, collections::BTreeMap, format, vec, vec::Vec};
+
+/// An error in the VM↔host resume protocol (e.g. the interrupt/`Continue` handshake), as
+/// distinct from a guest CPU fault (see [`FaultRecord`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryError {
+    /// A catch-all for protocol violations that don't warrant their own variant.
+    GenericError(&'static str),
+    /// A resumed command carried a sequence number other than the one expected or its immediate
+    /// predecessor (see the retry note on `vm::interrupt` callers).
+    SequenceMismatch { expected: u8, got: u8 },
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::GenericError(msg) => write!(f, "{}", msg),
+            MemoryError::SequenceMismatch { expected, got } => {
+                write!(f, "sequence mismatch: expected {}, got {}", expected, got)
+            }
+        }
+    }
+}
+
+impl core::error::Error for MemoryError {}
+
+/// Why a guest instruction faulted, closely following the reasons a RISC-V trap handler would
+/// distinguish via `mcause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FaultCause {
+    InstructionAddressMisaligned = 0,
+    IllegalInstruction = 2,
+    Breakpoint = 3,
+    LoadAddressMisaligned = 4,
+    LoadAccessFault = 5,
+    StoreAddressMisaligned = 6,
+    StoreAccessFault = 7,
+    /// An `ecall` trapped with no [`EventHandler`] registered to service it.
+    EnvironmentCall = 8,
+    /// Not yet raised by any implemented instruction (this ISA subset has no divide op), but
+    /// kept so a future M-extension op has somewhere to report to.
+    DivideByZero = 24,
+    /// A page failed Merkle verification against [`AuthenticatedMemory::root`]. Not an `mcause`
+    /// RISC-V defines; it's this VM's own extension for an untrusted page turning out to not
+    /// match what was committed to.
+    IntegrityFault = 25,
+}
+
+/// A structured record of a guest fault: which instruction faulted, why, and (for memory faults)
+/// which address it was accessing. This is the payload of a `BufferType::Fault` buffer sent to
+/// the host when a fault isn't recovered by a guest-registered trap handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultRecord {
+    pub cause: FaultCause,
+    /// The program counter of the faulting instruction.
+    pub pc: u32,
+    /// The effective address being accessed, for memory faults; `0` for faults with no
+    /// associated address (e.g. an illegal instruction).
+    pub address: u32,
+}
+
+impl FaultRecord {
+    /// Encodes this record as `cause (4 bytes LE) || pc (4 bytes LE) || address (4 bytes LE)`,
+    /// the layout carried in the body of a `BufferType::Fault` buffer.
+    pub fn to_bytes(&self) -> [u8; 12] {
+        let mut out = [0u8; 12];
+        out[0..4].copy_from_slice(&(self.cause as u32).to_le_bytes());
+        out[4..8].copy_from_slice(&self.pc.to_le_bytes());
+        out[8..12].copy_from_slice(&self.address.to_le_bytes());
+        out
+    }
+}
+
+/// A CPU trap, carrying the context (faulting address or raw instruction word) needed to report
+/// it precisely -- the Rust-side counterpart to [`FaultCause`], which is only the bare `u32`
+/// tag delivered to a guest trap handler over the [`FaultRecord`] ABI. Threaded through
+/// [`MemorySegment`]'s and [`Cpu`]'s `read_*`/`write_*` methods, [`Cpu::fetch_instruction`], and
+/// [`Cpu::execute_inner`] (via [`Cpu::step`]) in place of a bare `&'static str`, so a caller can
+/// tell a recoverable misaligned access from a fatal out-of-bounds one without parsing a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    InstructionAddressMisaligned,
+    LoadAddressMisaligned,
+    StoreAddressMisaligned,
+    LoadAccessFault(u32),
+    StoreAccessFault(u32),
+    IllegalInstruction(u32),
+    Breakpoint,
+    EnvironmentCall,
+    /// A page fetched through [`AuthenticatedMemory`] didn't match its committed Merkle root.
+    MerkleVerificationFailed(u32),
+}
+
+impl Trap {
+    /// The faulting address this trap carries, or `0` for traps that don't have one (mirroring
+    /// [`FaultRecord::address`]'s convention).
+    pub fn address(&self) -> u32 {
+        match *self {
+            Trap::LoadAccessFault(addr)
+            | Trap::StoreAccessFault(addr)
+            | Trap::MerkleVerificationFailed(addr) => addr,
+            _ => 0,
+        }
+    }
+
+    /// The wire-stable [`FaultCause`] this trap is reported as over the [`FaultRecord`] ABI.
+    pub fn cause(&self) -> FaultCause {
+        match self {
+            Trap::InstructionAddressMisaligned => FaultCause::InstructionAddressMisaligned,
+            Trap::LoadAddressMisaligned => FaultCause::LoadAddressMisaligned,
+            Trap::StoreAddressMisaligned => FaultCause::StoreAddressMisaligned,
+            Trap::LoadAccessFault(_) => FaultCause::LoadAccessFault,
+            Trap::StoreAccessFault(_) => FaultCause::StoreAccessFault,
+            Trap::IllegalInstruction(_) => FaultCause::IllegalInstruction,
+            Trap::Breakpoint => FaultCause::Breakpoint,
+            Trap::EnvironmentCall => FaultCause::EnvironmentCall,
+            Trap::MerkleVerificationFailed(_) => FaultCause::IntegrityFault,
+        }
+    }
+}
+
+/// The outcome of a single [`Cpu::step`] (or a whole [`Cpu::run`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The instruction executed without fault, exit, or yield; the guest keeps running.
+    Continue,
+    /// Execution trapped at `pc`; unlike [`Cpu::execute`], no guest trap handler is consulted.
+    Trapped { trap: Trap, pc: u32 },
+    /// The guest thread exited via an [`EcallOutcome::ExitThread`] ecall, with the given code.
+    Exited(i32),
+}
+
+/// The outcome of [`Cpu::run_bounded`]: the same cases as [`StepOutcome`], plus
+/// [`RunResult::BudgetExhausted`] once `max_cycles` has been charged. `pc` and the register file
+/// are left exactly where the last completed instruction left them either way, so a
+/// `BudgetExhausted` run can always be resumed with another [`Cpu::run_bounded`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunResult {
+    /// The guest yielded; see [`StepOutcome::Continue`] and [`Cpu::run`].
+    Continue,
+    /// Execution trapped at `pc`.
+    Trapped { trap: Trap, pc: u32 },
+    /// The guest thread exited, with the given code.
+    Exited(i32),
+    /// `max_cycles` was charged before the guest exited, trapped, or yielded.
+    BudgetExhausted,
+}
+
+impl From<StepOutcome> for RunResult {
+    fn from(outcome: StepOutcome) -> Self {
+        match outcome {
+            StepOutcome::Continue => RunResult::Continue,
+            StepOutcome::Trapped { trap, pc } => RunResult::Trapped { trap, pc },
+            StepOutcome::Exited(code) => RunResult::Exited(code),
+        }
+    }
+}
 
 /// Represents a single page of memory.
 #[derive(Clone, Debug)]
@@ -21,6 +184,22 @@ fn page_start(address: u32) -> u32 {
     address & !((PAGE_SIZE as u32) - 1)
 }
 
+/// Turns a [`PagedMemory::get_page`] error into the [`Trap`] [`MemorySegment`]'s read/write
+/// methods should raise: [`AuthenticatedMemory`]'s distinctive
+/// [`AUTHENTICATED_MEMORY_VERIFICATION_FAILED`] message becomes
+/// [`Trap::MerkleVerificationFailed`] rather than a generic access fault, so a guest (or its
+/// host) can tell a corrupted/malicious page apart from one that's merely out of bounds.
+#[inline]
+fn page_fault_trap(address: u32, err: &'static str, is_store: bool) -> Trap {
+    if err == AUTHENTICATED_MEMORY_VERIFICATION_FAILED {
+        Trap::MerkleVerificationFailed(address)
+    } else if is_store {
+        Trap::StoreAccessFault(address)
+    } else {
+        Trap::LoadAccessFault(address)
+    }
+}
+
 /// A generic trait representing a memory that is split into pages.
 /// This allows abstracting over different ways of storing pages.
 pub trait PagedMemory {
@@ -30,6 +209,15 @@ pub trait PagedMemory {
 
     /// Retrieves a mutable reference to the page at the given index.
     fn get_page(&mut self, page_index: u32) -> Result<Self::PageRef<'_>, &'static str>;
+
+    /// Writes back any buffered changes this implementation may be holding, so that nothing is
+    /// lost if the memory is dropped right after this call returns. Implementations that always
+    /// persist writes immediately (like [`VecMemory`]) can leave this as a no-op; implementations
+    /// that cache dirty pages (like `OutsourcedMemory` in the `vm` crate) should flush every dirty
+    /// entry here.
+    fn flush(&mut self) -> Result<(), &'static str> {
+        Ok(())
+    }
 }
 
 /// A simple implementation of `PagedMemory` using a vector of pages.
@@ -61,6 +249,303 @@ impl VecMemory {
     }
 }
 
+/// The error [`AuthenticatedMemory::get_page`] returns when a page doesn't match the sibling
+/// path leading up to [`AuthenticatedMemory::root`]. Recognized by [`page_fault_trap`] so
+/// [`MemorySegment`]'s read/write methods can raise [`Trap::MerkleVerificationFailed`] instead of
+/// a generic access fault.
+const AUTHENTICATED_MEMORY_VERIFICATION_FAILED: &str =
+    "page failed Merkle verification against AuthenticatedMemory's root";
+
+/// A wrapper that authenticates `M`'s pages against a binary Merkle tree, with leaves
+/// `H(page_index || page.data)`, without ever holding the tree itself resident. Only
+/// [`AuthenticatedMemory::root`] (the commitment) is kept in memory; each [`AuthenticatedMemory::get_page`]
+/// call takes the requested page's sibling path as an argument and verifies leaf-to-root against
+/// `root`, the same way [`MerkleAccumulator::verify_inclusion_proof`] verifies a proof against a
+/// root it doesn't hold the tree for. This is what lets a CPU backed by only a few resident pages
+/// still cryptographically commit to the full memory image -- the untrusted host holding the rest
+/// of the pages supplies the sibling path alongside each page it hands back.
+///
+/// Tree positions follow [`MerkleAccumulator`]'s convention: a complete binary tree over
+/// `capacity` leaves (the next power of two at or above the page count, padded with all-zero
+/// leaves), leaf `i` at per-level position `i`, with even positions as left children and odd
+/// positions as right children walking up to the root.
+///
+/// [`MerkleAccumulator`]: crate::accumulator::MerkleAccumulator
+pub struct AuthenticatedMemory<M: PagedMemory, H: Hasher<32>> {
+    inner: M,
+    n_pages: usize,
+    capacity: usize,
+    root: [u8; 32],
+    _marker: PhantomData<H>,
+}
+
+impl<M: PagedMemory, H: Hasher<32>> AuthenticatedMemory<M, H> {
+    /// Wraps `inner`, authenticating its pages against the already-computed `root` (e.g. one
+    /// published by whoever built the tree over the full memory image). Unlike building the tree
+    /// locally, this never needs to read a single page of `inner` up front.
+    pub fn new(inner: M, n_pages: usize, root: [u8; 32]) -> Self {
+        let capacity = n_pages.max(1).next_power_of_two();
+        Self {
+            inner,
+            n_pages,
+            capacity,
+            root,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The Merkle root committing to every page of the wrapped memory.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    fn hash_leaf(page_index: u32, data: &[u8]) -> [u8; 32] {
+        let mut hasher = H::new();
+        hasher.update(&page_index.to_le_bytes());
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    fn hash_internal_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = H::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize()
+    }
+
+    /// Walks `leaf` up to the root along `sibling_path` (one sibling hash per level, leaf to
+    /// root), the same way [`MerkleAccumulator::verify_inclusion_proof`] does. Shared by
+    /// [`get_page`](Self::get_page), to verify a fetched page, and
+    /// [`AuthenticatedPageRef::drop`], to recompute the root after a mutation.
+    fn root_along(mut leaf: [u8; 32], page_index: u32, sibling_path: &[[u8; 32]]) -> [u8; 32] {
+        let mut pos = page_index as usize;
+        for sibling in sibling_path {
+            leaf = if pos % 2 == 0 {
+                Self::hash_internal_node(&leaf, sibling)
+            } else {
+                Self::hash_internal_node(sibling, &leaf)
+            };
+            pos /= 2;
+        }
+        leaf
+    }
+
+    /// Fetches the page at `page_index` and verifies it against [`AuthenticatedMemory::root`]
+    /// using the caller-supplied `sibling_path` (one sibling hash per level, leaf to root) before
+    /// handing it out: a page that doesn't hash to a leaf reconciling with `root` fails with
+    /// [`AUTHENTICATED_MEMORY_VERIFICATION_FAILED`] rather than being trusted silently.
+    pub fn get_page<'a>(
+        &'a mut self,
+        page_index: u32,
+        sibling_path: &'a [[u8; 32]],
+    ) -> Result<AuthenticatedPageRef<'a, M, H>, &'static str> {
+        if page_index as usize >= self.n_pages {
+            return Err("page index out of range");
+        }
+
+        let page = self.inner.get_page(page_index)?;
+        let leaf = Self::hash_leaf(page_index, &page.data);
+        if Self::root_along(leaf, page_index, sibling_path) != self.root {
+            return Err(AUTHENTICATED_MEMORY_VERIFICATION_FAILED);
+        }
+
+        Ok(AuthenticatedPageRef {
+            page,
+            page_index,
+            root: &mut self.root,
+            sibling_path,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn flush(&mut self) -> Result<(), &'static str> {
+        self.inner.flush()
+    }
+}
+
+/// The page handle [`AuthenticatedMemory::get_page`] hands out: derefs straight through to the
+/// underlying page, and on drop recomputes the leaf from the (possibly mutated) page data and
+/// walks it back up the same `sibling_path` it was verified against, updating
+/// [`AuthenticatedMemory::root`] in `O(log n)`. Borrows only [`AuthenticatedMemory`]'s `root` field
+/// (not the whole struct), leaving the wrapped `M::PageRef` -- itself borrowed from
+/// [`AuthenticatedMemory`]'s `inner` -- free to coexist.
+pub struct AuthenticatedPageRef<'a, M: PagedMemory, H: Hasher<32>> {
+    page: M::PageRef<'a>,
+    page_index: u32,
+    root: &'a mut [u8; 32],
+    sibling_path: &'a [[u8; 32]],
+    _marker: PhantomData<H>,
+}
+
+impl<'a, M: PagedMemory, H: Hasher<32>> Deref for AuthenticatedPageRef<'a, M, H> {
+    type Target = Page;
+
+    fn deref(&self) -> &Page {
+        &self.page
+    }
+}
+
+impl<'a, M: PagedMemory, H: Hasher<32>> DerefMut for AuthenticatedPageRef<'a, M, H> {
+    fn deref_mut(&mut self) -> &mut Page {
+        &mut self.page
+    }
+}
+
+impl<'a, M: PagedMemory, H: Hasher<32>> Drop for AuthenticatedPageRef<'a, M, H> {
+    fn drop(&mut self) {
+        let leaf = AuthenticatedMemory::<M, H>::hash_leaf(self.page_index, &self.page.data);
+        *self.root = AuthenticatedMemory::<M, H>::root_along(leaf, self.page_index, self.sibling_path);
+    }
+}
+
+/// A backing store [`PagedCache`] can demand-page from, e.g. an ELF image or a host-held scratch
+/// area. Distinct from [`PagedMemory`] itself: a backend deals in raw page contents keyed by
+/// index, not resident page handles.
+pub trait PageBackend {
+    /// Reads page `page_index`'s full contents from the backend.
+    fn load(&mut self, page_index: u32) -> Result<[u8; PAGE_SIZE], Trap>;
+
+    /// Writes a dirty resident page's contents back to the backend, e.g. when it's evicted from
+    /// [`PagedCache`] or on [`PagedMemory::flush`].
+    fn store(&mut self, page_index: u32, data: &Page) -> Result<(), Trap>;
+}
+
+/// Turns a [`PageBackend`] failure into the [`&'static str`] [`PagedMemory::get_page`] expects.
+/// The richer [`Trap`] the backend produced doesn't survive this boundary (same tradeoff
+/// [`AuthenticatedMemory`] makes the other way, collapsing its own distinctive failure down to a
+/// sentinel string); [`MemorySegment`] reconstructs a generic access fault from whichever one of
+/// these comes back.
+fn backend_error_to_str(is_store: bool) -> &'static str {
+    if is_store {
+        "PagedCache: backend store failed"
+    } else {
+        "PagedCache: backend load failed"
+    }
+}
+
+struct CacheEntry {
+    page: Page,
+    dirty: bool,
+    last_used: u64,
+}
+
+/// A [`PagedMemory`] that keeps at most `capacity` pages resident from a [`PageBackend`], tracking
+/// a dirty flag per page and evicting the least-recently-used page (storing it back first if
+/// dirty) once the cache is full. This mirrors the mapmemory/softpage approach RISC-V emulators
+/// use to page in from an ELF or host, letting a guest execute programs far larger than physical
+/// RAM.
+pub struct PagedCache<B: PageBackend> {
+    backend: B,
+    capacity: usize,
+    resident: BTreeMap<u32, CacheEntry>,
+    clock: u64,
+}
+
+impl<B: PageBackend> PagedCache<B> {
+    /// Creates a cache over `backend` that keeps at most `capacity` pages resident. Panics if
+    /// `capacity` is `0`, since a cache that can hold nothing can never satisfy `get_page`.
+    pub fn new(backend: B, capacity: usize) -> Self {
+        assert!(capacity > 0, "PagedCache capacity must be at least 1");
+        Self {
+            backend,
+            capacity,
+            resident: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Evicts the least-recently-used resident page, storing it back to the backend first if
+    /// it's dirty.
+    fn evict_one(&mut self) -> Result<(), &'static str> {
+        let victim = *self
+            .resident
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(page_index, _)| page_index)
+            .expect("evict_one called on an empty cache");
+        let entry = self.resident.remove(&victim).expect("victim was just looked up");
+        if entry.dirty {
+            self.backend
+                .store(victim, &entry.page)
+                .map_err(|_| backend_error_to_str(true))?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: PageBackend> PagedMemory for PagedCache<B> {
+    type PageRef<'a> = CachePageRef<'a, B> where Self: 'a;
+
+    fn get_page(&mut self, page_index: u32) -> Result<Self::PageRef<'_>, &'static str> {
+        if !self.resident.contains_key(&page_index) {
+            if self.resident.len() >= self.capacity {
+                self.evict_one()?;
+            }
+            let data = self
+                .backend
+                .load(page_index)
+                .map_err(|_| backend_error_to_str(false))?;
+            let last_used = self.tick();
+            self.resident.insert(
+                page_index,
+                CacheEntry {
+                    page: Page { data },
+                    dirty: false,
+                    last_used,
+                },
+            );
+        }
+
+        let last_used = self.tick();
+        let entry = self.resident.get_mut(&page_index).expect("just inserted or already resident");
+        entry.last_used = last_used;
+        Ok(CachePageRef {
+            entry,
+            _marker: PhantomData,
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), &'static str> {
+        for (&page_index, entry) in self.resident.iter_mut() {
+            if entry.dirty {
+                self.backend
+                    .store(page_index, &entry.page)
+                    .map_err(|_| backend_error_to_str(true))?;
+                entry.dirty = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The [`PagedMemory::PageRef`] [`PagedCache`] hands out: derefs straight through to the resident
+/// page, marking it dirty as soon as it's mutably accessed (conservatively, since there's no way
+/// to tell a real mutation from a no-op write through `&mut Page`).
+pub struct CachePageRef<'a, B: PageBackend> {
+    entry: &'a mut CacheEntry,
+    _marker: PhantomData<B>,
+}
+
+impl<'a, B: PageBackend> Deref for CachePageRef<'a, B> {
+    type Target = Page;
+
+    fn deref(&self) -> &Page {
+        &self.entry.page
+    }
+}
+
+impl<'a, B: PageBackend> DerefMut for CachePageRef<'a, B> {
+    fn deref_mut(&mut self) -> &mut Page {
+        self.entry.dirty = true;
+        &mut self.entry.page
+    }
+}
+
 /// Represents a contiguous region of memory, implemented via a paged memory.
 #[derive(Debug)]
 pub struct MemorySegment<M: PagedMemory> {
@@ -89,38 +574,61 @@ impl<M: PagedMemory> MemorySegment<M> {
         address >= self.start_address && address < self.start_address + self.size
     }
 
+    #[inline]
+    /// Returns the number of bytes from `address` (inclusive) to the end of this segment, or
+    /// `0` if `address` isn't inside it. Lets a caller copying a guest buffer that may straddle
+    /// segment boundaries size each chunk to what's actually resolvable through this segment
+    /// before it needs to re-resolve the next one.
+    pub fn remaining_from(&self, address: u32) -> u32 {
+        if !self.contains(address) {
+            return 0;
+        }
+        self.start_address + self.size - address
+    }
+
+    /// Writes back any buffered changes in this segment's underlying [`PagedMemory`].
+    pub fn flush(&mut self) -> Result<(), &'static str> {
+        self.paged_memory.flush()
+    }
+
     /// Reads a byte from the specified address.
     #[inline]
-    pub fn read_u8(&mut self, address: u32) -> Result<u8, &'static str> {
+    pub fn read_u8(&mut self, address: u32) -> Result<u8, Trap> {
         if address < self.start_address || address > self.start_address + self.size - 1 {
-            return Err("Address out of bounds");
+            return Err(Trap::LoadAccessFault(address));
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let page = self.paged_memory.get_page(page_index)?;
+        let page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, false))?;
 
         Ok(page.data[offset])
     }
 
     /// Reads a 16-bit value from the specified address.
     #[inline]
-    pub fn read_u16(&mut self, address: u32) -> Result<u16, &'static str> {
+    pub fn read_u16(&mut self, address: u32) -> Result<u16, Trap> {
         if address < self.start_address || address > self.start_address + self.size - 2 {
-            return Err("Address out of bounds");
+            return Err(Trap::LoadAccessFault(address));
         }
 
         if address % 2 != 0 {
-            return Err("Unaligned address");
+            return Err(Trap::LoadAddressMisaligned);
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let page = self.paged_memory.get_page(page_index)?;
+        let page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, false))?;
 
         let value = u16::from_le_bytes([page.data[offset], page.data[offset + 1]]);
 
@@ -129,20 +637,23 @@ impl<M: PagedMemory> MemorySegment<M> {
 
     /// Reads a 32-bit value from the specified address.
     #[inline]
-    pub fn read_u32(&mut self, address: u32) -> Result<u32, &'static str> {
+    pub fn read_u32(&mut self, address: u32) -> Result<u32, Trap> {
         if address < self.start_address || address > self.start_address + self.size - 4 {
-            return Err("Address out of bounds");
+            return Err(Trap::LoadAccessFault(address));
         }
 
         if address % 4 != 0 {
-            return Err("Unaligned address");
+            return Err(Trap::LoadAddressMisaligned);
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let page = self.paged_memory.get_page(page_index)?;
+        let page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, false))?;
 
         let value = u32::from_le_bytes([
             page.data[offset],
@@ -156,16 +667,19 @@ impl<M: PagedMemory> MemorySegment<M> {
 
     /// Writes a byte to the specified address.
     #[inline]
-    pub fn write_u8(&mut self, address: u32, value: u8) -> Result<(), &'static str> {
+    pub fn write_u8(&mut self, address: u32, value: u8) -> Result<(), Trap> {
         if address < self.start_address || address > self.start_address + self.size - 1 {
-            return Err("Address out of bounds");
+            return Err(Trap::StoreAccessFault(address));
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let mut page = self.paged_memory.get_page(page_index)?;
+        let mut page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, true))?;
 
         page.data[offset] = value;
 
@@ -174,20 +688,23 @@ impl<M: PagedMemory> MemorySegment<M> {
 
     /// Writes a 16-bit value to the specified address.
     #[inline]
-    pub fn write_u16(&mut self, address: u32, value: u16) -> Result<(), &'static str> {
+    pub fn write_u16(&mut self, address: u32, value: u16) -> Result<(), Trap> {
         if address < self.start_address || address > self.start_address + self.size - 2 {
-            return Err("Address out of bounds");
+            return Err(Trap::StoreAccessFault(address));
         }
 
         if address % 2 != 0 {
-            return Err("Unaligned address");
+            return Err(Trap::StoreAddressMisaligned);
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let mut page = self.paged_memory.get_page(page_index)?;
+        let mut page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, true))?;
 
         page.data[offset] = value as u8;
         page.data[offset + 1] = (value >> 8) as u8;
@@ -197,20 +714,23 @@ impl<M: PagedMemory> MemorySegment<M> {
 
     /// Writes a 32-bit value to the specified address.
     #[inline]
-    pub fn write_u32(&mut self, address: u32, value: u32) -> Result<(), &'static str> {
+    pub fn write_u32(&mut self, address: u32, value: u32) -> Result<(), Trap> {
         if address < self.start_address || address > self.start_address + self.size - 4 {
-            return Err("Address out of bounds");
+            return Err(Trap::StoreAccessFault(address));
         }
 
         if address % 4 != 0 {
-            return Err("Unaligned address");
+            return Err(Trap::StoreAddressMisaligned);
         }
 
         let relative_address = address - page_start(self.start_address);
         let page_index = relative_address / (PAGE_SIZE as u32);
         let offset = (relative_address % (PAGE_SIZE as u32)) as usize;
 
-        let mut page = self.paged_memory.get_page(page_index)?;
+        let mut page = self
+            .paged_memory
+            .get_page(page_index)
+            .map_err(|e| page_fault_trap(address, e, true))?;
 
         page.data[offset] = value as u8;
         page.data[offset + 1] = (value >> 8) as u8;
@@ -221,6 +741,112 @@ impl<M: PagedMemory> MemorySegment<M> {
     }
 }
 
+/// A guest-registered trap handler: where to resume execution, and where to write the trap
+/// frame describing what was trapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TrapHandler {
+    entrypoint: u32,
+    frame: u32,
+}
+
+/// The register an ecall's syscall number is read from (`a7`/x17), and the register its
+/// arguments start at (`a0`/x10), following the RISC-V calling convention's "a7 holds the
+/// syscall number, a0..a6 hold up to 7 arguments, a0 holds the return value" amendment -- the
+/// same convention Linux's RISC-V syscall ABI uses.
+pub const ECALL_NUMBER_REG: usize = 17;
+pub const ECALL_ARG0_REG: usize = 10;
+
+/// What a [`EventHandler`] wants the CPU to do after handling a trapped ecall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcallOutcome {
+    /// Resume execution at the next instruction; `regs[ECALL_ARG0_REG]` already holds whatever
+    /// return value the handler wrote.
+    Continue,
+    /// The guest thread exited with this status code. The basic [`Cpu::execute`] loop still just
+    /// resumes at the next instruction (since it has no notion of "the program is done"); a
+    /// richer run loop built on top of `execute` is expected to check this via
+    /// [`Cpu::take_exit_code`] after every step and stop instead of fetching past the exit.
+    ExitThread(i32),
+    /// The guest wants to yield control back to the host (e.g. a long-running operation
+    /// ECALL_YIELD polls for cancellation on). Like `ExitThread`, surfaced to the embedder via
+    /// [`Cpu::take_yielded`] rather than treated as an error.
+    Yield,
+}
+
+/// A view of a [`Cpu`]'s three memory segments, handed to [`EventHandler::handle_ecall`] so a
+/// handler can marshal pointer-taking ecall arguments (e.g. read a buffer the guest passed by
+/// address) without needing access to the rest of `Cpu`.
+pub struct CpuMemory<'a, M: PagedMemory> {
+    pub code_seg: &'a mut MemorySegment<M>,
+    pub data_seg: &'a mut MemorySegment<M>,
+    pub stack_seg: &'a mut MemorySegment<M>,
+}
+
+impl<'a, M: PagedMemory> CpuMemory<'a, M> {
+    /// Reads a byte, checking the stack, data and code segments in that order (the same order
+    /// [`Cpu`]'s own address dispatch uses).
+    pub fn read_u8(&mut self, address: u32) -> Result<u8, Trap> {
+        if self.stack_seg.contains(address) {
+            self.stack_seg.read_u8(address)
+        } else if self.data_seg.contains(address) {
+            self.data_seg.read_u8(address)
+        } else if self.code_seg.contains(address) {
+            self.code_seg.read_u8(address)
+        } else {
+            Err(Trap::LoadAccessFault(address))
+        }
+    }
+
+    /// Reads a 32-bit value; see [`CpuMemory::read_u8`] for the segment dispatch order.
+    pub fn read_u32(&mut self, address: u32) -> Result<u32, Trap> {
+        if self.stack_seg.contains(address) {
+            self.stack_seg.read_u32(address)
+        } else if self.data_seg.contains(address) {
+            self.data_seg.read_u32(address)
+        } else if self.code_seg.contains(address) {
+            self.code_seg.read_u32(address)
+        } else {
+            Err(Trap::LoadAccessFault(address))
+        }
+    }
+
+    /// Writes a byte. Code is read-only from an ecall's perspective, same as from `execute`.
+    pub fn write_u8(&mut self, address: u32, value: u8) -> Result<(), Trap> {
+        if self.stack_seg.contains(address) {
+            self.stack_seg.write_u8(address, value)
+        } else if self.data_seg.contains(address) {
+            self.data_seg.write_u8(address, value)
+        } else {
+            Err(Trap::StoreAccessFault(address))
+        }
+    }
+
+    /// Writes a 32-bit value; see [`CpuMemory::write_u8`] for why code is excluded.
+    pub fn write_u32(&mut self, address: u32, value: u32) -> Result<(), Trap> {
+        if self.stack_seg.contains(address) {
+            self.stack_seg.write_u32(address, value)
+        } else if self.data_seg.contains(address) {
+            self.data_seg.write_u32(address, value)
+        } else {
+            Err(Trap::StoreAccessFault(address))
+        }
+    }
+}
+
+/// Services the ecalls a guest program traps into -- the hashing/signing/randomness/UX ecalls
+/// the `app-sdk` guest-side wrappers already assume exist (see e.g. `app-sdk::ecalls`). Plugged
+/// into a [`Cpu`] via [`Cpu::set_event_handler`] so the emulator's instruction-execution core
+/// stays decoupled from what any particular ecall actually does.
+pub trait EventHandler<M: PagedMemory> {
+    /// Called when the CPU traps `Op::Ecall`. `regs` is the full register file -- the syscall
+    /// number has already been read from `regs[ECALL_NUMBER_REG]` by the caller, but the handler
+    /// reads its own arguments from `regs[ECALL_ARG0_REG..]` and writes its return value back to
+    /// `regs[ECALL_ARG0_REG]` itself, since the number of arguments and whether there even is a
+    /// return value varies per ecall. `memory` lets it dereference pointer arguments.
+    fn handle_ecall(&mut self, regs: &mut [u32; 32], memory: &mut CpuMemory<'_, M>)
+        -> EcallOutcome;
+}
+
 /// Represents the state of the Risc-V CPU, with registers and three memory segments
 /// for code, data and stack.
 pub struct Cpu<M: PagedMemory> {
@@ -229,6 +855,30 @@ pub struct Cpu<M: PagedMemory> {
     pub code_seg: MemorySegment<M>,
     pub data_seg: MemorySegment<M>,
     pub stack_seg: MemorySegment<M>,
+    /// The guest's trap handler, if one has been registered with [`Cpu::set_trap_handler`].
+    trap_handler: Option<TrapHandler>,
+    /// Services trapped ecalls, if one has been registered with [`Cpu::set_event_handler`]. An
+    /// `Op::Ecall` with no handler registered behaves as an unhandled-ecall fault.
+    event_handler: Option<Box<dyn EventHandler<M>>>,
+    /// Set by [`EcallOutcome::ExitThread`]; drained by [`Cpu::take_exit_code`].
+    exit_code: Option<i32>,
+    /// Set by [`EcallOutcome::Yield`]; drained by [`Cpu::take_yielded`].
+    yielded: bool,
+    /// Cycles charged so far (see [`Cpu::run_bounded`]), one per instruction unless
+    /// [`Cpu::set_cost_table`] has been called.
+    cycles: u64,
+    /// Per-instruction-class cycle weights charged to `cycles`; `None` charges a flat `1`.
+    cost_table: Option<GasCostTable>,
+    /// Decoded-instruction cache (see [`Cpu::set_decode_cache_size`]); `None` disables it.
+    decode_cache: Option<Vec<Option<DecodeCacheEntry>>>,
+}
+
+/// One slot of [`Cpu`]'s decoded-instruction cache: the address the cached [`Op`] was decoded
+/// from, so a direct-mapped collision with a different address is detected as a miss.
+#[derive(Clone, Copy)]
+struct DecodeCacheEntry {
+    address: u32,
+    op: Op,
 }
 
 impl<M: PagedMemory> fmt::Debug for Cpu<M> {
@@ -240,6 +890,166 @@ impl<M: PagedMemory> fmt::Debug for Cpu<M> {
     }
 }
 
+/// Extracts the inclusive bit range `[hi:lo]` of `v`, right-aligned.
+#[inline(always)]
+fn bits(v: u16, hi: u32, lo: u32) -> u32 {
+    ((v as u32) >> lo) & ((1u32 << (hi - lo + 1)) - 1)
+}
+
+/// Extracts a single bit of `v`, right-aligned.
+#[inline(always)]
+fn bit(v: u16, n: u32) -> u32 {
+    bits(v, n, n)
+}
+
+/// Sign-extends the low `width` bits of `value` to a full `i32`.
+#[inline(always)]
+fn sign_extend(value: u32, width: u32) -> i32 {
+    let shift = 32 - width;
+    ((value << shift) as i32) >> shift
+}
+
+/// Expands a 16-bit RVC parcel (as returned by [`Cpu::fetch_instruction`] for a compressed
+/// instruction) into its 32-bit [`Op`] equivalent. Only the subset of the C extension commonly
+/// emitted by `rv32imc` toolchains is expanded -- `C.ADDI`/`C.LI`/`C.LUI`, `C.LW`/`C.SW`,
+/// `C.J`/`C.JAL`, `C.BEQZ`/`C.BNEZ`, `C.MV`/`C.ADD`, `C.JR`/`C.JALR`, and `C.SLLI`; anything else
+/// (e.g. `C.ADDI16SP`, `C.EBREAK`, the `CB`-format ALU ops, the stack-pointer-relative loads and
+/// stores) decodes to [`Op::Unknown`], the same as an unrecognized 32-bit instruction.
+fn expand_compressed(parcel: u16) -> Op {
+    let quadrant = parcel & 0b11;
+    let funct3 = bits(parcel, 15, 13);
+    // CR/CI-format 5-bit register fields.
+    let rd_rs1 = bits(parcel, 11, 7) as u8;
+    let rs2 = bits(parcel, 6, 2) as u8;
+    // CL/CS/CB-format 3-bit register fields, biased into x8..=x15.
+    let rd_prime = (bits(parcel, 4, 2) + 8) as u8;
+    let rs1_prime = (bits(parcel, 9, 7) + 8) as u8;
+
+    match quadrant {
+        0b00 => match funct3 {
+            0b010 => {
+                // C.LW
+                let imm = (bits(parcel, 12, 10) << 3) | (bit(parcel, 6) << 2) | (bit(parcel, 5) << 6);
+                Op::Lw { rd: rd_prime, rs1: rs1_prime, imm: imm as i32 }
+            }
+            0b110 => {
+                // C.SW
+                let imm = (bits(parcel, 12, 10) << 3) | (bit(parcel, 6) << 2) | (bit(parcel, 5) << 6);
+                Op::Sw { rs1: rs1_prime, rs2: rd_prime, imm: imm as i32 }
+            }
+            _ => Op::Unknown,
+        },
+        0b01 => match funct3 {
+            0b000 => {
+                // C.ADDI (rd == 0 is C.NOP, which this still decodes to correctly as a no-op add)
+                let imm = sign_extend((bit(parcel, 12) << 5) | bits(parcel, 6, 2), 6);
+                Op::Addi { rd: rd_rs1, rs1: rd_rs1, imm }
+            }
+            0b001 => {
+                // C.JAL (RV32-only encoding; rd is implicitly x1)
+                let imm = sign_extend(
+                    (bit(parcel, 12) << 11)
+                        | (bit(parcel, 11) << 4)
+                        | (bits(parcel, 10, 9) << 8)
+                        | (bit(parcel, 8) << 10)
+                        | (bit(parcel, 7) << 6)
+                        | (bit(parcel, 6) << 7)
+                        | (bits(parcel, 5, 3) << 1)
+                        | (bit(parcel, 2) << 5),
+                    12,
+                );
+                Op::Jal { rd: 1, imm }
+            }
+            0b010 => {
+                // C.LI
+                let imm = sign_extend((bit(parcel, 12) << 5) | bits(parcel, 6, 2), 6);
+                Op::Addi { rd: rd_rs1, rs1: 0, imm }
+            }
+            0b011 if rd_rs1 != 0 && rd_rs1 != 2 => {
+                // C.LUI (rd == 2 is C.ADDI16SP instead, which isn't expanded)
+                let raw = (bit(parcel, 12) << 5) | bits(parcel, 6, 2);
+                Op::Lui { rd: rd_rs1, imm: sign_extend(raw, 6) << 12 }
+            }
+            0b101 => {
+                // C.J
+                let imm = sign_extend(
+                    (bit(parcel, 12) << 11)
+                        | (bit(parcel, 11) << 4)
+                        | (bits(parcel, 10, 9) << 8)
+                        | (bit(parcel, 8) << 10)
+                        | (bit(parcel, 7) << 6)
+                        | (bit(parcel, 6) << 7)
+                        | (bits(parcel, 5, 3) << 1)
+                        | (bit(parcel, 2) << 5),
+                    12,
+                );
+                Op::Jal { rd: 0, imm }
+            }
+            0b110 => {
+                // C.BEQZ
+                let imm = sign_extend(
+                    (bit(parcel, 12) << 8)
+                        | (bits(parcel, 11, 10) << 3)
+                        | (bits(parcel, 6, 5) << 6)
+                        | (bits(parcel, 4, 3) << 1)
+                        | (bit(parcel, 2) << 5),
+                    9,
+                );
+                Op::Beq { rs1: rs1_prime, rs2: 0, imm }
+            }
+            0b111 => {
+                // C.BNEZ
+                let imm = sign_extend(
+                    (bit(parcel, 12) << 8)
+                        | (bits(parcel, 11, 10) << 3)
+                        | (bits(parcel, 6, 5) << 6)
+                        | (bits(parcel, 4, 3) << 1)
+                        | (bit(parcel, 2) << 5),
+                    9,
+                );
+                Op::Bne { rs1: rs1_prime, rs2: 0, imm }
+            }
+            _ => Op::Unknown,
+        },
+        0b10 => match funct3 {
+            0b000 => {
+                // C.SLLI
+                Op::Slli { rd: rd_rs1, rs1: rd_rs1, imm: bits(parcel, 6, 2) }
+            }
+            0b100 if bit(parcel, 12) == 0 && rs2 != 0 => {
+                // C.MV
+                Op::Add { rd: rd_rs1, rs1: 0, rs2 }
+            }
+            0b100 if bit(parcel, 12) == 0 => {
+                // C.JR (rs2 == 0, rd_rs1 != 0 is guaranteed by the encoding)
+                Op::Jalr { rd: 0, rs1: rd_rs1, imm: 0 }
+            }
+            0b100 if rs2 != 0 => {
+                // C.ADD
+                Op::Add { rd: rd_rs1, rs1: rd_rs1, rs2 }
+            }
+            0b100 if rd_rs1 != 0 => {
+                // C.JALR (rs2 == 0, rd_rs1 != 0; rd_rs1 == 0 would be C.EBREAK instead)
+                Op::Jalr { rd: 1, rs1: rd_rs1, imm: 0 }
+            }
+            _ => Op::Unknown,
+        },
+        _ => Op::Unknown, // quadrant 0b11 marks a full-width instruction, never reached here.
+    }
+}
+
+/// Traps with [`Trap::InstructionAddressMisaligned`] if the branch/jump target computed from
+/// `pc + imm` isn't 2-byte aligned -- the alignment RVC relaxes this to, now that `Cpu` can fetch
+/// compressed instructions starting on any halfword boundary.
+#[inline(always)]
+fn check_branch_target_aligned(pc: u32, imm: i32) -> Result<(), Trap> {
+    if pc.wrapping_add(imm as u32) & 1 != 0 {
+        Err(Trap::InstructionAddressMisaligned)
+    } else {
+        Ok(())
+    }
+}
+
 impl<M: PagedMemory> Cpu<M> {
     /// Creates a new `Cpu` instance.
     pub fn new(
@@ -254,10 +1064,159 @@ impl<M: PagedMemory> Cpu<M> {
             code_seg,
             data_seg,
             stack_seg,
+            trap_handler: None,
+            event_handler: None,
+            exit_code: None,
+            yielded: false,
+            cycles: 0,
+            cost_table: None,
+            decode_cache: None,
+        }
+    }
+
+    /// Cycles charged so far (see [`Cpu::run_bounded`]).
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Sets the per-instruction-class cycle weights [`Cpu::run_bounded`] charges to `cycles` (see
+    /// [`GasCostTable`]). Without one, every instruction costs a flat `1` cycle.
+    pub fn set_cost_table(&mut self, table: GasCostTable) {
+        self.cost_table = Some(table);
+    }
+
+    /// Enables a decoded-instruction cache with at least `size` direct-mapped slots (rounded up
+    /// to the next power of two), checked by `execute_inner` before calling into
+    /// `riscv::decode`/`expand_compressed` again. Worthwhile for hot loops, where the same few
+    /// addresses get decoded over and over; small targets that never call this pay nothing for
+    /// it. Sound only because the code segment is read-only in this VM (see [`CpuMemory`]) -- a
+    /// cached entry can never go stale once written.
+    pub fn set_decode_cache_size(&mut self, size: usize) {
+        let capacity = size.max(1).next_power_of_two();
+        self.decode_cache = Some(vec![None; capacity]);
+    }
+
+    /// Disables the decoded-instruction cache set up by [`Cpu::set_decode_cache_size`].
+    pub fn clear_decode_cache(&mut self) {
+        self.decode_cache = None;
+    }
+
+    /// Decodes the instruction `inst` fetched from `address`, consulting and populating the
+    /// decode cache (see [`Cpu::set_decode_cache_size`]) if one is enabled.
+    #[inline(always)]
+    fn decode_cached(&mut self, address: u32, inst: u32, is_compressed: bool) -> Op {
+        let Some(cache) = &mut self.decode_cache else {
+            return Self::decode_raw(inst, is_compressed);
+        };
+        let slot = (address >> 2) as usize & (cache.len() - 1);
+        if let Some(entry) = &cache[slot] {
+            if entry.address == address {
+                return entry.op;
+            }
+        }
+        let op = Self::decode_raw(inst, is_compressed);
+        cache[slot] = Some(DecodeCacheEntry { address, op });
+        op
+    }
+
+    #[inline(always)]
+    fn decode_raw(inst: u32, is_compressed: bool) -> Op {
+        if is_compressed {
+            expand_compressed(inst as u16)
+        } else {
+            crate::riscv::decode::decode(inst)
         }
     }
 
-    fn read_u8(&mut self, address: u32) -> Result<u8, &'static str> {
+    /// Registers `entrypoint` as the guest's trap handler and `frame` as the guest address of a
+    /// 12-byte trap frame. A recoverable fault now writes a `{ trap_cause, faulting_addr,
+    /// faulting_pc }` record to `frame`, pushes the trapping `pc` onto the guest stack as a
+    /// return address, and transfers control to `entrypoint`, instead of aborting execution.
+    pub fn set_trap_handler(&mut self, entrypoint: u32, frame: u32) {
+        self.trap_handler = Some(TrapHandler { entrypoint, frame });
+    }
+
+    /// Clears a previously-registered trap handler; subsequent faults are unhandled again.
+    pub fn clear_trap_handler(&mut self) {
+        self.trap_handler = None;
+    }
+
+    /// Registers the [`EventHandler`] that services this CPU's trapped ecalls.
+    pub fn set_event_handler(&mut self, handler: impl EventHandler<M> + 'static) {
+        self.event_handler = Some(Box::new(handler));
+    }
+
+    /// Clears a previously-registered event handler; subsequent ecalls are unhandled again.
+    pub fn clear_event_handler(&mut self) {
+        self.event_handler = None;
+    }
+
+    /// Takes the exit code set by the most recent [`EcallOutcome::ExitThread`], if any, clearing
+    /// it. A run loop built on top of [`Cpu::execute`] should call this after every step and stop
+    /// instead of fetching another instruction once it returns `Some`.
+    pub fn take_exit_code(&mut self) -> Option<i32> {
+        self.exit_code.take()
+    }
+
+    /// Takes the yield flag set by the most recent [`EcallOutcome::Yield`], if any, clearing it.
+    pub fn take_yielded(&mut self) -> bool {
+        core::mem::replace(&mut self.yielded, false)
+    }
+
+    /// Delivers a trap to the guest: writes `{ trap_cause, faulting_addr, faulting_pc }` into the
+    /// registered trap frame, pushes the trapping `pc` onto the guest stack as a return address,
+    /// and transfers control to the registered handler. Returns `false` if no handler is
+    /// registered, or if writing the frame or stack fails (e.g. the guest registered a bad
+    /// address) -- callers should fall back to their own fatal-error handling in that case.
+    pub fn raise_trap(&mut self, trap_cause: u32, faulting_addr: u32) -> bool {
+        let Some(TrapHandler { entrypoint, frame }) = self.trap_handler else {
+            return false;
+        };
+
+        let faulting_pc = self.pc;
+        let delivered = self
+            .write_u32(frame, trap_cause)
+            .and_then(|_| self.write_u32(frame + 4, faulting_addr))
+            .and_then(|_| self.write_u32(frame + 8, faulting_pc))
+            .and_then(|_| {
+                // x2 is the stack pointer; push the trapping pc as a return address so the
+                // guest's handler can resume or unwind the faulting code if it chooses to.
+                let new_sp = self.regs[2].wrapping_sub(4);
+                self.write_u32(new_sp, faulting_pc)?;
+                self.regs[2] = new_sp;
+                Ok(())
+            })
+            .is_ok();
+
+        if delivered {
+            self.pc = entrypoint;
+        }
+        delivered
+    }
+
+    /// Flushes every segment's underlying [`PagedMemory`], writing back any dirty pages still
+    /// only held in a resident cache. Meant to be called at guest exit and other syscall
+    /// boundaries where the host needs an up-to-date view of memory, rather than waiting for
+    /// pages to be flushed lazily on eviction.
+    pub fn flush_all(&mut self) -> Result<(), &'static str> {
+        self.code_seg.flush()?;
+        self.data_seg.flush()?;
+        self.stack_seg.flush()?;
+        Ok(())
+    }
+
+    /// Delivers `fault` to the guest via [`Cpu::raise_trap`], returning `Ok(())` so the VM keeps
+    /// running if it was delivered. Otherwise returns the fault so the caller can surface it to
+    /// the host as a `BufferType::Fault` buffer.
+    fn raise_fault(&mut self, fault: FaultRecord) -> Result<(), FaultRecord> {
+        if self.raise_trap(fault.cause as u32, fault.address) {
+            Ok(())
+        } else {
+            Err(fault)
+        }
+    }
+
+    fn read_u8(&mut self, address: u32) -> Result<u8, Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.read_u8(address);
         } else if self.data_seg.contains(address) {
@@ -265,10 +1224,10 @@ impl<M: PagedMemory> Cpu<M> {
         } else if self.code_seg.contains(address) {
             return self.code_seg.read_u8(address);
         }
-        Err("Address out of bounds")
+        Err(Trap::LoadAccessFault(address))
     }
 
-    fn read_u16(&mut self, address: u32) -> Result<u16, &'static str> {
+    fn read_u16(&mut self, address: u32) -> Result<u16, Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.read_u16(address);
         } else if self.data_seg.contains(address) {
@@ -276,10 +1235,10 @@ impl<M: PagedMemory> Cpu<M> {
         } else if self.code_seg.contains(address) {
             return self.code_seg.read_u16(address);
         }
-        Err("Address out of bounds")
+        Err(Trap::LoadAccessFault(address))
     }
 
-    fn read_u32(&mut self, address: u32) -> Result<u32, &'static str> {
+    fn read_u32(&mut self, address: u32) -> Result<u32, Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.read_u32(address);
         } else if self.data_seg.contains(address) {
@@ -287,45 +1246,102 @@ impl<M: PagedMemory> Cpu<M> {
         } else if self.code_seg.contains(address) {
             return self.code_seg.read_u32(address);
         }
-        Err("Address out of bounds")
+        Err(Trap::LoadAccessFault(address))
     }
 
-    fn write_u8(&mut self, address: u32, value: u8) -> Result<(), &'static str> {
+    fn write_u8(&mut self, address: u32, value: u8) -> Result<(), Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.write_u8(address, value);
         } else if self.data_seg.contains(address) {
             return self.data_seg.write_u8(address, value);
         }
-        Err("Address out of bounds")
+        Err(Trap::StoreAccessFault(address))
     }
 
-    fn write_u16(&mut self, address: u32, value: u16) -> Result<(), &'static str> {
+    fn write_u16(&mut self, address: u32, value: u16) -> Result<(), Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.write_u16(address, value);
         } else if self.data_seg.contains(address) {
             return self.data_seg.write_u16(address, value);
         }
-        Err("Address out of bounds")
+        Err(Trap::StoreAccessFault(address))
     }
 
-    fn write_u32(&mut self, address: u32, value: u32) -> Result<(), &'static str> {
+    fn write_u32(&mut self, address: u32, value: u32) -> Result<(), Trap> {
         if self.stack_seg.contains(address) {
             return self.stack_seg.write_u32(address, value);
         } else if self.data_seg.contains(address) {
             return self.data_seg.write_u32(address, value);
         }
-        Err("Address out of bounds")
+        Err(Trap::StoreAccessFault(address))
     }
 
     #[inline(always)]
-    /// Fetches the next instruction to be executed.
-    pub fn fetch_instruction(&mut self) -> Result<u32, &'static str> {
-        self.code_seg.read_u32(self.pc)
+    /// Fetches the next instruction to be executed. Reads the first halfword at `pc` and
+    /// inspects its low two bits: anything other than `0b11` is a complete 16-bit RVC
+    /// instruction, returned zero-extended to `u32`; otherwise the low bits mark a 32-bit
+    /// instruction and the remaining halfword is read too. [`Cpu::execute_inner`] tells the two
+    /// apart the same way (`inst & 0b11 != 0b11`), so the returned value alone is enough to
+    /// decode and size the instruction correctly.
+    pub fn fetch_instruction(&mut self) -> Result<u32, Trap> {
+        let low = match self.code_seg.read_u16(self.pc) {
+            Err(Trap::LoadAddressMisaligned) => return Err(Trap::InstructionAddressMisaligned),
+            other => other?,
+        };
+        if low & 0b11 != 0b11 {
+            return Ok(low as u32);
+        }
+        // A 32-bit instruction need only be 2-byte aligned once RVC is in play, so the second
+        // halfword is read directly rather than via `read_u32` (which still requires 4-byte
+        // alignment, correctly, for plain data accesses).
+        let high = match self.code_seg.read_u16(self.pc.wrapping_add(2)) {
+            Err(Trap::LoadAddressMisaligned) => return Err(Trap::InstructionAddressMisaligned),
+            other => other?,
+        };
+        Ok((low as u32) | ((high as u32) << 16))
+    }
+
+    /// Classifies `inst` for gas accounting (see [`InstructionClass`]), without executing it.
+    /// Meant to be called by the loop driving [`Cpu::execute`] alongside each fetched
+    /// instruction, so it can charge [`crate::metrics::MetricsSink::on_instruction_gas`] for the
+    /// right class. `inst` is whatever [`Cpu::fetch_instruction`] returned, compressed or not.
+    pub fn classify(inst: u32) -> InstructionClass {
+        let op = Self::decode_raw(inst, inst & 0b11 != 0b11);
+        match op {
+            Op::Lb { .. }
+            | Op::Lh { .. }
+            | Op::Lw { .. }
+            | Op::Lbu { .. }
+            | Op::Lhu { .. }
+            | Op::Sb { .. }
+            | Op::Sh { .. }
+            | Op::Sw { .. } => InstructionClass::LoadStore,
+            Op::Ecall => InstructionClass::Ecall,
+            _ => InstructionClass::Arithmetic,
+        }
+    }
+
+    /// Decodes and executes `inst`. A recoverable fault (illegal instruction, breakpoint, or
+    /// misaligned/out-of-bounds memory access) is delivered via [`Cpu::raise_fault`]: if a trap
+    /// handler is registered this returns `Ok(())` with control already transferred to it,
+    /// otherwise it returns the [`FaultRecord`] so the caller can surface it to the host as a
+    /// `BufferType::Fault` buffer.
+    #[inline(always)]
+    pub fn execute(&mut self, inst: u32) -> Result<(), FaultRecord> {
+        let faulting_pc = self.pc;
+        match self.execute_inner(inst) {
+            Ok(()) => Ok(()),
+            Err(trap) => self.raise_fault(FaultRecord {
+                cause: trap.cause(),
+                pc: faulting_pc,
+                address: trap.address(),
+            }),
+        }
     }
 
     #[rustfmt::skip]
     #[inline(always)]
-    pub fn execute(&mut self, inst: u32) -> Result<(), &'static str> {
+    fn execute_inner(&mut self, inst: u32) -> Result<(), Trap> {
         // TODO: for now, treat everything as a NOP
         // This is a placeholder for actual instruction decoding and execution logic
         // match inst {
@@ -333,10 +1349,14 @@ impl<M: PagedMemory> Cpu<M> {
         //     _ => panic!("Unknown instruction"),
         // }
 
-        let mut pc_inc: u32 = 4;
-        const INST_SIZE: u32 = 4;
+        // A 16-bit RVC parcel always has `0b11` in its low two bits when it is in fact a
+        // full-width 32-bit instruction; anything else is a compressed instruction, per
+        // `fetch_instruction`, which only reads the second halfword once it sees that marker.
+        let is_compressed = inst & 0b11 != 0b11;
+        let inst_size: u32 = if is_compressed { 2 } else { 4 };
+        let mut pc_inc: u32 = inst_size;
 
-        let op = crate::riscv::decode::decode(inst);
+        let op = self.decode_cached(self.pc, inst, is_compressed);
         match op {
             Op::Add { rd, rs1, rs2 } => { self.regs[rd as usize] = self.regs[rs1 as usize].wrapping_add(self.regs[rs2 as usize]); },
             Op::Sub { rd, rs1, rs2 } => { self.regs[rd as usize] = self.regs[rs1 as usize].wrapping_sub(self.regs[rs2 as usize]); },
@@ -353,41 +1373,51 @@ impl<M: PagedMemory> Cpu<M> {
             Op::Auipc { rd, imm } => { self.regs[rd as usize] = self.pc.wrapping_add(imm as u32); },
             Op::Beq { rs1, rs2, imm } => {
                 if self.regs[rs1 as usize] == self.regs[rs2 as usize] {
+                    check_branch_target_aligned(self.pc, imm)?;
                     pc_inc = imm as u32;
                 }
             },
             Op::Bne { rs1, rs2, imm } => {
                 if self.regs[rs1 as usize] != self.regs[rs2 as usize] {
+                    check_branch_target_aligned(self.pc, imm)?;
                     pc_inc = imm as u32;
                 }
             },
             Op::Blt { rs1, rs2, imm } => {
                 if (self.regs[rs1 as usize] as i32) < (self.regs[rs2 as usize] as i32) {
+                    check_branch_target_aligned(self.pc, imm)?;
                     pc_inc = imm as u32;
                 }
             },
             Op::Bge { rs1, rs2, imm } => {
                 if (self.regs[rs1 as usize] as i32) >= (self.regs[rs2 as usize] as i32) {
+                    check_branch_target_aligned(self.pc, imm)?;
                     pc_inc = imm as u32;
                 }
             },
             Op::Bltu { rs1, rs2, imm } => {
                 if self.regs[rs1 as usize] < self.regs[rs2 as usize] {
+                    check_branch_target_aligned(self.pc, imm)?;
                     pc_inc = imm as u32;
                 }
             },
             Op::Bgeu { rs1, rs2, imm } => {
                 if self.regs[rs1 as usize] >= self.regs[rs2 as usize] {
-                    self.pc = self.pc.wrapping_add(imm as u32);
+                    check_branch_target_aligned(self.pc, imm)?;
+                    pc_inc = imm as u32;
                 }
             },
             Op::Jal { rd, imm } => {
+                check_branch_target_aligned(self.pc, imm)?;
                 pc_inc = imm as u32;
-                self.regs[rd as usize] = self.pc.wrapping_add(INST_SIZE);
+                self.regs[rd as usize] = self.pc.wrapping_add(inst_size);
             },
             Op::Jalr { rd, rs1, imm } => {
+                // Masking off bit 0 (rather than requiring 4-byte alignment) is what makes a
+                // computed target 2-byte-aligned valid now that RVC instructions can start on
+                // any halfword boundary.
                 let new_pc = self.regs[rs1 as usize].wrapping_add(imm as u32) & !1;
-                self.regs[rd as usize] = self.pc.wrapping_add(INST_SIZE);
+                self.regs[rd as usize] = self.pc.wrapping_add(inst_size);
                 self.pc = new_pc;
                 pc_inc = 0;
             },
@@ -398,17 +1428,11 @@ impl<M: PagedMemory> Cpu<M> {
             },
             Op::Lh { rd, rs1, imm } => {
                 let addr = self.regs[rs1 as usize].wrapping_add(imm as u32);
-                if addr & 1 != 0 {
-                    return Err("Unaligned 16-bit read");
-                }
                 let value = self.read_u16(addr)?;
                 self.regs[rd as usize] = value as i16 as i32 as u32;
             },
             Op::Lw { rd, rs1, imm } => {
                 let addr = self.regs[rs1 as usize].wrapping_add(imm as u32);
-                if addr & 3 != 0 {
-                    return Err("Unaligned 32-bit read");
-                }
                 let value = self.read_u32(addr)?;
                 self.regs[rd as usize] = value;
             },
@@ -419,9 +1443,6 @@ impl<M: PagedMemory> Cpu<M> {
             },
             Op::Lhu { rd, rs1, imm } => {
                 let addr = self.regs[rs1 as usize].wrapping_add(imm as u32);
-                if addr & 1 != 0 {
-                    return Err("Unaligned 16-bit read");
-                }
                 let value = self.read_u16(addr)?;
                 self.regs[rd as usize] = value as u32;
             },
@@ -434,17 +1455,11 @@ impl<M: PagedMemory> Cpu<M> {
             },
             Op::Sh { rs1, rs2, imm } => {
                 let addr = self.regs[rs1 as usize].wrapping_add(imm as u32);
-                if addr & 1 != 0 {
-                    return Err("Unaligned 16-bit write");
-                }
                 let value = self.regs[rs2 as usize] as u16;
                 self.write_u16(addr, value)?;
             },
             Op::Sw { rs1, rs2, imm } => {
                 let addr = self.regs[rs1 as usize].wrapping_add(imm as u32);
-                if addr & 3 != 0 {
-                    return Err("Unaligned 32-bit write");
-                }
                 let value = self.regs[rs2 as usize];
                 self.write_u32(addr, value)?;
             },
@@ -456,13 +1471,27 @@ impl<M: PagedMemory> Cpu<M> {
             Op::Xori { rd, rs1, imm } => { self.regs[rd as usize] = self.regs[rs1 as usize] ^ (imm as u32); },
 
             Op::Ecall => {
-                todo!();
+                let Some(mut handler) = self.event_handler.take() else {
+                    return Err(Trap::EnvironmentCall);
+                };
+                let mut memory = CpuMemory {
+                    code_seg: &mut self.code_seg,
+                    data_seg: &mut self.data_seg,
+                    stack_seg: &mut self.stack_seg,
+                };
+                let outcome = handler.handle_ecall(&mut self.regs, &mut memory);
+                self.event_handler = Some(handler);
+                match outcome {
+                    EcallOutcome::Continue => {}
+                    EcallOutcome::ExitThread(code) => self.exit_code = Some(code),
+                    EcallOutcome::Yield => self.yielded = true,
+                }
             },
             Op::Break => {
-                todo!();
+                return Err(Trap::Breakpoint);
             },
             Op::Unknown => {
-                return Err("Unknown instruction");
+                return Err(Trap::IllegalInstruction(inst));
             },
         }
 
@@ -471,12 +1500,105 @@ impl<M: PagedMemory> Cpu<M> {
 
         Ok(())
     }
+
+    /// Fetches and executes a single instruction, reporting the outcome as a [`StepOutcome`]
+    /// instead of converting a trap straight into a [`FaultRecord`]. Unlike [`Cpu::execute`],
+    /// this does not consult or invoke a guest trap handler: a trap always stops the step and is
+    /// reported to the caller directly, which is what [`Cpu::run`] needs to implement bounded
+    /// execution around [`EcallOutcome::ExitThread`]/[`EcallOutcome::Yield`].
+    pub fn step(&mut self) -> StepOutcome {
+        let faulting_pc = self.pc;
+        let inst = match self.fetch_instruction() {
+            Ok(inst) => inst,
+            Err(trap) => {
+                return StepOutcome::Trapped {
+                    trap,
+                    pc: faulting_pc,
+                }
+            }
+        };
+        let cost = match &self.cost_table {
+            Some(table) => table.cost(Self::classify(inst)),
+            None => 1,
+        };
+        self.cycles = self.cycles.saturating_add(cost);
+        match self.execute_inner(inst) {
+            Ok(()) => {
+                if let Some(code) = self.take_exit_code() {
+                    StepOutcome::Exited(code)
+                } else {
+                    StepOutcome::Continue
+                }
+            }
+            Err(trap) => StepOutcome::Trapped {
+                trap,
+                pc: faulting_pc,
+            },
+        }
+    }
+
+    /// Runs [`Cpu::step`] in a loop until the guest exits, traps, or yields.
+    ///
+    /// A [`EcallOutcome::Yield`] ends the run with [`StepOutcome::Continue`], handing control
+    /// back to the caller (e.g. to let the host service an async request) without losing the
+    /// trap/exit distinction a single `step()` call would otherwise report.
+    pub fn run(&mut self) -> StepOutcome {
+        loop {
+            match self.step() {
+                StepOutcome::Continue => {
+                    if self.take_yielded() {
+                        return StepOutcome::Continue;
+                    }
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+
+    /// Like [`Cpu::run`], but stops with [`RunResult::BudgetExhausted`] once `max_cycles` worth of
+    /// instructions (weighted by [`Cpu::set_cost_table`], if set) have been charged against
+    /// [`Cpu::cycles`], instead of running forever. The budget is only ever checked between
+    /// instructions, so a `BudgetExhausted` result always leaves `pc` and the register file in a
+    /// resumable state -- calling `run_bounded` again continues right where it left off, with a
+    /// fresh budget.
+    pub fn run_bounded(&mut self, max_cycles: u64) -> RunResult {
+        let budget = self.cycles.saturating_add(max_cycles);
+        loop {
+            if self.cycles >= budget {
+                return RunResult::BudgetExhausted;
+            }
+            match self.step() {
+                StepOutcome::Continue => {
+                    if self.take_yielded() {
+                        return RunResult::Continue;
+                    }
+                }
+                outcome => return outcome.into(),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fault_record_to_bytes() {
+        let fault = FaultRecord {
+            cause: FaultCause::LoadAccessFault,
+            pc: 0x1000,
+            address: 0xdead_beef,
+        };
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&(FaultCause::LoadAccessFault as u32).to_le_bytes());
+        expected.extend_from_slice(&0x1000u32.to_le_bytes());
+        expected.extend_from_slice(&0xdead_beefu32.to_le_bytes());
+
+        assert_eq!(&fault.to_bytes(), expected.as_slice());
+    }
+
     #[test]
     fn test_vec_memory_new() {
         let n_pages = 5;
@@ -518,4 +1640,360 @@ mod tests {
         let page = vec_memory.get_page(page_index).expect("Page should exist");
         assert_eq!(page.data[42], 42);
     }
+
+    fn test_cpu(code: &[u32]) -> Cpu<VecMemory> {
+        let code_seg = MemorySegment::new(0, PAGE_SIZE as u32, VecMemory::new(1)).unwrap();
+        let data_seg =
+            MemorySegment::new(PAGE_SIZE as u32, PAGE_SIZE as u32, VecMemory::new(1)).unwrap();
+        let stack_seg = MemorySegment::new(
+            2 * PAGE_SIZE as u32,
+            PAGE_SIZE as u32,
+            VecMemory::new(1),
+        )
+        .unwrap();
+        let mut cpu = Cpu::new(0, code_seg, data_seg, stack_seg);
+        for (i, inst) in code.iter().enumerate() {
+            cpu.write_u32((i * 4) as u32, *inst).unwrap();
+        }
+        cpu
+    }
+
+    #[test]
+    fn test_step_traps_on_ecall_with_no_event_handler() {
+        const ECALL: u32 = 0x0000_0073;
+        let mut cpu = test_cpu(&[ECALL]);
+
+        match cpu.step() {
+            StepOutcome::Trapped { trap, pc } => {
+                assert_eq!(trap, Trap::EnvironmentCall);
+                assert_eq!(pc, 0);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_run_stops_at_the_first_trap() {
+        const ECALL: u32 = 0x0000_0073;
+        const ADDI_X1_X0_1: u32 = 0x0010_0093;
+        let mut cpu = test_cpu(&[ADDI_X1_X0_1, ECALL]);
+
+        match cpu.run() {
+            StepOutcome::Trapped { trap, pc } => {
+                assert_eq!(trap, Trap::EnvironmentCall);
+                assert_eq!(pc, 4);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+        assert_eq!(cpu.regs[1], 1);
+    }
+
+    #[test]
+    fn test_run_bounded_stops_at_the_budget_and_resumes() {
+        const ADDI_X1_X1_1: u32 = 0x0010_8093; // addi x1, x1, 1
+        const ECALL: u32 = 0x0000_0073;
+        let mut cpu = test_cpu(&[ADDI_X1_X1_1, ADDI_X1_X1_1, ADDI_X1_X1_1, ECALL]);
+
+        assert_eq!(cpu.run_bounded(2), RunResult::BudgetExhausted);
+        assert_eq!(cpu.regs[1], 2);
+        assert_eq!(cpu.pc, 8);
+        assert_eq!(cpu.cycles(), 2);
+
+        match cpu.run_bounded(10) {
+            RunResult::Trapped { trap, pc } => {
+                assert_eq!(trap, Trap::EnvironmentCall);
+                assert_eq!(pc, 12);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+        assert_eq!(cpu.regs[1], 3);
+    }
+
+    #[test]
+    fn test_run_bounded_charges_per_opcode_cost_weights() {
+        const ADDI_X1_X1_1: u32 = 0x0010_8093; // addi x1, x1, 1
+        let mut cpu = test_cpu(&[ADDI_X1_X1_1, ADDI_X1_X1_1]);
+        cpu.set_cost_table(GasCostTable {
+            arithmetic: 3,
+            load_store: 1,
+            multiply_divide: 1,
+            ecall: 1,
+        });
+
+        assert_eq!(cpu.run_bounded(3), RunResult::BudgetExhausted);
+        assert_eq!(cpu.regs[1], 1);
+        assert_eq!(cpu.cycles(), 3);
+    }
+
+    #[test]
+    fn test_decode_cache_does_not_change_execution_results() {
+        const ADDI_X1_X1_1: u32 = 0x0010_8093; // addi x1, x1, 1
+        const ECALL: u32 = 0x0000_0073;
+        let mut cpu = test_cpu(&[ADDI_X1_X1_1, ADDI_X1_X1_1, ADDI_X1_X1_1, ECALL]);
+        cpu.set_decode_cache_size(4);
+
+        match cpu.run() {
+            StepOutcome::Trapped { trap, pc } => {
+                assert_eq!(trap, Trap::EnvironmentCall);
+                assert_eq!(pc, 12);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+        assert_eq!(cpu.regs[1], 3);
+    }
+
+    #[test]
+    fn test_decode_cache_populates_a_slot_on_first_decode() {
+        const ADDI_X1_X1_1: u32 = 0x0010_8093; // addi x1, x1, 1
+        let mut cpu = test_cpu(&[ADDI_X1_X1_1]);
+        cpu.set_decode_cache_size(4);
+
+        assert_eq!(cpu.step(), StepOutcome::Continue);
+
+        let cache = cpu.decode_cache.as_ref().unwrap();
+        let slot = (0usize >> 2) & (cache.len() - 1);
+        let entry = cache[slot].expect("decoding pc 0 should have populated its slot");
+        assert_eq!(entry.address, 0);
+    }
+
+    fn test_cpu_halfwords(halfwords: &[u16]) -> Cpu<VecMemory> {
+        let code_seg = MemorySegment::new(0, PAGE_SIZE as u32, VecMemory::new(1)).unwrap();
+        let data_seg =
+            MemorySegment::new(PAGE_SIZE as u32, PAGE_SIZE as u32, VecMemory::new(1)).unwrap();
+        let stack_seg = MemorySegment::new(
+            2 * PAGE_SIZE as u32,
+            PAGE_SIZE as u32,
+            VecMemory::new(1),
+        )
+        .unwrap();
+        let mut cpu = Cpu::new(0, code_seg, data_seg, stack_seg);
+        for (i, half) in halfwords.iter().enumerate() {
+            cpu.write_u16((i * 2) as u32, *half).unwrap();
+        }
+        cpu
+    }
+
+    #[test]
+    fn test_step_runs_a_compressed_instruction_and_advances_pc_by_two() {
+        let mut cpu = test_cpu_halfwords(&[0x4295]); // c.li x5, 5
+        assert_eq!(cpu.step(), StepOutcome::Continue);
+        assert_eq!(cpu.regs[5], 5);
+        assert_eq!(cpu.pc, 2);
+    }
+
+    #[test]
+    fn test_run_mixes_compressed_and_full_width_instructions() {
+        // c.li x5, 5 ; c.addi x5, 3 ; c.mv x6, x5 ; ecall (full-width, 2-byte aligned)
+        const ECALL: u32 = 0x0000_0073;
+        let mut cpu = test_cpu_halfwords(&[
+            0x4295,
+            0x028d,
+            0x8316,
+            ECALL as u16,
+            (ECALL >> 16) as u16,
+        ]);
+
+        match cpu.run() {
+            StepOutcome::Trapped { trap, pc } => {
+                assert_eq!(trap, Trap::EnvironmentCall);
+                assert_eq!(pc, 6);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+        assert_eq!(cpu.regs[5], 8);
+        assert_eq!(cpu.regs[6], 8);
+    }
+
+    // Example implementation of the Hasher trait using SHA-256, mirroring
+    // accumulator::tests::Sha256Hasher.
+    struct Sha256Hasher {
+        hasher: sha2::Sha256,
+    }
+
+    impl Hasher<32> for Sha256Hasher {
+        fn new() -> Self {
+            use sha2::Digest;
+            Sha256Hasher {
+                hasher: sha2::Sha256::new(),
+            }
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            use sha2::Digest;
+            self.hasher.update(data);
+        }
+
+        fn finalize(self) -> [u8; 32] {
+            use sha2::Digest;
+            let result = self.hasher.finalize();
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&result);
+            hash
+        }
+    }
+
+    /// Stand-in for the prover a real deployment would outsource tree storage to: builds the full
+    /// tree over `pages` and returns the root plus each page's sibling path (leaf to root), the
+    /// data an untrusted host would hand back alongside a page.
+    fn build_tree(pages: &[[u8; PAGE_SIZE]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let capacity = pages.len().max(1).next_power_of_two();
+        let depth = capacity.trailing_zeros() as usize;
+        let mut tree = vec![[0u8; 32]; 2 * capacity - 1];
+        for (i, data) in pages.iter().enumerate() {
+            tree[capacity - 1 + i] =
+                AuthenticatedMemory::<VecMemory, Sha256Hasher>::hash_leaf(i as u32, data);
+        }
+        for i in (0..capacity - 1).rev() {
+            tree[i] = AuthenticatedMemory::<VecMemory, Sha256Hasher>::hash_internal_node(
+                &tree[2 * i + 1],
+                &tree[2 * i + 2],
+            );
+        }
+
+        let paths = (0..pages.len())
+            .map(|i| {
+                let mut idx = capacity - 1 + i;
+                let mut path = Vec::with_capacity(depth);
+                for _ in 0..depth {
+                    let sibling = if idx % 2 == 1 { idx + 1 } else { idx - 1 };
+                    path.push(tree[sibling]);
+                    idx = (idx - 1) / 2;
+                }
+                path
+            })
+            .collect();
+        (tree[0], paths)
+    }
+
+    #[test]
+    fn test_authenticated_memory_verifies_untampered_pages() {
+        let pages = vec![[0u8; PAGE_SIZE]; 3];
+        let (root, paths) = build_tree(&pages);
+        let mut memory = AuthenticatedMemory::<VecMemory, Sha256Hasher>::new(VecMemory::new(3), 3, root);
+        for i in 0..3 {
+            assert!(memory.get_page(i as u32, &paths[i]).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_authenticated_memory_updates_root_on_mutation() {
+        let pages = vec![[0u8; PAGE_SIZE]; 2];
+        let (root, paths) = build_tree(&pages);
+        let mut memory = AuthenticatedMemory::<VecMemory, Sha256Hasher>::new(VecMemory::new(2), 2, root);
+        let root_before = memory.root();
+
+        {
+            let mut page = memory.get_page(0, &paths[0]).unwrap();
+            page.data[0] = 0x42;
+        }
+
+        let root_after = memory.root();
+        assert_ne!(root_before, root_after);
+
+        // The new root must match what a fresh tree over the mutated pages would have.
+        let mut mutated_pages = pages;
+        mutated_pages[0][0] = 0x42;
+        let (expected_root, _) = build_tree(&mutated_pages);
+        assert_eq!(root_after, expected_root);
+    }
+
+    #[test]
+    fn test_authenticated_memory_detects_tampering() {
+        let pages = vec![[0u8; PAGE_SIZE]; 2];
+        let (root, paths) = build_tree(&pages);
+        let mut memory = AuthenticatedMemory::<VecMemory, Sha256Hasher>::new(VecMemory::new(2), 2, root);
+
+        // Reach past the wrapper straight into the backing memory to corrupt a page without
+        // producing a matching sibling path, simulating an untrusted host handing back stale data.
+        memory.inner.pages[1].data[0] ^= 0xff;
+
+        match memory.get_page(1, &paths[1]) {
+            Err(e) => assert_eq!(e, AUTHENTICATED_MEMORY_VERIFICATION_FAILED),
+            Ok(_) => panic!("expected a tampered page to fail verification"),
+        }
+    }
+
+    #[test]
+    fn test_authenticated_memory_rejects_wrong_sibling_path() {
+        let pages = vec![[0u8; PAGE_SIZE]; 2];
+        let (root, paths) = build_tree(&pages);
+        let mut memory = AuthenticatedMemory::<VecMemory, Sha256Hasher>::new(VecMemory::new(2), 2, root);
+
+        // The page itself is untampered, but it's handed a sibling path for a different page --
+        // an untrusted host can't substitute one page's proof for another's.
+        match memory.get_page(0, &paths[1]) {
+            Err(e) => assert_eq!(e, AUTHENTICATED_MEMORY_VERIFICATION_FAILED),
+            Ok(_) => panic!("expected a mismatched sibling path to fail verification"),
+        }
+    }
+
+    struct VecBackend {
+        pages: Vec<[u8; PAGE_SIZE]>,
+        stores: Vec<u32>,
+    }
+
+    impl VecBackend {
+        fn new(n_pages: usize) -> Self {
+            VecBackend {
+                pages: vec![[0u8; PAGE_SIZE]; n_pages],
+                stores: Vec::new(),
+            }
+        }
+    }
+
+    impl PageBackend for VecBackend {
+        fn load(&mut self, page_index: u32) -> Result<[u8; PAGE_SIZE], Trap> {
+            self.pages
+                .get(page_index as usize)
+                .copied()
+                .ok_or(Trap::LoadAccessFault(page_index))
+        }
+
+        fn store(&mut self, page_index: u32, data: &Page) -> Result<(), Trap> {
+            self.stores.push(page_index);
+            self.pages[page_index as usize] = data.data;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_paged_cache_evicts_the_least_recently_used_page() {
+        let mut cache = PagedCache::new(VecBackend::new(3), 2);
+        cache.get_page(0).unwrap();
+        cache.get_page(1).unwrap();
+        // Touch page 0 again so page 1, not page 0, becomes the least recently used.
+        cache.get_page(0).unwrap();
+        cache.get_page(2).unwrap();
+
+        assert!(cache.resident.contains_key(&0));
+        assert!(cache.resident.contains_key(&2));
+        assert!(!cache.resident.contains_key(&1));
+    }
+
+    #[test]
+    fn test_paged_cache_stores_back_a_dirty_victim_on_eviction() {
+        let mut cache = PagedCache::new(VecBackend::new(2), 1);
+        {
+            let mut page = cache.get_page(0).unwrap();
+            page.data[0] = 7;
+        }
+        cache.get_page(1).unwrap(); // evicts page 0, which is dirty
+
+        assert_eq!(cache.backend.stores, vec![0]);
+        assert_eq!(cache.backend.pages[0][0], 7);
+    }
+
+    #[test]
+    fn test_paged_cache_flush_stores_back_every_dirty_page() {
+        let mut cache = PagedCache::new(VecBackend::new(2), 2);
+        cache.get_page(0).unwrap().data[0] = 1;
+        cache.get_page(1).unwrap().data[0] = 2;
+
+        cache.flush().unwrap();
+
+        assert_eq!(cache.backend.pages[0][0], 1);
+        assert_eq!(cache.backend.pages[1][0], 2);
+        assert!(!cache.resident[&0].dirty);
+        assert!(!cache.resident[&1].dirty);
+    }
 }
@@ -0,0 +1,23 @@
+//! Feeds arbitrary bytes through the exact decode path a Bitcoin V-App client message takes
+//! before it would reach a handler.
+//!
+//! The literal entry point this was asked for — `apps/bitcoin/app`'s `process_message`,
+//! decoding a postcard-encoded `common::message::Request` and dispatching to `handle_sign_psbt`
+//! — doesn't exist in this tree: `apps/bitcoin/app` calls into a `common::message` module and a
+//! `sdk::App` type that are never defined, and `handle_sign_psbt` itself has no implementation.
+//! What *is* real and load-bearing is `bitcoin_common::message::Request`, the protobuf message
+//! (via `quick_protobuf`, not postcard) actually shared between the V-App and its host client,
+//! including the `RequestSignPsbt` variant that carries the raw PSBT bytes. This harness targets
+//! that boundary instead, so malformed or truncated messages — PSBT payloads included — are
+//! still caught before they'd reach a handler.
+#![no_main]
+
+use bitcoin_common::message::Request;
+use libfuzzer_sys::fuzz_target;
+use quick_protobuf::{BytesReader, MessageRead};
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = BytesReader::from_bytes(data);
+    // Decoding is allowed to fail on malformed input; it must never panic.
+    let _ = Request::from_reader(&mut reader, data);
+});
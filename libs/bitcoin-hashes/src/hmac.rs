@@ -0,0 +1,132 @@
+//! Generic HMAC (RFC 2104) over any [`HashEngine`].
+//!
+//! BIP-32 child derivation runs HMAC-SHA512 on every step, but nothing in this crate exposed a
+//! keyed MAC on top of [`sha512::HashEngine`] — this fills that gap once, generically, so it
+//! also covers HMAC-SHA256 for free the day a `sha256::HashEngine` shows up.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::HashEngine;
+
+/// HMAC engine generic over any [`HashEngine`] `H`. Feed the message in with [`Hmac::input`],
+/// then call [`Hmac::finalize`] to get the MAC — the same streaming shape as `H` itself.
+#[derive(Clone)]
+pub struct Hmac<H: HashEngine> {
+    inner: H,
+    outer: H,
+    opad: Vec<u8>,
+}
+
+impl<H: HashEngine> Hmac<H> {
+    /// Starts a new HMAC computation keyed with `key`. Keys longer than `H::BLOCK_SIZE` are
+    /// hashed down first; shorter keys are zero-padded, exactly as RFC 2104 specifies.
+    pub fn new(key: &[u8]) -> Self {
+        let block_key = Self::block_key(key);
+
+        let mut ipad = vec![0x36u8; H::BLOCK_SIZE];
+        let mut opad = vec![0x5cu8; H::BLOCK_SIZE];
+        for i in 0..H::BLOCK_SIZE {
+            ipad[i] ^= block_key[i];
+            opad[i] ^= block_key[i];
+        }
+
+        let mut inner = H::default();
+        inner.input(&ipad);
+
+        Self {
+            inner,
+            outer: H::default(),
+            opad,
+        }
+    }
+
+    /// Derives the `H::BLOCK_SIZE`-byte key actually XORed into the pads.
+    fn block_key(key: &[u8]) -> Vec<u8> {
+        let mut block_key = vec![0u8; H::BLOCK_SIZE];
+        if key.len() > H::BLOCK_SIZE {
+            let mut engine = H::default();
+            engine.input(key);
+            let digest = engine.finalize();
+            let digest = digest.as_ref();
+            block_key[..digest.len()].copy_from_slice(digest);
+        } else {
+            block_key[..key.len()].copy_from_slice(key);
+        }
+        block_key
+    }
+
+    /// Feeds more of the message into the MAC.
+    pub fn input(&mut self, data: &[u8]) {
+        self.inner.input(data);
+    }
+
+    /// Consumes the engine, producing the MAC: the outer hash of `opad ⊕ key` followed by the
+    /// inner hash's digest.
+    pub fn finalize(mut self) -> H::Digest {
+        let inner_digest = self.inner.finalize();
+        self.outer.input(&self.opad);
+        self.outer.input(inner_digest.as_ref());
+        self.outer.finalize()
+    }
+
+    /// One-shot helper: MACs `data` under `key` in a single call.
+    pub fn mac(key: &[u8], data: &[u8]) -> H::Digest {
+        let mut engine = Self::new(key);
+        engine.input(data);
+        engine.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha512;
+
+    fn to_hex(bytes: &[u8]) -> alloc::string::String {
+        use core::fmt::Write;
+        let mut s = alloc::string::String::with_capacity(bytes.len() * 2);
+        for b in bytes {
+            write!(s, "{:02x}", b).unwrap();
+        }
+        s
+    }
+
+    // RFC 4231 test vectors.
+    #[test]
+    fn hmac_sha512_rfc4231_case1() {
+        let key = [0x0bu8; 20];
+        let data = b"Hi There";
+        let mac = Hmac::<sha512::HashEngine>::mac(&key, data);
+        assert_eq!(
+            to_hex(&mac),
+            "87aa7cdea5ef619d4ff0b4241a1d6cb0\
+             2379f4e2ce4ec2787ad0b30545e17cde\
+             daa833b7d6b8a702038b274eaea3f4e4\
+             be9d914eeb61f1702e696c203a126854"
+        );
+    }
+
+    #[test]
+    fn hmac_sha512_rfc4231_case2() {
+        let key = b"Jefe";
+        let data = b"what do ya want for nothing?";
+        let mac = Hmac::<sha512::HashEngine>::mac(key, data);
+        assert_eq!(
+            to_hex(&mac),
+            "164b7a7bfcf819e2e395fbe73b56e0a3\
+             87bd64222e831fd610270cd7ea25055\
+             49758bf75c05a994a6d034f65f8f0e6\
+             fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn hmac_streaming_matches_one_shot() {
+        let key = [0x0bu8; 20];
+        let mut streamed = Hmac::<sha512::HashEngine>::new(&key);
+        streamed.input(b"Hi ");
+        streamed.input(b"There");
+        assert_eq!(streamed.finalize(), Hmac::<sha512::HashEngine>::mac(&key, b"Hi There"));
+    }
+}
@@ -0,0 +1,36 @@
+//! Streaming hash engines and MACs built on them.
+//!
+//! This only vendors as much of `rust-bitcoin`'s `bitcoin_hashes` as this codebase actually
+//! reaches: [`sha512::HashEngine`] wraps the device's native SHA-512 (via `sdk::hash::Sha512`),
+//! and [`Hmac`] builds the standard HMAC construction on top of any [`HashEngine`].
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+mod hmac;
+pub mod sha512;
+
+pub use hmac::Hmac;
+
+/// A streaming hash engine: bytes are fed in incrementally via [`input`](HashEngine::input), and
+/// the digest is produced by consuming the engine with [`finalize`](HashEngine::finalize).
+///
+/// Implemented once per algorithm (see [`sha512::HashEngine`]) so algorithm-generic
+/// constructions, like [`Hmac`], can be written against this trait instead of any one digest.
+pub trait HashEngine: Default + Clone {
+    /// Size, in bytes, of the algorithm's internal block. This is the unit HMAC pads its key to,
+    /// not the digest size.
+    const BLOCK_SIZE: usize;
+
+    /// The fixed-size digest this engine produces.
+    type Digest: AsRef<[u8]> + AsMut<[u8]> + Default + Clone;
+
+    /// Number of bytes fed into the engine so far.
+    fn n_bytes_hashed(&self) -> usize;
+
+    /// Feeds more data into the engine.
+    fn input(&mut self, data: &[u8]);
+
+    /// Consumes the engine, producing its digest.
+    fn finalize(self) -> Self::Digest;
+}
@@ -43,9 +43,17 @@ impl Default for HashEngine {
 impl crate::HashEngine for HashEngine {
     const BLOCK_SIZE: usize = 128;
 
+    type Digest = [u8; 64];
+
     fn n_bytes_hashed(&self) -> usize { self.length }
 
     fn input(&mut self, inp: &[u8]) { self.hasher.update(inp); }
+
+    fn finalize(self) -> [u8; 64] {
+        let mut digest = [0u8; 64];
+        self.hasher.digest(&mut digest);
+        digest
+    }
 }
 
 #[cfg(test)]
@@ -48,6 +48,16 @@ pub const CURVE_ORDER: [u8; 32] = [
     0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41
 ];
 
+/// Half the order of the secp256k1 curve, i.e. `CURVE_ORDER / 2`. The largest value an
+/// [`crate::ecdsa::Signature`]'s `s` may take and still be in "low S" (BIP-62) form.
+#[rustfmt::skip]
+pub const CURVE_ORDER_HALF: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d,
+    0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0
+];
+
 /// The X coordinate of the generator.
 #[rustfmt::skip]
 pub const GENERATOR_X: [u8; 32] = [
@@ -83,6 +93,25 @@ pub const ONE: [u8; 32] = [
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
 ];
 
+/// The value two as big-endian array of bytes.
+pub const TWO: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
+];
+
+/// Returns whether `scalar` is a valid nonzero ECDSA/Schnorr scalar, i.e. `0 < scalar <
+/// CURVE_ORDER`. Useful to validate externally-supplied `r`/`s` bytes before handing them to
+/// [`crate::ecdsa::Signature::from_compact`] or a secret key constructor.
+pub fn is_valid_scalar(scalar: &[u8; 32]) -> bool {
+    *scalar != ZERO
+        && sdk::bignum::BigNum::<32>::from_be_bytes(*scalar)
+            < sdk::bignum::BigNum::<32>::from_be_bytes(CURVE_ORDER)
+}
+
+/// Returns whether `value` is a valid field element, i.e. `value < FIELD_SIZE`.
+pub fn is_valid_field_element(value: &[u8; 32]) -> bool {
+    sdk::bignum::BigNum::<32>::from_be_bytes(*value) < sdk::bignum::BigNum::<32>::from_be_bytes(FIELD_SIZE)
+}
+
 /// The curve Prime, represented as a ModulusProvider from Vanadium's app-sdk
 #[derive(Debug, Clone, Copy)]
 pub struct P;
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Elliptic Curve Diffie-Hellman (ECDH) shared secret computation.
+
+use core::borrow::Borrow;
+use core::fmt;
+
+use sdk::curve::Secp256k1Point;
+use sdk::hash::{Hasher, Sha256};
+
+use crate::{Error, FixedHex, PublicKey, SecretKey};
+
+/// The result of a Diffie-Hellman key exchange.
+///
+/// Create using [`SharedSecret::new`] or, for a custom reduction of the shared point, using
+/// [`SharedSecret::new_with_hash`]. Get the bytes out using [`SharedSecret::secret_bytes`].
+#[derive(Copy, Clone, Eq, Hash)]
+pub struct SharedSecret {
+    data: [u8; 32],
+}
+impl_array_newtype!(SharedSecret, u8, 32);
+impl_pretty_debug!(SharedSecret);
+
+impl PartialEq for SharedSecret {
+    /// Compares two shared secrets in constant time, so that callers checking a computed secret
+    /// against an expected one don't leak timing information about where the two diverge.
+    fn eq(&self, other: &SharedSecret) -> bool {
+        let mut diff = 0u8;
+        for i in 0..32 {
+            diff |= self.data[i] ^ other.data[i];
+        }
+        diff == 0
+    }
+}
+
+impl SharedSecret {
+    /// Computes an ECDH shared secret as `sha256(x || y)`, where the leading byte of `x || y` is
+    /// the `0x02`/`0x03` SEC parity byte for the shared point and the remaining 32 bytes are its
+    /// X coordinate. This matches libsecp256k1's default ECDH hash function.
+    #[inline]
+    pub fn new(point: &PublicKey, scalar: &SecretKey) -> Result<SharedSecret, Error> {
+        SharedSecret::new_with_hash(point, scalar, |x, y| {
+            let mut hasher = Sha256::new();
+            hasher.update(&[0x02 | (y[31] & 1)]);
+            hasher.update(&x);
+            let mut digest = [0u8; 32];
+            hasher.digest(&mut digest);
+            digest
+        })
+    }
+
+    /// Computes the shared point `scalar * point` and reduces it with a caller-supplied function,
+    /// instead of the default SHA-256-based hash used by [`SharedSecret::new`]. This allows
+    /// callers who want the unhashed coordinates, or who need a different KDF, to get at the
+    /// point directly.
+    ///
+    /// `hash` is called with the shared point's X and Y coordinates, in that order.
+    pub fn new_with_hash<F>(
+        point: &PublicKey,
+        scalar: &SecretKey,
+        mut hash: F,
+    ) -> Result<SharedSecret, Error>
+    where
+        F: FnMut([u8; 32], [u8; 32]) -> [u8; 32],
+    {
+        let scalar_bytes = scalar.secret_bytes();
+        if scalar_bytes == [0u8; 32] {
+            return Err(Error::InvalidSharedSecret);
+        }
+
+        let peer_bytes = point.serialize_uncompressed();
+        let mut peer_x = [0u8; 32];
+        let mut peer_y = [0u8; 32];
+        peer_x.copy_from_slice(&peer_bytes[1..33]);
+        peer_y.copy_from_slice(&peer_bytes[33..65]);
+
+        let shared_point = &Secp256k1Point::new(peer_x, peer_y) * &scalar_bytes;
+        let shared_bytes = shared_point.to_bytes();
+
+        // The point at infinity has no valid affine representation; by convention it's the one
+        // point whose serialization is all zero.
+        if shared_bytes == [0u8; 65] {
+            return Err(Error::InvalidSharedSecret);
+        }
+
+        let mut x = [0u8; 32];
+        let mut y = [0u8; 32];
+        x.copy_from_slice(&shared_bytes[1..33]);
+        y.copy_from_slice(&shared_bytes[33..65]);
+
+        Ok(SharedSecret { data: hash(x, y) })
+    }
+
+    /// Creates a new shared secret from the given byte array.
+    #[inline]
+    pub fn from_bytes(data: [u8; 32]) -> SharedSecret { SharedSecret { data } }
+
+    /// Returns the shared secret as a byte value.
+    #[inline]
+    pub fn secret_bytes(&self) -> [u8; 32] { self.data }
+}
+
+impl FixedHex<32> for SharedSecret {
+    fn to_byte_array(&self) -> [u8; 32] { self.data }
+
+    fn from_byte_array(bytes: [u8; 32]) -> Result<SharedSecret, Error> { Ok(SharedSecret { data: bytes }) }
+}
+
+impl fmt::LowerHex for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.data.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::LowerHex::fmt(self, f) }
+}
+
+impl<I> Borrow<I> for SharedSecret
+where
+    [u8; 32]: Borrow<I>,
+    I: ?Sized,
+{
+    fn borrow(&self) -> &I { (&self.data).borrow() }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SharedSecret {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            let mut buf = [0u8; 64];
+            let hex = self.to_hex_in(&mut buf).map_err(serde::ser::Error::custom)?;
+            s.serialize_str(hex)
+        } else {
+            s.serialize_bytes(&self.data)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SharedSecret {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<SharedSecret, D::Error> {
+        if d.is_human_readable() {
+            struct HexVisitor;
+            impl<'de> serde::de::Visitor<'de> for HexVisitor {
+                type Value = SharedSecret;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a hex-encoded 32-byte ECDH shared secret")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<SharedSecret, E> {
+                    SharedSecret::from_hex(v).map_err(E::custom)
+                }
+            }
+            d.deserialize_str(HexVisitor)
+        } else {
+            struct BytesVisitor;
+            impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+                type Value = SharedSecret;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a 32-byte ECDH shared secret")
+                }
+
+                fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<SharedSecret, E> {
+                    if v.len() != 32 {
+                        return Err(E::invalid_length(v.len(), &self));
+                    }
+                    let mut data = [0u8; 32];
+                    data.copy_from_slice(v);
+                    Ok(SharedSecret { data })
+                }
+            }
+            d.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
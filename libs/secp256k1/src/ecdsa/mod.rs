@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Support for ECDSA signatures.
+
+#[cfg(feature = "recovery")]
+pub mod recovery;
+
+use core::ops::Deref;
+use core::{fmt, str};
+
+use sdk::bignum::BigNum;
+
+use crate::{constants, Error, FixedHex};
+
+/// An ECDSA signature, consisting of the pair `(r, s)`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signature {
+    r: [u8; 32],
+    s: [u8; 32],
+}
+
+impl Signature {
+    /// Creates a `Signature` directly from a 64-byte compact `r || s` encoding.
+    #[inline]
+    pub fn from_compact(data: &[u8]) -> Result<Signature, Error> {
+        if data.len() != constants::COMPACT_SIGNATURE_SIZE {
+            return Err(Error::InvalidSignature);
+        }
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&data[..32]);
+        s.copy_from_slice(&data[32..]);
+        Ok(Signature { r, s })
+    }
+
+    /// Serializes the signature in compact format as `r || s`.
+    #[inline]
+    pub fn serialize_compact(&self) -> [u8; 64] {
+        let mut ret = [0u8; 64];
+        ret[..32].copy_from_slice(&self.r);
+        ret[32..].copy_from_slice(&self.s);
+        ret
+    }
+
+    /// Returns the `(r, s)` pair as big-endian byte arrays.
+    #[inline]
+    pub(crate) fn r_s(&self) -> ([u8; 32], [u8; 32]) { (self.r, self.s) }
+
+    /// Converts a DER-encoded signature to a compact `Signature`.
+    ///
+    /// This mirrors libsecp256k1's strict DER parser: it rejects non-minimal integer encodings
+    /// and trailing garbage. Use [`Signature::from_der_lax`] to parse the malformed-but-accepted
+    /// DER signatures that exist on the Bitcoin blockchain.
+    pub fn from_der(data: &[u8]) -> Result<Signature, Error> {
+        let (r, s, rest) = parse_der(data, false)?;
+        if !rest.is_empty() {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(Signature { r, s })
+    }
+
+    /// Converts a DER-encoded signature to a compact `Signature`, tolerating the malformed (but
+    /// historically accepted) encodings libsecp256k1's lax parser allows.
+    pub fn from_der_lax(data: &[u8]) -> Result<Signature, Error> {
+        let (r, s, _rest) = parse_der(data, true)?;
+        Ok(Signature { r, s })
+    }
+
+    /// Serializes the signature in DER format.
+    pub fn serialize_der(&self) -> SerializedSignature {
+        let (r_enc, r_enc_len) = encode_der_integer(&self.r);
+        let (s_enc, s_enc_len) = encode_der_integer(&self.s);
+
+        let mut data = [0u8; constants::MAX_SIGNATURE_SIZE];
+        let mut len = 0;
+        data[len] = 0x30;
+        len += 1;
+        let content_len = 2 + r_enc_len + 2 + s_enc_len;
+        data[len] = content_len as u8;
+        len += 1;
+        data[len] = 0x02;
+        len += 1;
+        data[len] = r_enc_len as u8;
+        len += 1;
+        data[len..len + r_enc_len].copy_from_slice(&r_enc[..r_enc_len]);
+        len += r_enc_len;
+        data[len] = 0x02;
+        len += 1;
+        data[len] = s_enc_len as u8;
+        len += 1;
+        data[len..len + s_enc_len].copy_from_slice(&s_enc[..s_enc_len]);
+        len += s_enc_len;
+
+        SerializedSignature { data, len }
+    }
+
+    /// Normalizes the signature to "low S" form (`s <= n / 2`), as required by BIP-62 and
+    /// Bitcoin's standardness rules. Returns whether `s` was negated.
+    pub fn normalize_s(&mut self) -> bool {
+        if BigNum::<32>::from_be_bytes(self.s) > BigNum::<32>::from_be_bytes(constants::CURVE_ORDER_HALF) {
+            self.s =
+                (&BigNum::<32>::from_be_bytes(constants::CURVE_ORDER) - &BigNum::<32>::from_be_bytes(self.s))
+                    .to_be_bytes();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.serialize_der().as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for Signature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Signature, Error> {
+        let mut buf = [0u8; constants::MAX_SIGNATURE_SIZE];
+        let len = crate::from_hex(s, &mut buf).map_err(|_| Error::InvalidSignature)?;
+        Signature::from_der(&buf[..len])
+    }
+}
+
+impl FixedHex<{ constants::COMPACT_SIGNATURE_SIZE }> for Signature {
+    fn to_byte_array(&self) -> [u8; constants::COMPACT_SIGNATURE_SIZE] { self.serialize_compact() }
+
+    fn from_byte_array(bytes: [u8; constants::COMPACT_SIGNATURE_SIZE]) -> Result<Signature, Error> {
+        Signature::from_compact(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Signature {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            s.collect_str(self)
+        } else {
+            s.serialize_bytes(self.serialize_der().as_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Signature {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Signature, D::Error> {
+        use core::str::FromStr;
+
+        if d.is_human_readable() {
+            struct HexVisitor;
+            impl<'de> serde::de::Visitor<'de> for HexVisitor {
+                type Value = Signature;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a DER-encoded, hex-formatted ECDSA signature")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Signature, E> {
+                    Signature::from_str(v).map_err(E::custom)
+                }
+            }
+            d.deserialize_str(HexVisitor)
+        } else {
+            struct BytesVisitor;
+            impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+                type Value = Signature;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a DER-encoded ECDSA signature")
+                }
+
+                fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Signature, E> {
+                    Signature::from_der(v).map_err(|_| E::invalid_length(v.len(), &self))
+                }
+            }
+            d.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
+
+/// A DER-encoded signature, stored in a fixed-size stack buffer.
+#[derive(Copy, Clone)]
+pub struct SerializedSignature {
+    data: [u8; constants::MAX_SIGNATURE_SIZE],
+    len: usize,
+}
+
+impl SerializedSignature {
+    /// Returns the serialized signature as a byte slice.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] { &self.data[..self.len] }
+
+    /// Returns the number of leading zero bytes in the (minimally-encoded) `r` component, i.e.
+    /// how much smaller than 32 bytes `r`'s DER integer is. Used by the signature-grinding
+    /// routines that look for a short `r`.
+    pub fn r_leading_zeros(&self) -> usize {
+        // data layout: 0x30 <len> 0x02 <rlen> <r...> 0x02 <slen> <s...>
+        let rlen = self.data[3] as usize;
+        32 - core::cmp::min(32, rlen)
+    }
+}
+
+impl Deref for SerializedSignature {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] { self.as_bytes() }
+}
+
+/// DER-encodes a 32-byte big-endian integer, trimming non-significant leading zero bytes and
+/// prepending a single `0x00` pad byte when the high bit would otherwise make the value look
+/// negative. Returns a 33-byte buffer together with the number of bytes actually used.
+fn encode_der_integer(bytes: &[u8; 32]) -> ([u8; 33], usize) {
+    let mut start = 0;
+    while start < 31 && bytes[start] == 0 {
+        start += 1;
+    }
+
+    let mut out = [0u8; 33];
+    let mut len = 0;
+    if bytes[start] & 0x80 != 0 {
+        out[0] = 0x00;
+        len += 1;
+    }
+    let significant = &bytes[start..];
+    out[len..len + significant.len()].copy_from_slice(significant);
+    len += significant.len();
+
+    (out, len)
+}
+
+/// Parses the two DER integers composing an ECDSA signature. When `lax` is `false`, enforces
+/// strict, minimal DER encoding (matching libsecp256k1's strict parser); when `true`, tolerates
+/// the malformed encodings allowed by the historical "lax DER" parser. Returns the `(r, s)` pair
+/// as 32-byte big-endian arrays, left-padded or truncated from the high end as needed, along with
+/// any bytes following the signature.
+fn parse_der(data: &[u8], lax: bool) -> Result<([u8; 32], [u8; 32], &[u8]), Error> {
+    if data.len() < 8 || data.len() > constants::MAX_SIGNATURE_SIZE {
+        return Err(Error::InvalidSignature);
+    }
+
+    let mut pos = 0;
+    if data[pos] != 0x30 {
+        return Err(Error::InvalidSignature);
+    }
+    pos += 1;
+
+    let seq_len = data[pos] as usize;
+    pos += 1;
+    if !lax && pos + seq_len != data.len() {
+        return Err(Error::InvalidSignature);
+    }
+
+    let (r, pos_after_r) = parse_der_integer(data, pos, lax)?;
+    let (s, pos_after_s) = parse_der_integer(data, pos_after_r, lax)?;
+
+    Ok((r, s, &data[pos_after_s..]))
+}
+
+fn parse_der_integer(data: &[u8], mut pos: usize, lax: bool) -> Result<([u8; 32], usize), Error> {
+    if pos + 2 > data.len() || data[pos] != 0x02 {
+        return Err(Error::InvalidSignature);
+    }
+    pos += 1;
+
+    let int_len = data[pos] as usize;
+    pos += 1;
+    if pos + int_len > data.len() {
+        return Err(Error::InvalidSignature);
+    }
+
+    let int_bytes = &data[pos..pos + int_len];
+    if !lax {
+        if int_len == 0 {
+            return Err(Error::InvalidSignature);
+        }
+        if int_bytes[0] & 0x80 != 0 {
+            return Err(Error::InvalidSignature); // would be negative
+        }
+        if int_len > 1 && int_bytes[0] == 0 && int_bytes[1] & 0x80 == 0 {
+            return Err(Error::InvalidSignature); // non-minimal encoding
+        }
+        if int_len > 33 {
+            return Err(Error::InvalidSignature);
+        }
+    }
+
+    let mut out = [0u8; 32];
+    let significant = if int_bytes.len() > 32 {
+        &int_bytes[int_bytes.len() - 32..]
+    } else {
+        int_bytes
+    };
+    out[32 - significant.len()..].copy_from_slice(significant);
+
+    Ok((out, pos + int_len))
+}
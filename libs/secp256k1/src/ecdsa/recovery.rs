@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Support for recoverable ECDSA signatures, i.e. signatures from which the signer's public key
+//! can be recovered given only the message and the signature itself.
+
+use core::fmt;
+
+use sdk::bignum::{BigNum, ModulusProvider};
+use sdk::curve::Secp256k1Point;
+
+use crate::ecdsa::Signature;
+use crate::{constants, Context, Error, Message, PublicKey, Secp256k1, SecretKey, Signing};
+
+/// A tag to recover the public key from a compact signature, taking values in `0..=3`.
+///
+/// The two low bits of the tag disambiguate the four candidate curve points with the signature's
+/// `r` as (a possibly order-shifted) X coordinate; see [`RecoverableSignature`] for details.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RecoveryId(i32);
+
+impl RecoveryId {
+    /// Creates a `RecoveryId` from an `i32`, failing if it isn't in `0..=3`.
+    #[inline]
+    pub fn from_i32(id: i32) -> Result<RecoveryId, Error> {
+        match id {
+            0..=3 => Ok(RecoveryId(id)),
+            _ => Err(Error::InvalidRecoveryId),
+        }
+    }
+
+    /// Returns the inner `i32` value.
+    #[inline]
+    pub fn to_i32(self) -> i32 { self.0 }
+}
+
+/// An ECDSA signature together with a [`RecoveryId`], from which the signer's [`PublicKey`] can
+/// be recovered.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RecoverableSignature {
+    r: [u8; 32],
+    s: [u8; 32],
+    recid: RecoveryId,
+}
+
+impl RecoverableSignature {
+    /// Creates a `RecoverableSignature` from the compact `r || s` representation and a separate
+    /// [`RecoveryId`].
+    pub fn from_compact(data: &[u8], recid: RecoveryId) -> Result<RecoverableSignature, Error> {
+        if data.len() != constants::COMPACT_SIGNATURE_SIZE {
+            return Err(Error::InvalidSignature);
+        }
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&data[..32]);
+        s.copy_from_slice(&data[32..]);
+        Ok(RecoverableSignature { r, s, recid })
+    }
+
+    /// Serializes the signature as 65 bytes: the compact `r || s` encoding followed by the
+    /// recovery id.
+    pub fn serialize_compact(&self) -> (RecoveryId, [u8; 65]) {
+        let mut ret = [0u8; 65];
+        ret[..32].copy_from_slice(&self.r);
+        ret[32..64].copy_from_slice(&self.s);
+        ret[64] = self.recid.0 as u8;
+        (self.recid, ret)
+    }
+
+    /// Drops the recovery id, returning a plain [`Signature`].
+    #[inline]
+    pub fn to_standard(&self) -> Signature {
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&self.r);
+        compact[32..].copy_from_slice(&self.s);
+        Signature::from_compact(&compact).expect("r || s is always a valid compact signature")
+    }
+}
+
+/// Recovers the public key that produced `signature` over `msg`.
+pub fn recover(msg: &Message, signature: &RecoverableSignature) -> Result<PublicKey, Error> {
+    let e_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+
+    if signature.r == [0u8; 32] || signature.s == [0u8; 32] {
+        return Err(Error::InvalidSignature);
+    }
+
+    let recid = signature.recid.0;
+
+    // When recid is 2 or 3, the actual X coordinate of R is r + n (the rare case where r, taken
+    // modulo n, overflowed back into [0, n) during signing).
+    let x_bytes = if recid >= 2 {
+        let x = &BigNum::<32>::from_be_bytes(signature.r) + &BigNum::<32>::from_be_bytes(constants::CURVE_ORDER);
+        let x_bytes = x.to_be_bytes();
+        if !constants::is_valid_field_element(&x_bytes) {
+            return Err(Error::InvalidRecoveryId);
+        }
+        x_bytes
+    } else {
+        signature.r
+    };
+
+    // Recover R = (x, y) with y² = x³ + 7 (mod p), picking the root whose parity matches recid.
+    let x_mod = constants::P.new_big_num_mod(x_bytes);
+    let y_squared = &(&(&x_mod * &x_mod) * &x_mod) + &constants::P.new_big_num_mod(SEVEN);
+
+    // p ≡ 3 (mod 4), so the square root (when it exists) is y_squared^((p + 1) / 4) mod p.
+    let y = y_squared.pow(&BigNum::<32>::from_be_bytes(constants::SQR_EXPONENT));
+    if &y * &y != y_squared {
+        return Err(Error::InvalidRecoveryId); // x is not the X coordinate of a curve point
+    }
+
+    let y_bytes = y.to_be_bytes();
+    let y_is_odd = y_bytes[31] & 1 == 1;
+    let y_bytes = if y_is_odd == (recid & 1 == 1) {
+        y_bytes
+    } else {
+        (&BigNum::<32>::from_be_bytes(constants::FIELD_SIZE) - &BigNum::<32>::from_be_bytes(y_bytes)).to_be_bytes()
+    };
+
+    let r_point = Secp256k1Point::new(x_bytes, y_bytes);
+
+    // Q = r⁻¹ · (s·R − e·G), with r⁻¹ computed mod n via Fermat's little theorem.
+    let s_r = &r_point * &signature.s;
+    let e_g = &constants::G * &e_bytes;
+    let diff = &s_r - &e_g;
+
+    let r_inv = crate::scalar_inverse(signature.r);
+
+    Ok(PublicKey::from(&diff * &r_inv))
+}
+
+const SEVEN: [u8; 32] = {
+    let mut b = [0u8; 32];
+    b[31] = 7;
+    b
+};
+
+impl fmt::Display for RecoveryId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+impl<C: Signing> Secp256k1<C> {
+    /// Signs `msg` with `seckey`, producing a signature from which `seckey`'s public key can
+    /// later be recovered using [`recover`].
+    pub fn sign_ecdsa_recoverable(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+    ) -> RecoverableSignature {
+        let sig = self.sign_ecdsa(msg, seckey).serialize_compact();
+        let pubkey = PublicKey::from_secret_key(self, seckey);
+
+        for id in 0..=3 {
+            let recid = RecoveryId(id);
+            let candidate = RecoverableSignature { r: sig[..32].try_into().unwrap(), s: sig[32..].try_into().unwrap(), recid };
+            if recover(msg, &candidate) == Ok(pubkey) {
+                return candidate;
+            }
+        }
+
+        unreachable!("one of the four recovery ids always recovers the signer's public key")
+    }
+}
+
+impl<C: Context> Secp256k1<C> {
+    /// Recovers the public key that produced `signature` over `msg`. Thin context-bound wrapper
+    /// around the free function [`recover`], for callers who already have a `Secp256k1` handle.
+    #[inline]
+    pub fn recover_ecdsa(&self, msg: &Message, signature: &RecoverableSignature) -> Result<PublicKey, Error> {
+        recover(msg, signature)
+    }
+}
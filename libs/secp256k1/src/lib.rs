@@ -137,6 +137,13 @@
 //! * `serde` - implements serialization and deserialization for types in this crate using `serde`.
 //!           **Important**: `serde` encoding is **not** the same as consensus encoding!
 //!
+//! It also honors one `--cfg` flag outside of Cargo features:
+//!
+//! * `secp256k1_fuzz` - replaces `sign_ecdsa`, `sign_ecdsa_grind_r` and `verify_ecdsa` with a
+//!   fast, deterministic, **cryptographically broken** stand-in so that fuzz harnesses can forge
+//!   "valid" signatures cheaply and exercise the surrounding codepaths (DER/compact encoding,
+//!   serde, ...). This must never be set for a production build.
+//!
 
 // suppress dead code warnings in vlib-secp256k1, as we prefer to keep the code as close to the original as possible
 #![allow(dead_code)]
@@ -171,6 +178,7 @@ mod key;
 mod sdk_helpers;
 
 pub mod constants;
+pub mod ecdh;
 pub mod ecdsa;
 pub mod scalar;
 pub mod schnorr;
@@ -180,6 +188,9 @@ mod serde_util;
 use core::marker::PhantomData;
 use core::{fmt, str};
 
+use sdk::bignum::{BigNum, ModulusProvider};
+use sdk::curve::Secp256k1Point;
+
 #[cfg(feature = "serde")]
 pub use serde;
 
@@ -224,7 +235,7 @@ impl Message {
     #[inline]
     #[deprecated(since = "0.28.0", note = "use from_digest_slice instead")]
     pub fn from_slice(digest: &[u8]) -> Result<Message, Error> {
-        Message::from_digest_slice(digest)
+        Message::from_digest_slice(digest).map_err(Error::from)
     }
 
     /// Creates a [`Message`] from a `digest`.
@@ -248,16 +259,33 @@ impl Message {
     ///
     /// [secure signature]: https://twitter.com/pwuille/status/1063582706288586752
     #[inline]
-    pub fn from_digest_slice(digest: &[u8]) -> Result<Message, Error> {
+    pub fn from_digest_slice(digest: &[u8]) -> Result<Message, MessageLengthError> {
         match digest.len() {
             constants::MESSAGE_SIZE => {
                 let mut ret = [0u8; constants::MESSAGE_SIZE];
                 ret[..].copy_from_slice(digest);
                 Ok(Message(ret))
             }
-            _ => Err(Error::InvalidMessage),
+            got => Err(MessageLengthError { expected: constants::MESSAGE_SIZE, got }),
         }
     }
+
+    /// Creates a [`Message`] by hashing `data` with hash algorithm `H`. Requires the `hashes`
+    /// feature to be enabled.
+    #[cfg(feature = "hashes")]
+    #[allow(deprecated)]
+    #[inline]
+    pub fn from_hashed_data<H: ThirtyTwoByteHash + hashes::Hash>(data: &[u8]) -> Message {
+        <H as hashes::Hash>::hash(data).into()
+    }
+
+    /// Creates a [`Message`] by hashing `data` with SHA-256. Requires the `hashes` feature to be
+    /// enabled.
+    #[cfg(feature = "hashes")]
+    #[inline]
+    pub fn from_hashed_data_sha256(data: &[u8]) -> Message {
+        Message::from_hashed_data::<hashes::sha256::Hash>(data)
+    }
 }
 
 #[allow(deprecated)]
@@ -279,6 +307,34 @@ impl fmt::Display for Message {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { fmt::LowerHex::fmt(self, f) }
 }
 
+impl FixedHex<{ constants::MESSAGE_SIZE }> for Message {
+    fn to_byte_array(&self) -> [u8; constants::MESSAGE_SIZE] { self.0 }
+
+    fn from_byte_array(bytes: [u8; constants::MESSAGE_SIZE]) -> Result<Message, Error> {
+        Ok(Message(bytes))
+    }
+}
+
+/// Returned by [`Message::from_digest_slice`] when the input isn't exactly
+/// [`constants::MESSAGE_SIZE`] bytes long.
+#[derive(Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+pub struct MessageLengthError {
+    /// The expected length, i.e. [`constants::MESSAGE_SIZE`].
+    pub expected: usize,
+    /// The length of the slice that was passed in.
+    pub got: usize,
+}
+
+impl fmt::Display for MessageLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "message was {} bytes, expected {} (do you need to hash?)", self.got, self.expected)
+    }
+}
+
+impl From<MessageLengthError> for Error {
+    fn from(_: MessageLengthError) -> Self { Error::InvalidMessage }
+}
+
 /// The main error type for this library.
 #[derive(Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub enum Error {
@@ -306,6 +362,8 @@ pub enum Error {
     InvalidParityValue(key::InvalidParityValue),
     /// Bad EllSwift value
     InvalidEllSwift,
+    /// Buffer passed to a [`FixedHex`] method was the wrong size.
+    InvalidHexLength,
 }
 
 impl fmt::Display for Error {
@@ -327,6 +385,7 @@ impl fmt::Display for Error {
             ),
             InvalidParityValue(e) => write_err!(f, "couldn't create parity"; e),
             InvalidEllSwift => f.write_str("malformed EllSwift value"),
+            InvalidHexLength => f.write_str("hex buffer or string had the wrong length"),
         }
     }
 }
@@ -353,6 +412,331 @@ impl<C: Context> fmt::Debug for Secp256k1<C> {
     }
 }
 
+/// Computes `HMAC-SHA256(key, data)`.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    use sdk::hash::{Hasher, Sha256};
+
+    const BLOCK_SIZE: usize = 64;
+
+    let mut key_block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        let mut hasher = Sha256::new();
+        hasher.update(key);
+        let mut digest = [0u8; 32];
+        hasher.digest(&mut digest);
+        key_block[..32].copy_from_slice(&digest);
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut ipad = [0x36u8; BLOCK_SIZE];
+    let mut opad = [0x5cu8; BLOCK_SIZE];
+    for i in 0..BLOCK_SIZE {
+        ipad[i] ^= key_block[i];
+        opad[i] ^= key_block[i];
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(&ipad);
+    inner.update(data);
+    let mut inner_digest = [0u8; 32];
+    inner.digest(&mut inner_digest);
+
+    let mut outer = Sha256::new();
+    outer.update(&opad);
+    outer.update(&inner_digest);
+    let mut result = [0u8; 32];
+    outer.digest(&mut result);
+    result
+}
+
+/// Derives a deterministic ECDSA nonce per RFC 6979, using the libsecp256k1 convention of mixing
+/// in 32 bytes of optional extra entropy (used by [`Secp256k1::sign_ecdsa_with_noncedata`] and the
+/// grinding functions).
+fn nonce_rfc6979(msg: &[u8; 32], key: &[u8; 32], extra: Option<&[u8; 32]>) -> [u8; 32] {
+    let mut v = [0x01u8; 32];
+    let mut k = [0x00u8; 32];
+
+    let mut buf = [0u8; 32 + 1 + 32 + 32 + 32];
+    for first_pass in [true, false] {
+        let mut len = 0;
+        buf[len..len + 32].copy_from_slice(&v);
+        len += 32;
+        buf[len] = if first_pass { 0x00 } else { 0x01 };
+        len += 1;
+        buf[len..len + 32].copy_from_slice(key);
+        len += 32;
+        buf[len..len + 32].copy_from_slice(msg);
+        len += 32;
+        if let Some(extra) = extra {
+            buf[len..len + 32].copy_from_slice(extra);
+            len += 32;
+        }
+        k = hmac_sha256(&k, &buf[..len]);
+        v = hmac_sha256(&k, &v);
+    }
+
+    loop {
+        v = hmac_sha256(&k, &v);
+        if v != [0u8; 32]
+            && BigNum::<32>::from_be_bytes(v) < BigNum::<32>::from_be_bytes(constants::CURVE_ORDER)
+        {
+            return v;
+        }
+        let mut buf = [0u8; 33];
+        buf[..32].copy_from_slice(&v);
+        buf[32] = 0x00;
+        k = hmac_sha256(&k, &buf);
+        v = hmac_sha256(&k, &v);
+    }
+}
+
+/// Computes the modular inverse of `scalar` modulo the curve order `n`, via Fermat's little
+/// theorem (`n` is prime).
+pub(crate) fn scalar_inverse(scalar: [u8; 32]) -> [u8; 32] {
+    let n_minus_two =
+        &BigNum::<32>::from_be_bytes(constants::CURVE_ORDER) - &BigNum::<32>::from_be_bytes(constants::TWO);
+    constants::N.new_big_num_mod(scalar).pow(&n_minus_two).to_be_bytes()
+}
+
+/// Computes the `secp256k1_fuzz` stand-in signature for `msg` under `pubkey`: a cheap,
+/// deterministic, non-cryptographic binding of the two, with the same `(r, s)` compact layout as
+/// a real signature. Shared by the fuzz-mode `sign_ecdsa`/`sign_ecdsa_grind_r` and `verify_ecdsa`
+/// so that signing and verifying agree without either side doing real curve arithmetic.
+#[cfg(secp256k1_fuzz)]
+fn fuzz_signature(msg: &Message, pubkey: &PublicKey) -> ecdsa::Signature {
+    let msg_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+    let pubkey_bytes = pubkey.serialize_uncompressed();
+
+    let mut r = [0u8; 32];
+    for i in 0..32 {
+        r[i] = msg_bytes[i] ^ pubkey_bytes[1 + i];
+    }
+
+    let mut compact = [0u8; constants::COMPACT_SIGNATURE_SIZE];
+    compact[..32].copy_from_slice(&r);
+    compact[32..].copy_from_slice(&msg_bytes);
+    ecdsa::Signature::from_compact(&compact).expect("r and s are always 32 bytes each")
+}
+
+impl<C: Signing> Secp256k1<C> {
+    /// Signs `msg` with `seckey`, using an RFC 6979 deterministic nonce.
+    #[cfg(not(secp256k1_fuzz))]
+    #[inline]
+    pub fn sign_ecdsa(&self, msg: &Message, seckey: &SecretKey) -> ecdsa::Signature {
+        self.sign_ecdsa_with_noncedata_opt(msg, seckey, None)
+    }
+
+    /// `secp256k1_fuzz` stand-in for [`Self::sign_ecdsa`]: a fast, trivially-broken scheme that
+    /// exercises the surrounding signature machinery (DER/compact encoding, serde) without doing
+    /// real curve arithmetic. **Must never be enabled in production** — the cfg only exists so
+    /// fuzz harnesses can forge "valid" signatures cheaply.
+    #[cfg(secp256k1_fuzz)]
+    pub fn sign_ecdsa(&self, msg: &Message, seckey: &SecretKey) -> ecdsa::Signature {
+        let pubkey = PublicKey::from_secret_key(self, seckey);
+        fuzz_signature(msg, &pubkey)
+    }
+
+    /// Signs `msg` with `seckey`, mixing `noncedata` into the RFC 6979 nonce derivation as extra
+    /// entropy.
+    #[inline]
+    pub fn sign_ecdsa_with_noncedata(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+        noncedata: &[u8; 32],
+    ) -> ecdsa::Signature {
+        self.sign_ecdsa_with_noncedata_opt(msg, seckey, Some(*noncedata))
+    }
+
+    fn sign_ecdsa_with_noncedata_opt(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+        noncedata: Option<[u8; 32]>,
+    ) -> ecdsa::Signature {
+        let msg_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+        let d = seckey.secret_bytes();
+
+        loop {
+            let k = nonce_rfc6979(&msg_bytes, &d, noncedata.as_ref());
+
+            let r_point = &constants::G * &k;
+            let r_bytes = r_point.to_bytes();
+            let mut rx = [0u8; 32];
+            rx.copy_from_slice(&r_bytes[1..33]);
+            let r = constants::N.new_big_num_mod(rx).to_be_bytes();
+            if r == [0u8; 32] {
+                continue; // negligible probability; libsecp256k1 retries with a fresh nonce too
+            }
+
+            let k_inv = scalar_inverse(k);
+            let e_mod = constants::N.new_big_num_mod(msg_bytes);
+            let r_mod = constants::N.new_big_num_mod(r);
+            let d_mod = constants::N.new_big_num_mod(d);
+            let s = (&constants::N.new_big_num_mod(k_inv) * &(&e_mod + &(&r_mod * &d_mod))).to_be_bytes();
+            if s == [0u8; 32] {
+                continue;
+            }
+
+            let mut compact = [0u8; constants::COMPACT_SIGNATURE_SIZE];
+            compact[..32].copy_from_slice(&rx);
+            compact[32..].copy_from_slice(&s);
+            let mut sig =
+                ecdsa::Signature::from_compact(&compact).expect("r and s are always 32 bytes each");
+            sig.normalize_s();
+            return sig;
+        }
+    }
+
+    /// Signs `msg` with `seckey`, grinding the nonce until the resulting signature's DER encoding
+    /// satisfies `predicate`. The returned signature is always normalized to low-S form.
+    pub fn sign_ecdsa_grind_with<F: Fn(&ecdsa::SerializedSignature) -> bool>(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+        predicate: F,
+    ) -> ecdsa::Signature {
+        let mut extra = [0u8; 32];
+        let mut counter: u32 = 0;
+        loop {
+            // `sign_ecdsa_with_noncedata_opt` already normalizes to low-S.
+            let sig = self.sign_ecdsa_with_noncedata_opt(msg, seckey, Some(extra));
+            if predicate(&sig.serialize_der()) {
+                return sig;
+            }
+            counter = counter.wrapping_add(1);
+            extra[..4].copy_from_slice(&counter.to_be_bytes());
+        }
+    }
+
+    /// Signs `msg` with `seckey`, grinding the nonce until `r` has at least `bytes_to_grind`
+    /// leading zero bytes in its DER encoding. The returned signature is normalized to low-S form.
+    #[cfg(not(secp256k1_fuzz))]
+    pub fn sign_ecdsa_grind_r(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+        bytes_to_grind: usize,
+    ) -> ecdsa::Signature {
+        self.sign_ecdsa_grind_with(msg, seckey, |der| der.r_leading_zeros() >= bytes_to_grind)
+    }
+
+    /// `secp256k1_fuzz` stand-in for [`Self::sign_ecdsa_grind_r`]: there is no nonce to grind in
+    /// the fuzz scheme, so this just delegates to [`Self::sign_ecdsa`]. **Must never be enabled in
+    /// production.**
+    #[cfg(secp256k1_fuzz)]
+    #[inline]
+    pub fn sign_ecdsa_grind_r(
+        &self,
+        msg: &Message,
+        seckey: &SecretKey,
+        _bytes_to_grind: usize,
+    ) -> ecdsa::Signature {
+        self.sign_ecdsa(msg, seckey)
+    }
+
+    /// Signs `msg` with `seckey`, grinding for a signature whose `r` has (at least) one leading
+    /// zero byte, which shaves a byte off the DER encoding in the common case.
+    #[inline]
+    pub fn sign_ecdsa_low_r(&self, msg: &Message, seckey: &SecretKey) -> ecdsa::Signature {
+        self.sign_ecdsa_grind_r(msg, seckey, 1)
+    }
+}
+
+impl<C: Verification> Secp256k1<C> {
+    /// Verifies that `signature` is a valid ECDSA signature over `msg` by `pubkey`.
+    #[cfg(not(secp256k1_fuzz))]
+    pub fn verify_ecdsa(
+        &self,
+        msg: &Message,
+        signature: &ecdsa::Signature,
+        pubkey: &PublicKey,
+    ) -> Result<(), Error> {
+        let (r, s) = signature.r_s();
+        if !constants::is_valid_scalar(&r) || !constants::is_valid_scalar(&s) {
+            return Err(Error::InvalidSignature);
+        }
+
+        let msg_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+
+        let s_inv = scalar_inverse(s);
+        let e_mod = constants::N.new_big_num_mod(msg_bytes);
+        let r_mod = constants::N.new_big_num_mod(r);
+        let s_inv_mod = constants::N.new_big_num_mod(s_inv);
+        let u1 = (&s_inv_mod * &e_mod).to_be_bytes();
+        let u2 = (&s_inv_mod * &r_mod).to_be_bytes();
+
+        let pubkey_bytes = pubkey.serialize_uncompressed();
+        let mut px = [0u8; 32];
+        let mut py = [0u8; 32];
+        px.copy_from_slice(&pubkey_bytes[1..33]);
+        py.copy_from_slice(&pubkey_bytes[33..65]);
+        let pubkey_point = Secp256k1Point::new(px, py);
+
+        let point = &(&constants::G * &u1) + &(&pubkey_point * &u2);
+        let point_bytes = point.to_bytes();
+        if point_bytes == [0u8; 65] {
+            return Err(Error::IncorrectSignature);
+        }
+
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&point_bytes[1..33]);
+        if constants::N.new_big_num_mod(x).to_be_bytes() == r {
+            Ok(())
+        } else {
+            Err(Error::IncorrectSignature)
+        }
+    }
+
+    /// `secp256k1_fuzz` stand-in for [`Self::verify_ecdsa`]: a cheap equality check against the
+    /// same stand-in scheme used by the fuzz-mode `sign_ecdsa`. **Must never be enabled in
+    /// production.**
+    #[cfg(secp256k1_fuzz)]
+    pub fn verify_ecdsa(
+        &self,
+        msg: &Message,
+        signature: &ecdsa::Signature,
+        pubkey: &PublicKey,
+    ) -> Result<(), Error> {
+        if *signature == fuzz_signature(msg, pubkey) {
+            Ok(())
+        } else {
+            Err(Error::IncorrectSignature)
+        }
+    }
+}
+
+/// A fixed-size byte value that can be hex-encoded into, or parsed out of, a caller-supplied
+/// buffer without allocating. [`Message`], [`SecretKey`] and the signature types implement this
+/// so that `no_std` callers get one buffer-based hex codec for all of them, instead of going
+/// through `Display`/`FromStr`, which assume an allocator is available.
+pub trait FixedHex<const N: usize>: Sized {
+    /// Returns this value's underlying fixed-size byte representation.
+    fn to_byte_array(&self) -> [u8; N];
+
+    /// Reconstructs a value from its underlying fixed-size byte representation.
+    fn from_byte_array(bytes: [u8; N]) -> Result<Self, Error>;
+
+    /// Hex-encodes this value into `buf`, returning the written prefix as a `&str`.
+    ///
+    /// `buf` must be at least `2 * N` bytes long, or this returns
+    /// [`Error::InvalidHexLength`].
+    fn to_hex_in<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, Error> {
+        to_hex(&self.to_byte_array(), buf).map_err(|()| Error::InvalidHexLength)
+    }
+
+    /// Parses a value from its hex representation.
+    fn from_hex(hex: &str) -> Result<Self, Error> {
+        let mut bytes = [0u8; N];
+        let n = from_hex(hex, &mut bytes).map_err(|()| Error::InvalidHexLength)?;
+        if n != N {
+            return Err(Error::InvalidHexLength);
+        }
+        Self::from_byte_array(bytes)
+    }
+}
+
 /// Utility function used to parse hex into a target u8 buffer. Returns
 /// the number of bytes converted or an error if it encounters an invalid
 /// character or unexpected end of string.
@@ -505,11 +889,11 @@ mod tests {
 
         assert_eq!(
             Message::from_digest_slice(&[0; constants::MESSAGE_SIZE - 1]),
-            Err(Error::InvalidMessage)
+            Err(MessageLengthError { expected: constants::MESSAGE_SIZE, got: constants::MESSAGE_SIZE - 1 })
         );
         assert_eq!(
             Message::from_digest_slice(&[0; constants::MESSAGE_SIZE + 1]),
-            Err(Error::InvalidMessage)
+            Err(MessageLengthError { expected: constants::MESSAGE_SIZE, got: constants::MESSAGE_SIZE + 1 })
         );
         assert!(Message::from_digest_slice(&[0; constants::MESSAGE_SIZE]).is_ok());
         assert!(Message::from_digest_slice(&[1; constants::MESSAGE_SIZE]).is_ok());
@@ -557,6 +941,52 @@ mod tests {
         assert_eq!(secp.verify_ecdsa(&msg, &sig, &pk), Ok(()));
     }
 
+    #[test]
+    #[cfg(not(secp256k1_fuzz))] // fixed sig vectors can't work with fuzz-sigs
+    #[cfg(feature = "alloc")]
+    fn test_sign_ecdsa_normalizes_high_s() {
+        // `test_serde`'s fixed (sk, msg) pair happens to already produce a low-S signature, so it
+        // wouldn't have caught `sign_ecdsa`/`sign_ecdsa_with_noncedata` forgetting to normalize.
+        // Search for a message whose raw RFC 6979 nonce actually yields a high-S signature before
+        // normalization, by recomputing the same math `sign_ecdsa_with_noncedata_opt` does.
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let d = sk.secret_bytes();
+
+        let mut msg_bytes = [0u8; 32];
+        let raw_s = loop {
+            msg_bytes[31] = msg_bytes[31].wrapping_add(1);
+
+            let k = nonce_rfc6979(&msg_bytes, &d, None);
+            let r_point = &constants::G * &k;
+            let r_bytes = r_point.to_bytes();
+            let mut rx = [0u8; 32];
+            rx.copy_from_slice(&r_bytes[1..33]);
+            let r = constants::N.new_big_num_mod(rx).to_be_bytes();
+            if r == [0u8; 32] {
+                continue;
+            }
+
+            let k_inv = scalar_inverse(k);
+            let e_mod = constants::N.new_big_num_mod(msg_bytes);
+            let r_mod = constants::N.new_big_num_mod(r);
+            let d_mod = constants::N.new_big_num_mod(d);
+            let s = (&constants::N.new_big_num_mod(k_inv) * &(&e_mod + &(&r_mod * &d_mod))).to_be_bytes();
+            if s == [0u8; 32] {
+                continue;
+            }
+            if BigNum::<32>::from_be_bytes(s) > BigNum::<32>::from_be_bytes(constants::CURVE_ORDER_HALF) {
+                break s;
+            }
+        };
+        // Sanity check on the test itself: this message really does produce a high-S raw signature.
+        assert!(BigNum::<32>::from_be_bytes(raw_s) > BigNum::<32>::from_be_bytes(constants::CURVE_ORDER_HALF));
+
+        let msg = Message::from_digest_slice(&msg_bytes).unwrap();
+        let (_, s) = secp.sign_ecdsa(&msg, &sk).r_s();
+        assert!(BigNum::<32>::from_be_bytes(s) <= BigNum::<32>::from_be_bytes(constants::CURVE_ORDER_HALF));
+    }
+
     #[test]
     #[cfg(not(secp256k1_fuzz))] // fuzz-sigs have fixed size/format
     #[cfg(feature = "alloc")]
@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Support for Schnorr signatures (BIP-340), as used by Bitcoin Taproot.
+
+use core::fmt;
+
+#[cfg(feature = "hashes")]
+use hashes::{sha256, Hash, HashEngine};
+use sdk::bignum::{BigNum, ModulusProvider};
+use sdk::curve::Secp256k1Point;
+
+use crate::{constants, Error, FixedHex, Keypair, Message, Secp256k1, Signing, Verification, XOnlyPublicKey};
+
+/// A Schnorr signature, as defined in BIP-340: the X coordinate of the nonce point `R` followed
+/// by the scalar `s`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    bytes: [u8; constants::SCHNORR_SIGNATURE_SIZE],
+}
+
+impl Signature {
+    /// Creates a `Signature` directly from the 64-byte `r || s` encoding.
+    #[inline]
+    pub fn from_slice(data: &[u8]) -> Result<Signature, Error> {
+        if data.len() != constants::SCHNORR_SIGNATURE_SIZE {
+            return Err(Error::InvalidSignature);
+        }
+        let mut bytes = [0u8; constants::SCHNORR_SIGNATURE_SIZE];
+        bytes.copy_from_slice(data);
+        Ok(Signature { bytes })
+    }
+
+    /// Returns a reference to the signature's `r || s` byte encoding.
+    #[inline]
+    pub fn as_byte_array(&self) -> &[u8; constants::SCHNORR_SIGNATURE_SIZE] { &self.bytes }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.bytes.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FixedHex<{ constants::SCHNORR_SIGNATURE_SIZE }> for Signature {
+    fn to_byte_array(&self) -> [u8; constants::SCHNORR_SIGNATURE_SIZE] { self.bytes }
+
+    fn from_byte_array(bytes: [u8; constants::SCHNORR_SIGNATURE_SIZE]) -> Result<Signature, Error> {
+        Ok(Signature { bytes })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Signature {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            let mut buf = [0u8; constants::SCHNORR_SIGNATURE_SIZE * 2];
+            let hex = self.to_hex_in(&mut buf).map_err(serde::ser::Error::custom)?;
+            s.serialize_str(hex)
+        } else {
+            s.serialize_bytes(&self.bytes)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Signature {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Signature, D::Error> {
+        if d.is_human_readable() {
+            struct HexVisitor;
+            impl<'de> serde::de::Visitor<'de> for HexVisitor {
+                type Value = Signature;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a hex-encoded Schnorr signature")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Signature, E> {
+                    Signature::from_hex(v).map_err(E::custom)
+                }
+            }
+            d.deserialize_str(HexVisitor)
+        } else {
+            struct BytesVisitor;
+            impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+                type Value = Signature;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("64 bytes of Schnorr signature")
+                }
+
+                fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Signature, E> {
+                    Signature::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+                }
+            }
+            d.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
+
+/// Computes `SHA256(SHA256(tag) || SHA256(tag) || msgs...)`, the tagged hash construction used
+/// throughout BIP-340 to domain-separate the auxiliary, nonce and challenge hashes from each
+/// other and from unrelated uses of SHA-256.
+#[cfg(feature = "hashes")]
+fn tagged_hash(tag: &[u8], msgs: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = sha256::Hash::hash(tag);
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag_hash[..]);
+    engine.input(&tag_hash[..]);
+    for msg in msgs {
+        engine.input(msg);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Negates `scalar` modulo the curve order, i.e. returns `n - scalar`. `scalar` must already be
+/// reduced mod `n`.
+#[cfg(feature = "hashes")]
+fn negate_scalar(scalar: [u8; 32]) -> [u8; 32] {
+    (&BigNum::<32>::from_be_bytes(constants::CURVE_ORDER) - &BigNum::<32>::from_be_bytes(scalar)).to_be_bytes()
+}
+
+/// Reconstructs the even-Y curve point whose X coordinate is `x`, i.e. the point represented by
+/// an x-only public key, by solving `y² = x³ + 7 (mod p)` and picking the even root.
+#[cfg(feature = "hashes")]
+fn lift_x(x: [u8; 32]) -> Result<Secp256k1Point, Error> {
+    let x_mod = constants::P.new_big_num_mod(x);
+    let y_squared = &(&(&x_mod * &x_mod) * &x_mod) + &constants::P.new_big_num_mod(SEVEN);
+
+    // p ≡ 3 (mod 4), so the square root (when it exists) is y_squared^((p + 1) / 4) mod p.
+    let y = y_squared.pow(&BigNum::<32>::from_be_bytes(constants::SQR_EXPONENT));
+    if &y * &y != y_squared {
+        return Err(Error::InvalidPublicKey); // x is not the X coordinate of a curve point
+    }
+
+    let y_bytes = y.to_be_bytes();
+    let y_bytes = if y_bytes[31] & 1 == 1 {
+        (&BigNum::<32>::from_be_bytes(constants::FIELD_SIZE) - &BigNum::<32>::from_be_bytes(y_bytes)).to_be_bytes()
+    } else {
+        y_bytes
+    };
+
+    Ok(Secp256k1Point::new(x, y_bytes))
+}
+
+#[cfg(feature = "hashes")]
+const SEVEN: [u8; 32] = {
+    let mut b = [0u8; 32];
+    b[31] = 7;
+    b
+};
+
+impl<C: Signing> Secp256k1<C> {
+    /// Creates a Schnorr signature over `msg` with `keypair`, mixing in `aux_rand` as the 32 bytes
+    /// of auxiliary randomness described by BIP-340. Callers should prefer fresh randomness for
+    /// `aux_rand`; [`Self::sign_schnorr_no_aux_rand`] is available for fully deterministic tests.
+    #[cfg(feature = "hashes")]
+    pub fn sign_schnorr_with_aux_rand(
+        &self,
+        msg: &Message,
+        keypair: &Keypair,
+        aux_rand: &[u8; 32],
+    ) -> Signature {
+        let pubkey_bytes = keypair.public_key().serialize_uncompressed();
+        let mut px = [0u8; 32];
+        px.copy_from_slice(&pubkey_bytes[1..33]);
+        let p_is_odd = pubkey_bytes[64] & 1 == 1;
+
+        let d = keypair.secret_bytes();
+        let d = if p_is_odd { negate_scalar(d) } else { d };
+
+        let t_hash = tagged_hash("BIP0340/aux".as_bytes(), &[aux_rand]);
+        let mut t = [0u8; 32];
+        for i in 0..32 {
+            t[i] = d[i] ^ t_hash[i];
+        }
+
+        let msg_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+        let rand = tagged_hash("BIP0340/nonce".as_bytes(), &[&t, &px, &msg_bytes]);
+        let k = constants::N.new_big_num_mod(rand).to_be_bytes();
+
+        let r_point = &constants::G * &k;
+        let r_bytes = r_point.to_bytes();
+        let r_is_odd = r_bytes[64] & 1 == 1;
+        let k = if r_is_odd { negate_scalar(k) } else { k };
+
+        let mut rx = [0u8; 32];
+        rx.copy_from_slice(&r_bytes[1..33]);
+
+        let e = tagged_hash("BIP0340/challenge".as_bytes(), &[&rx, &px, &msg_bytes]);
+        let e = constants::N.new_big_num_mod(e);
+
+        let k_mod = constants::N.new_big_num_mod(k);
+        let d_mod = constants::N.new_big_num_mod(d);
+        let s = (&k_mod + &(&e * &d_mod)).to_be_bytes();
+
+        let mut bytes = [0u8; constants::SCHNORR_SIGNATURE_SIZE];
+        bytes[..32].copy_from_slice(&rx);
+        bytes[32..].copy_from_slice(&s);
+        Signature { bytes }
+    }
+
+    /// Creates a Schnorr signature over `msg` with `keypair`, using no auxiliary randomness (all
+    /// zero bytes). This is fully deterministic and is mainly useful for reproducible tests;
+    /// [`Self::sign_schnorr_with_aux_rand`] should be preferred otherwise.
+    #[cfg(feature = "hashes")]
+    #[inline]
+    pub fn sign_schnorr_no_aux_rand(&self, msg: &Message, keypair: &Keypair) -> Signature {
+        self.sign_schnorr_with_aux_rand(msg, keypair, &[0u8; 32])
+    }
+}
+
+impl<C: Verification> Secp256k1<C> {
+    /// Verifies a Schnorr `signature` over `msg` against the x-only public key `pubkey`.
+    #[cfg(feature = "hashes")]
+    pub fn verify_schnorr(
+        &self,
+        signature: &Signature,
+        msg: &Message,
+        pubkey: &XOnlyPublicKey,
+    ) -> Result<(), Error> {
+        let rx: [u8; 32] = signature.bytes[..32].try_into().expect("64-byte signature");
+        let s: [u8; 32] = signature.bytes[32..].try_into().expect("64-byte signature");
+
+        if !constants::is_valid_field_element(&rx) || BigNum::<32>::from_be_bytes(s) >= BigNum::<32>::from_be_bytes(constants::CURVE_ORDER) {
+            return Err(Error::InvalidSignature);
+        }
+
+        let px = pubkey.serialize();
+        let p_point = lift_x(px)?;
+
+        let msg_bytes: [u8; 32] = msg.as_ref().try_into().expect("Message is 32 bytes");
+        let e = tagged_hash("BIP0340/challenge".as_bytes(), &[&rx, &px, &msg_bytes]);
+
+        let s_g = &constants::G * &s;
+        let e_p = &p_point * &e;
+        let r_point = &s_g - &e_p;
+        let r_bytes = r_point.to_bytes();
+
+        if r_bytes == [0u8; 65] {
+            return Err(Error::IncorrectSignature); // R is the point at infinity
+        }
+        if r_bytes[64] & 1 == 1 {
+            return Err(Error::IncorrectSignature); // R does not have even Y
+        }
+        if &r_bytes[1..33] != &rx[..] {
+            return Err(Error::IncorrectSignature);
+        }
+
+        Ok(())
+    }
+}
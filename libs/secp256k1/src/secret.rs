@@ -6,7 +6,7 @@ use core::fmt;
 
 use crate::constants::SECRET_KEY_SIZE;
 use crate::key::{Keypair, SecretKey};
-use crate::to_hex;
+use crate::{to_hex, Error, FixedHex};
 macro_rules! impl_display_secret {
     // Default hasher exists only in standard library and not alloc
     ($thing:ident) => {
@@ -104,6 +104,14 @@ impl SecretKey {
     pub fn display_secret(&self) -> DisplaySecret { DisplaySecret { secret: self.secret_bytes() } }
 }
 
+impl FixedHex<SECRET_KEY_SIZE> for SecretKey {
+    fn to_byte_array(&self) -> [u8; SECRET_KEY_SIZE] { self.secret_bytes() }
+
+    fn from_byte_array(bytes: [u8; SECRET_KEY_SIZE]) -> Result<SecretKey, Error> {
+        SecretKey::from_slice(&bytes).map_err(|_| Error::InvalidSecretKey)
+    }
+}
+
 impl Keypair {
     /// Formats the explicit byte value of the secret key kept inside the type as a
     /// little-endian hexadecimal string using the provided formatter.
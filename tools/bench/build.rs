@@ -0,0 +1,27 @@
+// Captures the vanadium checkout's git commit and working-tree cleanliness at build time, so the
+// benchmark results file can be tagged with exactly what was built without shelling out to `git`
+// (and risking a stale or unavailable checkout) at benchmark run time.
+
+use std::process::Command;
+
+fn git_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok().map(|s| s.trim().to_string())
+}
+
+fn main() {
+    let commit = git_output(&["rev-parse", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+
+    let dirty = git_output(&["status", "--porcelain"])
+        .map(|status| !status.is_empty())
+        .unwrap_or(false);
+
+    println!("cargo:rustc-env=VANADIUM_BUILD_COMMIT={commit}");
+    println!("cargo:rustc-env=VANADIUM_BUILD_DIRTY={dirty}");
+
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/index");
+}
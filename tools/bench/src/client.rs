@@ -0,0 +1,103 @@
+use crate::workload::Workload;
+use sdk::vanadium_client::{VAppClient, VAppExecutionError};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub enum BenchClientError {
+    VAppExecutionError(VAppExecutionError),
+    GenericError(&'static str),
+}
+
+impl From<VAppExecutionError> for BenchClientError {
+    fn from(e: VAppExecutionError) -> Self {
+        Self::VAppExecutionError(e)
+    }
+}
+
+impl std::fmt::Display for BenchClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BenchClientError::VAppExecutionError(e) => write!(f, "VAppExecutionError: {}", e),
+            BenchClientError::GenericError(e) => write!(f, "GenericError: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BenchClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BenchClientError::VAppExecutionError(e) => Some(e),
+            BenchClientError::GenericError(_) => None,
+        }
+    }
+}
+
+/// Drives a running benchmark V-App: either the legacy single-message shape ([`Self::run_and_exit`])
+/// or a declarative [`Workload`] ([`Self::run_workload`]).
+pub struct BenchClient<'a> {
+    app_client: &'a mut (dyn VAppClient + Send + Sync),
+}
+
+impl<'a> BenchClient<'a> {
+    pub fn new(app_client: &'a mut (dyn VAppClient + Send + Sync)) -> Self {
+        Self { app_client }
+    }
+
+    /// Sends the repetition count as a single 8-byte big-endian message, matching the bench cases
+    /// that read it once via `sdk::xrecv(8)` and then loop internally before exiting. Returns once
+    /// the V-App exits.
+    pub async fn run_and_exit(&mut self, repetitions: u64) -> Result<(), BenchClientError> {
+        match self
+            .app_client
+            .send_message(repetitions.to_be_bytes().to_vec())
+            .await
+        {
+            Ok(_) => Err(BenchClientError::GenericError(
+                "V-App should have exited after running its repetitions",
+            )),
+            Err(VAppExecutionError::AppExited(_status)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Runs every [`Operation`](crate::workload::Operation) in `workload` in order, sending its
+    /// `request` payload `warmup + iterations` times and reading back one reply per send, except
+    /// for the very last send of the very last operation, which is expected to make the V-App
+    /// exit instead. Returns the total wall-clock time spent in the non-warmup sends.
+    pub async fn run_workload(
+        &mut self,
+        workload: &Workload,
+    ) -> Result<Duration, BenchClientError> {
+        let mut measured = Duration::ZERO;
+        let n_ops = workload.operations.len();
+
+        for (op_index, op) in workload.operations.iter().enumerate() {
+            let total_sends = op.warmup + op.iterations;
+
+            for i in 0..total_sends {
+                let is_last_send = op_index + 1 == n_ops && i + 1 == total_sends;
+                let start = (i >= op.warmup).then(Instant::now);
+
+                if is_last_send {
+                    match self.app_client.send_message(op.request.clone()).await {
+                        Ok(_) => {
+                            return Err(BenchClientError::GenericError(
+                                "V-App should have exited after its last operation",
+                            ))
+                        }
+                        Err(VAppExecutionError::AppExited(_status)) => {}
+                        Err(e) => return Err(e.into()),
+                    }
+                } else {
+                    self.app_client.send_message(op.request.clone()).await?;
+                }
+
+                if let Some(start) = start {
+                    measured += start.elapsed();
+                }
+            }
+        }
+
+        Ok(measured)
+    }
+}
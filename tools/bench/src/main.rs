@@ -19,6 +19,11 @@ use std::sync::Arc;
 use std::time::Instant;
 
 mod client;
+mod results;
+mod workload;
+
+use results::{BenchResults, CaseResult};
+use workload::Workload;
 
 const DEFAULT_REPETITIONS: u64 = 10;
 
@@ -36,6 +41,20 @@ struct Args {
 
     /// Filter test cases by name (can specify multiple)
     filters: Vec<String>,
+
+    /// Where to write the structured JSON results file for this run.
+    #[arg(long, default_value = "bench_results.json")]
+    output: PathBuf,
+
+    /// A prior results file (as written by a previous `--output`) to compare this run against,
+    /// printing a percentage delta for each case's timings and instruction count.
+    #[arg(long)]
+    compare_to: Option<PathBuf>,
+
+    /// Percentage increase in a timing or the instruction count, relative to `--compare-to`,
+    /// that counts as a regression. Only meaningful together with `--compare-to`.
+    #[arg(long, default_value_t = 5.0)]
+    regression_threshold: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +62,9 @@ struct BenchCase {
     case_name: String,
     crate_name: String,
     repetitions: u64,
+    /// Parsed from `workload.json`, if the case directory has one. Empty means the case sticks
+    /// to the legacy single-run shape driven by `repetitions`.
+    workloads: Vec<Workload>,
 }
 
 impl BenchCase {
@@ -116,10 +138,18 @@ fn discover_bench_cases(
             .into());
         }
 
+        let workload_path = entry.path().join("workload.json");
+        let workloads = if workload_path.is_file() {
+            workload::load_workloads(&workload_path)?
+        } else {
+            Vec::new()
+        };
+
         let case = BenchCase {
             case_name,
             crate_name,
             repetitions,
+            workloads,
         };
 
         cases.push(case);
@@ -131,10 +161,10 @@ fn discover_bench_cases(
 
 #[cfg(feature = "metrics")]
 fn save_metrics(
-    case: &BenchCase,
+    label: &str,
     metrics: &common::metrics::VAppMetrics,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let filename = format!("{}.metrics", case.case_name);
+    let filename = format!("{}.metrics", label);
     let mut file = File::create(&filename)?;
 
     writeln!(file, "V-App Name: {}", metrics.get_vapp_name())?;
@@ -142,16 +172,20 @@ fn save_metrics(
     writeln!(file, "Instruction Count: {}", metrics.instruction_count)?;
     writeln!(file, "Page Loads: {}", metrics.page_loads)?;
     writeln!(file, "Page Commits: {}", metrics.page_commits)?;
+    writeln!(file, "Cache Hits: {}", metrics.cache_hits)?;
+    writeln!(file, "Cache Misses: {}", metrics.cache_misses)?;
 
     Ok(())
 }
 
-// Helper function to run a benchmark case and return total time in ms
+// Helper function to run a benchmark case. Returns the total time in ms and, when the "metrics"
+// feature is enabled, the metrics collected for the run (so the caller can fold them into an
+// aggregate across cases).
 async fn run_bench_case(
     case: &BenchCase,
     repetitions: u64,
     vanadium_client: &mut VanadiumAppClient<Box<dyn std::error::Error + Send + Sync>>,
-) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(f64, Option<common::metrics::VAppMetrics>), Box<dyn std::error::Error + Send + Sync>> {
     // Best-effort cleanup in case a prior run didn't stop cleanly.
     let _ = vanadium_client.stop_vapp().await;
 
@@ -181,29 +215,117 @@ async fn run_bench_case(
 
     // Save metrics if the feature is enabled
     #[cfg(feature = "metrics")]
-    {
+    let metrics = {
         // do not save metrics on the baseline run with 0 repetitions
         if repetitions > 0 {
             match vanadium_client.get_metrics().await {
                 Ok(metrics) => {
-                    if let Err(e) = save_metrics(case, &metrics) {
+                    if let Err(e) = save_metrics(&case.case_name, &metrics) {
                         eprintln!(
                             "Warning: Failed to save metrics for {}: {}",
                             case.case_name, e
                         );
                     }
+                    Some(metrics)
                 }
                 Err(e) => {
                     eprintln!(
                         "Warning: Failed to get metrics for {}: {}",
                         case.case_name, e
                     );
+                    None
                 }
             }
+        } else {
+            None
         }
-    }
+    };
+    #[cfg(not(feature = "metrics"))]
+    let metrics = None;
+
+    Ok((total_ms, metrics))
+}
+
+// Runs a single declarative workload against `case`'s compiled V-App, returning the measured
+// (non-warmup) time in milliseconds. Mirrors `run_bench_case`'s start/stop dance, but drives the
+// V-App through `BenchClient::run_workload` instead of the legacy repetitions-only shape.
+async fn run_bench_workload(
+    case: &BenchCase,
+    workload: &Workload,
+    vanadium_client: &mut VanadiumAppClient<Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<(f64, Option<common::metrics::VAppMetrics>), Box<dyn std::error::Error + Send + Sync>>
+{
+    let _ = vanadium_client.stop_vapp().await;
+
+    #[cfg(feature = "debug")]
+    let print_writer = Box::new(sdk::linewriter::FileLineWriter::new(
+        "print.log",
+        true,
+        true,
+    ));
+    #[cfg(not(feature = "debug"))]
+    let print_writer = Box::new(std::io::sink());
+
+    vanadium_client
+        .start_vapp(&case.app_path(), Box::new(print_writer))
+        .await?;
+
+    let mut client = BenchClient::new(vanadium_client);
+    let bench_result = client.run_workload(workload).await;
+
+    let _ = vanadium_client.stop_vapp().await;
+
+    let total_ms = bench_result?.as_secs_f64() * 1000.0;
 
-    Ok(total_ms)
+    #[cfg(feature = "metrics")]
+    let metrics = match vanadium_client.get_metrics().await {
+        Ok(metrics) => {
+            let label = format!("{}.{}", case.case_name, workload.name);
+            if let Err(e) = save_metrics(&label, &metrics) {
+                eprintln!("Warning: Failed to save metrics for {}: {}", label, e);
+            }
+            Some(metrics)
+        }
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to get metrics for {}/{}: {}",
+                case.case_name, workload.name, e
+            );
+            None
+        }
+    };
+    #[cfg(not(feature = "metrics"))]
+    let metrics = None;
+
+    Ok((total_ms, metrics))
+}
+
+// Prints a line for every metric of `case_result` that regressed or improved by more than
+// `threshold` percent relative to `baseline`, and sets `*regressed` if any metric regressed.
+fn report_comparison(
+    baseline: Option<&BenchResults>,
+    case_result: &CaseResult,
+    threshold: f64,
+    regressed: &mut bool,
+) {
+    let Some(baseline) = baseline else {
+        return;
+    };
+
+    for delta in results::compare(baseline, case_result) {
+        if delta.percent_change > threshold {
+            *regressed = true;
+            println!(
+                "    {:<18} {:+.2}%  ({:.3} -> {:.3})  REGRESSION",
+                delta.metric, delta.percent_change, delta.baseline, delta.current
+            );
+        } else if delta.percent_change < -threshold {
+            println!(
+                "    {:<18} {:+.2}%  ({:.3} -> {:.3})  improved",
+                delta.metric, delta.percent_change, delta.baseline, delta.current
+            );
+        }
+    }
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -272,6 +394,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Print the name/model of the connected device(s) before running benchmarks.
     let app_info = vanadium_client.get_app_info().await?;
     println!("Device: {}", app_info.device_model);
+    println!(
+        "Build: {}{}",
+        results::BUILD_COMMIT,
+        if results::BUILD_DIRTY { " (dirty)" } else { "" }
+    );
+
+    let baseline = match &args.compare_to {
+        Some(path) => Some(BenchResults::load(path)?),
+        None => None,
+    };
+    let mut results = BenchResults::new(app_info.device_model.clone());
+    let mut regressed = false;
 
     if testcases.len() == 0 {
         println!("No test cases found matching the provided arguments.");
@@ -295,23 +429,118 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     );
     println!("{:-<83}", "");
 
+    #[cfg(feature = "metrics")]
+    let mut metrics_aggregate = common::metrics::MetricsAggregate::new();
+
     for case in testcases {
-        print!("{:<15} {:>10} ", case.case_name, case.repetitions);
-        std::io::stdout().flush().unwrap(); // show test name and repetitions before running it
+        if case.workloads.is_empty() {
+            print!("{:<15} {:>10} ", case.case_name, case.repetitions);
+            std::io::stdout().flush().unwrap(); // show test name and repetitions before running it
 
-        // Run with 0 repetitions to measure initialization time
-        let init_ms = run_bench_case(case, 0, &mut vanadium_client).await?;
+            // Run with 0 repetitions to measure initialization time
+            let (init_ms, _) = run_bench_case(case, 0, &mut vanadium_client).await?;
 
-        // Run with actual repetitions
-        let total_with_init_ms =
-            run_bench_case(case, case.repetitions, &mut vanadium_client).await?;
+            // Run with actual repetitions
+            let (total_with_init_ms, metrics) =
+                run_bench_case(case, case.repetitions, &mut vanadium_client).await?;
 
-        // Subtract initialization time from total
-        let total_ms = (total_with_init_ms - init_ms).max(0.0);
-        let avg_ms = total_ms / case.repetitions as f64;
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &metrics {
+                metrics_aggregate.record(metrics);
+            }
+            #[cfg(not(feature = "metrics"))]
+            let _ = metrics;
+
+            // Subtract initialization time from total
+            let total_ms = (total_with_init_ms - init_ms).max(0.0);
+            let avg_ms = total_ms / case.repetitions as f64;
+
+            println!("{:>18.3} {:>18.3} {:>18.3}", init_ms, total_ms, avg_ms);
+
+            let case_result = CaseResult {
+                case_name: case.case_name.clone(),
+                workload_name: None,
+                runs: case.repetitions,
+                init_ms,
+                total_ms,
+                avg_ms,
+                instruction_count: metrics.as_ref().map(|m| m.instruction_count),
+                page_loads: metrics.as_ref().map(|m| m.page_loads),
+                page_commits: metrics.as_ref().map(|m| m.page_commits),
+            };
+            report_comparison(
+                baseline.as_ref(),
+                &case_result,
+                args.regression_threshold,
+                &mut regressed,
+            );
+            results.cases.push(case_result);
+            continue;
+        }
 
-        println!("{:>18.3} {:>18.3} {:>18.3}", init_ms, total_ms, avg_ms);
+        // A declarative `workload.json` was found: run each workload as its own row instead of
+        // the single case-wide repetitions count.
+        for workload in &case.workloads {
+            let runs: u64 = workload.operations.iter().map(|op| op.iterations).sum();
+            let row_name = format!("{}/{}", case.case_name, workload.name);
+            print!("{:<15} {:>10} ", row_name, runs);
+            std::io::stdout().flush().unwrap();
+
+            let (total_ms, metrics) =
+                run_bench_workload(case, workload, &mut vanadium_client).await?;
+            let avg_ms = if runs > 0 { total_ms / runs as f64 } else { 0.0 };
+
+            println!("{:>18.3} {:>18.3} {:>18.3}", 0.0, total_ms, avg_ms);
+
+            let case_result = CaseResult {
+                case_name: case.case_name.clone(),
+                workload_name: Some(workload.name.clone()),
+                runs,
+                init_ms: 0.0,
+                total_ms,
+                avg_ms,
+                instruction_count: metrics.as_ref().map(|m| m.instruction_count),
+                page_loads: metrics.as_ref().map(|m| m.page_loads),
+                page_commits: metrics.as_ref().map(|m| m.page_commits),
+            };
+            report_comparison(
+                baseline.as_ref(),
+                &case_result,
+                args.regression_threshold,
+                &mut regressed,
+            );
+            results.cases.push(case_result);
+        }
     }
     println!("{:=<83}", "");
+
+    // Print aggregate metrics across all cases (V-Apps reused across cases share a `vapp_hash`
+    // and so are totalled together).
+    #[cfg(feature = "metrics")]
+    for metrics in metrics_aggregate.iter() {
+        println!(
+            "{}: {} page loads, {} page commits, {} cache hits, {} cache misses",
+            metrics.get_vapp_name(),
+            metrics.page_loads,
+            metrics.page_commits,
+            metrics.cache_hits,
+            metrics.cache_misses
+        );
+    }
+
+    results.save(&args.output)?;
+
+    if regressed {
+        eprintln!(
+            "\nOne or more cases regressed by more than {:.1}% compared to {}.",
+            args.regression_threshold,
+            args.compare_to
+                .as_ref()
+                .expect("regressed implies a baseline was loaded")
+                .display()
+        );
+        std::process::exit(1);
+    }
+
     Ok(())
 }
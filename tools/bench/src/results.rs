@@ -0,0 +1,115 @@
+//! Structured, persisted benchmark results: one JSON file per run, tagged with the build that
+//! produced it, so two runs (e.g. before/after a change) can be compared with [`compare`] instead
+//! of eyeballing two printed tables.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Git commit of the vanadium checkout this binary was built from, captured by `build.rs`.
+pub const BUILD_COMMIT: &str = env!("VANADIUM_BUILD_COMMIT");
+/// Whether the checkout had uncommitted changes when this binary was built.
+pub const BUILD_DIRTY: bool = match env!("VANADIUM_BUILD_DIRTY").as_bytes() {
+    b"true" => true,
+    _ => false,
+};
+
+/// Results for a single row of the summary table: either a legacy repetitions-only case, or one
+/// workload of a `workload.json` case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub case_name: String,
+    /// `None` for the legacy, repetitions-only shape; `Some(name)` for a named workload.
+    pub workload_name: Option<String>,
+    pub runs: u64,
+    pub init_ms: f64,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+    /// Present only when the `metrics` feature collected a [`common::metrics::VAppMetrics`] for
+    /// this row.
+    pub instruction_count: Option<u64>,
+    pub page_loads: Option<u32>,
+    pub page_commits: Option<u32>,
+}
+
+impl CaseResult {
+    /// The key used to match this result against the same row in another results file.
+    fn key(&self) -> (&str, Option<&str>) {
+        (self.case_name.as_str(), self.workload_name.as_deref())
+    }
+}
+
+/// A full benchmark run: which build produced it, which device it ran on, and its per-row
+/// results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchResults {
+    pub commit: String,
+    pub dirty: bool,
+    pub device_model: String,
+    pub cases: Vec<CaseResult>,
+}
+
+impl BenchResults {
+    pub fn new(device_model: String) -> Self {
+        Self {
+            commit: BUILD_COMMIT.to_string(),
+            dirty: BUILD_DIRTY,
+            device_model,
+            cases: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// The percentage delta of a single metric between a baseline and a new run, positive meaning the
+/// new run got slower/bigger.
+pub struct MetricDelta {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    pub percent_change: f64,
+}
+
+impl MetricDelta {
+    fn new(metric: &'static str, baseline: f64, current: f64) -> Option<Self> {
+        if baseline == 0.0 {
+            return None;
+        }
+        Some(Self {
+            metric,
+            baseline,
+            current,
+            percent_change: (current - baseline) / baseline * 100.0,
+        })
+    }
+}
+
+/// Compares `current` against `baseline` (the matching row, if any, found by case/workload name),
+/// returning every metric whose value could be compared. An empty `baseline` match (new row)
+/// yields no deltas.
+pub fn compare(baseline: &BenchResults, current: &CaseResult) -> Vec<MetricDelta> {
+    let Some(previous) = baseline.cases.iter().find(|c| c.key() == current.key()) else {
+        return Vec::new();
+    };
+
+    let mut deltas = Vec::new();
+    deltas.extend(MetricDelta::new("total_ms", previous.total_ms, current.total_ms));
+    deltas.extend(MetricDelta::new("avg_ms", previous.avg_ms, current.avg_ms));
+    if let (Some(prev), Some(cur)) = (previous.instruction_count, current.instruction_count) {
+        deltas.extend(MetricDelta::new(
+            "instruction_count",
+            prev as f64,
+            cur as f64,
+        ));
+    }
+    deltas
+}
@@ -0,0 +1,56 @@
+//! Declarative workload files (`cases/<name>/workload.json`): a named benchmark described as an
+//! ordered list of operations, each carrying its own request payload, iteration count and
+//! optional warmup count, so one compiled V-App binary can be exercised with several distinct
+//! request sequences without recompiling.
+
+use serde::Deserialize;
+use std::path::Path;
+
+/// A single request replayed against the V-App some number of times.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Operation {
+    /// The request payload, as it's sent to the V-App via `send_message`.
+    #[serde(with = "hex_bytes")]
+    pub request: Vec<u8>,
+    /// Number of timed repetitions of this operation.
+    pub iterations: u64,
+    /// Number of untimed repetitions run immediately before the timed ones, to warm up caches
+    /// (e.g. the host-side page cache) before measuring.
+    #[serde(default)]
+    pub warmup: u64,
+}
+
+/// A named benchmark scenario: an ordered sequence of [`Operation`]s run against one V-App
+/// process, back to back.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workload {
+    pub name: String,
+    pub operations: Vec<Operation>,
+}
+
+/// A workload file, as found at `cases/<name>/workload.json`.
+#[derive(Debug, Clone, Deserialize)]
+struct WorkloadFile {
+    workloads: Vec<Workload>,
+}
+
+/// Parses `path` (typically `<case_dir>/workload.json`) into its list of [`Workload`]s.
+pub fn load_workloads(path: &Path) -> Result<Vec<Workload>, Box<dyn std::error::Error + Send + Sync>> {
+    let contents = std::fs::read_to_string(path)?;
+    let file: WorkloadFile = serde_json::from_str(&contents)?;
+    Ok(file.workloads)
+}
+
+/// (De)serializes a byte vector as a lowercase hex string, so workload files stay readable JSON
+/// instead of arrays of small integers.
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(&s).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,59 @@
+//! AES-256-GCM, backed by the secure element's syscalls.
+//!
+//! This is the RISC-V/Speculos counterpart to the `aes-gcm` crate used by
+//! [`crate::crypto_provider::RustCrypto`] on other targets: both are driven through
+//! [`crate::crypto_provider::CryptoProvider::aead_encrypt`]/`aead_decrypt`, so callers never
+//! reach for this module directly.
+
+/// Encrypts `buf` in place with AES-256-GCM under `key`/`nonce`, authenticating `aad` alongside
+/// it, and returns the 16-byte authentication tag.
+pub fn encrypt_in_place(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; 16] {
+    let mut tag = [0u8; 16];
+    unsafe {
+        let result = ledger_device_sdk::sys::cx_aes_gcm_encrypt(
+            key.as_ptr(),
+            key.len(),
+            nonce.as_ptr(),
+            nonce.len(),
+            aad.as_ptr(),
+            aad.len(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            tag.as_mut_ptr(),
+            tag.len(),
+        );
+        assert!(result == ledger_device_sdk::sys::CX_OK, "AES-GCM encryption failed");
+    }
+    tag
+}
+
+/// Decrypts `buf` in place with AES-256-GCM under `key`/`nonce`, verifying it against `tag` and
+/// `aad`. Returns an error (without touching `buf`'s plaintext-shaped output) if authentication
+/// fails.
+pub fn decrypt_in_place(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    buf: &mut [u8],
+    tag: &[u8; 16],
+) -> Result<(), &'static str> {
+    unsafe {
+        let result = ledger_device_sdk::sys::cx_aes_gcm_decrypt(
+            key.as_ptr(),
+            key.len(),
+            nonce.as_ptr(),
+            nonce.len(),
+            aad.as_ptr(),
+            aad.len(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            tag.as_ptr(),
+            tag.len(),
+        );
+        if result == ledger_device_sdk::sys::CX_OK {
+            Ok(())
+        } else {
+            Err("AES-GCM authentication failed")
+        }
+    }
+}
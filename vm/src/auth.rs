@@ -2,22 +2,36 @@
 /// and is used for anything that needs a permanent bind to the identity of the instance of the app. Therefore, it
 /// doesn't persist if the Vanadium app is reinstalled or upgraded.
 use common::accumulator::Hasher;
-use ledger_device_sdk::hmac::{sha2::Sha2_256 as HmacSha256, HMACInit};
 use ledger_device_sdk::NVMData;
 
-use crate::hash::Sha256Hasher;
+use crate::crypto_provider::CryptoProvider;
 use crate::nvm::LazyStorage;
 
 // This key is initialized the first time the Vanadium app is launched.
 #[link_section = ".nvm_data"]
 static mut VM_AUTH_KEY: NVMData<LazyStorage<[u8; 32]>> = NVMData::new(LazyStorage::new());
 
+// The device's static KEM keypair used to establish an HPKE sealed-page channel (see
+// [`hpke_decap`]). Like [`VM_AUTH_KEY`], it is generated on first use and doesn't survive a
+// reinstall or upgrade of the Vanadium app.
+#[link_section = ".nvm_data"]
+static mut VM_KEM_SECRET_KEY: NVMData<LazyStorage<[u8; 32]>> = NVMData::new(LazyStorage::new());
+
 pub struct VMAuthKey;
 
+/// The device's static KEM keypair (see [`VM_KEM_SECRET_KEY`]), used as the recipient key of the
+/// HPKE sealed-page channel (see [`hpke_decap`]).
+pub struct VMKemKey;
+
 const TAG_APP_ID: &[u8] = b"VND_APP_ID";
 const TAG_APP_AUTH_KEY: &[u8] = b"VND_APP_AUTH_KEY";
 const TAG_PAGE_HMAC: &[u8] = b"VND_PAGE_TAG";
 const TAG_PAGE_HMAC_MASK: &[u8] = b"VND_HMAC_MASK";
+const TAG_VAPP_REGISTRATION_KEY: &[u8] = b"VND_VAPP_REG_KEY";
+const TAG_HPKE_PSK: &[u8] = b"VND_HPKE_PSK";
+const TAG_HPKE_KEY: &[u8] = b"VND_HPKE_KEY";
+const TAG_HPKE_BASE_NONCE: &[u8] = b"VND_HPKE_BASE_NONCE";
+const TAG_HPKE_EXPORTER: &[u8] = b"VND_HPKE_EXPORTER";
 
 impl VMAuthKey {
     /// Gets a mutable reference to the auth key storage.
@@ -35,20 +49,11 @@ impl VMAuthKey {
     }
 
     /// Ensures the auth key is initialized. If uninitialized, generates a new secure 32-byte random key.
-    fn ensure_initialized() {
+    fn ensure_initialized<C: CryptoProvider>() {
         let storage = Self::get_storage_mut();
         if !storage.is_initialized() {
             let mut key = [0u8; 32];
-            unsafe {
-                let result = ledger_device_sdk::sys::cx_get_random_bytes(
-                    key.as_mut_ptr() as *mut core::ffi::c_void,
-                    key.len(),
-                );
-                assert!(
-                    result == ledger_device_sdk::sys::CX_OK,
-                    "Failed to generate random bytes"
-                );
-            }
+            C::random_bytes(&mut key);
             storage.initialize(&key);
         }
     }
@@ -56,8 +61,8 @@ impl VMAuthKey {
     /// Creates a new `VMAuthKey` instance, ensuring the auth key is initialized.
     ///
     /// On first call, generates a secure 32-byte random key if not already initialized.
-    pub fn get() -> Self {
-        Self::ensure_initialized();
+    pub fn get<C: CryptoProvider>() -> Self {
+        Self::ensure_initialized::<C>();
         VMAuthKey
     }
 
@@ -65,68 +70,222 @@ impl VMAuthKey {
     ///
     /// This produces a deterministic, domain-separated hash that commits to auth_key.
     /// It can also be used as a subkey.
-    pub fn tagged_hash(&self, tag: &[u8], buffer: &[u8]) -> [u8; 32] {
+    pub fn tagged_hash<C: CryptoProvider>(&self, tag: &[u8], buffer: &[u8]) -> [u8; 32] {
         let storage = Self::get_storage_ref();
         let auth_key = storage.get_ref(); // panics if not initialized, but initialization is ensured in get()
 
         // Compute SHA256(tag)
-        let mut tag_hash = [0u8; 32];
-        let mut hasher = Sha256Hasher::new();
+        let mut hasher = C::Hasher::new();
         hasher.update(tag);
-        hasher.digest(&mut tag_hash);
+        let tag_hash = hasher.finalize();
 
         // Compute SHA256(SHA256(tag) || auth_key || buffer)
-        let mut result = [0u8; 32];
-        let mut hasher = Sha256Hasher::new();
+        let mut hasher = C::Hasher::new();
         hasher.update(&tag_hash);
         hasher.update(auth_key);
         hasher.update(buffer);
-        hasher.digest(&mut result);
+        hasher.finalize()
+    }
+}
+
+impl VMKemKey {
+    /// Gets a mutable reference to the KEM secret key storage.
+    #[inline(never)]
+    fn get_storage_mut() -> &'static mut LazyStorage<[u8; 32]> {
+        let data = &raw mut VM_KEM_SECRET_KEY;
+        unsafe { (*data).get_mut() }
+    }
+
+    /// Gets a reference to the KEM secret key storage.
+    #[inline(never)]
+    fn get_storage_ref() -> &'static LazyStorage<[u8; 32]> {
+        let data = &raw const VM_KEM_SECRET_KEY;
+        unsafe { (*data).get_ref() }
+    }
+
+    /// Ensures the KEM keypair is initialized, resampling until the random bytes are a valid
+    /// secp256k1 scalar (out-of-range draws have negligible but nonzero probability).
+    fn ensure_initialized<C: CryptoProvider>() {
+        let storage = Self::get_storage_mut();
+        if !storage.is_initialized() {
+            let mut key = [0u8; 32];
+            loop {
+                C::random_bytes(&mut key);
+                if secp256k1::SecretKey::from_slice(&key).is_ok() {
+                    break;
+                }
+            }
+            storage.initialize(&key);
+        }
+    }
+
+    /// Creates a new `VMKemKey` instance, ensuring the keypair is initialized.
+    pub fn get<C: CryptoProvider>() -> Self {
+        Self::ensure_initialized::<C>();
+        VMKemKey
+    }
 
-        result
+    fn secret_key(&self) -> secp256k1::SecretKey {
+        let storage = Self::get_storage_ref();
+        // Initialization is ensured in get(), and ensure_initialized() only ever stores bytes
+        // that have already been validated as a secp256k1 scalar.
+        secp256k1::SecretKey::from_slice(storage.get_ref()).expect("VM KEM secret key is invalid")
+    }
+
+    /// Returns the device's static KEM public key, serialized as a 33-byte compressed SEC1
+    /// point. The host uses this as `pkR` when it runs HPKE `Encap` to open a sealed-page
+    /// channel (see [`hpke_decap`]).
+    pub fn public_key(&self) -> [u8; 33] {
+        let secp = secp256k1::Secp256k1::new();
+        secp256k1::PublicKey::from_secret_key(&secp, &self.secret_key()).serialize()
+    }
+}
+
+/// The symmetric state of an HPKE sealed-page channel, produced by [`hpke_decap`]: a per-page
+/// AEAD key and base nonce (see [`hpke_seal_page`]/[`hpke_open_page`]), plus an exporter secret
+/// callers may use to derive further session-bound material.
+pub struct HpkeContext {
+    key: [u8; 32],
+    base_nonce: [u8; 12],
+    exporter_secret: [u8; 32],
+}
+
+impl HpkeContext {
+    /// Returns the `exporter_secret` output of the HPKE KeySchedule (see [`hpke_decap`]), for
+    /// callers that need session-bound key material beyond the per-page AEAD channel itself.
+    pub fn exporter_secret(&self) -> &[u8; 32] {
+        &self.exporter_secret
+    }
+}
+
+/// Runs an HPKE-style PSK-mode KeySchedule (RFC 9180 §5.1) over a 32-byte KEM shared secret,
+/// binding it to the exact key exchange (`enc`, `pk_r`) and to this app instance (`psk`).
+///
+/// This follows RFC 9180's `secret = Extract(shared_secret, psk)` then one labeled `Expand` per
+/// output, but without pulling in a general-purpose HKDF: every output needed here is a single
+/// 32-byte HMAC block, which is exactly what `LabeledExpand` reduces to whenever the requested
+/// length is the hash length.
+fn hpke_key_schedule<C: CryptoProvider>(
+    shared_secret: &[u8; 32],
+    enc: &[u8; 33],
+    pk_r: &[u8; 33],
+    psk: &[u8; 32],
+) -> HpkeContext {
+    let secret = C::hmac_sha256(shared_secret, &[psk]);
+    let key = C::hmac_sha256(&secret, &[TAG_HPKE_KEY, enc, pk_r]);
+    let nonce_material = C::hmac_sha256(&secret, &[TAG_HPKE_BASE_NONCE, enc, pk_r]);
+    let exporter_secret = C::hmac_sha256(&secret, &[TAG_HPKE_EXPORTER, enc, pk_r]);
+
+    let mut base_nonce = [0u8; 12];
+    base_nonce.copy_from_slice(&nonce_material[..12]);
+
+    HpkeContext {
+        key,
+        base_nonce,
+        exporter_secret,
     }
 }
 
+/// Device-side HPKE `Decap`: recovers the KEM shared secret from the host's ephemeral public
+/// key `enc` (produced by the host's `Encap(pkR)`) via `DH(skR, pkE)`, then runs the PSK-mode
+/// KeySchedule with the VM auth key as PSK, so the resulting [`HpkeContext`] is bound to this
+/// device instance's identity (see [`get_vanadium_app_id`]).
+pub fn hpke_decap<C: CryptoProvider>(enc: &[u8; 33]) -> Result<HpkeContext, &'static str> {
+    let kem = VMKemKey::get::<C>();
+    let pk_e = secp256k1::PublicKey::from_slice(enc).map_err(|_| "invalid encapsulated public key")?;
+    let shared_secret = secp256k1::ecdh::SharedSecret::new(&pk_e, &kem.secret_key())
+        .map_err(|_| "ECDH key exchange failed")?;
+
+    let auth_key = VMAuthKey::get::<C>();
+    let psk = auth_key.tagged_hash::<C>(TAG_HPKE_PSK, b"");
+
+    Ok(hpke_key_schedule::<C>(
+        &shared_secret.secret_bytes(),
+        enc,
+        &kem.public_key(),
+        &psk,
+    ))
+}
+
+/// Forms the per-page AEAD nonce `base_nonce XOR le(page_index)`, so `page_index` plays the role
+/// of the AEAD sequence number and the same page can never be sealed twice under the same nonce.
+fn hpke_page_nonce(base_nonce: &[u8; 12], page_index: u32) -> [u8; 12] {
+    let mut nonce = *base_nonce;
+    for (byte, index_byte) in nonce.iter_mut().zip(page_index.to_le_bytes()) {
+        *byte ^= index_byte;
+    }
+    nonce
+}
+
+/// Seals one page under `ctx`, encrypting `page` in place and returning its 16-byte
+/// authentication tag. Replaces [`compute_page_hmac_mask`]'s XOR mask with a proper AEAD, while
+/// [`compute_code_page_hmac`] still provides an independent integrity check over the plaintext.
+pub fn hpke_seal_page<C: CryptoProvider>(ctx: &HpkeContext, page_index: u32, page: &mut [u8]) -> [u8; 16] {
+    let nonce = hpke_page_nonce(&ctx.base_nonce, page_index);
+    C::aead_encrypt(&ctx.key, &nonce, &[], page)
+}
+
+/// Opens one page sealed by [`hpke_seal_page`], decrypting `page` in place. Returns an error,
+/// leaving `page` unmodified, if `tag` doesn't authenticate.
+pub fn hpke_open_page<C: CryptoProvider>(
+    ctx: &HpkeContext,
+    page_index: u32,
+    page: &mut [u8],
+    tag: &[u8; 16],
+) -> Result<(), &'static str> {
+    let nonce = hpke_page_nonce(&ctx.base_nonce, page_index);
+    C::aead_decrypt(&ctx.key, &nonce, &[], page, tag)
+}
+
 /// Computes the app auth key bound to a specific V-App.
 #[inline]
-pub fn get_vapp_auth_key(vapp_hash: &[u8; 32]) -> [u8; 32] {
-    let auth_key = VMAuthKey::get();
-    auth_key.tagged_hash(TAG_APP_AUTH_KEY, vapp_hash)
+pub fn get_vapp_auth_key<C: CryptoProvider>(vapp_hash: &[u8; 32]) -> [u8; 32] {
+    let auth_key = VMAuthKey::get::<C>();
+    auth_key.tagged_hash::<C>(TAG_APP_AUTH_KEY, vapp_hash)
 }
 
 /// Computes the page HMAC for one code page.
-pub fn compute_code_page_hmac(
+pub fn compute_code_page_hmac<C: CryptoProvider>(
     app_auth_key: &[u8; 32],
     vapp_hash: &[u8; 32],
     page_index: u32,
     page_hash: &[u8; 32],
 ) -> Result<[u8; 32], ()> {
-    let mut mac = HmacSha256::new(app_auth_key);
-    mac.update(TAG_PAGE_HMAC).map_err(|_| ())?;
-    mac.update(vapp_hash).map_err(|_| ())?;
-    mac.update(&page_index.to_be_bytes()).map_err(|_| ())?;
-    mac.update(page_hash).map_err(|_| ())?;
-
-    let mut out = [0u8; 32];
-    mac.finalize(&mut out).map_err(|_| ())?;
-    Ok(out)
+    Ok(C::hmac_sha256(
+        app_auth_key,
+        &[TAG_PAGE_HMAC, vapp_hash, &page_index.to_be_bytes(), page_hash],
+    ))
 }
 
 /// Computes SHA256("VND_HMAC_MASK" || ephemeral_sk || be32(page_index)).
-pub fn compute_page_hmac_mask(ephemeral_sk: &[u8; 32], page_index: u32) -> [u8; 32] {
-    let mut hasher = Sha256Hasher::new();
+///
+/// This is a plain XOR mask: it gives no authentication of its own (relying entirely on
+/// [`compute_code_page_hmac`] for that) and breaks catastrophically if the same `ephemeral_sk`/
+/// `page_index` pair is ever reused. [`hpke_seal_page`]/[`hpke_open_page`] supersede it with a
+/// proper AEAD derived from an authenticated key exchange; this is kept only for the existing
+/// preload handshake in [`crate::handlers::preload_vapp`], which new callers should not copy.
+pub fn compute_page_hmac_mask<C: CryptoProvider>(ephemeral_sk: &[u8; 32], page_index: u32) -> [u8; 32] {
+    let mut hasher = C::Hasher::new();
     hasher.update(TAG_PAGE_HMAC_MASK);
     hasher.update(ephemeral_sk);
     hasher.update(&page_index.to_be_bytes());
+    hasher.finalize()
+}
 
-    let mut out = [0u8; 32];
-    hasher.digest(&mut out);
-    out
+/// Computes this device instance's registration token for a V-App: `HMAC-SHA256(device_key,
+/// vapp_hash)`, where `device_key` is derived from the instance's auth key, domain-separated via
+/// [`TAG_VAPP_REGISTRATION_KEY`]. The host is expected to store the returned token and present it
+/// back on a later `RegisterVApp` request, so [`crate::handlers::register_vapp`] can recognize an
+/// already-approved V-App without re-prompting the user.
+pub fn compute_vapp_registration_hmac<C: CryptoProvider>(vapp_hash: &[u8; 32]) -> [u8; 32] {
+    let auth_key = VMAuthKey::get::<C>();
+    let device_key = auth_key.tagged_hash::<C>(TAG_VAPP_REGISTRATION_KEY, b"");
+    C::hmac_sha256(&device_key, &[vapp_hash])
 }
 
 /// Returns a public identifier that uniquely identifies this instance of the Vanadium app.
 /// This is derived from the auth key, so it is stable across app restarts but changes if the app is reinstalled or upgraded.
-pub fn get_vanadium_app_id() -> [u8; 32] {
-    let auth_key = VMAuthKey::get();
-    auth_key.tagged_hash(TAG_APP_ID, b"")
+pub fn get_vanadium_app_id<C: CryptoProvider>() -> [u8; 32] {
+    let auth_key = VMAuthKey::get::<C>();
+    auth_key.tagged_hash::<C>(TAG_APP_ID, b"")
 }
@@ -0,0 +1,209 @@
+/// Abstracts the hashing, HMAC, RNG and signature-verification primitives used by the V-App
+/// preload flow (see [`crate::auth`] and [`crate::handlers::preload_vapp`]) behind a trait, so
+/// that the page-hash batching, HMAC masking and Merkle-root validation logic can be exercised
+/// with deterministic keys in a host unit test, instead of only on real hardware.
+///
+/// The backend is selected at compile time: [`LedgerSyscalls`] wraps the device's secure
+/// element syscalls and is used on the RISC-V/Speculos target, while [`RustCrypto`] is a
+/// pure-Rust implementation used everywhere else.
+use common::accumulator::Hasher;
+
+pub trait CryptoProvider {
+    /// The hasher used for page hashes and the code Merkle tree; must match the hasher the
+    /// host uses to build `manifest.code_merkle_root`.
+    type Hasher: Hasher<32>;
+
+    /// Fills `buf` with cryptographically secure random bytes.
+    fn random_bytes(buf: &mut [u8]);
+
+    /// Computes HMAC-SHA256(key, data).
+    fn hmac_sha256(key: &[u8; 32], data: &[&[u8]]) -> [u8; 32];
+
+    /// Encrypts `buf` in place under AES-256-GCM with `key`/`nonce`, authenticating `aad`
+    /// alongside it, and returns the 16-byte authentication tag. Used to keep outsourced V-App
+    /// pages confidential (see [`crate::handlers::lib::outsourced_mem::OutsourcedMemory`]).
+    fn aead_encrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; 16];
+
+    /// Decrypts `buf` in place under AES-256-GCM with `key`/`nonce`, verifying it against `tag`
+    /// and `aad`. Returns an error, leaving `buf` unmodified, if authentication fails.
+    fn aead_decrypt(
+        key: &[u8; 32],
+        nonce: &[u8; 12],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8; 16],
+    ) -> Result<(), &'static str>;
+
+    /// Verifies `signature` (64-byte compact `r || s`) over `msg_hash` by `pubkey` (a
+    /// 33-byte SEC1-compressed secp256k1 point). Used to check a V-App manifest's registration
+    /// signature against the trusted-key allowlist (see [`crate::vapp::TrustedKeyStore`]).
+    ///
+    /// This has one shared implementation rather than a per-backend one: verification only
+    /// operates on public data, so there is no side-channel reason to route it through the
+    /// secure element, and the vendored `secp256k1` crate is `no_std` and runs identically
+    /// on-device and on a host.
+    fn verify_ecdsa_secp256k1(pubkey: &[u8; 33], msg_hash: &[u8; 32], signature: &[u8; 64]) -> bool {
+        let Ok(msg) = secp256k1::Message::from_slice(msg_hash) else {
+            return false;
+        };
+        let Ok(pk) = secp256k1::PublicKey::from_slice(pubkey) else {
+            return false;
+        };
+        let Ok(sig) = secp256k1::ecdsa::Signature::from_compact(signature) else {
+            return false;
+        };
+        secp256k1::Secp256k1::verification_only()
+            .verify_ecdsa(&msg, &sig, &pk)
+            .is_ok()
+    }
+}
+
+/// The [`CryptoProvider`] used by the rest of the VM outside of tests.
+#[cfg(target_arch = "riscv32")]
+pub type DefaultCryptoProvider = LedgerSyscalls;
+#[cfg(not(target_arch = "riscv32"))]
+pub type DefaultCryptoProvider = RustCrypto;
+
+#[cfg(target_arch = "riscv32")]
+pub use ledger_syscalls::LedgerSyscalls;
+
+#[cfg(target_arch = "riscv32")]
+mod ledger_syscalls {
+    use super::CryptoProvider;
+    use crate::hash::Sha256Hasher;
+    use ledger_device_sdk::hmac::{sha2::Sha2_256 as HmacSha256, HMACInit};
+
+    /// [`CryptoProvider`] backend backed by the secure element's syscalls.
+    pub struct LedgerSyscalls;
+
+    impl CryptoProvider for LedgerSyscalls {
+        type Hasher = Sha256Hasher;
+
+        fn random_bytes(buf: &mut [u8]) {
+            unsafe {
+                let result = ledger_device_sdk::sys::cx_get_random_bytes(
+                    buf.as_mut_ptr() as *mut core::ffi::c_void,
+                    buf.len(),
+                );
+                assert!(
+                    result == ledger_device_sdk::sys::CX_OK,
+                    "Failed to generate random bytes"
+                );
+            }
+        }
+
+        fn hmac_sha256(key: &[u8; 32], data: &[&[u8]]) -> [u8; 32] {
+            let mut mac = HmacSha256::new(key);
+            for chunk in data {
+                mac.update(chunk).expect("HMAC update failed");
+            }
+            let mut out = [0u8; 32];
+            mac.finalize(&mut out).expect("HMAC finalize failed");
+            out
+        }
+
+        fn aead_encrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; 16] {
+            crate::aes::encrypt_in_place(key, nonce, aad, buf)
+        }
+
+        fn aead_decrypt(
+            key: &[u8; 32],
+            nonce: &[u8; 12],
+            aad: &[u8],
+            buf: &mut [u8],
+            tag: &[u8; 16],
+        ) -> Result<(), &'static str> {
+            crate::aes::decrypt_in_place(key, nonce, aad, buf, tag)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+pub use rustcrypto::RustCrypto;
+
+#[cfg(not(target_arch = "riscv32"))]
+mod rustcrypto {
+    use super::CryptoProvider;
+    use aes_gcm::aead::generic_array::GenericArray;
+    use aes_gcm::{AeadInPlace, Aes256Gcm, KeyInit};
+    use common::accumulator::Hasher;
+    use hmac::{Hmac, Mac};
+    use sha2::{Digest, Sha256};
+
+    /// Pure-Rust [`Hasher`] implementation backed by the `sha2` crate.
+    pub struct Sha256Hasher(Sha256);
+
+    impl Hasher<32> for Sha256Hasher {
+        fn new() -> Self {
+            Sha256Hasher(Sha256::new())
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+
+        fn finalize(self) -> [u8; 32] {
+            self.0.finalize().into()
+        }
+    }
+
+    /// Pure-Rust [`CryptoProvider`] backend, used on hosts that can't run the secure
+    /// element's syscalls (e.g. native unit tests).
+    pub struct RustCrypto;
+
+    /// A small deterministic PRNG, seeded from a fixed constant. It is not cryptographically
+    /// secure: it only exists so host tests get reproducible ephemeral keys instead of calling
+    /// out to hardware RNG.
+    struct SplitMix64(u64);
+
+    impl SplitMix64 {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        }
+    }
+
+    impl CryptoProvider for RustCrypto {
+        type Hasher = Sha256Hasher;
+
+        fn random_bytes(buf: &mut [u8]) {
+            let mut rng = SplitMix64(0xC0FFEE);
+            for chunk in buf.chunks_mut(8) {
+                let word = rng.next().to_le_bytes();
+                chunk.copy_from_slice(&word[..chunk.len()]);
+            }
+        }
+
+        fn hmac_sha256(key: &[u8; 32], data: &[&[u8]]) -> [u8; 32] {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key size");
+            for chunk in data {
+                mac.update(chunk);
+            }
+            mac.finalize().into_bytes().into()
+        }
+
+        fn aead_encrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], buf: &mut [u8]) -> [u8; 16] {
+            let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+            let tag = cipher
+                .encrypt_in_place_detached(GenericArray::from_slice(nonce), aad, buf)
+                .expect("encryption of a single page never fails");
+            tag.into()
+        }
+
+        fn aead_decrypt(
+            key: &[u8; 32],
+            nonce: &[u8; 12],
+            aad: &[u8],
+            buf: &mut [u8],
+            tag: &[u8; 16],
+        ) -> Result<(), &'static str> {
+            let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
+            cipher
+                .decrypt_in_place_detached(GenericArray::from_slice(nonce), aad, buf, GenericArray::from_slice(tag))
+                .map_err(|_| "AES-GCM authentication failed")
+        }
+    }
+}
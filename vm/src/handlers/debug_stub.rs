@@ -0,0 +1,17 @@
+use crate::{AppSW, COMM_BUFFER_SIZE};
+use alloc::vec::Vec;
+use ledger_device_sdk::io::Command;
+
+/// Handles a `DebugStub` APDU, each of which carries one GDB Remote Serial Protocol packet (see
+/// [`crate::handlers::lib::gdbstub`]) from a host GDB attached to a running V-App.
+///
+/// A real implementation needs mutable access to the `Cpu` driving the V-App, and must be able to
+/// pause that execution loop to wait for the next `DebugStub` APDU (the same cooperative
+/// `io::interrupt` round-trip `handlers::preload_vapp` already uses for `GetPage`) instead of
+/// resuming it immediately. That loop lives in `handlers::start_vapp`, which this build doesn't
+/// include, so there is no `Cpu` to hand `gdbstub::GdbStub::handle_packet` here; this handler
+/// exists to reserve the instruction and wire up APDU parsing ahead of that integration.
+pub fn handler_debug_stub(command: Command<COMM_BUFFER_SIZE>) -> Result<Vec<u8>, AppSW> {
+    let _packet = command.get_data();
+    Err(AppSW::InsNotSupported)
+}
@@ -26,7 +26,9 @@ pub fn handler_get_app_info(
 
     // Vanadium app ID
     response.push(32u8);
-    response.extend_from_slice(&crate::auth::get_vanadium_app_id());
+    response.extend_from_slice(&crate::auth::get_vanadium_app_id::<
+        crate::crypto_provider::DefaultCryptoProvider,
+    >());
 
     Ok(response)
 }
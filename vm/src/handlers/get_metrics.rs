@@ -2,6 +2,18 @@ use alloc::vec::Vec;
 
 use crate::{AppSW, COMM_BUFFER_SIZE};
 use common::metrics::VAppMetrics;
+use common::tlv;
+
+/// Version of the TLV tail appended after the fixed 88-byte legacy layout (see
+/// [`handler_get_metrics`]). Bump this if a future field changes how the TLV tail itself should
+/// be interpreted; individual new fields don't need a bump, since an unrecognized TLV tag is
+/// simply skipped by [`common::tlv::TlvReader`].
+const METRICS_FORMAT_VERSION: u8 = 1;
+
+/// Tag for the `gas_used` TLV field (see [`common::metrics::VAppMetrics::gas_used`]).
+const TAG_GAS_USED: u8 = 0;
+/// Tag for the `gas_limit` TLV field (see [`common::metrics::VAppMetrics::gas_limit`]).
+const TAG_GAS_LIMIT: u8 = 1;
 
 /// Global storage for the last V-App's metrics
 static mut LAST_VAPP_METRICS: VAppMetrics = VAppMetrics::new();
@@ -29,8 +41,20 @@ fn get_last_metrics() -> VAppMetrics {
 /// - 8 bytes: instruction count (big-endian)
 /// - 4 bytes: page loads (big-endian)
 /// - 4 bytes: page commits (big-endian)
+/// - 4 bytes: cache hits (big-endian)
+/// - 4 bytes: cache misses (big-endian)
+///
+/// Total: 88 bytes, unchanged since this layout shipped, so existing consumers that only read
+/// these fixed 88 bytes keep working untouched.
+///
+/// Followed by a TLV tail, for fields added after the fixed layout was frozen:
+/// - 1 byte: metrics format version ([`METRICS_FORMAT_VERSION`])
+/// - [`common::tlv`]-encoded fields, currently [`TAG_GAS_USED`] and [`TAG_GAS_LIMIT`] (both
+///   `Uint64`; see [`common::metrics::VAppMetrics`])
 ///
-/// Total: 80 bytes
+/// A consumer that only understands the fixed layout reads the first 88 bytes and ignores the
+/// rest; one that understands the tail reads the version byte and then iterates the TLV fields
+/// via [`common::tlv::TlvReader`], skipping any tag it doesn't recognize.
 pub fn handler_get_metrics(
     _command: ledger_device_sdk::io::Command<COMM_BUFFER_SIZE>,
 ) -> Result<Vec<u8>, AppSW> {
@@ -41,7 +65,7 @@ pub fn handler_get_metrics(
         return Err(AppSW::IncorrectData);
     }
 
-    let mut response = Vec::with_capacity(80);
+    let mut response = Vec::with_capacity(88);
 
     // V-App name (32 bytes)
     response.extend_from_slice(&metrics.vapp_name);
@@ -58,5 +82,16 @@ pub fn handler_get_metrics(
     // Page commits (4 bytes, big-endian)
     response.extend_from_slice(&metrics.page_commits.to_be_bytes());
 
+    // Cache hits (4 bytes, big-endian)
+    response.extend_from_slice(&metrics.cache_hits.to_be_bytes());
+
+    // Cache misses (4 bytes, big-endian)
+    response.extend_from_slice(&metrics.cache_misses.to_be_bytes());
+
+    // TLV tail: format version, then gas fields.
+    response.push(METRICS_FORMAT_VERSION);
+    tlv::write_u64(TAG_GAS_USED, metrics.gas_used, &mut response);
+    tlv::write_u64(TAG_GAS_LIMIT, metrics.gas_limit, &mut response);
+
     Ok(response)
 }
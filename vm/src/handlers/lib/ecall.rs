@@ -1,11 +1,12 @@
 use core::{cell::RefCell, cmp::min};
 
-use alloc::{rc::Rc, vec};
+use alloc::{rc::Rc, vec, vec::Vec};
 use common::{
     client_commands::{
         Message, ReceiveBufferMessage, ReceiveBufferResponse, SendBufferMessage,
-        SendPanicBufferMessage,
+        SendPanicBufferMessage, YieldMessage,
     },
+    compress,
     ecall_constants::*,
     manifest::Manifest,
     vm::{Cpu, EcallHandler},
@@ -100,6 +101,13 @@ struct GuestPointer(pub u32);
 pub struct CommEcallHandler<'a> {
     comm: Rc<RefCell<&'a mut ledger_device_sdk::io::Comm>>,
     manifest: &'a Manifest,
+    /// Calls to `ECALL_YIELD` since the last progress-screen redraw/host heartbeat; reset to 0
+    /// every time `handle_yield` actually redraws, so the guest can call it on every loop
+    /// iteration without every call paying for a screen redraw and a host round-trip.
+    yield_ticks: RefCell<u32>,
+    /// Sticky once the host asks the V-App to cancel via `ECALL_YIELD`'s heartbeat reply, so a
+    /// cancellation request isn't lost if the guest doesn't act on it the very tick it arrives.
+    yield_cancelled: RefCell<bool>,
 }
 
 impl<'a> CommEcallHandler<'a> {
@@ -107,7 +115,52 @@ impl<'a> CommEcallHandler<'a> {
         comm: Rc<RefCell<&'a mut ledger_device_sdk::io::Comm>>,
         manifest: &'a Manifest,
     ) -> Self {
-        Self { comm, manifest }
+        Self {
+            comm,
+            manifest,
+            yield_ticks: RefCell::new(0),
+            yield_cancelled: RefCell::new(false),
+        }
+    }
+
+    // Copies `dst.len()` bytes from guest address `ptr` into `dst`, re-resolving the backing
+    // segment at each segment boundary the range crosses, so a buffer that straddles e.g. the
+    // data and stack segments is read correctly instead of silently reading the wrong bytes or
+    // failing.
+    fn copy_from_guest(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        ptr: u32,
+        dst: &mut [u8],
+    ) -> Result<(), &'static str> {
+        let mut copied = 0;
+        while copied < dst.len() {
+            let addr = ptr.checked_add(copied as u32).ok_or("Buffer overflow")?;
+            let segment = cpu.get_segment(addr)?;
+            let take = min(dst.len() - copied, segment.remaining_from(addr) as usize);
+            segment.read_buffer(addr, &mut dst[copied..copied + take])?;
+            copied += take;
+        }
+        Ok(())
+    }
+
+    // Copies `src` to guest address `ptr`, re-resolving the backing segment at each segment
+    // boundary the range crosses. See `copy_from_guest`.
+    fn copy_to_guest(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        ptr: u32,
+        src: &[u8],
+    ) -> Result<(), &'static str> {
+        let mut copied = 0;
+        while copied < src.len() {
+            let addr = ptr.checked_add(copied as u32).ok_or("Buffer overflow")?;
+            let segment = cpu.get_segment(addr)?;
+            let take = min(src.len() - copied, segment.remaining_from(addr) as usize);
+            segment.write_buffer(addr, &src[copied..copied + take])?;
+            copied += take;
+        }
+        Ok(())
     }
 
     // TODO: can we refactor this and handle_xsend? They are almost identical
@@ -141,14 +194,12 @@ impl<'a> CommEcallHandler<'a> {
 
         let mut g_ptr = buffer.0;
 
-        let segment = cpu.get_segment(g_ptr)?;
-
         // loop while size > 0
         while size > 0 {
             let copy_size = min(size, 255 - 4); // send maximum 251 bytes per message
 
             let mut buffer = vec![0; copy_size];
-            segment.read_buffer(g_ptr, &mut buffer)?;
+            self.copy_from_guest(cpu, g_ptr, &mut buffer)?;
 
             let mut comm = self.comm.borrow_mut();
             SendPanicBufferMessage::new(size as u32, buffer).serialize_to_comm(&mut comm);
@@ -200,18 +251,28 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         let mut g_ptr = buffer.0;
-
-        let segment = cpu.get_segment(g_ptr)?;
+        let compressing = self.manifest.supports_compression();
 
         // loop while size > 0
         while size > 0 {
-            let copy_size = min(size, 255 - 4); // send maximum 251 bytes per message
-
-            let mut buffer = vec![0; copy_size];
-            segment.read_buffer(g_ptr, &mut buffer)?;
+            // Leave a byte of headroom when compressing: a chunk that doesn't shrink falls back
+            // to a verbatim encoding with a 1-byte tag, which must still fit in 251 bytes.
+            let max_chunk = if compressing { 255 - 4 - 1 } else { 255 - 4 };
+            let copy_size = min(size, max_chunk);
+
+            let mut chunk = vec![0; copy_size];
+            self.copy_from_guest(cpu, g_ptr, &mut chunk)?;
+
+            let payload = if compressing {
+                let mut compressed = Vec::new();
+                compress::compress(&chunk, &mut compressed);
+                compressed
+            } else {
+                chunk
+            };
 
             let mut comm = self.comm.borrow_mut();
-            SendBufferMessage::new(size as u32, buffer).serialize_to_comm(&mut comm);
+            SendBufferMessage::new(size as u32, payload).serialize_to_comm(&mut comm);
             comm.reply(AppSW::InterruptedExecution);
 
             let Instruction::Continue(p1, p2) = comm.next_command() else {
@@ -239,7 +300,176 @@ impl<'a> CommEcallHandler<'a> {
     ) -> Result<usize, &'static str> {
         let mut g_ptr = buffer.0;
 
-        let segment = cpu.get_segment(g_ptr)?;
+        let mut remaining_length = None;
+        let mut total_received: usize = 0;
+        while remaining_length != Some(0) {
+            let mut comm = self.comm.borrow_mut();
+            ReceiveBufferMessage::new().serialize_to_comm(&mut comm);
+            comm.reply(AppSW::InterruptedExecution);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Data"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+
+            let raw_data = comm.get_data().map_err(|_| "Invalid response from host")?;
+            let response = ReceiveBufferResponse::deserialize(raw_data)?;
+
+            drop(comm); // TODO: figure out how to avoid having to deal with this drop explicitly
+
+            match remaining_length {
+                None => {
+                    // first chunk, check if the total length is acceptable
+                    if response.remaining_length > max_size as u32 {
+                        return Err("Received data is too large");
+                    }
+                    remaining_length = Some(response.remaining_length);
+                }
+                Some(remaining) => {
+                    if remaining != response.remaining_length {
+                        return Err("Mismatching remaining length");
+                    }
+                }
+            }
+
+            // `remaining_length` is still the total byte count not yet delivered as of *before*
+            // this chunk, so it's a safe upper bound on this chunk's decompressed size: a single
+            // fixed-size allocation, no growing buffers mid-decode.
+            let content: Vec<u8> = if self.manifest.supports_compression() {
+                let mut decompressed = vec![0u8; remaining_length.unwrap() as usize];
+                let written = compress::decompress_into(&response.content, &mut decompressed)?;
+                decompressed.truncate(written);
+                decompressed
+            } else {
+                response.content
+            };
+
+            self.copy_to_guest(cpu, g_ptr, &content)?;
+
+            remaining_length = Some(remaining_length.unwrap() - content.len() as u32);
+            g_ptr += content.len() as u32;
+            total_received += content.len();
+        }
+        Ok(total_received)
+    }
+
+    // Reads `count` `(ptr: u32, len: u32)` descriptors from the guest's scatter/gather array at
+    // `iovec`, as laid out by the `IoVec` type in `app-sdk`. Used by `handle_xsendv`/`handle_xrecvv`.
+    fn read_iovec(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        iovec: GuestPointer,
+        count: usize,
+    ) -> Result<Vec<(u32, u32)>, &'static str> {
+        let mut descriptors = Vec::with_capacity(count);
+        for i in 0..count {
+            let descriptor_ptr = iovec
+                .0
+                .checked_add((i * 8) as u32)
+                .ok_or("Buffer overflow")?;
+
+            let mut raw = [0u8; 8];
+            self.copy_from_guest(cpu, descriptor_ptr, &mut raw)?;
+
+            let ptr = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+            let len = u32::from_le_bytes(raw[4..8].try_into().unwrap());
+            if ptr.checked_add(len).is_none() {
+                return Err("Buffer overflow");
+            }
+            descriptors.push((ptr, len));
+        }
+        Ok(descriptors)
+    }
+
+    // Sends the concatenation of `count` scatter/gather segments described at `iovec` to the
+    // host as a single logical buffer, over the same chunking loop as `handle_xsend`, without
+    // ever materializing that concatenation: each chunk is filled by reading across as many
+    // descriptors as it takes to collect `copy_size` bytes.
+    fn handle_xsendv(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        iovec: GuestPointer,
+        count: usize,
+    ) -> Result<(), &'static str> {
+        let descriptors = self.read_iovec(cpu, iovec, count)?;
+        let mut size: usize = descriptors.iter().map(|&(_, len)| len as usize).sum();
+
+        if size == 0 {
+            // We must not read the pointer for an empty buffer; Rust always uses address 0x01 for
+            // an empty buffer
+
+            let mut comm = self.comm.borrow_mut();
+            SendBufferMessage::new(size as u32, vec![]).serialize_to_comm(&mut comm);
+            comm.reply(AppSW::InterruptedExecution);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Continue"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+            return Ok(());
+        }
+
+        let mut segments = descriptors.into_iter();
+        let mut current = segments.next();
+
+        // loop while size > 0
+        while size > 0 {
+            let copy_size = min(size, 255 - 4); // send maximum 251 bytes per message
+
+            let mut buffer = vec![0; copy_size];
+            let mut filled = 0;
+            while filled < copy_size {
+                let (ptr, len) = current.ok_or("Descriptor array exhausted before total size")?;
+                let take = min(copy_size - filled, len as usize);
+
+                self.copy_from_guest(cpu, ptr, &mut buffer[filled..filled + take])?;
+                filled += take;
+
+                current = if take < len as usize {
+                    Some((ptr + take as u32, len - take as u32))
+                } else {
+                    segments.next()
+                };
+            }
+
+            let mut comm = self.comm.borrow_mut();
+            SendBufferMessage::new(size as u32, buffer).serialize_to_comm(&mut comm);
+            comm.reply(AppSW::InterruptedExecution);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Continue"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+
+            size -= copy_size;
+        }
+
+        Ok(())
+    }
+
+    // Receives up to the total capacity of the scatter/gather segments described at `iovec`,
+    // filling each in order before moving on to the next, over the same chunking loop as
+    // `handle_xrecv`. Returns the total number of bytes received.
+    fn handle_xrecvv(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        iovec: GuestPointer,
+        count: usize,
+    ) -> Result<usize, &'static str> {
+        let descriptors = self.read_iovec(cpu, iovec, count)?;
+        let max_size: usize = descriptors.iter().map(|&(_, len)| len as usize).sum();
+
+        let mut segments = descriptors.into_iter();
+        let mut current = segments.next();
 
         let mut remaining_length = None;
         let mut total_received: usize = 0;
@@ -276,10 +506,22 @@ impl<'a> CommEcallHandler<'a> {
                 }
             }
 
-            segment.write_buffer(g_ptr, &response.content)?;
+            let mut written = 0;
+            while written < response.content.len() {
+                let (ptr, len) = current.ok_or("Descriptor array exhausted before all data was received")?;
+                let take = min(response.content.len() - written, len as usize);
+
+                self.copy_to_guest(cpu, ptr, &response.content[written..written + take])?;
+                written += take;
+
+                current = if take < len as usize {
+                    Some((ptr + take as u32, len - take as u32))
+                } else {
+                    segments.next()
+                };
+            }
 
             remaining_length = Some(remaining_length.unwrap() - response.content.len() as u32);
-            g_ptr += response.content.len() as u32;
             total_received += response.content.len();
         }
         Ok(total_received)
@@ -301,9 +543,9 @@ impl<'a> CommEcallHandler<'a> {
         // copy inputs to local memory
         // we use r_local both for the input and for the result
         let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(n.0)?.read_buffer(n.0, &mut r_local)?;
+        self.copy_from_guest(cpu, n.0, &mut r_local[..len])?;
         let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(m.0)?.read_buffer(m.0, &mut m_local)?;
+        self.copy_from_guest(cpu, m.0, &mut m_local[..m_len])?;
 
         unsafe {
             let res = ledger_secure_sdk_sys::cx_math_modm_no_throw(
@@ -318,8 +560,7 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         // copy r_local to r
-        let segment = cpu.get_segment(r.0)?;
-        segment.write_buffer(r.0, &r_local)?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
         Ok(())
     }
 
@@ -338,11 +579,11 @@ impl<'a> CommEcallHandler<'a> {
 
         // copy inputs to local memory
         let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(a.0)?.read_buffer(a.0, &mut a_local)?;
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
         let mut b_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(b.0)?.read_buffer(b.0, &mut b_local)?;
+        self.copy_from_guest(cpu, b.0, &mut b_local[..len])?;
         let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(m.0)?.read_buffer(m.0, &mut m_local)?;
+        self.copy_from_guest(cpu, m.0, &mut m_local[..len])?;
 
         let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
         unsafe {
@@ -359,8 +600,7 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         // copy r_local to r
-        let segment = cpu.get_segment(r.0)?;
-        segment.write_buffer(r.0, &r_local)?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
         Ok(())
     }
 
@@ -379,11 +619,11 @@ impl<'a> CommEcallHandler<'a> {
 
         // copy inputs to local memory
         let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(a.0)?.read_buffer(a.0, &mut a_local)?;
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
         let mut b_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(b.0)?.read_buffer(b.0, &mut b_local)?;
+        self.copy_from_guest(cpu, b.0, &mut b_local[..len])?;
         let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(m.0)?.read_buffer(m.0, &mut m_local)?;
+        self.copy_from_guest(cpu, m.0, &mut m_local[..len])?;
 
         let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
         unsafe {
@@ -400,8 +640,7 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         // copy r_local to r
-        let segment = cpu.get_segment(r.0)?;
-        segment.write_buffer(r.0, &r_local)?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
         Ok(())
     }
 
@@ -420,11 +659,11 @@ impl<'a> CommEcallHandler<'a> {
 
         // copy inputs to local memory
         let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(a.0)?.read_buffer(a.0, &mut a_local)?;
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
         let mut b_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(b.0)?.read_buffer(b.0, &mut b_local)?;
+        self.copy_from_guest(cpu, b.0, &mut b_local[..len])?;
         let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(m.0)?.read_buffer(m.0, &mut m_local)?;
+        self.copy_from_guest(cpu, m.0, &mut m_local[..len])?;
 
         let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
         unsafe {
@@ -441,8 +680,7 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         // copy r_local to r
-        let segment = cpu.get_segment(r.0)?;
-        segment.write_buffer(r.0, &r_local)?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
         Ok(())
     }
 
@@ -465,11 +703,11 @@ impl<'a> CommEcallHandler<'a> {
 
         // copy inputs to local memory
         let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(a.0)?.read_buffer(a.0, &mut a_local)?;
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
         let mut e_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(e.0)?.read_buffer(e.0, &mut e_local)?;
+        self.copy_from_guest(cpu, e.0, &mut e_local[..len_e])?;
         let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
-        cpu.get_segment(m.0)?.read_buffer(m.0, &mut m_local)?;
+        self.copy_from_guest(cpu, m.0, &mut m_local[..len])?;
 
         let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
         unsafe {
@@ -487,13 +725,187 @@ impl<'a> CommEcallHandler<'a> {
         }
 
         // copy r_local to r
-        let segment = cpu.get_segment(r.0)?;
-        segment.write_buffer(r.0, &r_local)?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
         Ok(())
     }
+
+    // Computes the quotient and remainder of `a / b`. There's no `cx_math` primitive for
+    // full-width division, so this is a schoolbook binary long division over the big-endian
+    // operands, shifting/subtracting one bit of `a` at a time -- the same per-bit cost class
+    // `handle_bn_powm` already pays for a big RSA-sized exponent.
+    fn handle_bn_divm(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        q: GuestPointer,
+        r: GuestPointer,
+        a: GuestPointer,
+        b: GuestPointer,
+        len: usize,
+    ) -> Result<(), &'static str> {
+        if len > MAX_BIGNUMBER_SIZE {
+            return Err("len is too large");
+        }
+
+        let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
+        let mut b_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        self.copy_from_guest(cpu, b.0, &mut b_local[..len])?;
+
+        if b_local[..len].iter().all(|&byte| byte == 0) {
+            return Err("division by zero");
+        }
+
+        let mut q_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        for bit in 0..len * 8 {
+            let a_bit = (a_local[bit / 8] >> (7 - (bit % 8))) & 1;
+
+            // remainder <<= 1; remainder |= a_bit
+            let mut carry = a_bit;
+            for byte in r_local[..len].iter_mut().rev() {
+                let next_carry = *byte >> 7;
+                *byte = (*byte << 1) | carry;
+                carry = next_carry;
+            }
+
+            if r_local[..len] >= b_local[..len] {
+                let mut borrow = 0i16;
+                for i in (0..len).rev() {
+                    let diff = r_local[i] as i16 - b_local[i] as i16 - borrow;
+                    if diff < 0 {
+                        r_local[i] = (diff + 256) as u8;
+                        borrow = 1;
+                    } else {
+                        r_local[i] = diff as u8;
+                        borrow = 0;
+                    }
+                }
+                q_local[bit / 8] |= 1 << (7 - (bit % 8));
+            }
+        }
+
+        self.copy_to_guest(cpu, q.0, &q_local[..len])?;
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
+        Ok(())
+    }
+
+    // Computes the modular inverse of `a` mod `m`. `prime_modulus` selects which `cx_math`
+    // routine to use: `cx_math_invprimem_no_throw` is faster but only correct when `m` is prime,
+    // `cx_math_invintm_no_throw` handles an arbitrary modulus.
+    fn handle_bn_invm(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        r: GuestPointer,
+        a: GuestPointer,
+        m: GuestPointer,
+        len: usize,
+        prime_modulus: bool,
+    ) -> Result<(), &'static str> {
+        if len > MAX_BIGNUMBER_SIZE {
+            return Err("len is too large");
+        }
+
+        let mut a_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        self.copy_from_guest(cpu, a.0, &mut a_local[..len])?;
+        let mut m_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        self.copy_from_guest(cpu, m.0, &mut m_local[..len])?;
+
+        let mut r_local: [u8; MAX_BIGNUMBER_SIZE] = [0; MAX_BIGNUMBER_SIZE];
+        unsafe {
+            let res = if prime_modulus {
+                ledger_secure_sdk_sys::cx_math_invprimem_no_throw(
+                    r_local.as_mut_ptr(),
+                    a_local.as_ptr(),
+                    m_local.as_ptr(),
+                    len,
+                )
+            } else {
+                ledger_secure_sdk_sys::cx_math_invintm_no_throw(
+                    r_local.as_mut_ptr(),
+                    a_local.as_ptr(),
+                    m_local.as_ptr(),
+                    len,
+                )
+            };
+            if res != CX_OK {
+                return Err("invm failed");
+            }
+        }
+
+        // copy r_local to r
+        self.copy_to_guest(cpu, r.0, &r_local[..len])?;
+        Ok(())
+    }
+
+    // Cooperative yield point for a long-running computation: called on every iteration of the
+    // guest's loop, but only actually redraws the progress screen and round-trips to the host
+    // every `tick_period` calls (a `tick_period` of 0 redraws every call). Reuses ECALL_UX_IDLE's
+    // per-device screen code, swapping in the guest's progress string instead of the static
+    // "is ready" message.
+    //
+    // Returns whether the host has asked the V-App to cancel; this is sticky once raised, so the
+    // guest doesn't need to observe the exact tick the host replied on.
+    fn handle_yield(
+        &self,
+        cpu: &mut Cpu<OutsourcedMemory<'_>>,
+        tick_period: u32,
+        progress: GuestPointer,
+        progress_len: usize,
+    ) -> Result<bool, &'static str> {
+        if *self.yield_cancelled.borrow() {
+            return Ok(true);
+        }
+
+        {
+            let mut ticks = self.yield_ticks.borrow_mut();
+            *ticks += 1;
+            if tick_period != 0 && *ticks < tick_period {
+                return Ok(false);
+            }
+            *ticks = 0;
+        }
+
+        let mut message = vec![0u8; progress_len];
+        self.copy_from_guest(cpu, progress.0, &mut message)?;
+        let progress_str = core::str::from_utf8(&message).unwrap_or("");
+
+        #[cfg(not(any(target_os = "stax", target_os = "flex")))]
+        {
+            ledger_device_sdk::ui::gadgets::clear_screen();
+            let page = ledger_device_sdk::ui::gadgets::Page::from((
+                [self.manifest.get_app_name(), progress_str],
+                false,
+            ));
+            page.place();
+        }
+
+        #[cfg(any(target_os = "stax", target_os = "flex"))]
+        {
+            ledger_device_sdk::nbgl::NbglSpinner::new()
+                .text(progress_str)
+                .show(true);
+        }
+
+        let mut comm = self.comm.borrow_mut();
+        YieldMessage::new(message).serialize_to_comm(&mut comm);
+        comm.reply(AppSW::InterruptedExecution);
+
+        let Instruction::Continue(_, p2) = comm.next_command() else {
+            return Err("INS not supported"); // expected "Continue"
+        };
+
+        let cancelled = p2 != 0;
+        if cancelled {
+            *self.yield_cancelled.borrow_mut() = true;
+        }
+        Ok(cancelled)
+    }
 }
 
 // make an error type for the CommEcallHandler<'a>
+/// For recoverable conditions (malformed arguments, host bignum failures, buffer overflows,
+/// unknown ecall codes), `GenericError`/`UnhandledEcall` are now only reached when the V-app
+/// hasn't registered a trap handler via `ECALL_SET_TRAP_HANDLER` -- see `trap_or_fatal`.
 pub enum CommEcallError {
     Exit(i32),
     Panic,
@@ -501,6 +913,22 @@ pub enum CommEcallError {
     UnhandledEcall,
 }
 
+/// Delivers a recoverable ecall-level fault to the guest's trap handler, if one is registered;
+/// otherwise falls back to tearing down the VM with `CommEcallError::GenericError(message)`,
+/// matching the behavior before the trap subsystem existed.
+fn trap_or_fatal(
+    cpu: &mut Cpu<OutsourcedMemory<'_>>,
+    cause: TrapCause,
+    faulting_addr: u32,
+    message: &'static str,
+) -> Result<(), CommEcallError> {
+    if cpu.raise_trap(cause as u32, faulting_addr) {
+        Ok(())
+    } else {
+        Err(CommEcallError::GenericError(message))
+    }
+}
+
 impl<'a> EcallHandler for CommEcallHandler<'a> {
     type Memory = OutsourcedMemory<'a>;
     type Error = CommEcallError;
@@ -520,20 +948,40 @@ impl<'a> EcallHandler for CommEcallHandler<'a> {
 
         let ecall_code = reg!(T0);
         match ecall_code {
-            ECALL_EXIT => return Err(CommEcallError::Exit(reg!(A0) as i32)),
+            ECALL_EXIT => {
+                // Flush every segment's page cache before the V-App's memory is torn down, so no
+                // dirty page resident only on-device is ever lost.
+                cpu.flush_all()
+                    .map_err(|_| CommEcallError::GenericError("page cache flush failed"))?;
+                return Err(CommEcallError::Exit(reg!(A0) as i32));
+            }
             ECALL_FATAL => {
                 self.handle_panic(cpu, GPreg!(A0), reg!(A1) as usize)
                     .map_err(|_| CommEcallError::GenericError("xsend failed"))?;
                 return Err(CommEcallError::Panic);
             }
-            ECALL_XSEND => self
-                .handle_xsend(cpu, GPreg!(A0), reg!(A1) as usize)
-                .map_err(|_| CommEcallError::GenericError("xsend failed"))?,
-            ECALL_XRECV => {
-                let ret = self
-                    .handle_xrecv(cpu, GPreg!(A0), reg!(A1) as usize)
-                    .map_err(|_| CommEcallError::GenericError("xrecv failed"))?;
-                reg!(A0) = ret as u32;
+            ECALL_XSEND => {
+                if self.handle_xsend(cpu, GPreg!(A0), reg!(A1) as usize).is_err() {
+                    trap_or_fatal(cpu, TrapCause::OutOfBounds, GPreg!(A0).0, "xsend failed")?;
+                }
+            }
+            ECALL_XRECV => match self.handle_xrecv(cpu, GPreg!(A0), reg!(A1) as usize) {
+                Ok(ret) => reg!(A0) = ret as u32,
+                Err(_) => trap_or_fatal(cpu, TrapCause::OutOfBounds, GPreg!(A0).0, "xrecv failed")?,
+            },
+            ECALL_XSENDV => {
+                if self.handle_xsendv(cpu, GPreg!(A0), reg!(A1) as usize).is_err() {
+                    trap_or_fatal(cpu, TrapCause::OutOfBounds, GPreg!(A0).0, "xsendv failed")?;
+                }
+            }
+            ECALL_XRECVV => match self.handle_xrecvv(cpu, GPreg!(A0), reg!(A1) as usize) {
+                Ok(ret) => reg!(A0) = ret as u32,
+                Err(_) => {
+                    trap_or_fatal(cpu, TrapCause::OutOfBounds, GPreg!(A0).0, "xrecvv failed")?
+                }
+            },
+            ECALL_SET_TRAP_HANDLER => {
+                cpu.set_trap_handler(reg!(A0), reg!(A1));
             }
             ECALL_UX_IDLE => {
                 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
@@ -565,59 +1013,124 @@ impl<'a> EcallHandler for CommEcallHandler<'a> {
                         .show_and_return();
                 }
             }
-            ECALL_MODM => self
-                .handle_bn_modm(
-                    cpu,
-                    GPreg!(A0),
-                    GPreg!(A1),
-                    reg!(A2) as usize,
-                    GPreg!(A3),
-                    reg!(A4) as usize,
-                )
-                .map_err(|_| CommEcallError::GenericError("bn_modm failed"))?,
-            ECALL_ADDM => self
-                .handle_bn_addm(
-                    cpu,
-                    GPreg!(A0),
-                    GPreg!(A1),
-                    GPreg!(A2),
-                    GPreg!(A3),
-                    reg!(A4) as usize,
-                )
-                .map_err(|_| CommEcallError::GenericError("bn_addm failed"))?,
-            ECALL_SUBM => self
-                .handle_bn_subm(
-                    cpu,
-                    GPreg!(A0),
-                    GPreg!(A1),
-                    GPreg!(A2),
-                    GPreg!(A3),
-                    reg!(A4) as usize,
-                )
-                .map_err(|_| CommEcallError::GenericError("bn_subm failed"))?,
-            ECALL_MULTM => self
-                .handle_bn_multm(
-                    cpu,
-                    GPreg!(A0),
-                    GPreg!(A1),
-                    GPreg!(A2),
-                    GPreg!(A3),
-                    reg!(A4) as usize,
-                )
-                .map_err(|_| CommEcallError::GenericError("bn_multm failed"))?,
-            ECALL_POWM => self
-                .handle_bn_powm(
-                    cpu,
-                    GPreg!(A0),
-                    GPreg!(A1),
-                    GPreg!(A2),
-                    reg!(A3) as usize,
-                    GPreg!(A4),
-                    reg!(A5) as usize,
-                )
-                .map_err(|_| CommEcallError::GenericError("bn_powm failed"))?,
+            ECALL_YIELD => {
+                match self.handle_yield(cpu, reg!(A0) as u32, GPreg!(A1), reg!(A2) as usize) {
+                    Ok(cancelled) => reg!(A0) = cancelled as u32,
+                    Err(_) => {
+                        trap_or_fatal(cpu, TrapCause::OutOfBounds, GPreg!(A1).0, "yield failed")?
+                    }
+                }
+            }
+            ECALL_MODM => {
+                if self
+                    .handle_bn_modm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        reg!(A2) as usize,
+                        GPreg!(A3),
+                        reg!(A4) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_modm failed")?;
+                }
+            }
+            ECALL_ADDM => {
+                if self
+                    .handle_bn_addm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        GPreg!(A3),
+                        reg!(A4) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_addm failed")?;
+                }
+            }
+            ECALL_SUBM => {
+                if self
+                    .handle_bn_subm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        GPreg!(A3),
+                        reg!(A4) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_subm failed")?;
+                }
+            }
+            ECALL_MULTM => {
+                if self
+                    .handle_bn_multm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        GPreg!(A3),
+                        reg!(A4) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_multm failed")?;
+                }
+            }
+            ECALL_POWM => {
+                if self
+                    .handle_bn_powm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        reg!(A3) as usize,
+                        GPreg!(A4),
+                        reg!(A5) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_powm failed")?;
+                }
+            }
+            ECALL_DIVM => {
+                if self
+                    .handle_bn_divm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        GPreg!(A3),
+                        reg!(A4) as usize,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_divm failed")?;
+                }
+            }
+            ECALL_INVM => {
+                if self
+                    .handle_bn_invm(
+                        cpu,
+                        GPreg!(A0),
+                        GPreg!(A1),
+                        GPreg!(A2),
+                        reg!(A3) as usize,
+                        reg!(A4) != 0,
+                    )
+                    .is_err()
+                {
+                    trap_or_fatal(cpu, TrapCause::BigNumberTooLarge, 0, "bn_invm failed")?;
+                }
+            }
             _ => {
-                return Err(CommEcallError::UnhandledEcall);
+                if !cpu.raise_trap(TrapCause::InvalidEcall as u32, 0) {
+                    return Err(CommEcallError::UnhandledEcall);
+                }
             }
         }
 
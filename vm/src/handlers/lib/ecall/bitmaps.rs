@@ -6,7 +6,20 @@ pub trait ToIconDetails {
 #[cfg(any(target_os = "stax", target_os = "flex"))]
 mod large_screen {
     use super::*;
-    use ledger_secure_sdk_sys::{nbgl_icon_details_t, NBGL_BPP_4};
+    use common::ux::IconBpp;
+    use ledger_secure_sdk_sys::{nbgl_icon_details_t, NBGL_BPP_1, NBGL_BPP_2, NBGL_BPP_4};
+
+    /// Scratch storage for a [`common::ux::Icon::Custom`] icon's `nbgl_icon_details_t`,
+    /// populated just before its pointer is handed to NBGL. The bitmap bytes it points at live
+    /// in the `Icon` value itself (valid as long as the caller keeps it alive, the same
+    /// requirement as for any `&Icon`); this static only needs to outlive the pointer's return.
+    static mut CUSTOM_ICON_DETAILS: nbgl_icon_details_t = nbgl_icon_details_t {
+        width: 0,
+        height: 0,
+        bpp: NBGL_BPP_4,
+        isFile: true,
+        bitmap: core::ptr::null(),
+    };
 
     const CHECK_CIRCLE_64PX_BITMAP: [u8; 571] = [
         0x40, 0x00, 0x40, 0x00, 0x21, 0x33, 0x02, 0x00, 0x31, 0x02, 0x1f, 0x8b, 0x08, 0x00, 0x00,
@@ -115,6 +128,26 @@ mod large_screen {
                 common::ux::Icon::Confirm => core::ptr::null(), // only for small screen devices
                 common::ux::Icon::Reject => core::ptr::null(),  // only for small screen devices
                 common::ux::Icon::Processing => core::ptr::null(), // only for small screen devices
+                common::ux::Icon::Custom { width, height, bpp, bitmap } => {
+                    if !common::ux::Icon::validate_custom_dimensions(*width, *height, bitmap) {
+                        return core::ptr::null();
+                    }
+                    let nbgl_bpp = match bpp {
+                        IconBpp::Bpp1 => NBGL_BPP_1,
+                        IconBpp::Bpp2 => NBGL_BPP_2,
+                        IconBpp::Bpp4 => NBGL_BPP_4,
+                    };
+                    unsafe {
+                        CUSTOM_ICON_DETAILS = nbgl_icon_details_t {
+                            width: *width,
+                            height: *height,
+                            bpp: nbgl_bpp,
+                            isFile: true,
+                            bitmap: bitmap.as_ptr(),
+                        };
+                        core::ptr::addr_of!(CUSTOM_ICON_DETAILS)
+                    }
+                }
             }
         }
     }
@@ -123,7 +156,18 @@ mod large_screen {
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
 mod small_screen {
     use super::*;
-    use ledger_secure_sdk_sys::{nbgl_icon_details_t, NBGL_BPP_1};
+    use common::ux::IconBpp;
+    use ledger_secure_sdk_sys::{nbgl_icon_details_t, NBGL_BPP_1, NBGL_BPP_2, NBGL_BPP_4};
+
+    /// Scratch storage for a [`common::ux::Icon::Custom`] icon's `nbgl_icon_details_t`; see the
+    /// identical static in `large_screen` for why this is safe.
+    static mut CUSTOM_ICON_DETAILS: nbgl_icon_details_t = nbgl_icon_details_t {
+        width: 0,
+        height: 0,
+        bpp: NBGL_BPP_1,
+        isFile: true,
+        bitmap: core::ptr::null(),
+    };
 
     const VALIDATE_14X14_BITMAP: [u8; 23] = [
         0x0e, 0x00, 0x0e, 0x00, 0x02, 0x0f, 0x00, 0x00, 0x32, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3,
@@ -171,6 +215,26 @@ mod small_screen {
                 common::ux::Icon::Confirm => &VALIDATE_14PX,
                 common::ux::Icon::Reject => &CROSSMARK_14PX,
                 common::ux::Icon::Processing => &PROCESSING_14PX,
+                common::ux::Icon::Custom { width, height, bpp, bitmap } => {
+                    if !common::ux::Icon::validate_custom_dimensions(*width, *height, bitmap) {
+                        return core::ptr::null();
+                    }
+                    let nbgl_bpp = match bpp {
+                        IconBpp::Bpp1 => NBGL_BPP_1,
+                        IconBpp::Bpp2 => NBGL_BPP_2,
+                        IconBpp::Bpp4 => NBGL_BPP_4,
+                    };
+                    unsafe {
+                        CUSTOM_ICON_DETAILS = nbgl_icon_details_t {
+                            width: *width,
+                            height: *height,
+                            bpp: nbgl_bpp,
+                            isFile: true,
+                            bitmap: bitmap.as_ptr(),
+                        };
+                        core::ptr::addr_of!(CUSTOM_ICON_DETAILS)
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,283 @@
+//! GDB Remote Serial Protocol (RSP) stub, letting a host GDB attach to a running V-App.
+//!
+//! Gated behind the `debug` cargo feature so release builds carry none of this. The state machine
+//! here is transport-agnostic: it consumes one RSP packet at a time and produces at most one reply
+//! packet, so it can be driven cooperatively from inside the same APDU round-trip loop that already
+//! pages V-App memory in and out via `interrupt()` (see `handlers::lib::outsourced_mem`), rather
+//! than blocking the device waiting for a debugger. The execution loop that owns the `Cpu` (in
+//! `handlers::start_vapp`) is expected to check [`GdbStub::should_break`] before each
+//! `Cpu::execute` step, and while stopped, to route incoming `Instruction::DebugStub` packets to
+//! [`GdbStub::handle_packet`] instead of executing, until it returns [`GdbAction::Resume`].
+
+use alloc::vec::Vec;
+use common::vm::{Cpu, PagedMemory};
+
+/// Signal number GDB reports for a breakpoint/single-step stop (`SIGTRAP`).
+const SIGTRAP: u8 = 5;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn hex_encode_into(bytes: &[u8], out: &mut Vec<u8>) {
+    for &b in bytes {
+        out.push(HEX_DIGITS[(b >> 4) as usize]);
+        out.push(HEX_DIGITS[(b & 0xf) as usize]);
+    }
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn hex_decode(hex: &[u8]) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(hex.len() / 2);
+    for pair in hex.chunks_exact(2) {
+        out.push((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?);
+    }
+    Some(out)
+}
+
+fn hex_decode_u32_be(hex: &[u8]) -> Option<u32> {
+    let bytes = hex_decode(hex)?;
+    if bytes.len() > 4 {
+        return None;
+    }
+    let mut value = 0u32;
+    for &b in &bytes {
+        value = (value << 8) | b as u32;
+    }
+    Some(value)
+}
+
+/// Splits `a,b` (as found in `m`/`M`/`Z`/`z` packets) into its two comma-separated fields.
+fn split_once(s: &[u8], sep: u8) -> Option<(&[u8], &[u8])> {
+    let pos = s.iter().position(|&b| b == sep)?;
+    Some((&s[..pos], &s[pos + 1..]))
+}
+
+/// Frames `payload` as `$<payload>#<hex-checksum>`.
+pub fn frame_packet(payload: &[u8]) -> Vec<u8> {
+    let checksum = payload.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    let mut out = Vec::with_capacity(payload.len() + 4);
+    out.push(b'$');
+    out.extend_from_slice(payload);
+    out.push(b'#');
+    hex_encode_into(&[checksum], &mut out);
+    out
+}
+
+/// Parses and checksum-verifies a single framed RSP packet `$<payload>#<hex-checksum>`, returning
+/// the payload on success.
+pub fn parse_packet(raw: &[u8]) -> Option<&[u8]> {
+    let raw = raw.strip_prefix(b"$")?;
+    let hash_pos = raw.iter().position(|&b| b == b'#')?;
+    let (payload, rest) = raw.split_at(hash_pos);
+    let checksum_hex = rest.get(1..3)?;
+    let expected = hex_decode(checksum_hex)?[0];
+    let actual = payload.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    (actual == expected).then_some(payload)
+}
+
+/// What the execution loop should do after [`GdbStub::handle_packet`] returns.
+pub enum GdbAction {
+    /// Bytes to send back over the wire this round (an ack byte, optionally followed by a framed
+    /// reply packet).
+    Reply(Vec<u8>),
+    /// Resume execution. If `single_step` is set, run exactly one instruction and stop again;
+    /// otherwise run until a breakpoint is hit. Either way, the execution loop should send
+    /// [`GdbStub::stop_reply`] once it stops.
+    Resume { single_step: bool },
+}
+
+/// Per-session GDB stub state: the set of active software breakpoints.
+#[derive(Default)]
+pub struct GdbStub {
+    breakpoints: Vec<u32>,
+}
+
+impl GdbStub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether execution should stop before running the instruction at `pc`.
+    pub fn should_break(&self, pc: u32) -> bool {
+        self.breakpoints.contains(&pc)
+    }
+
+    /// Builds the stop-reply packet (`+$S05#..`) to send once execution has halted, whether due
+    /// to a breakpoint or a completed single-step.
+    pub fn stop_reply(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(3);
+        body.push(b'S');
+        hex_encode_into(&[SIGTRAP], &mut body);
+        let mut out = Vec::from(&b"+"[..]);
+        out.extend(frame_packet(&body));
+        out
+    }
+
+    /// Handles one incoming RSP packet against `cpu`'s register/memory state.
+    pub fn handle_packet<M: PagedMemory>(&mut self, cpu: &mut Cpu<M>, packet: &[u8]) -> GdbAction {
+        let Some(payload) = parse_packet(packet) else {
+            return GdbAction::Reply(Vec::from(&b"-"[..]));
+        };
+
+        match payload.first() {
+            Some(b'c') => return GdbAction::Resume { single_step: false },
+            Some(b's') => return GdbAction::Resume { single_step: true },
+            _ => {}
+        }
+
+        let body = match payload.first() {
+            Some(b'g') => self.cmd_read_registers(cpu),
+            Some(b'G') => self.cmd_write_registers(cpu, &payload[1..]),
+            Some(b'm') => self.cmd_read_memory(cpu, &payload[1..]),
+            Some(b'M') => self.cmd_write_memory(cpu, &payload[1..]),
+            Some(b'Z') => self.cmd_insert_breakpoint(&payload[1..]),
+            Some(b'z') => self.cmd_remove_breakpoint(&payload[1..]),
+            // Unrecognized/unsupported command: an empty reply tells GDB so.
+            _ => Vec::new(),
+        };
+
+        let mut out = Vec::from(&b"+"[..]);
+        out.extend(frame_packet(&body));
+        GdbAction::Reply(out)
+    }
+
+    fn cmd_read_registers<M: PagedMemory>(&self, cpu: &Cpu<M>) -> Vec<u8> {
+        let mut out = Vec::with_capacity(33 * 8);
+        for reg in cpu.regs.iter().chain(core::iter::once(&cpu.pc)) {
+            hex_encode_into(&reg.to_le_bytes(), &mut out);
+        }
+        out
+    }
+
+    fn cmd_write_registers<M: PagedMemory>(&self, cpu: &mut Cpu<M>, hex: &[u8]) -> Vec<u8> {
+        let Some(bytes) = hex_decode(hex) else {
+            return Vec::new();
+        };
+        if bytes.len() != 33 * 4 {
+            return Vec::new();
+        }
+        for (i, chunk) in bytes.chunks_exact(4).enumerate() {
+            let value = u32::from_le_bytes(chunk.try_into().unwrap());
+            if i < 32 {
+                cpu.regs[i] = value;
+            } else {
+                cpu.pc = value;
+            }
+        }
+        Vec::from(&b"OK"[..])
+    }
+
+    fn cmd_read_memory<M: PagedMemory>(&self, cpu: &mut Cpu<M>, args: &[u8]) -> Vec<u8> {
+        let Some((addr_hex, len_hex)) = split_once(args, b',') else {
+            return Vec::new();
+        };
+        let (Some(addr), Some(len)) = (hex_decode_u32_be(addr_hex), hex_decode_u32_be(len_hex)) else {
+            return Vec::new();
+        };
+
+        let mut out = Vec::with_capacity(len as usize * 2);
+        for offset in 0..len {
+            match read_byte(cpu, addr.wrapping_add(offset)) {
+                Ok(byte) => hex_encode_into(&[byte], &mut out),
+                Err(_) => return Vec::from(&b"E01"[..]),
+            }
+        }
+        out
+    }
+
+    fn cmd_write_memory<M: PagedMemory>(&self, cpu: &mut Cpu<M>, args: &[u8]) -> Vec<u8> {
+        let Some((header, data_hex)) = split_once(args, b':') else {
+            return Vec::new();
+        };
+        let Some((addr_hex, len_hex)) = split_once(header, b',') else {
+            return Vec::new();
+        };
+        let (Some(addr), Some(len)) = (hex_decode_u32_be(addr_hex), hex_decode_u32_be(len_hex)) else {
+            return Vec::new();
+        };
+        let Some(data) = hex_decode(data_hex) else {
+            return Vec::new();
+        };
+        if data.len() != len as usize {
+            return Vec::new();
+        }
+
+        for (offset, byte) in data.iter().enumerate() {
+            if write_byte(cpu, addr.wrapping_add(offset as u32), *byte).is_err() {
+                return Vec::from(&b"E01"[..]);
+            }
+        }
+        Vec::from(&b"OK"[..])
+    }
+
+    fn cmd_insert_breakpoint(&mut self, args: &[u8]) -> Vec<u8> {
+        let Some((kind, rest)) = split_once(args, b',') else {
+            return Vec::new();
+        };
+        if kind != b"0" {
+            return Vec::new(); // only software breakpoints (type 0) are supported
+        }
+        let Some((addr_hex, _size_hex)) = split_once(rest, b',') else {
+            return Vec::new();
+        };
+        let Some(addr) = hex_decode_u32_be(addr_hex) else {
+            return Vec::new();
+        };
+        if !self.breakpoints.contains(&addr) {
+            self.breakpoints.push(addr);
+        }
+        Vec::from(&b"OK"[..])
+    }
+
+    fn cmd_remove_breakpoint(&mut self, args: &[u8]) -> Vec<u8> {
+        let Some((kind, rest)) = split_once(args, b',') else {
+            return Vec::new();
+        };
+        if kind != b"0" {
+            return Vec::new();
+        }
+        let Some((addr_hex, _size_hex)) = split_once(rest, b',') else {
+            return Vec::new();
+        };
+        let Some(addr) = hex_decode_u32_be(addr_hex) else {
+            return Vec::new();
+        };
+        self.breakpoints.retain(|&bp| bp != addr);
+        Vec::from(&b"OK"[..])
+    }
+}
+
+/// Reads a byte from whichever of `cpu`'s memory segments contains `address`, mirroring the
+/// segment priority `Cpu` itself uses internally (its own `read_u8` is private to its module).
+fn read_byte<M: PagedMemory>(cpu: &mut Cpu<M>, address: u32) -> Result<u8, &'static str> {
+    if cpu.stack_seg.contains(address) {
+        cpu.stack_seg.read_u8(address)
+    } else if cpu.data_seg.contains(address) {
+        cpu.data_seg.read_u8(address)
+    } else if cpu.code_seg.contains(address) {
+        cpu.code_seg.read_u8(address)
+    } else {
+        Err("Address out of bounds")
+    }
+}
+
+/// Writes a byte to whichever of `cpu`'s writable memory segments contains `address`.
+fn write_byte<M: PagedMemory>(cpu: &mut Cpu<M>, address: u32, value: u8) -> Result<(), &'static str> {
+    if cpu.stack_seg.contains(address) {
+        cpu.stack_seg.write_u8(address, value)
+    } else if cpu.data_seg.contains(address) {
+        cpu.data_seg.write_u8(address, value)
+    } else {
+        Err("Address out of bounds")
+    }
+}
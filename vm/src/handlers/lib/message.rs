@@ -0,0 +1,152 @@
+//! Fragmentation/reassembly for protobuf `Request`/`Response` messages that don't fit in a
+//! single [`io_exchange`](super::io::io_exchange) round trip: `io_exchange` only ever moves one
+//! `comm.apdu_buffer` worth of bytes, but a manifest-heavy `RegisterVApp` or an xpub-heavy
+//! response can easily be larger than that.
+//!
+//! Every frame is prefixed with a small header: a 4-byte big-endian `total_length` (the size of
+//! the complete, reassembled message) followed by a 4-byte big-endian `offset` (how many bytes of
+//! that message precede this frame's body). The sender just slices the message into
+//! fixed-size bodies and walks `offset` forward; the receiver doesn't need to buffer anything
+//! beyond the frames it already has, since each frame says exactly where it belongs.
+
+use alloc::vec::Vec;
+
+/// Size in bytes of the `total_length`/`offset` header prefixed to every frame.
+pub const FRAME_HEADER_LEN: usize = 8;
+
+/// Splits `message` into a sequence of framed chunks, each at most `max_frame_len` bytes
+/// (header included). A zero-length message still produces exactly one frame (an empty final
+/// frame with `total_length == 0`), so the receiver always gets at least one frame to resolve an
+/// empty reply.
+pub fn frame_message(message: &[u8], max_frame_len: usize) -> Vec<Vec<u8>> {
+    let total_length = message.len() as u32;
+    let max_body_len = max_frame_len.saturating_sub(FRAME_HEADER_LEN).max(1);
+
+    let mut frames = Vec::new();
+    let mut offset = 0usize;
+    loop {
+        let end = (offset + max_body_len).min(message.len());
+        let body = &message[offset..end];
+
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + body.len());
+        frame.extend_from_slice(&total_length.to_be_bytes());
+        frame.extend_from_slice(&(offset as u32).to_be_bytes());
+        frame.extend_from_slice(body);
+        frames.push(frame);
+
+        offset = end;
+        if offset >= message.len() {
+            break;
+        }
+    }
+    frames
+}
+
+/// Errors raised while reassembling a framed message. Each one means the host desynced from the
+/// VM's expectations; the caller should reply with a dedicated status word rather than silently
+/// reinterpreting the bytes already buffered. [`Reassembler::accept`] has already reset its
+/// internal state by the time any of these is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReassemblyError {
+    /// A frame was shorter than [`FRAME_HEADER_LEN`].
+    FrameTooShort,
+    /// The next frame didn't start where the previous one left off (a gap, an overlap, or a
+    /// restart from zero mid-stream).
+    OffsetMismatch { expected: u32, got: u32 },
+    /// A frame claimed a different `total_length` than an earlier frame of the same message.
+    LengthMismatch { expected: u32, got: u32 },
+    /// The declared `total_length` exceeds the caller-supplied maximum message size.
+    LengthOverflow,
+}
+
+impl core::fmt::Display for ReassemblyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReassemblyError::FrameTooShort => write!(f, "frame too short to contain its header"),
+            ReassemblyError::OffsetMismatch { expected, got } => write!(
+                f,
+                "reassembly desync: expected frame at offset {expected}, got offset {got}"
+            ),
+            ReassemblyError::LengthMismatch { expected, got } => write!(
+                f,
+                "reassembly desync: message length changed mid-stream (was {expected}, now {got})"
+            ),
+            ReassemblyError::LengthOverflow => {
+                write!(f, "declared message length exceeds the reassembly buffer limit")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ReassemblyError {}
+
+/// Accumulates framed chunks (see [`frame_message`]) received from a peer into the complete
+/// message they encode, one [`Reassembler::accept`] call per frame.
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    buffer: Vec<u8>,
+    total_length: Option<u32>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new(), total_length: None }
+    }
+
+    /// Discards any partially-reassembled message, e.g. after a [`ReassemblyError`].
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.total_length = None;
+    }
+
+    /// Feeds one received frame. Returns `Ok(Some(message))` once `total_length` bytes have been
+    /// accumulated (the now-complete message; the reassembler is reset for the next one),
+    /// `Ok(None)` if more frames are still expected. `max_message_len` bounds how large a message
+    /// this reassembler is willing to buffer, regardless of what a frame claims.
+    pub fn accept(
+        &mut self,
+        frame: &[u8],
+        max_message_len: usize,
+    ) -> Result<Option<Vec<u8>>, ReassemblyError> {
+        if frame.len() < FRAME_HEADER_LEN {
+            self.reset();
+            return Err(ReassemblyError::FrameTooShort);
+        }
+
+        let mut total_length_bytes = [0u8; 4];
+        total_length_bytes.copy_from_slice(&frame[0..4]);
+        let total_length = u32::from_be_bytes(total_length_bytes);
+
+        let mut offset_bytes = [0u8; 4];
+        offset_bytes.copy_from_slice(&frame[4..8]);
+        let offset = u32::from_be_bytes(offset_bytes);
+
+        if total_length as usize > max_message_len {
+            self.reset();
+            return Err(ReassemblyError::LengthOverflow);
+        }
+
+        match self.total_length {
+            None => self.total_length = Some(total_length),
+            Some(expected) if expected != total_length => {
+                self.reset();
+                return Err(ReassemblyError::LengthMismatch { expected, got: total_length });
+            }
+            _ => {}
+        }
+
+        if offset as usize != self.buffer.len() {
+            let expected = self.buffer.len() as u32;
+            self.reset();
+            return Err(ReassemblyError::OffsetMismatch { expected, got: offset });
+        }
+
+        self.buffer.extend_from_slice(&frame[FRAME_HEADER_LEN..]);
+
+        if self.buffer.len() as u32 >= total_length {
+            Ok(Some(core::mem::replace(&mut self.buffer, Vec::new())))
+        } else {
+            Ok(None)
+        }
+    }
+}
@@ -2,6 +2,10 @@ use common::client_commands::Message;
 
 pub mod ecall;
 pub mod evict;
+#[cfg(feature = "debug")]
+pub mod gdbstub;
+pub mod io;
+pub mod message;
 pub mod outsourced_mem;
 pub mod vapp;
 
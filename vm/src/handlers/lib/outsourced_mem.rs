@@ -1,57 +1,327 @@
 use core::cell::RefCell;
 
 use alloc::rc::Rc;
+use alloc::vec::Vec;
+use common::accumulator::Hasher;
 use common::vm::{Page, PagedMemory};
 use ledger_device_sdk::io;
 
 use common::constants::PAGE_SIZE;
-use common::client_commands::ClientCommandCode;
+use common::client_commands::{
+    ClientCommandCode, CommitPageContentMessage, CommitPageMessage, Message, SectionKind,
+};
 
+use crate::crypto_provider::{CryptoProvider, DefaultCryptoProvider};
 use crate::{AppSW, Instruction};
 
+/// The hasher used to authenticate outsourced pages. Tied to [`DefaultCryptoProvider`] so that
+/// swapping the VM's crypto backend also swaps this Merkle tree's hash function.
+type PageHasher = <DefaultCryptoProvider as CryptoProvider>::Hasher;
 
-// TODO: temporary implementation that stores a single page, and without page integrity checks
+/// An upper bound on the depth of the page-authentication Merkle tree, i.e. on the number of
+/// sibling hashes the host may send along with a page; guards against a malicious host claiming
+/// an implausibly deep tree.
+const MAX_TREE_DEPTH: usize = 32;
+
+/// Size, in bytes, of the AES-GCM authentication tag stored alongside each encrypted page.
+const TAG_SIZE: usize = 16;
+
+/// Number of pages kept resident at once. Sized to trade off device RAM against host round-trips;
+/// raising it cuts down on `GetPage`/`CommitPage` traffic for working sets that revisit pages.
+const CACHE_SIZE: usize = 4;
+
+/// Builds the 12-byte AES-GCM nonce for a page, from its index and its version counter. Binding
+/// both into the nonce means a re-committed page (which always bumps `version`) never reuses a
+/// nonce under the session key, even though the page index alone repeats across commits.
+fn page_nonce(page_index: u32, version: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..4].copy_from_slice(&page_index.to_be_bytes());
+    nonce[4..8].copy_from_slice(&version.to_be_bytes());
+    nonce
+}
+
+/// Computes the Merkle leaf hash for the page at `page_index`, as
+/// `H(page_index || version || ciphertext || tag)`. Binding in `version` means the leaf changes
+/// on every commit even if the (encrypted) content happens to repeat, so the Merkle root also
+/// attests to freshness, not just integrity.
+fn hash_leaf(page_index: u32, version: u32, ciphertext: &[u8], tag: &[u8; TAG_SIZE]) -> [u8; 32] {
+    let mut hasher = PageHasher::new();
+    hasher.update(&page_index.to_be_bytes());
+    hasher.update(&version.to_be_bytes());
+    hasher.update(ciphertext);
+    hasher.update(tag);
+    hasher.finalize()
+}
+
+/// Computes a Merkle internal-node hash, as `H(left || right)`.
+fn hash_internal(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = PageHasher::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+/// Describes the outsourced segment (code, data or stack) that an `OutsourcedMemory` instance
+/// backs: its on-device identifier (sent along with every `GetPage`/`CommitPage` so the host knows
+/// which segment's page store to address), its extent, and its access policy.
+#[derive(Clone, Copy)]
+pub struct SegmentDescriptor {
+    /// Which section this segment backs; sent to the host to select the page store it addresses.
+    pub section: SectionKind,
+    /// Base address of the segment in the V-App's address space.
+    pub base: u32,
+    /// Number of pages in the segment; fixes the shape of its Merkle tree.
+    pub n_pages: u32,
+    /// Whether writes to this segment (i.e. evicting a dirty page back to the host) are
+    /// forbidden.
+    pub is_readonly: bool,
+}
+
+/// A single resident page: its content, its place in the Merkle tree, and enough bookkeeping to
+/// commit it back to the host on eviction.
+struct CacheSlot {
+    idx: u32,
+    page: Page,
+    /// Whether this slot may hold changes the host doesn't have yet. Since [`PagedMemory`] gives
+    /// no signal of whether a returned `&mut Page` was actually written through, any writable page
+    /// is conservatively treated as dirty, matching the single-page implementation this cache
+    /// replaces.
+    dirty: bool,
+    version: u32,
+    siblings: Vec<[u8; 32]>,
+    /// Logical timestamp of last access, used to pick an eviction victim (the slot with the
+    /// smallest value).
+    last_used: u64,
+}
+
+/// Outsourced, host-backed memory that treats the host's page store as an untrusted block store:
+/// every page is authenticated against a Merkle tree whose root is kept in device RAM (see
+/// [`Self::root`]), using the same implicit binary-heap leaf layout as
+/// [`common::accumulator::MerkleAccumulator`]. `n_pages == 1` is a degenerate, single-leaf tree
+/// whose root is just the leaf hash.
+///
+/// Up to [`CACHE_SIZE`] pages are kept resident at a time, least-recently-used eviction, to cut
+/// down on `GetPage`/`CommitPage` round-trips for working sets that revisit pages.
 pub struct OutsourcedMemory<'c> {
     comm: Rc<RefCell<&'c mut io::Comm>>,
-    idx: Option<u32>,
-    page: Page,
-    is_readonly: bool
+    /// Key used to encrypt/decrypt pages with AES-256-GCM, derived once at V-App startup and
+    /// never sent to the host; this is what keeps outsourced page content confidential.
+    session_key: [u8; 32],
+    segment: SegmentDescriptor,
+    /// The current, device-authenticated Merkle root.
+    root: [u8; 32],
+    slots: Vec<CacheSlot>,
+    /// Monotonically increasing counter used to time-stamp slot accesses for LRU eviction.
+    clock: u64,
+    /// Number of pages fetched from the host (i.e. cache misses that required a round-trip).
+    page_loads: u32,
+    /// Number of pages committed to the host (on eviction or explicit flush).
+    page_commits: u32,
+    /// Number of [`PagedMemory::get_page`] calls served from the resident cache.
+    cache_hits: u32,
+    /// Number of [`PagedMemory::get_page`] calls that missed the resident cache.
+    cache_misses: u32,
 }
 
 impl<'c> OutsourcedMemory<'c> {
-    pub fn new(comm: Rc<RefCell<&'c mut io::Comm>>, is_readonly: bool) -> Self {
-        Self {
+    /// Creates a new `OutsourcedMemory` backing `segment`, trusting `root` as the current Merkle
+    /// root of its content (e.g. one already authenticated via the V-App manifest), and
+    /// encrypting/decrypting page content under `session_key`.
+    ///
+    /// Before returning, resolves any write-ahead journal entry the host may still be holding for
+    /// this segment from a commit interrupted on a previous run (see [`Self::replay_journal`]).
+    pub fn new(
+        comm: Rc<RefCell<&'c mut io::Comm>>,
+        segment: SegmentDescriptor,
+        root: [u8; 32],
+        session_key: [u8; 32],
+    ) -> Result<Self, &'static str> {
+        let mut memory = Self {
             comm,
-            idx: None,
-            page: Page { data: [0; PAGE_SIZE] },
-            is_readonly
-        }
+            session_key,
+            segment,
+            root,
+            slots: Vec::with_capacity(CACHE_SIZE),
+            clock: 0,
+            page_loads: 0,
+            page_commits: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+        };
+        memory.replay_journal()?;
+        Ok(memory)
     }
 
-    fn commit_page(&mut self) -> Result<(), &'static str> {
-        let Some(idx) = self.idx else {
-            panic!("No page to commit");
-        };
+    /// Returns the current, device-authenticated Merkle root.
+    #[inline]
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Number of pages fetched from the host since this `OutsourcedMemory` was created.
+    #[inline]
+    pub fn page_loads(&self) -> u32 {
+        self.page_loads
+    }
+
+    /// Number of pages committed to the host since this `OutsourcedMemory` was created.
+    #[inline]
+    pub fn page_commits(&self) -> u32 {
+        self.page_commits
+    }
 
+    /// Number of [`PagedMemory::get_page`] calls served from the resident cache, without a host
+    /// round-trip.
+    #[inline]
+    pub fn cache_hits(&self) -> u32 {
+        self.cache_hits
+    }
+
+    /// Number of [`PagedMemory::get_page`] calls that missed the resident cache and required
+    /// fetching the page from the host.
+    #[inline]
+    pub fn cache_misses(&self) -> u32 {
+        self.cache_misses
+    }
+
+    /// Returns the position of `page_index`'s leaf in the tree's implicit binary-heap layout.
+    #[inline]
+    fn leaf_position(&self, page_index: u32) -> usize {
+        self.segment.n_pages as usize - 1 + page_index as usize
+    }
+
+    /// Commits the page held in `slots[slot]` back to the host, rolling the device-side Merkle
+    /// root forward. Fails without contacting the host if the segment is read-only.
+    ///
+    /// Follows a write-ahead journaling discipline: the host is told the new version and root
+    /// *before* any new page content is sent, so that if execution is interrupted partway through,
+    /// [`Self::replay_journal`] can tell on the next resume whether the write completed.
+    fn commit_slot(&mut self, slot: usize) -> Result<(), &'static str> {
+        if self.segment.is_readonly {
+            return Err("Cannot commit a page in a read-only segment");
+        }
+
+        let idx = self.slots[slot].idx;
+
+        // A commit always moves to a new version, so its nonce can never collide with the one
+        // used to encrypt the page the host is currently holding.
+        let new_version = self.slots[slot].version.wrapping_add(1);
+
+        let mut ciphertext = self.slots[slot].page.data;
+        let tag = DefaultCryptoProvider::aead_encrypt(
+            &self.session_key,
+            &page_nonce(idx, new_version),
+            &[],
+            &mut ciphertext,
+        );
+
+        // Compute the root the commit will produce, up front, so it can be journaled before any
+        // byte of the new page content is sent to the host.
+        let mut pos = self.leaf_position(idx);
+        let mut new_root = hash_leaf(idx, new_version, &ciphertext, &tag);
+        for sibling in &self.slots[slot].siblings {
+            new_root = if pos % 2 == 0 { hash_internal(sibling, &new_root) } else { hash_internal(&new_root, sibling) };
+            pos = (pos - 1) / 2;
+        }
+
+        {
+            let mut comm = self.comm.borrow_mut();
+
+            // First message: the journal record (segment, page index, new version, new root). The
+            // host must persist and acknowledge this before the device sends anything else.
+            let journal_message =
+                CommitPageMessage::new(self.segment.section, idx, new_version, new_root);
+            journal_message.serialize_with(|chunk| comm.append(chunk));
+            comm.reply(AppSW::InterruptedExecution);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Continue"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+
+            // Second message: the (now encrypted) page content, its authentication tag and its new
+            // version.
+            let content_message =
+                CommitPageContentMessage::new(ciphertext.to_vec(), tag, new_version);
+            content_message.serialize_with(|chunk| comm.append(chunk));
+            comm.reply(AppSW::InterruptedExecution);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Continue"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+
+            // Third message: the commit marker, telling the host the write completed (finalize =
+            // 1) so it can drop the journal entry.
+            comm.append(&[ClientCommandCode::CommitPageDone as u8]);
+            comm.append(&[self.segment.section as u8]);
+            comm.append(&idx.to_be_bytes());
+            comm.append(&[1]);
+
+            let Instruction::Continue(p1, p2) = comm.next_command() else {
+                return Err("INS not supported"); // expected "Continue"
+            };
+
+            if (p1, p2) != (0, 0) {
+                return Err("Wrong P1/P2");
+            }
+        }
+
+        self.root = new_root;
+        self.slots[slot].version = new_version;
+        self.slots[slot].dirty = false;
+        self.page_commits += 1;
+
+        Ok(())
+    }
+
+    /// Resolves any write-ahead journal entry the host may still be holding for this segment from
+    /// a `CommitPage`/`CommitPageContent` exchange interrupted before its `CommitPageDone` marker.
+    ///
+    /// Compares the journal's claimed new root against `self.root` (the on-device root, trusted
+    /// from the V-App manifest): if they already match, the write had fully landed before the
+    /// interruption and the journal entry is just stale bookkeeping to discard; otherwise the
+    /// write never completed and the entry is discarded in favor of the last known-good root. In
+    /// both cases a [`ClientCommandCode::CommitPageDone`] is sent so the host clears the entry.
+    fn replay_journal(&mut self) -> Result<(), &'static str> {
         let mut comm = self.comm.borrow_mut();
 
-        // First message: communicate the page to commit
-        // TODO: should add a byte to identify in which segment does the page belong
-        comm.append(&[ClientCommandCode::CommitPage as u8]);
-        comm.append(&idx.to_be_bytes());
+        comm.append(&[ClientCommandCode::QueryJournal as u8]);
+        comm.append(&[self.segment.section as u8]);
         comm.reply(AppSW::InterruptedExecution);
 
-        let Instruction::Continue(p1, p2) = comm.next_command() else {
+        let Instruction::Continue(has_entry, _) = comm.next_command() else {
             return Err("INS not supported"); // expected "Continue"
         };
 
-        if (p1, p2) != (0, 0) {
-            return Err("Wrong P1/P2");
+        if has_entry == 0 {
+            return Ok(());
+        }
+
+        let journal_data = comm.get_data().map_err(|_| "Wrong APDU length")?;
+        if journal_data.len() != 4 + 4 + 32 {
+            return Err("Wrong APDU length");
         }
+        let page_index = u32::from_be_bytes(journal_data[0..4].try_into().expect("4 bytes"));
+        let mut claimed_root = [0u8; 32];
+        claimed_root.copy_from_slice(&journal_data[8..40]);
 
-        // Second message  message: communicate the page content
-        comm.append(&[ClientCommandCode::CommitPageContent as u8]);
-        comm.append(&self.page.data);
+        // The write is only considered to have landed (finalize = 1) if the journaled root
+        // matches the one this device already trusts; otherwise it's told to discard the entry
+        // (finalize = 0), leaving the last known-good root (and the page content it authenticates)
+        // in effect.
+        let finalize = claimed_root == self.root;
+        comm.append(&[ClientCommandCode::CommitPageDone as u8]);
+        comm.append(&[self.segment.section as u8]);
+        comm.append(&page_index.to_be_bytes());
+        comm.append(&[finalize as u8]);
+        comm.reply(AppSW::InterruptedExecution);
 
         let Instruction::Continue(p1, p2) = comm.next_command() else {
             return Err("INS not supported"); // expected "Continue"
@@ -63,24 +333,46 @@ impl<'c> OutsourcedMemory<'c> {
 
         Ok(())
     }
-}
 
-impl<'c> PagedMemory for OutsourcedMemory<'c> {
-    type PageRef<'a> = &'a mut Page where Self: 'a;
+    /// Evicts the least-recently-used slot (committing it first if dirty), freeing up a slot to
+    /// hold a freshly-fetched page. Returns the freed slot's index, or `None` if the cache isn't
+    /// yet full.
+    fn evict_if_full(&mut self) -> Result<Option<usize>, &'static str> {
+        if self.slots.len() < CACHE_SIZE {
+            return Ok(None);
+        }
 
-    fn get_page<'a>(&'a mut self, page_index: u32) -> Result<Self::PageRef<'a>, &'static str> {
-        if let Some(idx) = &mut self.idx {
-            if *idx == page_index {
-                return Ok(&mut self.page);
-            } else {
-                if !self.is_readonly {
-                    self.commit_page()?;
-                }
-            }
+        let victim = self
+            .slots
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, slot)| slot.last_used)
+            .map(|(i, _)| i)
+            .expect("cache is full, so it has at least one slot");
+
+        if self.slots[victim].dirty {
+            self.commit_slot(victim)?;
+        }
+
+        Ok(Some(victim))
+    }
+
+    /// Finds the resident slot holding `page_index`, if any.
+    fn find_slot(&self, page_index: u32) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.idx == page_index)
+    }
+
+    /// Fetches `page_index` from the host, Merkle-authenticates and decrypts it, and inserts it
+    /// into the cache, evicting the least-recently-used slot (committing it first if dirty) if
+    /// the cache is already full. Returns the index of the slot it was inserted into.
+    fn fetch_and_cache(&mut self, page_index: u32) -> Result<usize, &'static str> {
+        if page_index >= self.segment.n_pages {
+            return Err("Page index out of bounds");
         }
 
         let mut comm = self.comm.borrow_mut();
         comm.append(&[ClientCommandCode::GetPage as u8]);
+        comm.append(&[self.segment.section as u8]);
         comm.append(&page_index.to_be_bytes());
         comm.reply(AppSW::InterruptedExecution);
 
@@ -92,17 +384,142 @@ impl<'c> PagedMemory for OutsourcedMemory<'c> {
             return Err("Wrong P2");
         }
 
+        // The host replies with the encrypted page content, its authentication tag and version,
+        // followed by the Merkle authentication path: a single length byte, then that many
+        // 32-byte sibling hashes, ordered leaf to root.
         let fetched_data = comm.get_data().map_err(|_| "Wrong APDU length")?;
-        if fetched_data.len() != PAGE_SIZE - 1 {
+        if fetched_data.len() < PAGE_SIZE + TAG_SIZE + 4 {
+            return Err("Wrong APDU length");
+        }
+        let (page_data, rest) = fetched_data.split_at(PAGE_SIZE - 1);
+        let (tag_bytes, rest) = rest.split_at(TAG_SIZE);
+        let (version_bytes, proof_data) = rest.split_at(4);
+
+        let Some((&n_siblings, sibling_bytes)) = proof_data.split_first() else {
+            return Err("Wrong APDU length");
+        };
+        let n_siblings = n_siblings as usize;
+        if n_siblings > MAX_TREE_DEPTH || sibling_bytes.len() != n_siblings * 32 {
             return Err("Wrong APDU length");
         }
-        // overwrite page content
-        self.page.data[0..PAGE_SIZE - 1].copy_from_slice(fetched_data);
-        self.page.data[PAGE_SIZE - 1] = p1;
 
-        // update index
-        self.idx = Some(page_index);
+        let mut tag = [0u8; TAG_SIZE];
+        tag.copy_from_slice(tag_bytes);
+        let version = u32::from_be_bytes(version_bytes.try_into().expect("4 bytes"));
+
+        let mut page = Page { data: [0; PAGE_SIZE] };
+        page.data[0..PAGE_SIZE - 1].copy_from_slice(page_data);
+        page.data[PAGE_SIZE - 1] = p1;
+
+        let mut siblings = Vec::with_capacity(n_siblings);
+        let mut pos = self.leaf_position(page_index);
+        let mut hash = hash_leaf(page_index, version, &page.data, &tag);
+        for chunk in sibling_bytes.chunks_exact(32) {
+            let mut sibling = [0u8; 32];
+            sibling.copy_from_slice(chunk);
+            hash = if pos % 2 == 0 { hash_internal(&sibling, &hash) } else { hash_internal(&hash, &sibling) };
+            pos = (pos - 1) / 2;
+            siblings.push(sibling);
+        }
+
+        if hash != self.root {
+            return Err("Page authentication failed");
+        }
+
+        drop(comm);
+
+        // Only decrypt once the ciphertext and tag have been Merkle-authenticated above, so an
+        // authentication failure never runs AEAD decryption on untrusted host-supplied bytes.
+        DefaultCryptoProvider::aead_decrypt(&self.session_key, &page_nonce(page_index, version), &[], &mut page.data, &tag)?;
+
+        self.page_loads += 1;
+
+        let slot = CacheSlot {
+            idx: page_index,
+            page,
+            // Conservatively assumed dirty for writable segments; see [`CacheSlot::dirty`].
+            dirty: !self.segment.is_readonly,
+            version,
+            siblings,
+            last_used: self.clock,
+        };
+
+        let slot_index = match self.evict_if_full()? {
+            Some(freed) => {
+                self.slots[freed] = slot;
+                freed
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        };
+
+        Ok(slot_index)
+    }
+
+    /// Opportunistically fetches `page_index + 1` into the cache ahead of it being requested, to
+    /// help access patterns (like a streaming `Vec::push`) that walk pages in order. Only fires
+    /// when the next page exists, isn't already resident, and the cache has a free slot, so a
+    /// prefetch can never evict the page that was just demand-fetched.
+    fn prefetch_next(&mut self, page_index: u32) {
+        let Some(next) = page_index.checked_add(1) else {
+            return;
+        };
+        if next >= self.segment.n_pages || self.slots.len() >= CACHE_SIZE {
+            return;
+        }
+        if self.find_slot(next).is_some() {
+            return;
+        }
+        // Best-effort: a failed prefetch just means the next access falls back to a normal,
+        // on-demand fetch.
+        let _ = self.fetch_and_cache(next);
+    }
+
+    /// Writes back every dirty resident page, re-MACing each one before it could otherwise be
+    /// discarded. Meant to be called at guest exit and other syscall boundaries, as a batched
+    /// complement to the lazy per-eviction flush in [`Self::evict_if_full`].
+    pub fn flush(&mut self) -> Result<(), &'static str> {
+        for slot in 0..self.slots.len() {
+            if self.slots[slot].dirty {
+                self.commit_slot(slot)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'c> PagedMemory for OutsourcedMemory<'c> {
+    type PageRef<'a> = &'a mut Page where Self: 'a;
+
+    fn get_page<'a>(&'a mut self, page_index: u32) -> Result<Self::PageRef<'a>, &'static str> {
+        if page_index >= self.segment.n_pages {
+            return Err("Page index out of bounds");
+        }
+
+        self.clock += 1;
+
+        let slot_index = if let Some(slot) = self.find_slot(page_index) {
+            self.cache_hits += 1;
+            self.slots[slot].last_used = self.clock;
+            slot
+        } else {
+            self.cache_misses += 1;
+            let slot_index = self.fetch_and_cache(page_index)?;
+            self.slots[slot_index].last_used = self.clock;
+            // Helps a sequential scan (e.g. the streaming `Vec::push` pattern the allocator
+            // benchmark exercises) skip the round trip for its next page entirely.
+            self.prefetch_next(page_index);
+            // `prefetch_next` never evicts (see its doc comment), so `slot_index` still points
+            // at the page we just fetched.
+            slot_index
+        };
+
+        Ok(&mut self.slots[slot_index].page)
+    }
 
-        Ok(&mut self.page)
+    fn flush(&mut self) -> Result<(), &'static str> {
+        OutsourcedMemory::flush(self)
     }
 }
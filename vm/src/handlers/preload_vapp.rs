@@ -1,7 +1,8 @@
 use crate::{
     auth::{compute_code_page_hmac, compute_page_hmac_mask, get_vapp_auth_key},
+    crypto_provider::{CryptoProvider, DefaultCryptoProvider},
     handlers::lib::outsourced_mem::OutsourcedMemory,
-    hash::Sha256Hasher,
+    handshake::{ClientFinished, ClientInit, ServerHandshake},
     io::{interrupt, SerializeToComm},
     AppSW, COMM_BUFFER_SIZE,
 };
@@ -11,40 +12,64 @@ use common::{
     client_commands::{GetCodePageHashes, GetCodePageHashesResponse, Message},
     manifest::Manifest,
 };
-use ledger_device_sdk::{nbgl::NbglSpinner, sys};
+use ledger_device_sdk::nbgl::NbglSpinner;
+
+type Hasher = <DefaultCryptoProvider as CryptoProvider>::Hasher;
 
 pub fn handler_preload_vapp(
     command: ledger_device_sdk::io::Command<COMM_BUFFER_SIZE>,
 ) -> Result<Vec<u8>, AppSW> {
     let data_raw = command.get_data();
 
+    // The initial APDU carries the manifest followed by the handshake's `ClientInit`: the two are
+    // concatenated rather than exchanged as separate round-trips, since neither needs a reply from
+    // the device to be produced.
     let (manifest, rest) =
         postcard::take_from_bytes::<Manifest>(data_raw).map_err(|_| AppSW::IncorrectData)?;
 
-    if rest.len() != 0 {
-        return Err(AppSW::IncorrectData); // extra data
-    }
-
     manifest.validate().map_err(|_| AppSW::IncorrectData)?; // ensure manifest is valid
 
+    let client_init = ClientInit::from_bytes(rest).map_err(|_| AppSW::IncorrectData)?;
+
     // Implements the logic to preload the V-App's code, by receiving all the page hashes from the client, and
     // sending back the encrypted HMACs; finally, after validating the Merkle root, send the decryption key.
     // See the documentation in docs/security.md for more details.
 
-    let mut ephemeral_sk = [0u8; 32];
-    unsafe {
-        sys::cx_rng_no_throw(ephemeral_sk.as_mut_ptr(), ephemeral_sk.len());
-    }
+    let vapp_hash = manifest.get_vapp_hash::<Hasher, 32>();
 
-    let vapp_hash = manifest.get_vapp_hash::<Sha256Hasher, 32>();
+    let app_auth_key = get_vapp_auth_key::<DefaultCryptoProvider>(&vapp_hash);
+
+    let mut seq: u8 = 0;
+
+    // Run the UKEY2-style handshake (see `crate::handshake`) to agree on `ephemeral_sk` with the
+    // host, instead of picking it unilaterally: an unauthenticated `ephemeral_sk` wouldn't bind the
+    // preload session to a specific host, letting a MITM substitute itself.
+    let mut handshake = ServerHandshake::new();
+    let server_init = handshake
+        .process_client_init::<DefaultCryptoProvider>(&client_init)
+        .map_err(|_| AppSW::IncorrectData)?;
+
+    let mut resp = command.into_response();
+    resp.append(&server_init.to_bytes()).unwrap();
+    let command = interrupt(resp, &mut seq).map_err(|_| AppSW::IncorrectData)?;
 
-    let app_auth_key = get_vapp_auth_key(&vapp_hash);
+    let client_finished =
+        ClientFinished::from_bytes(command.get_data()).map_err(|_| AppSW::IncorrectData)?;
+    let handshake_output = handshake
+        .process_client_finished::<DefaultCryptoProvider>(&client_finished)
+        .map_err(|_| AppSW::IncorrectData)?;
+    let ephemeral_sk = handshake_output.ephemeral_sk;
 
     let mut resp = command.into_response();
     GetCodePageHashes::new(0, &[]).serialize_to_comm(&mut resp);
-    let mut command = interrupt(resp).map_err(|_| AppSW::IncorrectData)?;
+    let mut command = interrupt(resp, &mut seq).map_err(|_| AppSW::IncorrectData)?;
 
-    NbglSpinner::new().show("Preloading V-App...");
+    // The 6-digit auth string lets the user confirm, by comparing against what the host displays,
+    // that no MITM intercepted the handshake.
+    NbglSpinner::new().show(&alloc::format!(
+        "Preloading V-App... ({:06})",
+        handshake_output.auth_string
+    ));
 
     let n_code_pages_rounded = OutsourcedMemory::<'_, COMM_BUFFER_SIZE>::n_pages_adjusted(
         manifest.n_code_pages() as usize,
@@ -52,8 +77,7 @@ pub fn handler_preload_vapp(
 
     let mut n_page_hashes_received = 0usize;
 
-    let mut root_computer =
-        MerkleAccumulatorRootComputer::<32, Sha256Hasher>::new(n_code_pages_rounded);
+    let mut root_computer = MerkleAccumulatorRootComputer::<32, Hasher>::new(n_code_pages_rounded);
 
     let mut response_data = Vec::with_capacity(GetCodePageHashesResponse::max_hashes());
 
@@ -77,9 +101,10 @@ pub fn handler_preload_vapp(
 
         for page_hash_i in batch.code_page_hashes.into_iter() {
             let i = n_page_hashes_received as u32;
-            let page_sk_i = compute_page_hmac_mask(&ephemeral_sk, i);
-            let hmac = compute_code_page_hmac(&app_auth_key, &vapp_hash, i, page_hash_i)
-                .map_err(|_| AppSW::IncorrectData)?;
+            let page_sk_i = compute_page_hmac_mask::<DefaultCryptoProvider>(&ephemeral_sk, i);
+            let hmac =
+                compute_code_page_hmac::<DefaultCryptoProvider>(&app_auth_key, &vapp_hash, i, page_hash_i)
+                    .map_err(|_| AppSW::IncorrectData)?;
             let mut encrypted_hmac_i = [0u8; 32];
             for j in 0..32 {
                 encrypted_hmac_i[j] = hmac[j] ^ page_sk_i[j];
@@ -97,7 +122,7 @@ pub fn handler_preload_vapp(
         // Send encrypted HMACs, and request the next batch
         GetCodePageHashes::new(n_page_hashes_received as u32, response_data.as_slice())
             .serialize_to_comm(&mut resp);
-        command = interrupt(resp).map_err(|_| AppSW::IncorrectData)?;
+        command = interrupt(resp, &mut seq).map_err(|_| AppSW::IncorrectData)?;
     }
 
     if n_page_hashes_received != n_code_pages_rounded {
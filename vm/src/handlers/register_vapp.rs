@@ -1,14 +1,71 @@
-use crate::AppSW;
-use alloc::{vec, vec::Vec};
-use ledger_device_sdk::io;
+use alloc::{string::String, vec, vec::Vec};
 
-pub fn handler_register_vapp(comm: &mut io::Comm) -> Result<Vec<u8>, AppSW> {
-    let _manifest_raw = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+use common::manifest::Manifest;
+use ledger_device_sdk::nbgl::{Field, NbglReview};
 
-    // TODO: check manifest, ask user confirmation, compute hmac
+use crate::{
+    auth::compute_vapp_registration_hmac,
+    crypto_provider::{CryptoProvider, DefaultCryptoProvider},
+    AppSW, COMM_BUFFER_SIZE,
+};
 
-    let hmac = [0x42u8; 32];
-    comm.append(&hmac);
+type Hasher = <DefaultCryptoProvider as CryptoProvider>::Hasher;
 
-    Ok(vec![])
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Renders `bytes` as a lowercase hex string, for display in the on-device confirmation screen.
+fn hex_string(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        out.push(HEX_DIGITS[(b & 0xf) as usize] as char);
+    }
+    out
+}
+
+/// Handler for the RegisterVApp command.
+///
+/// Deserializes the submitted manifest, validates it, and asks the user to confirm the V-App's
+/// name, version and hash on-device. If approved, returns a 32-byte registration token -
+/// `HMAC-SHA256(device_key, vapp_hash)` (see [`compute_vapp_registration_hmac`]) - that the host
+/// is expected to store and resubmit on later launches of the same V-App, so the device can
+/// recognize it without asking the user to confirm again.
+pub fn handler_register_vapp(
+    command: ledger_device_sdk::io::Command<COMM_BUFFER_SIZE>,
+) -> Result<Vec<u8>, AppSW> {
+    let manifest_raw = command.get_data();
+
+    let manifest: Manifest =
+        postcard::from_bytes(manifest_raw).map_err(|_| AppSW::IncorrectData)?;
+    manifest.validate().map_err(|_| AppSW::IncorrectData)?;
+
+    let vapp_hash = manifest.get_vapp_hash::<Hasher, 32>();
+    let vapp_hash_hex = hex_string(&vapp_hash);
+
+    let fields = [
+        Field {
+            name: "Name",
+            value: manifest.get_app_name(),
+        },
+        Field {
+            name: "Version",
+            value: manifest.get_app_version(),
+        },
+        Field {
+            name: "Hash",
+            value: &vapp_hash_hex,
+        },
+    ];
+
+    let approved = NbglReview::new()
+        .titles("Register V-App", "", "V-App registered")
+        .show(&fields);
+
+    if !approved {
+        return Err(AppSW::Deny);
+    }
+
+    let hmac = compute_vapp_registration_hmac::<DefaultCryptoProvider>(&vapp_hash);
+
+    Ok(hmac.to_vec())
 }
@@ -0,0 +1,435 @@
+//! UKEY2-style authenticated key agreement establishing the per-session `ephemeral_sk` consumed
+//! by [`crate::auth::compute_page_hmac_mask`] (and, going forward,
+//! [`crate::auth::hpke_decap`]'s `enc`). Without this, nothing binds `ephemeral_sk` to a
+//! specific host, so a MITM could substitute itself as the host during the preload handshake.
+//!
+//! Message flow (the device always plays the "server" role):
+//!
+//! ```text
+//! host -> device: ClientInit     { nonce, supported_ciphers, commitment = SHA256(ClientFinished) }
+//! device -> host: ServerInit     { nonce, selected_cipher, server_ephemeral_pk }
+//! host -> device: ClientFinished { client_ephemeral_pk }
+//! ```
+//!
+//! The device verifies `commitment == SHA256(ClientFinished)` before deriving any key material,
+//! which binds the client's ephemeral key choice to before it ever saw the server's, preventing
+//! it from being chosen adaptively afterwards.
+
+use alloc::vec::Vec;
+
+use common::accumulator::Hasher;
+
+use crate::auth::get_vanadium_app_id;
+use crate::crypto_provider::CryptoProvider;
+
+const TAG_UKEY2_AUTH: &[u8] = b"VND UKEY2 auth";
+const TAG_UKEY2_NEXT: &[u8] = b"VND UKEY2 next";
+
+/// Cipher suites a [`ClientInit`] may advertise. Only one is defined today; the list exists so a
+/// future suite can be added without changing the wire format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Cipher {
+    EcdhSecp256k1Hkdfsha256 = 0,
+}
+
+impl TryFrom<u8> for Cipher {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Cipher::EcdhSecp256k1Hkdfsha256),
+            _ => Err("unsupported UKEY2 cipher"),
+        }
+    }
+}
+
+/// First handshake message, sent by the host.
+pub struct ClientInit {
+    pub nonce: [u8; 32],
+    pub supported_ciphers: Vec<Cipher>,
+    pub commitment: [u8; 32],
+}
+
+impl ClientInit {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 + 1 + self.supported_ciphers.len() + 32);
+        out.extend_from_slice(&self.nonce);
+        out.push(self.supported_ciphers.len() as u8);
+        out.extend(self.supported_ciphers.iter().map(|cipher| *cipher as u8));
+        out.extend_from_slice(&self.commitment);
+        out
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, &'static str> {
+        if buf.len() < 32 + 1 {
+            return Err("ClientInit is too short");
+        }
+        let mut nonce = [0u8; 32];
+        nonce.copy_from_slice(&buf[0..32]);
+
+        let n_ciphers = buf[32] as usize;
+        let ciphers_end = 33 + n_ciphers;
+        if buf.len() != ciphers_end + 32 {
+            return Err("ClientInit has an inconsistent length");
+        }
+        let supported_ciphers = buf[33..ciphers_end]
+            .iter()
+            .map(|&b| Cipher::try_from(b))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&buf[ciphers_end..ciphers_end + 32]);
+
+        Ok(ClientInit {
+            nonce,
+            supported_ciphers,
+            commitment,
+        })
+    }
+}
+
+/// Second handshake message, sent by the device.
+pub struct ServerInit {
+    pub nonce: [u8; 32],
+    pub selected_cipher: Cipher,
+    pub server_ephemeral_pk: [u8; 33],
+}
+
+impl ServerInit {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 + 1 + 33);
+        out.extend_from_slice(&self.nonce);
+        out.push(self.selected_cipher as u8);
+        out.extend_from_slice(&self.server_ephemeral_pk);
+        out
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, &'static str> {
+        if buf.len() != 32 + 1 + 33 {
+            return Err("ServerInit has an unexpected length");
+        }
+        let mut nonce = [0u8; 32];
+        nonce.copy_from_slice(&buf[0..32]);
+        let selected_cipher = Cipher::try_from(buf[32])?;
+        let mut server_ephemeral_pk = [0u8; 33];
+        server_ephemeral_pk.copy_from_slice(&buf[33..66]);
+        Ok(ServerInit {
+            nonce,
+            selected_cipher,
+            server_ephemeral_pk,
+        })
+    }
+}
+
+/// Third handshake message, sent by the host. Its serialized bytes are exactly what
+/// [`ClientInit::commitment`] must commit to.
+pub struct ClientFinished {
+    pub client_ephemeral_pk: [u8; 33],
+}
+
+impl ClientFinished {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.client_ephemeral_pk.to_vec()
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, &'static str> {
+        if buf.len() != 33 {
+            return Err("ClientFinished has an unexpected length");
+        }
+        let mut client_ephemeral_pk = [0u8; 33];
+        client_ephemeral_pk.copy_from_slice(buf);
+        Ok(ClientFinished { client_ephemeral_pk })
+    }
+}
+
+/// The two outputs of a completed handshake, produced by [`ServerHandshake::process_client_finished`].
+pub struct HandshakeOutput {
+    /// The session key that supersedes a raw, unauthenticated `ephemeral_sk` (see
+    /// [`crate::auth::compute_page_hmac_mask`]).
+    pub ephemeral_sk: [u8; 32],
+    /// A 6-digit decimal authentication string for the user to compare out-of-band, e.g. shown
+    /// on the device screen and read back against what the host displays. Format with `{:06}`.
+    pub auth_string: u32,
+}
+
+/// Why a [`ServerHandshake`] step failed. Once set, [`ServerHandshake::stage`] latches into
+/// [`Stage::Failed`] so a caller can't push a half-failed handshake any further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// `ClientInit` didn't advertise any cipher this device supports.
+    UnsupportedCipher,
+    /// `SHA256(ClientFinished)` didn't match the commitment carried in `ClientInit`.
+    CommitmentMismatch,
+    /// A peer-supplied ephemeral public key wasn't a valid secp256k1 point.
+    InvalidPeerPublicKey,
+    /// The ECDH computation itself failed (e.g. the peer key reduces to the point at infinity).
+    KeyExchangeFailed,
+    /// A message arrived out of turn for the handshake's current stage.
+    UnexpectedMessage,
+}
+
+enum Stage {
+    AwaitingClientInit,
+    AwaitingClientFinished {
+        selected_cipher: Cipher,
+        server_ephemeral_sk: [u8; 32],
+        server_ephemeral_pk: [u8; 33],
+        client_init_bytes: Vec<u8>,
+        server_init_bytes: Vec<u8>,
+        commitment: [u8; 32],
+    },
+    Done,
+    Failed(HandshakeError),
+}
+
+/// Device-side ("server") state machine driving one UKEY2-style handshake to completion.
+pub struct ServerHandshake {
+    stage: Stage,
+}
+
+impl ServerHandshake {
+    pub fn new() -> Self {
+        ServerHandshake {
+            stage: Stage::AwaitingClientInit,
+        }
+    }
+
+    /// Processes `ClientInit`, returning the `ServerInit` message to send back.
+    pub fn process_client_init<C: CryptoProvider>(
+        &mut self,
+        client_init: &ClientInit,
+    ) -> Result<ServerInit, HandshakeError> {
+        if !matches!(self.stage, Stage::AwaitingClientInit) {
+            return Err(HandshakeError::UnexpectedMessage);
+        }
+
+        if !client_init
+            .supported_ciphers
+            .contains(&Cipher::EcdhSecp256k1Hkdfsha256)
+        {
+            self.stage = Stage::Failed(HandshakeError::UnsupportedCipher);
+            return Err(HandshakeError::UnsupportedCipher);
+        }
+        let selected_cipher = Cipher::EcdhSecp256k1Hkdfsha256;
+
+        let mut server_nonce = [0u8; 32];
+        C::random_bytes(&mut server_nonce);
+
+        let mut server_ephemeral_sk_bytes = [0u8; 32];
+        let server_ephemeral_sk = loop {
+            C::random_bytes(&mut server_ephemeral_sk_bytes);
+            if let Ok(sk) = secp256k1::SecretKey::from_slice(&server_ephemeral_sk_bytes) {
+                break sk;
+            }
+        };
+        let secp = secp256k1::Secp256k1::new();
+        let server_ephemeral_pk =
+            secp256k1::PublicKey::from_secret_key(&secp, &server_ephemeral_sk).serialize();
+
+        let server_init = ServerInit {
+            nonce: server_nonce,
+            selected_cipher,
+            server_ephemeral_pk,
+        };
+
+        self.stage = Stage::AwaitingClientFinished {
+            selected_cipher,
+            server_ephemeral_sk: server_ephemeral_sk_bytes,
+            server_ephemeral_pk,
+            client_init_bytes: client_init.to_bytes(),
+            server_init_bytes: server_init.to_bytes(),
+            commitment: client_init.commitment,
+        };
+
+        Ok(server_init)
+    }
+
+    /// Processes `ClientFinished`, verifying its commitment and deriving the session outputs.
+    pub fn process_client_finished<C: CryptoProvider>(
+        &mut self,
+        client_finished: &ClientFinished,
+    ) -> Result<HandshakeOutput, HandshakeError> {
+        let stage = core::mem::replace(&mut self.stage, Stage::Failed(HandshakeError::UnexpectedMessage));
+        let Stage::AwaitingClientFinished {
+            server_ephemeral_sk,
+            client_init_bytes,
+            server_init_bytes,
+            commitment,
+            ..
+        } = stage
+        else {
+            return Err(HandshakeError::UnexpectedMessage);
+        };
+
+        let mut hasher = C::Hasher::new();
+        hasher.update(&client_finished.to_bytes());
+        if hasher.finalize() != commitment {
+            self.stage = Stage::Failed(HandshakeError::CommitmentMismatch);
+            return Err(HandshakeError::CommitmentMismatch);
+        }
+
+        let Ok(peer_pk) = secp256k1::PublicKey::from_slice(&client_finished.client_ephemeral_pk) else {
+            self.stage = Stage::Failed(HandshakeError::InvalidPeerPublicKey);
+            return Err(HandshakeError::InvalidPeerPublicKey);
+        };
+        let own_sk = secp256k1::SecretKey::from_slice(&server_ephemeral_sk)
+            .expect("server_ephemeral_sk was validated when it was generated");
+        let Ok(dhs) = secp256k1::ecdh::SharedSecret::new(&peer_pk, &own_sk) else {
+            self.stage = Stage::Failed(HandshakeError::KeyExchangeFailed);
+            return Err(HandshakeError::KeyExchangeFailed);
+        };
+
+        let mut salt = Vec::with_capacity(client_init_bytes.len() + server_init_bytes.len() + 32);
+        salt.extend_from_slice(&client_init_bytes);
+        salt.extend_from_slice(&server_init_bytes);
+        salt.extend_from_slice(&get_vanadium_app_id::<C>());
+
+        let master = hkdf_extract::<C>(&salt, &dhs.secret_bytes());
+        let auth_string = auth_string_digits(&hkdf_expand::<C>(&master, TAG_UKEY2_AUTH));
+        let ephemeral_sk = hkdf_expand::<C>(&master, TAG_UKEY2_NEXT);
+
+        self.stage = Stage::Done;
+
+        Ok(HandshakeOutput {
+            ephemeral_sk,
+            auth_string,
+        })
+    }
+}
+
+/// HKDF-Extract, reducing the (arbitrary-length) transcript salt to a fixed 32 bytes first:
+/// [`CryptoProvider::hmac_sha256`] takes a fixed 32-byte key, so the salt can't be used as the
+/// HMAC key directly without widening that trait method for this one caller.
+fn hkdf_extract<C: CryptoProvider>(salt: &[u8], ikm: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = C::Hasher::new();
+    hasher.update(salt);
+    let salt_key = hasher.finalize();
+    C::hmac_sha256(&salt_key, &[ikm])
+}
+
+/// A single-block HKDF-Expand: `T(1) = HMAC(PRK, info || 0x01)`, which is all a 32-byte output
+/// needs.
+fn hkdf_expand<C: CryptoProvider>(prk: &[u8; 32], info: &[u8]) -> [u8; 32] {
+    C::hmac_sha256(prk, &[info, &[0x01]])
+}
+
+/// Reduces an HKDF-Expand output to a 6-digit decimal authentication string, in the spirit of
+/// UKEY2's own auth-string encoding.
+fn auth_string_digits(expand_output: &[u8; 32]) -> u32 {
+    let n = u32::from_be_bytes([
+        expand_output[0],
+        expand_output[1],
+        expand_output[2],
+        expand_output[3],
+    ]);
+    n % 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_provider::RustCrypto;
+
+    type C = RustCrypto;
+
+    /// Builds a `ClientInit`/`ClientFinished` pair the way the host side would: pick a client
+    /// ephemeral keypair, then commit to the resulting `ClientFinished` in `ClientInit`.
+    fn client_messages(client_sk: &secp256k1::SecretKey) -> (ClientInit, ClientFinished) {
+        let secp = secp256k1::Secp256k1::new();
+        let client_ephemeral_pk =
+            secp256k1::PublicKey::from_secret_key(&secp, client_sk).serialize();
+        let client_finished = ClientFinished { client_ephemeral_pk };
+
+        let mut hasher = C::Hasher::new();
+        hasher.update(&client_finished.to_bytes());
+        let commitment = hasher.finalize();
+
+        let mut nonce = [0u8; 32];
+        C::random_bytes(&mut nonce);
+
+        let client_init = ClientInit {
+            nonce,
+            supported_ciphers: alloc::vec![Cipher::EcdhSecp256k1Hkdfsha256],
+            commitment,
+        };
+
+        (client_init, client_finished)
+    }
+
+    #[test]
+    fn full_handshake_derives_the_session_key_the_host_would_compute() {
+        let client_sk = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let (client_init, client_finished) = client_messages(&client_sk);
+
+        let mut server = ServerHandshake::new();
+        let server_init = server.process_client_init::<C>(&client_init).unwrap();
+        let output = server.process_client_finished::<C>(&client_finished).unwrap();
+
+        // Recompute the session key independently, the way the host side would from its own
+        // `client_sk` and the device's `server_init`, and check it matches what the device derived.
+        let secp = secp256k1::Secp256k1::new();
+        let server_pk =
+            secp256k1::PublicKey::from_slice(&server_init.server_ephemeral_pk).unwrap();
+        let dhs = secp256k1::ecdh::SharedSecret::new(&server_pk, client_sk).unwrap();
+
+        let mut salt = Vec::new();
+        salt.extend_from_slice(&client_init.to_bytes());
+        salt.extend_from_slice(&server_init.to_bytes());
+        salt.extend_from_slice(&get_vanadium_app_id::<C>());
+
+        let master = hkdf_extract::<C>(&salt, &dhs.secret_bytes());
+        let expected_ephemeral_sk = hkdf_expand::<C>(&master, TAG_UKEY2_NEXT);
+        let expected_auth_string = auth_string_digits(&hkdf_expand::<C>(&master, TAG_UKEY2_AUTH));
+
+        assert_eq!(output.ephemeral_sk, expected_ephemeral_sk);
+        assert_eq!(output.auth_string, expected_auth_string);
+    }
+
+    #[test]
+    fn process_client_init_rejects_unsupported_cipher() {
+        let client_sk = secp256k1::SecretKey::from_slice(&[9u8; 32]).unwrap();
+        let (mut client_init, _) = client_messages(&client_sk);
+        client_init.supported_ciphers = Vec::new();
+
+        let mut server = ServerHandshake::new();
+        assert_eq!(
+            server.process_client_init::<C>(&client_init),
+            Err(HandshakeError::UnsupportedCipher)
+        );
+    }
+
+    #[test]
+    fn process_client_finished_rejects_commitment_mismatch() {
+        let client_sk = secp256k1::SecretKey::from_slice(&[11u8; 32]).unwrap();
+        let (client_init, _) = client_messages(&client_sk);
+
+        // A `ClientFinished` other than the one `client_init.commitment` actually committed to.
+        let other_sk = secp256k1::SecretKey::from_slice(&[12u8; 32]).unwrap();
+        let secp = secp256k1::Secp256k1::new();
+        let mismatched_finished = ClientFinished {
+            client_ephemeral_pk: secp256k1::PublicKey::from_secret_key(&secp, &other_sk)
+                .serialize(),
+        };
+
+        let mut server = ServerHandshake::new();
+        server.process_client_init::<C>(&client_init).unwrap();
+
+        assert_eq!(
+            server.process_client_finished::<C>(&mismatched_finished),
+            Err(HandshakeError::CommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn process_client_finished_rejects_out_of_order_message() {
+        let client_sk = secp256k1::SecretKey::from_slice(&[13u8; 32]).unwrap();
+        let (_, client_finished) = client_messages(&client_sk);
+
+        let mut server = ServerHandshake::new();
+        assert_eq!(
+            server.process_client_finished::<C>(&client_finished),
+            Err(HandshakeError::UnexpectedMessage)
+        );
+    }
+}
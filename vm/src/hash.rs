@@ -0,0 +1,69 @@
+//! Pure hashing backend used to compute a V-App's manifest hash (see
+//! `common::manifest::Manifest::get_vapp_hash`), kept separate from the `CryptoProvider` trait
+//! (see [`crate::crypto_provider`]) because [`VAppStore`](crate::vapp::VAppStore) only ever
+//! needs a hasher, never the HMAC/AEAD/RNG primitives that come with a full crypto provider.
+//!
+//! Unlike [`crate::crypto_provider::DefaultCryptoProvider`], which is selected by
+//! `target_arch`, the backend here is selected by Cargo feature, so a `--native` host build can
+//! opt into the hardware-identical pure-Rust implementation for testing without cross-compiling:
+//! enable `hash_device` to use the secure element's hardware SHA-256, or `hash_rustcrypto` for
+//! the pure-Rust one. Exactly one of the two must be enabled for a given build.
+
+use common::accumulator::Hasher;
+
+#[cfg(feature = "hash_device")]
+pub use device::Sha256Hasher;
+
+#[cfg(feature = "hash_device")]
+mod device {
+    use super::Hasher;
+    use ledger_device_sdk::hash::{sha2::Sha2_256, HashInit};
+
+    /// [`Hasher`] backed by the secure element's hardware SHA-256 implementation.
+    pub struct Sha256Hasher(Sha2_256);
+
+    impl Hasher<32> for Sha256Hasher {
+        fn new() -> Self {
+            Sha256Hasher(Sha2_256::new())
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data).expect("hardware SHA-256 update failed");
+        }
+
+        fn finalize(mut self) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            self.0
+                .finalize(&mut out)
+                .expect("hardware SHA-256 finalize failed");
+            out
+        }
+    }
+}
+
+#[cfg(feature = "hash_rustcrypto")]
+pub use rustcrypto::Sha256Hasher;
+
+#[cfg(feature = "hash_rustcrypto")]
+mod rustcrypto {
+    use super::Hasher;
+    use sha2::{Digest, Sha256};
+
+    /// [`Hasher`] backed by the pure-Rust `sha2` crate, used by `--native` host builds and
+    /// unit tests, which can't run the secure element's hardware hash.
+    pub struct Sha256Hasher(Sha256);
+
+    impl Hasher<32> for Sha256Hasher {
+        fn new() -> Self {
+            Sha256Hasher(Sha256::new())
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+
+        fn finalize(self) -> [u8; 32] {
+            self.0.finalize().into()
+        }
+    }
+}
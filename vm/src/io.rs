@@ -2,24 +2,54 @@ use ledger_device_sdk::io_new as io;
 
 use crate::{AppSW, Instruction};
 
-// Helper function to send the InterruptedExecution response, and make sure the next command is 'Continue'
+// Helper function to send the InterruptedExecution response, and make sure the next command is
+// 'Continue'.
+//
+// Each interruption is tagged with a sequence number carried in P1 of the `Continue` the host is
+// expected to send back (P2 is unused, and must be 0). The sequence number is bumped on every
+// call, so that if the transport dies and is relaunched mid-session, a reconnecting host that
+// isn't sure whether its last `Continue` actually reached the VM can simply re-send it: a
+// `Continue` echoing the *previous* sequence number is recognized as a harmless replay of an
+// interruption we already resolved, and is silently ignored while we keep waiting for the real
+// resume. Anything else is a genuine protocol violation.
 pub fn interrupt<'a, const N: usize>(
     tx: io::Tx<'a, N>,
+    seq: &mut u8,
 ) -> Result<io::Command<'a, N>, common::vm::MemoryError> {
-    let comm = tx.send(AppSW::InterruptedExecution).unwrap();
-    let command = comm.next_command();
-
-    let ins = command
-        .decode::<Instruction>()
-        .map_err(|_: io::Reply| common::vm::MemoryError::GenericError("Invalid response"))?;
-
-    let Instruction::Continue(p1, p2) = ins else {
-        // expected "Continue"
-        return Err(common::vm::MemoryError::GenericError("INS not supported"));
-    };
-    if (p1, p2) != (0, 0) {
-        return Err(common::vm::MemoryError::GenericError("Wrong P1/P2"));
-    }
+    let mut comm = tx.send(AppSW::InterruptedExecution).unwrap();
+
+    let expected_seq = *seq;
+    let previous_seq = expected_seq.wrapping_sub(1);
+    *seq = expected_seq.wrapping_add(1);
+
+    loop {
+        let command = comm.next_command();
+
+        let ins = command
+            .decode::<Instruction>()
+            .map_err(|_: io::Reply| common::vm::MemoryError::GenericError("Invalid response"))?;
 
-    Ok(command)
+        let Instruction::Continue(p1, p2) = ins else {
+            // expected "Continue"
+            return Err(common::vm::MemoryError::GenericError("INS not supported"));
+        };
+        if p2 != 0 {
+            return Err(common::vm::MemoryError::GenericError("Wrong P1/P2"));
+        }
+
+        if p1 == expected_seq {
+            return Ok(command);
+        }
+
+        if p1 == previous_seq {
+            // Duplicate resume for an interruption we already handled; wait for the next command
+            // instead of failing the whole exchange.
+            continue;
+        }
+
+        return Err(common::vm::MemoryError::SequenceMismatch {
+            expected: expected_seq,
+            got: p1,
+        });
+    }
 }
@@ -20,7 +20,9 @@
 
 mod aes;
 mod app_ui;
+mod crypto_provider;
 mod handlers;
+mod handshake;
 mod hash;
 mod io;
 mod nvm;
@@ -31,6 +33,8 @@ mod app_tests;
 
 use alloc::{string::ToString, vec::Vec};
 use app_ui::menu::show_home;
+#[cfg(feature = "debug")]
+use handlers::debug_stub::handler_debug_stub;
 #[cfg(feature = "metrics")]
 use handlers::get_metrics::handler_get_metrics;
 use handlers::{
@@ -174,6 +178,8 @@ pub enum Instruction {
     StartVApp,
     #[cfg(feature = "metrics")]
     GetMetrics,
+    #[cfg(feature = "debug")]
+    DebugStub(u8, u8), // one GDB Remote Serial Protocol packet
     Continue(u8, u8), // client response to a request from the VM
 }
 
@@ -202,6 +208,8 @@ impl TryFrom<ApduHeader> for Instruction {
             (0 | 2 | 3 | 0xf0, _, _) => Err(AppSW::WrongP1P2.into()),
             #[cfg(not(feature = "metrics"))]
             (0 | 2 | 3, _, _) => Err(AppSW::WrongP1P2.into()),
+            #[cfg(feature = "debug")]
+            (4, p1, p2) => Ok(Instruction::DebugStub(p1, p2)),
             (0xff, p1, p2) => Ok(Instruction::Continue(p1, p2)),
             (_, _, _) => Err(AppSW::InsNotSupported.into()),
         }
@@ -250,6 +258,8 @@ fn handle_apdu(command: Command<COMM_BUFFER_SIZE>) -> Result<Vec<u8>, AppSW> {
         Instruction::StartVApp => handler_start_vapp(command),
         #[cfg(feature = "metrics")]
         Instruction::GetMetrics => handler_get_metrics(command),
+        #[cfg(feature = "debug")]
+        Instruction::DebugStub(_, _) => handler_debug_stub(command),
         Instruction::Continue(_, _) => Err(AppSW::InsNotSupported), // 'Continue' command is only allowed when requested by the VM
     }
 }
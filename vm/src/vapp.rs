@@ -1,13 +1,17 @@
+use common::accumulator::Hasher;
 use common::manifest::{Manifest, APP_NAME_MAX_LEN, APP_VERSION_MAX_LEN};
 use ledger_device_sdk::NVMData;
 
 use crate::nvm::LazyStorage;
 
-use crate::hash::Sha256Hasher;
+use crate::crypto_provider::{CryptoProvider, DefaultCryptoProvider};
 
 /// Maximum number of V-Apps that can be registered.
 pub const MAX_REGISTERED_VAPPS: usize = 32;
 
+/// Maximum number of developer/CA public keys that can be enrolled as trusted signers.
+pub const MAX_TRUSTED_KEYS: usize = 8;
+
 /// A registered V-App entry stored in NVRAM.
 /// Uses fixed-size arrays for deterministic storage layout.
 #[derive(Clone, Copy)]
@@ -19,6 +23,9 @@ pub struct VAppEntry {
     pub vapp_name: [u8; APP_NAME_MAX_LEN],
     /// V-App version, null-padded to 32 bytes.
     pub vapp_version: [u8; APP_VERSION_MAX_LEN],
+    /// SEC1-compressed secp256k1 public key of the trusted key that signed this entry's
+    /// registration, so the device UI can show provenance during app management.
+    pub signer_pubkey: [u8; 33],
 }
 
 impl VAppEntry {
@@ -28,6 +35,7 @@ impl VAppEntry {
             vapp_hash: [0u8; 32],
             vapp_name: [0u8; APP_NAME_MAX_LEN],
             vapp_version: [0u8; APP_VERSION_MAX_LEN],
+            signer_pubkey: [0u8; 33],
         }
     }
 
@@ -64,6 +72,8 @@ pub enum VAppStoreError {
     NameTooLong,
     /// The app version is too long.
     VersionTooLong,
+    /// The registration signature does not verify against any enrolled trusted key.
+    UntrustedSignature,
 }
 
 // Use a fixed-length array of LazyStorage for zero-initialized NVM storage.
@@ -72,6 +82,117 @@ pub enum VAppStoreError {
 static mut VAPP_STORE: NVMData<[LazyStorage<VAppEntry>; MAX_REGISTERED_VAPPS]> =
     NVMData::new([LazyStorage::new(); MAX_REGISTERED_VAPPS]);
 
+/// A developer/CA public key enrolled as trusted to sign V-App manifest registrations.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct TrustedKeyEntry {
+    /// SEC1-compressed secp256k1 public key. All zeros indicates an empty slot.
+    pub pubkey: [u8; 33],
+}
+
+impl TrustedKeyEntry {
+    /// Creates an empty entry (sentinel value).
+    pub const fn empty() -> Self {
+        Self {
+            pubkey: [0u8; 33],
+        }
+    }
+}
+
+#[link_section = ".nvm_data"]
+static mut TRUSTED_KEY_STORE: NVMData<[LazyStorage<TrustedKeyEntry>; MAX_TRUSTED_KEYS]> =
+    NVMData::new([LazyStorage::new(); MAX_TRUSTED_KEYS]);
+
+/// The trusted-key store manages the developer/CA public keys allowed to sign V-App manifest
+/// registrations, turning [`VAppStore`] into an authenticated install list rather than a cache
+/// of whatever hash was last presented.
+///
+/// Not currently wired to any handler: [`crate::handlers::register_vapp`] implements a different,
+/// stateless design instead (the host stores a device-issued HMAC token and resubmits it on later
+/// launches - see that module's doc comment), which never touches this store or carries a
+/// signature to verify against it. Enrolling a key here also has no caller and therefore no
+/// answer yet to who is authorized to enroll one - that bootstrap question belongs to whatever
+/// design eventually replaces or augments the HMAC-token scheme.
+pub struct TrustedKeyStore;
+
+impl TrustedKeyStore {
+    /// Gets a mutable reference to the storage array.
+    #[inline(never)]
+    fn get_storage_mut() -> &'static mut [LazyStorage<TrustedKeyEntry>; MAX_TRUSTED_KEYS] {
+        let data = &raw mut TRUSTED_KEY_STORE;
+        unsafe { (*data).get_mut() }
+    }
+
+    /// Gets a reference to the storage array.
+    #[inline(never)]
+    fn get_storage_ref() -> &'static [LazyStorage<TrustedKeyEntry>; MAX_TRUSTED_KEYS] {
+        let data = &raw const TRUSTED_KEY_STORE;
+        unsafe { (*data).get_ref() }
+    }
+
+    /// Finds an enrolled key. Returns the index if found.
+    fn find(pubkey: &[u8; 33]) -> Option<usize> {
+        let storage = Self::get_storage_ref();
+        for i in 0..MAX_TRUSTED_KEYS {
+            if storage[i].is_initialized() && &storage[i].get_ref().pubkey == pubkey {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Returns whether `pubkey` is currently enrolled as a trusted signer.
+    #[allow(dead_code)] // No caller yet; see the blocked-on note on `TrustedKeyStore`.
+    pub fn is_trusted(pubkey: &[u8; 33]) -> bool {
+        Self::find(pubkey).is_some()
+    }
+
+    /// Enrolls `pubkey` as a trusted signer. A no-op if it is already enrolled.
+    #[allow(dead_code)] // No enrollment call site yet; see the blocked-on note on `TrustedKeyStore`.
+    pub fn enroll(pubkey: &[u8; 33]) -> Result<(), VAppStoreError> {
+        if Self::is_trusted(pubkey) {
+            return Ok(());
+        }
+        let storage = Self::get_storage_mut();
+        for i in 0..MAX_TRUSTED_KEYS {
+            if !storage[i].is_initialized() {
+                storage[i].initialize(&TrustedKeyEntry { pubkey: *pubkey });
+                return Ok(());
+            }
+        }
+        Err(VAppStoreError::StoreFull)
+    }
+
+    /// Revokes a previously enrolled trusted signer. Returns `false` if it wasn't enrolled.
+    #[allow(dead_code)] // Will be used by device UI for trusted-key management
+    pub fn revoke(pubkey: &[u8; 33]) -> bool {
+        match Self::find(pubkey) {
+            Some(index) => {
+                Self::get_storage_mut()[index].clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Verifies `signature` over `msg_hash` against every enrolled key, returning the first
+    /// key that verifies it.
+    #[allow(dead_code)] // Only called by `VAppStore::register`, itself unreachable; see above.
+    fn verify(msg_hash: &[u8; 32], signature: &[u8; 64]) -> Option<[u8; 33]> {
+        let storage = Self::get_storage_ref();
+        for i in 0..MAX_TRUSTED_KEYS {
+            if storage[i].is_initialized() {
+                let entry = storage[i].get_ref();
+                if DefaultCryptoProvider::verify_ecdsa_secp256k1(&entry.pubkey, msg_hash, signature)
+                {
+                    return Some(entry.pubkey);
+                }
+            }
+        }
+        None
+    }
+}
+
 /// The V-App store manages registered V-Apps in NVRAM.
 pub struct VAppStore;
 
@@ -90,20 +211,25 @@ impl VAppStore {
         unsafe { (*data).get_ref() }
     }
 
-    /// Checks if a V-App with the given hash is registered.
-    pub fn is_registered(vapp_hash: &[u8; 32]) -> bool {
-        Self::find_by_hash(vapp_hash).is_some()
+    /// Checks if the given manifest's V-App, hashed with `H`, is registered.
+    #[allow(dead_code)] // No caller yet; `register_vapp`'s handler doesn't use this store.
+    pub fn is_registered<H: Hasher<32>>(manifest: &Manifest) -> bool {
+        Self::find_by_hash::<H>(manifest).is_some()
     }
 
-    /// Finds an entry by its vapp_hash. Returns the index if found.
+    /// Hashes `manifest` with `H` and finds the entry with that vapp_hash. Returns the index
+    /// if found. `H` must be the same hasher the manifest was registered with (see
+    /// [`crate::hash`]).
     /// We don't use a constant time comparison, as knowledge about which apps are registered is not
     /// considered sensitive information.
-    pub fn find_by_hash(vapp_hash: &[u8; 32]) -> Option<usize> {
+    #[allow(dead_code)] // No caller yet; `register_vapp`'s handler doesn't use this store.
+    pub fn find_by_hash<H: Hasher<32>>(manifest: &Manifest) -> Option<usize> {
+        let vapp_hash = manifest.get_vapp_hash::<H, 32>();
         let storage = Self::get_storage_ref();
         for i in 0..MAX_REGISTERED_VAPPS {
             if storage[i].is_initialized() {
                 let entry = storage[i].get_ref();
-                if &entry.vapp_hash == vapp_hash {
+                if entry.vapp_hash == vapp_hash {
                     return Some(i);
                 }
             }
@@ -114,6 +240,7 @@ impl VAppStore {
     /// Finds an entry by app name. Returns the index if found.
     /// We don't use a constant time comparison, as knowledge about which apps are registered is not
     /// considered sensitive information.
+    #[allow(dead_code)] // Only called by `VAppStore::register`, itself unreachable; see above.
     pub fn find_by_name(vapp_name: &str) -> Option<usize> {
         let storage = Self::get_storage_ref();
         for i in 0..MAX_REGISTERED_VAPPS {
@@ -127,9 +254,22 @@ impl VAppStore {
         None
     }
 
-    /// Registers a V-App. If an app with the same name exists, it will be overwritten.
-    /// Returns Ok(()) on success, or an error if the store is full or parameters are invalid.
-    pub fn register(manifest: &Manifest) -> Result<(), VAppStoreError> {
+    /// Registers a V-App, after checking that `signature` (a 64-byte compact secp256k1 ECDSA
+    /// signature over the manifest's V-App hash) verifies against some key enrolled in the
+    /// [`TrustedKeyStore`]. If an app with the same name exists, it will be overwritten.
+    /// Returns Ok(()) on success, or an error if the signature doesn't verify, the store is
+    /// full, or parameters are invalid.
+    ///
+    /// Generic over `H` (see [`crate::hash`]) so the native test client and the on-device
+    /// build can each hash the manifest with their own backend.
+    ///
+    /// No caller yet: [`crate::handlers::register_vapp`] doesn't produce a signature to pass
+    /// here; see the blocked-on note on [`TrustedKeyStore`].
+    #[allow(dead_code)]
+    pub fn register<H: Hasher<32>>(
+        manifest: &Manifest,
+        signature: &[u8; 64],
+    ) -> Result<(), VAppStoreError> {
         let vapp_name = manifest.get_app_name();
         let vapp_version = manifest.get_app_version();
 
@@ -141,13 +281,18 @@ impl VAppStore {
         }
 
         // Compute the V-App hash from the manifest
-        let vapp_hash = manifest.get_vapp_hash::<Sha256Hasher, 32>();
+        let vapp_hash = manifest.get_vapp_hash::<H, 32>();
+
+        // Only accept the registration if it is signed by an enrolled trusted key.
+        let signer_pubkey = TrustedKeyStore::verify(&vapp_hash, signature)
+            .ok_or(VAppStoreError::UntrustedSignature)?;
 
         // Create the new entry
         let mut entry = VAppEntry::empty();
         entry.vapp_hash.copy_from_slice(&vapp_hash);
         entry.vapp_name[..vapp_name.len()].copy_from_slice(vapp_name.as_bytes());
         entry.vapp_version[..vapp_version.len()].copy_from_slice(vapp_version.as_bytes());
+        entry.signer_pubkey = signer_pubkey;
 
         let storage = Self::get_storage_mut();
 
@@ -217,6 +362,7 @@ impl VAppStore {
     }
 
     /// Uninstalls all V-Apps by clearing all entries in storage.
+    #[allow(dead_code)] // Will be used by device UI for app management
     pub fn uninstall_all() {
         let storage = Self::get_storage_mut();
         for i in 0..MAX_REGISTERED_VAPPS {